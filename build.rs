@@ -4,12 +4,15 @@ fn main() {
     // Tell cargo to look for shared libraries in the specified directory
     println!("cargo:rerun-if-changed=src/");
 
-    // Generate C header file location info
-    println!("cargo:warning=C headers are in include/sage_crypto.h");
+    // Node.js resolves the napi symbols when the addon is loaded
+    #[cfg(feature = "napi")]
+    napi_build::setup();
 
     // Platform-specific linker flags for FFI
-    #[cfg(feature = "ffi")]
+    #[cfg(feature = "ffi-core")]
     {
+        generate_header();
+
         let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
 
         match target_os.as_str() {
@@ -27,3 +30,30 @@ fn main() {
         }
     }
 }
+
+/// Generate sage_crypto.h in `OUT_DIR` from the `#[no_mangle]` functions and `#[repr(C)]` types
+///
+/// Build scripts must not write to the source tree; `make ffi` and
+/// `scripts/copy-header.sh` copy the header to include/.
+#[cfg(feature = "ffi-core")]
+fn generate_header() {
+    println!("cargo:rerun-if-changed=cbindgen.toml");
+
+    let crate_dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+    let out_dir = std::env::var("OUT_DIR").unwrap();
+    let mut config = cbindgen::Config::from_file(format!("{crate_dir}/cbindgen.toml"))
+        .expect("Failed to read cbindgen.toml");
+
+    // Match the header to the features of this build
+    if std::env::var_os("CARGO_FEATURE_FFI_PRIVATE_EXPORT").is_some() {
+        let after_includes = config.after_includes.take().unwrap_or_default();
+        config.after_includes = Some(format!("#define SAGE_PRIVATE_KEY_EXPORT\n{after_includes}"));
+    }
+
+    cbindgen::Builder::new()
+        .with_crate(&crate_dir)
+        .with_config(config)
+        .generate()
+        .expect("Failed to generate C header")
+        .write_to_file(format!("{out_dir}/sage_crypto.h"));
+}
@@ -0,0 +1,45 @@
+package io.sage.crypto;
+
+/**
+ * Natives of libsage_crypto_core built with {@code --features jni}.
+ *
+ * <p>Headers are flattened as {@code name, value, name, value, ...}.
+ */
+public final class SageCrypto {
+    public static final int ED25519 = 0;
+    public static final int SECP256K1 = 1;
+
+    static {
+        System.loadLibrary("sage_crypto_core");
+    }
+
+    private SageCrypto() {}
+
+    public static native byte[] generatePrivateKey(int keyType);
+
+    public static native byte[] publicKey(int keyType, byte[] privateKey);
+
+    public static native String keyId(int keyType, byte[] publicKey);
+
+    public static native byte[] sign(int keyType, byte[] privateKey, byte[] message);
+
+    public static native boolean verify(int keyType, byte[] publicKey, byte[] message, byte[] signature);
+
+    /** Returns the signature headers to add to the request; {@code body} may be null. */
+    public static native String[] signHttpRequest(
+            int keyType, byte[] privateKey, String method, String uri, String[] headers, byte[] body);
+
+    /** {@code body}, when not null, must match the signed content-digest header. */
+    public static native boolean verifyHttpRequest(
+            int keyType, byte[] publicKey, String method, String uri, String[] headers, byte[] body);
+
+    public static void main(String[] args) {
+        byte[] privateKey = generatePrivateKey(ED25519);
+        byte[] publicKey = publicKey(ED25519, privateKey);
+        System.out.println("Key ID: " + keyId(ED25519, publicKey));
+
+        byte[] message = "Hello, SAGE!".getBytes();
+        byte[] signature = sign(ED25519, privateKey, message);
+        System.out.println("Valid: " + verify(ED25519, publicKey, message, signature));
+    }
+}
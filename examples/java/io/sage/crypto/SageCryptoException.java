@@ -0,0 +1,8 @@
+package io.sage.crypto;
+
+/** Thrown by {@link SageCrypto} when an operation fails. */
+public class SageCryptoException extends RuntimeException {
+    public SageCryptoException(String message) {
+        super(message);
+    }
+}
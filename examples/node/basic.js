@@ -0,0 +1,29 @@
+// Native addon example; build it first with `make node`
+const {
+  WasmKeyPair,
+  WasmKeyType,
+  WasmHttpSigner,
+  WasmHttpVerifier,
+  sha256String,
+  version,
+} = require('../../sage_crypto_core.node');
+
+console.log('SAGE Crypto Core version:', version());
+
+// Generate key pair
+const keypair = new WasmKeyPair(WasmKeyType.Ed25519);
+console.log('Key ID:', keypair.keyId);
+
+// Sign and verify a message
+const message = new TextEncoder().encode('Hello, SAGE!');
+const signature = keypair.sign(message);
+console.log('Signature:', signature.toHex());
+console.log('Valid:', keypair.verify(message, signature));
+
+// Message signing through the HTTP helpers
+const signer = new WasmHttpSigner(keypair);
+const verifier = new WasmHttpVerifier(keypair.getPublicKey());
+const signatureHex = signer.signMessage('GET /api/data');
+console.log('HTTP message valid:', verifier.verifyMessage('GET /api/data', signatureHex));
+
+console.log('SHA-256:', sha256String('Hello, SAGE!'));
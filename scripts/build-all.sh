@@ -85,7 +85,7 @@ build_target() {
     
     # Copy header files for non-WASM targets
     if [[ "${target}" != "wasm32-unknown-unknown" ]]; then
-        cp include/sage_crypto.h "${output_target_dir}/" 2>/dev/null || true
+        "${SCRIPT_DIR}/copy-header.sh" "${target_dir}" "${output_target_dir}" 2>/dev/null || true
     fi
     
     echo "✓ Built for ${target}"
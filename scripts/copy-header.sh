@@ -0,0 +1,20 @@
+#!/bin/bash
+
+# Copy the C header generated by build.rs out of the cargo build directory
+# Usage: ./scripts/copy-header.sh [profile directory] [destination]
+#   e.g. ./scripts/copy-header.sh target/x86_64-unknown-linux-gnu/release dist
+
+set -e
+
+PROFILE_DIR="${1:-target/release}"
+DESTINATION="${2:-include}"
+
+# The most recently generated header, in case several build directories exist
+HEADER="$(ls -t "${PROFILE_DIR}"/build/sage_crypto_core-*/out/sage_crypto.h 2>/dev/null | head -n 1)"
+if [[ -z "${HEADER}" ]]; then
+    echo "No sage_crypto.h under ${PROFILE_DIR}; build with --features ffi first" >&2
+    exit 1
+fi
+
+mkdir -p "${DESTINATION}"
+cp "${HEADER}" "${DESTINATION}/"
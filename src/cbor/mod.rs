@@ -0,0 +1,264 @@
+//! Deterministically encoded CBOR (RFC 8949 Section 4.2.1)
+//!
+//! The compact binary counterpart of [`crate::jcs`]: equal CBOR data items
+//! always encode to the same bytes, whatever the order of their map entries,
+//! so their signatures match.
+
+use crate::crypto::{KeyPair, PublicKey, Signature, Signer, Verifier};
+use crate::error::{Error, Result};
+use serde::Serialize;
+
+pub use ciborium::Value;
+
+/// Encode a CBOR data item deterministically
+///
+/// Integers, lengths and floats use their shortest form, lengths are always
+/// definite and map entries are sorted by the bytes of their encoded keys.
+pub fn canonicalize(value: &Value) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    write_value(&mut out, value)?;
+    Ok(out)
+}
+
+/// Encode any serializable value deterministically
+pub fn to_canonical_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>> {
+    let value = Value::serialized(value).map_err(|e| Error::Serialization(e.to_string()))?;
+    canonicalize(&value)
+}
+
+/// Sign the deterministic encoding of `value`
+pub fn sign_cbor<T: Serialize + ?Sized>(keypair: &KeyPair, value: &T) -> Result<Signature> {
+    keypair.sign(&to_canonical_vec(value)?)
+}
+
+/// Verify a signature made with [`sign_cbor`] over any equivalent value
+pub fn verify_cbor<T: Serialize + ?Sized>(
+    public_key: &PublicKey,
+    value: &T,
+    signature: &Signature,
+) -> Result<()> {
+    public_key.verify(&to_canonical_vec(value)?, signature)
+}
+
+/// CBOR major types
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const TEXT: u8 = 3;
+const ARRAY: u8 = 4;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+
+fn write_value(out: &mut Vec<u8>, value: &Value) -> Result<()> {
+    match value {
+        Value::Integer(integer) => {
+            let integer = i128::from(*integer);
+            if integer >= 0 {
+                write_head(out, UNSIGNED, integer as u64);
+            } else {
+                write_head(out, NEGATIVE, (-1 - integer) as u64);
+            }
+        }
+        Value::Bytes(bytes) => {
+            write_head(out, BYTES, bytes.len() as u64);
+            out.extend_from_slice(bytes);
+        }
+        Value::Text(text) => {
+            write_head(out, TEXT, text.len() as u64);
+            out.extend_from_slice(text.as_bytes());
+        }
+        Value::Float(float) => write_float(out, *float),
+        Value::Bool(false) => out.push(0xf4),
+        Value::Bool(true) => out.push(0xf5),
+        Value::Null => out.push(0xf6),
+        Value::Tag(tag, content) => {
+            write_head(out, TAG, *tag);
+            write_value(out, content)?;
+        }
+        Value::Array(items) => {
+            write_head(out, ARRAY, items.len() as u64);
+            for item in items {
+                write_value(out, item)?;
+            }
+        }
+        Value::Map(entries) => {
+            let mut encoded = Vec::with_capacity(entries.len());
+            for (key, entry) in entries {
+                encoded.push((canonicalize(key)?, canonicalize(entry)?));
+            }
+            encoded.sort_by(|(a, _), (b, _)| a.cmp(b));
+            if encoded.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+                return Err(Error::Serialization(
+                    "CBOR map has duplicate keys".to_string(),
+                ));
+            }
+
+            write_head(out, MAP, encoded.len() as u64);
+            for (key, entry) in encoded {
+                out.extend_from_slice(&key);
+                out.extend_from_slice(&entry);
+            }
+        }
+        _ => {
+            return Err(Error::Unsupported(format!(
+                "Unsupported CBOR value {value:?}"
+            )))
+        }
+    }
+    Ok(())
+}
+
+/// Write the initial byte and shortest argument of a data item
+fn write_head(out: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+    if argument < 24 {
+        out.push(major | argument as u8);
+    } else if let Ok(argument) = u8::try_from(argument) {
+        out.push(major | 24);
+        out.push(argument);
+    } else if let Ok(argument) = u16::try_from(argument) {
+        out.push(major | 25);
+        out.extend_from_slice(&argument.to_be_bytes());
+    } else if let Ok(argument) = u32::try_from(argument) {
+        out.push(major | 26);
+        out.extend_from_slice(&argument.to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&argument.to_be_bytes());
+    }
+}
+
+/// Write a float in the shortest of half, single or double precision that
+/// preserves its value
+fn write_float(out: &mut Vec<u8>, float: f64) {
+    if float.is_nan() {
+        out.extend_from_slice(&[0xf9, 0x7e, 0x00]);
+        return;
+    }
+
+    let single = float as f32;
+    if f64::from(single) != float {
+        out.push(0xfb);
+        out.extend_from_slice(&float.to_bits().to_be_bytes());
+    } else if let Some(half) = half_bits(single) {
+        out.push(0xf9);
+        out.extend_from_slice(&half.to_be_bytes());
+    } else {
+        out.push(0xfa);
+        out.extend_from_slice(&single.to_bits().to_be_bytes());
+    }
+}
+
+/// The IEEE 754 half precision bits of a non-NaN `float`, if it is exactly representable
+fn half_bits(float: f32) -> Option<u16> {
+    let bits = float.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+
+    match exponent {
+        // Infinity
+        0xff => Some(sign | 0x7c00),
+        // Zero; single precision subnormals are too small for half precision
+        0 if mantissa == 0 => Some(sign),
+        0 => None,
+        _ => {
+            let exponent = exponent - 127;
+            if (-14..=15).contains(&exponent) {
+                (mantissa & 0x1fff == 0)
+                    .then(|| sign | ((exponent + 15) as u16) << 10 | (mantissa >> 13) as u16)
+            } else if (-24..-14).contains(&exponent) {
+                // Half precision subnormal in units of 2^-24
+                let significand = 0x80_0000 | mantissa;
+                let shift = -(exponent + 1);
+                (significand.trailing_zeros() as i32 >= shift)
+                    .then(|| sign | (significand >> shift) as u16)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyType;
+
+    fn encode(value: Value) -> String {
+        hex::encode(canonicalize(&value).unwrap())
+    }
+
+    #[test]
+    fn test_integers() {
+        // Examples from RFC 8949 Appendix A
+        assert_eq!(encode(Value::from(0)), "00");
+        assert_eq!(encode(Value::from(23)), "17");
+        assert_eq!(encode(Value::from(24)), "1818");
+        assert_eq!(encode(Value::from(1000)), "1903e8");
+        assert_eq!(encode(Value::from(1_000_000)), "1a000f4240");
+        assert_eq!(
+            encode(Value::from(1_000_000_000_000u64)),
+            "1b000000e8d4a51000"
+        );
+        assert_eq!(encode(Value::from(u64::MAX)), "1bffffffffffffffff");
+        assert_eq!(encode(Value::from(-1)), "20");
+        assert_eq!(encode(Value::from(-1000)), "3903e7");
+    }
+
+    #[test]
+    fn test_floats() {
+        // Examples from RFC 8949 Appendix A
+        assert_eq!(encode(Value::Float(0.0)), "f90000");
+        assert_eq!(encode(Value::Float(-0.0)), "f98000");
+        assert_eq!(encode(Value::Float(1.5)), "f93e00");
+        assert_eq!(encode(Value::Float(65504.0)), "f97bff");
+        assert_eq!(encode(Value::Float(100000.0)), "fa47c35000");
+        assert_eq!(encode(Value::Float(5.960464477539063e-8)), "f90001");
+        assert_eq!(encode(Value::Float(0.00006103515625)), "f90400");
+        assert_eq!(encode(Value::Float(1.1)), "fb3ff199999999999a");
+        assert_eq!(encode(Value::Float(1.0e300)), "fb7e37e43c8800759c");
+        assert_eq!(encode(Value::Float(f64::INFINITY)), "f97c00");
+        assert_eq!(encode(Value::Float(f64::NEG_INFINITY)), "f9fc00");
+        assert_eq!(encode(Value::Float(f64::NAN)), "f97e00");
+    }
+
+    #[test]
+    fn test_map_ordering() {
+        let map = Value::Map(vec![
+            (Value::Text("aa".into()), Value::from(3)),
+            (Value::Text("b".into()), Value::from(2)),
+            (Value::from(10), Value::from(1)),
+        ]);
+        // Keys sort by their encoded bytes: 0x0a < 0x6162 < 0x626161
+        assert_eq!(encode(map), "a30a0161620262616103");
+
+        let duplicate = Value::Map(vec![
+            (Value::Text("a".into()), Value::from(1)),
+            (Value::Text("a".into()), Value::from(2)),
+        ]);
+        assert!(canonicalize(&duplicate).is_err());
+    }
+
+    #[test]
+    fn test_sign_and_verify_cbor() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let message = Value::Map(vec![
+            (Value::Text("task".into()), Value::from(1)),
+            (Value::Text("data".into()), Value::Bytes(vec![1, 2, 3])),
+        ]);
+        let signature = sign_cbor(&keypair, &message).unwrap();
+
+        let reordered = Value::Map(vec![
+            (Value::Text("data".into()), Value::Bytes(vec![1, 2, 3])),
+            (Value::Text("task".into()), Value::from(1)),
+        ]);
+        assert!(verify_cbor(keypair.public_key(), &reordered, &signature).is_ok());
+
+        let changed = Value::Map(vec![
+            (Value::Text("data".into()), Value::Bytes(vec![1, 2, 3])),
+            (Value::Text("task".into()), Value::from(2)),
+        ]);
+        assert!(verify_cbor(keypair.public_key(), &changed, &signature).is_err());
+    }
+}
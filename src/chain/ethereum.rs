@@ -0,0 +1,317 @@
+//! Ethereum addresses and `did:ethr` identifiers
+//!
+//! An Ethereum address is the last 20 bytes of the Keccak-256 hash of an
+//! uncompressed secp256k1 public key, written with the EIP-55 mixed-case
+//! checksum. A `did:ethr` DID names an address or a compressed public key,
+//! optionally on a network other than mainnet.
+
+use crate::crypto::{KeyType, PublicKey};
+use crate::error::{Error, Result};
+use k256::elliptic_curve::sec1::ToEncodedPoint;
+use sha3::{Digest, Keccak256};
+use std::fmt;
+
+/// Length of an Ethereum address in bytes
+const ADDRESS_LENGTH: usize = 20;
+
+/// Length of a compressed secp256k1 public key in bytes
+const PUBLIC_KEY_LENGTH: usize = 33;
+
+/// DID method prefix of `did:ethr`
+const ETHR_PREFIX: &str = "did:ethr:";
+
+/// Network assumed when a `did:ethr` names none
+const DEFAULT_NETWORK: &str = "mainnet";
+
+/// Checksummed Ethereum address of a secp256k1 public key
+pub fn address(public_key: &PublicKey) -> Result<String> {
+    Ok(checksum(&address_bytes(public_key)?))
+}
+
+/// Decode a `0x`-prefixed Ethereum address
+///
+/// All-lowercase and all-uppercase addresses are accepted as is; mixed-case
+/// addresses must carry a valid EIP-55 checksum.
+pub fn decode_address(address: &str) -> Result<[u8; ADDRESS_LENGTH]> {
+    let digits = address
+        .strip_prefix("0x")
+        .ok_or_else(|| invalid_address(address, "missing 0x prefix"))?;
+    if digits.len() != ADDRESS_LENGTH * 2 {
+        return Err(invalid_address(address, "must be 20 bytes"));
+    }
+    let bytes: [u8; ADDRESS_LENGTH] = hex::decode(digits)
+        .map_err(|e| invalid_address(address, &e.to_string()))?
+        .try_into()
+        .map_err(|_| invalid_address(address, "must be 20 bytes"))?;
+
+    let mixed_case = digits.bytes().any(|b| b.is_ascii_lowercase())
+        && digits.bytes().any(|b| b.is_ascii_uppercase());
+    if mixed_case && checksum(&bytes)[2..] != *digits {
+        return Err(invalid_address(address, "invalid EIP-55 checksum"));
+    }
+    Ok(bytes)
+}
+
+/// Rewrite an Ethereum address with its EIP-55 checksum
+pub fn to_checksum_address(address: &str) -> Result<String> {
+    Ok(checksum(&decode_address(address)?))
+}
+
+/// A `did:ethr` identifier
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EthrDid {
+    network: Option<String>,
+    address: [u8; ADDRESS_LENGTH],
+    public_key: Option<[u8; PUBLIC_KEY_LENGTH]>,
+}
+
+impl EthrDid {
+    /// DID naming the Ethereum account `address`
+    pub fn from_address(address: &str) -> Result<Self> {
+        Ok(Self {
+            network: None,
+            address: decode_address(address)?,
+            public_key: None,
+        })
+    }
+
+    /// DID naming the account of a secp256k1 public key, by its address
+    pub fn from_public_key(public_key: &PublicKey) -> Result<Self> {
+        Ok(Self {
+            network: None,
+            address: address_bytes(public_key)?,
+            public_key: None,
+        })
+    }
+
+    /// DID naming a secp256k1 public key itself, in its compressed form
+    pub fn from_public_key_identifier(public_key: &PublicKey) -> Result<Self> {
+        Ok(Self {
+            network: None,
+            address: address_bytes(public_key)?,
+            public_key: match public_key {
+                PublicKey::Secp256k1(key_bytes) => Some(*key_bytes),
+                PublicKey::Ed25519(_) => None,
+            },
+        })
+    }
+
+    /// Set the network, e.g. `sepolia` or a hex chain ID such as `0xaa36a7`
+    ///
+    /// `mainnet` is the default and is left out of the DID.
+    pub fn with_network(mut self, network: impl Into<String>) -> Self {
+        let network = network.into();
+        self.network = (network != DEFAULT_NETWORK).then_some(network);
+        self
+    }
+
+    /// Parse a `did:ethr` DID, ignoring any fragment, path or query
+    pub fn parse(did: &str) -> Result<Self> {
+        let did = did.split(['#', '/', '?']).next().unwrap_or_default();
+        let specific = did
+            .strip_prefix(ETHR_PREFIX)
+            .ok_or_else(|| Error::InvalidInput(format!("Not a did:ethr DID: {did}")))?;
+        let (network, identifier) = match specific.rsplit_once(':') {
+            Some((network, _)) if network.is_empty() || network.contains(':') => {
+                return Err(Error::InvalidInput(format!(
+                    "Invalid did:ethr network: {did}"
+                )))
+            }
+            Some((network, identifier)) => (Some(network), identifier),
+            None => (None, specific),
+        };
+
+        let did = if identifier.len() == 2 + PUBLIC_KEY_LENGTH * 2 {
+            let bytes = identifier
+                .strip_prefix("0x")
+                .and_then(|digits| hex::decode(digits).ok())
+                .ok_or_else(|| {
+                    Error::InvalidInput(format!("Invalid did:ethr public key: {identifier}"))
+                })?;
+            Self::from_public_key_identifier(&PublicKey::from_bytes(KeyType::Secp256k1, &bytes)?)?
+        } else {
+            Self::from_address(identifier)?
+        };
+        Ok(match network {
+            Some(network) => did.with_network(network),
+            None => did,
+        })
+    }
+
+    /// Network of the DID, `mainnet` unless set
+    pub fn network(&self) -> &str {
+        self.network.as_deref().unwrap_or(DEFAULT_NETWORK)
+    }
+
+    /// Checksummed Ethereum address of the account
+    pub fn address(&self) -> String {
+        checksum(&self.address)
+    }
+
+    /// Public key named by the DID, if it names one rather than an address
+    pub fn public_key(&self) -> Option<PublicKey> {
+        self.public_key.map(PublicKey::Secp256k1)
+    }
+
+    /// Whether `public_key` controls the account named by the DID
+    pub fn matches(&self, public_key: &PublicKey) -> bool {
+        address_bytes(public_key).is_ok_and(|address| address == self.address)
+    }
+}
+
+impl fmt::Display for EthrDid {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(ETHR_PREFIX)?;
+        if let Some(network) = &self.network {
+            write!(f, "{network}:")?;
+        }
+        match &self.public_key {
+            Some(public_key) => write!(f, "0x{}", hex::encode(public_key)),
+            None => f.write_str(&checksum(&self.address)),
+        }
+    }
+}
+
+/// Address bytes of a secp256k1 public key
+fn address_bytes(public_key: &PublicKey) -> Result<[u8; ADDRESS_LENGTH]> {
+    let PublicKey::Secp256k1(key_bytes) = public_key else {
+        return Err(Error::InvalidKeyType(
+            "Ethereum addresses require a secp256k1 key".to_string(),
+        ));
+    };
+    let point = k256::PublicKey::from_sec1_bytes(key_bytes)
+        .map_err(|e| Error::InvalidKeyFormat(format!("Invalid secp256k1 point: {e}")))?
+        .to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    let mut address = [0u8; ADDRESS_LENGTH];
+    address.copy_from_slice(&hash[hash.len() - ADDRESS_LENGTH..]);
+    Ok(address)
+}
+
+/// EIP-55 mixed-case encoding of an address
+fn checksum(address: &[u8; ADDRESS_LENGTH]) -> String {
+    let lower = hex::encode(address);
+    let hash = Keccak256::digest(lower.as_bytes());
+    let digits: String = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = (hash[i / 2] >> (4 * (1 - i % 2))) & 0x0f;
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{digits}")
+}
+
+fn invalid_address(address: &str, reason: &str) -> Error {
+    Error::InvalidInput(format!("Invalid Ethereum address {address}: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyPair;
+
+    fn keypair() -> KeyPair {
+        let secret =
+            hex::decode("4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318")
+                .unwrap();
+        KeyPair::from_private_key_bytes(KeyType::Secp256k1, &secret).unwrap()
+    }
+
+    #[test]
+    fn test_address() {
+        assert_eq!(
+            address(keypair().public_key()).unwrap(),
+            "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"
+        );
+
+        let ed25519 = KeyPair::generate(KeyType::Ed25519).unwrap();
+        assert!(address(ed25519.public_key()).is_err());
+    }
+
+    #[test]
+    fn test_eip55_checksum() {
+        // Test vectors from EIP-55
+        for expected in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            assert_eq!(to_checksum_address(expected).unwrap(), expected);
+            let lower = format!("0x{}", expected[2..].to_lowercase());
+            assert_eq!(to_checksum_address(&lower).unwrap(), expected);
+            let upper = format!("0x{}", expected[2..].to_uppercase());
+            assert_eq!(to_checksum_address(&upper).unwrap(), expected);
+        }
+
+        assert!(decode_address("0x5aaeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+        assert!(decode_address("5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed").is_err());
+        assert!(decode_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeA").is_err());
+    }
+
+    #[test]
+    fn test_ethr_did() {
+        let keypair = keypair();
+        let did = EthrDid::from_public_key(keypair.public_key()).unwrap();
+        assert_eq!(
+            did.to_string(),
+            "did:ethr:0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"
+        );
+        assert_eq!(did.network(), "mainnet");
+        assert!(did.matches(keypair.public_key()));
+        assert!(!did.matches(KeyPair::generate(KeyType::Secp256k1).unwrap().public_key()));
+
+        let parsed =
+            EthrDid::parse("did:ethr:0x2c7536e3605d9c16a7a3d7b1898e529396a65c23#controller")
+                .unwrap();
+        assert_eq!(parsed, did);
+        assert_eq!(
+            EthrDid::parse("did:ethr:mainnet:0x2c7536E3605D9C16a7a3D7b1898e529396a65c23").unwrap(),
+            did
+        );
+
+        let sepolia = did.clone().with_network("sepolia");
+        assert_eq!(
+            sepolia.to_string(),
+            "did:ethr:sepolia:0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"
+        );
+        assert_eq!(EthrDid::parse(&sepolia.to_string()).unwrap(), sepolia);
+    }
+
+    #[test]
+    fn test_ethr_did_public_key() {
+        let keypair = keypair();
+        let did = EthrDid::from_public_key_identifier(keypair.public_key())
+            .unwrap()
+            .with_network("0xaa36a7");
+        let expected = "did:ethr:0xaa36a7:0x024e3b81af9c2234cad09d679ce6035ed1392347ce64ce405f5dcd36228a25de6e";
+        assert_eq!(did.to_string(), expected);
+
+        let parsed = EthrDid::parse(expected).unwrap();
+        assert_eq!(parsed.network(), "0xaa36a7");
+        assert_eq!(
+            parsed.public_key().map(|key| key.to_bytes()),
+            Some(keypair.public_key_bytes())
+        );
+        assert_eq!(
+            parsed.address(),
+            "0x2c7536E3605D9C16a7a3D7b1898e529396a65c23"
+        );
+        assert!(parsed.matches(keypair.public_key()));
+
+        for invalid in [
+            "did:key:0x2c7536E3605D9C16a7a3D7b1898e529396a65c23",
+            "did:ethr:0x2c7536E3605D9C16a7a3D7b1898e529396a65c2",
+            "did:ethr::0x2c7536E3605D9C16a7a3D7b1898e529396a65c23",
+            "did:ethr:a:b:0x2c7536E3605D9C16a7a3D7b1898e529396a65c23",
+        ] {
+            assert!(EthrDid::parse(invalid).is_err(), "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! Blockchain account utilities
+//!
+//! Helpers that map agent keys to the account addresses of blockchains, so
+//! an agent's identity can be correlated with its on-chain accounts.
+
+pub mod ethereum;
+pub mod solana;
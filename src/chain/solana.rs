@@ -0,0 +1,109 @@
+//! Solana addresses
+//!
+//! A Solana account address is the base58 encoding of a 32 byte Ed25519
+//! public key. Program derived addresses are deliberately off the curve, so
+//! only on-curve addresses parse as public keys.
+
+use crate::crypto::{KeyType, PublicKey};
+use crate::error::{Error, Result};
+
+/// Length of a Solana address in bytes
+const ADDRESS_LENGTH: usize = 32;
+
+/// Solana address of an Ed25519 public key
+pub fn address(public_key: &PublicKey) -> Result<String> {
+    match public_key {
+        PublicKey::Ed25519(key_bytes) => Ok(bs58::encode(key_bytes).into_string()),
+        PublicKey::Secp256k1(_) => Err(Error::InvalidKeyType(
+            "Solana addresses require an Ed25519 key".to_string(),
+        )),
+    }
+}
+
+/// Decode a Solana address into its 32 bytes, on or off the curve
+pub fn decode_address(address: &str) -> Result<[u8; ADDRESS_LENGTH]> {
+    let bytes = bs58::decode(address)
+        .into_vec()
+        .map_err(|e| Error::InvalidInput(format!("Invalid Solana address: {e}")))?;
+    bytes.try_into().map_err(|bytes: Vec<u8>| {
+        Error::InvalidInput(format!(
+            "Solana address must be {ADDRESS_LENGTH} bytes, got {}",
+            bytes.len()
+        ))
+    })
+}
+
+/// Parse a Solana address into the Ed25519 public key it names
+///
+/// Fails for off-curve addresses such as program derived addresses.
+pub fn public_key_from_address(address: &str) -> Result<PublicKey> {
+    let bytes = decode_address(address)?;
+    if !is_on_curve(&bytes) {
+        return Err(Error::InvalidKeyFormat(format!(
+            "Solana address {address} is not an Ed25519 public key"
+        )));
+    }
+    PublicKey::from_bytes(KeyType::Ed25519, &bytes)
+}
+
+/// Whether `address` is a valid Solana address
+pub fn is_valid_address(address: &str) -> bool {
+    decode_address(address).is_ok()
+}
+
+/// Whether `public_key` is the key of the Solana account `address`
+pub fn matches_address(public_key: &PublicKey, address: &str) -> bool {
+    match (public_key, decode_address(address)) {
+        (PublicKey::Ed25519(key_bytes), Ok(bytes)) => *key_bytes == bytes,
+        _ => false,
+    }
+}
+
+fn is_on_curve(bytes: &[u8; ADDRESS_LENGTH]) -> bool {
+    ed25519_dalek::VerifyingKey::from_bytes(bytes).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyPair;
+
+    #[test]
+    fn test_address_round_trip() {
+        let public_key = PublicKey::from_bytes(
+            KeyType::Ed25519,
+            &hex::decode("b00d8d938e7f773d51565aad36a623f5344f7f5d1960f9cf3e8e12620ea2810f")
+                .unwrap(),
+        )
+        .unwrap();
+        let solana_address = address(&public_key).unwrap();
+        assert_eq!(
+            solana_address,
+            "CrEjzKWCvT8wrrjCL3itq2C1zzHFR2w3RWPU3nuvgEce"
+        );
+        assert_eq!(
+            public_key_from_address(&solana_address).unwrap().to_bytes(),
+            public_key.to_bytes()
+        );
+        assert!(matches_address(&public_key, &solana_address));
+
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        assert!(!matches_address(keypair.public_key(), &solana_address));
+
+        let secp256k1 = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        assert!(address(secp256k1.public_key()).is_err());
+    }
+
+    #[test]
+    fn test_invalid_addresses() {
+        // The system program is 32 zero bytes
+        assert!(is_valid_address("11111111111111111111111111111111"));
+        assert!(!is_valid_address("0OIl"));
+        assert!(!is_valid_address("CrEjzKWCvT8wrrjCL3itq2C1zzHFR2w3"));
+
+        // Off-curve addresses, like program derived addresses, name no key
+        let off_curve = bs58::encode([2u8; 32]).into_string();
+        assert!(is_valid_address(&off_curve));
+        assert!(public_key_from_address(&off_curve).is_err());
+    }
+}
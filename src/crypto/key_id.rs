@@ -0,0 +1,157 @@
+//! Key identifier derivation strategies
+
+use crate::crypto::PublicKey;
+use crate::error::{Error, Result};
+use base64::{engine::general_purpose, Engine as _};
+use sha2::{Digest, Sha256};
+use std::fmt;
+use std::sync::Arc;
+
+/// Default number of SHA-256 bytes kept in a truncated key ID
+pub const DEFAULT_KEY_ID_LENGTH: usize = 8;
+
+/// Custom key ID derivation function
+pub type KeyIdFn = dyn Fn(&PublicKey) -> String + Send + Sync;
+
+/// Strategy used to derive a key ID from a public key
+#[derive(Clone)]
+pub enum KeyIdStrategy {
+    /// Hex-encoded SHA-256 of the public key bytes, truncated to the given number of bytes
+    Sha256Truncated(usize),
+    /// Hex-encoded full SHA-256 of the public key bytes
+    Sha256,
+    /// RFC 7638 JWK thumbprint (base64url encoded SHA-256)
+    JwkThumbprint,
+    /// Caller supplied derivation function
+    Custom(Arc<KeyIdFn>),
+}
+
+impl KeyIdStrategy {
+    /// Create a strategy from a custom derivation function
+    pub fn custom<F>(f: F) -> Self
+    where
+        F: Fn(&PublicKey) -> String + Send + Sync + 'static,
+    {
+        KeyIdStrategy::Custom(Arc::new(f))
+    }
+
+    /// Derive the key ID for a public key
+    pub fn derive(&self, public_key: &PublicKey) -> Result<String> {
+        match self {
+            KeyIdStrategy::Sha256Truncated(len) => {
+                if *len == 0 || *len > 32 {
+                    return Err(Error::InvalidInput(
+                        "Key ID truncation length must be between 1 and 32 bytes".to_string(),
+                    ));
+                }
+                let digest = Sha256::digest(public_key.to_bytes());
+                Ok(hex::encode(&digest[..*len]))
+            }
+            KeyIdStrategy::Sha256 => Ok(hex::encode(Sha256::digest(public_key.to_bytes()))),
+            KeyIdStrategy::JwkThumbprint => jwk_thumbprint(public_key),
+            KeyIdStrategy::Custom(f) => Ok(f(public_key)),
+        }
+    }
+}
+
+impl Default for KeyIdStrategy {
+    fn default() -> Self {
+        KeyIdStrategy::Sha256Truncated(DEFAULT_KEY_ID_LENGTH)
+    }
+}
+
+impl fmt::Debug for KeyIdStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyIdStrategy::Sha256Truncated(len) => {
+                f.debug_tuple("Sha256Truncated").field(len).finish()
+            }
+            KeyIdStrategy::Sha256 => write!(f, "Sha256"),
+            KeyIdStrategy::JwkThumbprint => write!(f, "JwkThumbprint"),
+            KeyIdStrategy::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+/// Compute the RFC 7638 JWK thumbprint of a public key
+pub fn jwk_thumbprint(public_key: &PublicKey) -> Result<String> {
+    // Members must be in lexicographic order with no whitespace (RFC 7638 section 3)
+    let canonical = match public_key {
+        PublicKey::Ed25519(key_bytes) => format!(
+            "{{\"crv\":\"Ed25519\",\"kty\":\"OKP\",\"x\":\"{}\"}}",
+            general_purpose::URL_SAFE_NO_PAD.encode(key_bytes)
+        ),
+        PublicKey::Secp256k1(key_bytes) => {
+            use k256::elliptic_curve::sec1::ToEncodedPoint;
+
+            let key = k256::PublicKey::from_sec1_bytes(key_bytes).map_err(|e| {
+                Error::InvalidKeyFormat(format!("Invalid Secp256k1 public key: {e}"))
+            })?;
+            let point = key.to_encoded_point(false);
+            let x = point
+                .x()
+                .ok_or_else(|| Error::InvalidKeyFormat("Missing x coordinate".to_string()))?;
+            let y = point
+                .y()
+                .ok_or_else(|| Error::InvalidKeyFormat("Missing y coordinate".to_string()))?;
+            format!(
+                "{{\"crv\":\"secp256k1\",\"kty\":\"EC\",\"x\":\"{}\",\"y\":\"{}\"}}",
+                general_purpose::URL_SAFE_NO_PAD.encode(x),
+                general_purpose::URL_SAFE_NO_PAD.encode(y)
+            )
+        }
+    };
+
+    Ok(general_purpose::URL_SAFE_NO_PAD.encode(Sha256::digest(canonical.as_bytes())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType};
+
+    #[test]
+    fn test_default_matches_public_key_id() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let key_id = KeyIdStrategy::default()
+            .derive(keypair.public_key())
+            .unwrap();
+        assert_eq!(key_id, keypair.public_key().key_id());
+        assert_eq!(key_id.len(), 16);
+    }
+
+    #[test]
+    fn test_full_and_truncated_lengths() {
+        let keypair = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        let full = KeyIdStrategy::Sha256.derive(keypair.public_key()).unwrap();
+        let short = KeyIdStrategy::Sha256Truncated(4)
+            .derive(keypair.public_key())
+            .unwrap();
+        assert_eq!(full.len(), 64);
+        assert!(full.starts_with(&short));
+        assert!(KeyIdStrategy::Sha256Truncated(33)
+            .derive(keypair.public_key())
+            .is_err());
+    }
+
+    #[test]
+    fn test_jwk_thumbprint_rfc8037_vector() {
+        // RFC 8037 Appendix A.3
+        let x = general_purpose::URL_SAFE_NO_PAD
+            .decode("11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo")
+            .unwrap();
+        let public_key = PublicKey::from_bytes(KeyType::Ed25519, &x).unwrap();
+        assert_eq!(
+            jwk_thumbprint(&public_key).unwrap(),
+            "kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k"
+        );
+    }
+
+    #[test]
+    fn test_custom_strategy() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let strategy = KeyIdStrategy::custom(|key| format!("agent-{}", key.to_bytes()[0]));
+        let key_id = strategy.derive(keypair.public_key()).unwrap();
+        assert!(key_id.starts_with("agent-"));
+    }
+}
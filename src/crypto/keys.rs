@@ -1,5 +1,6 @@
 //! Key pair management and operations
 
+use crate::crypto::key_id::{KeyIdStrategy, DEFAULT_KEY_ID_LENGTH};
 use crate::crypto::{Algorithm, Signature, Signer, Verifier};
 use crate::error::{Error, Result};
 use hex;
@@ -8,6 +9,12 @@ use rand::rngs::OsRng;
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
 
+/// Minimum seed length accepted by [`KeyPair::from_seed`]
+pub const MIN_SEED_LENGTH: usize = 32;
+
+/// Domain separator for seeded key derivation
+const SEED_DERIVATION_LABEL: &[u8] = b"sage-keypair-seed-v1";
+
 /// Key types supported by SAGE
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -65,7 +72,12 @@ impl PublicKey {
         let mut hasher = Sha256::new();
         hasher.update(self.to_bytes());
         let result = hasher.finalize();
-        hex::encode(&result[..8])
+        hex::encode(&result[..DEFAULT_KEY_ID_LENGTH])
+    }
+
+    /// Get the key ID derived with a specific strategy
+    pub fn key_id_with(&self, strategy: &KeyIdStrategy) -> Result<String> {
+        strategy.derive(self)
     }
 
     /// Create PublicKey from bytes
@@ -134,6 +146,15 @@ impl PrivateKey {
         }
     }
 
+    /// Overwrite the key material with zeros
+    pub fn wipe(&mut self) {
+        use zeroize::Zeroize;
+
+        match self {
+            PrivateKey::Ed25519(bytes) | PrivateKey::Secp256k1(bytes) => bytes.zeroize(),
+        }
+    }
+
     /// Encode private key to bytes (CAUTION: contains secret material)
     pub fn to_bytes(&self) -> Vec<u8> {
         match self {
@@ -210,14 +231,15 @@ impl KeyPair {
         &self.private_key
     }
 
+    /// Re-derive the key ID with a different strategy
+    pub fn with_key_id_strategy(mut self, strategy: KeyIdStrategy) -> Result<Self> {
+        self.key_id = strategy.derive(&self.public_key)?;
+        Ok(self)
+    }
+
     /// Generate key ID from public key
     fn generate_key_id(public_key: &PublicKey) -> String {
-        use sha2::{Digest, Sha256};
-
-        let mut hasher = Sha256::new();
-        hasher.update(public_key.to_bytes());
-        let result = hasher.finalize();
-        hex::encode(&result[..8])
+        public_key.key_id()
     }
 
     /// Create KeyPair from parts (used by importers)
@@ -230,6 +252,13 @@ impl KeyPair {
         }
     }
 
+    /// Overwrite the private key of this copy with zeros
+    ///
+    /// Clones made earlier, e.g. by an `HttpSigner`, keep their own copy.
+    pub fn wipe(&mut self) {
+        self.private_key.wipe();
+    }
+
     /// Get private key bytes
     pub fn private_key_bytes(&self) -> Vec<u8> {
         match &self.private_key {
@@ -243,6 +272,40 @@ impl KeyPair {
         self.public_key.to_bytes()
     }
 
+    /// Derive a key pair deterministically from a seed, e.g. a hardware-provisioned secret
+    ///
+    /// The private key is SHA-256 over a domain separator, the key type, a
+    /// counter and the seed. The counter is only bumped for the rare hashes
+    /// that are not a valid Secp256k1 scalar.
+    pub fn from_seed(key_type: KeyType, seed: &[u8]) -> Result<Self> {
+        use sha2::{Digest, Sha256};
+
+        if seed.len() < MIN_SEED_LENGTH {
+            return Err(Error::InvalidInput(format!(
+                "Seed must be at least {MIN_SEED_LENGTH} bytes"
+            )));
+        }
+
+        let key_type_label: &[u8] = match key_type {
+            KeyType::Ed25519 => b"ed25519",
+            KeyType::Secp256k1 => b"secp256k1",
+        };
+        for counter in 0..=u8::MAX {
+            let mut hasher = Sha256::new();
+            hasher.update(SEED_DERIVATION_LABEL);
+            hasher.update(key_type_label);
+            hasher.update([counter]);
+            hasher.update(seed);
+            if let Ok(keypair) = Self::from_private_key_bytes(key_type, &hasher.finalize()) {
+                return Ok(keypair);
+            }
+        }
+
+        Err(Error::KeyGeneration(
+            "Seed did not yield a valid private key".to_string(),
+        ))
+    }
+
     /// Create KeyPair from private key bytes
     pub fn from_private_key_bytes(key_type: KeyType, bytes: &[u8]) -> Result<Self> {
         let private_key = match key_type {
@@ -356,6 +419,113 @@ impl Verifier for PublicKey {
     }
 }
 
+impl From<ed25519_dalek::VerifyingKey> for PublicKey {
+    fn from(key: ed25519_dalek::VerifyingKey) -> Self {
+        PublicKey::Ed25519(key.to_bytes())
+    }
+}
+
+impl From<k256::ecdsa::VerifyingKey> for PublicKey {
+    fn from(key: k256::ecdsa::VerifyingKey) -> Self {
+        let compressed_point = key.to_encoded_point(true);
+        let mut bytes = [0u8; 33];
+        bytes.copy_from_slice(compressed_point.as_bytes());
+        PublicKey::Secp256k1(bytes)
+    }
+}
+
+impl From<k256::PublicKey> for PublicKey {
+    fn from(key: k256::PublicKey) -> Self {
+        k256::ecdsa::VerifyingKey::from(key).into()
+    }
+}
+
+impl TryFrom<&PublicKey> for ed25519_dalek::VerifyingKey {
+    type Error = Error;
+
+    fn try_from(key: &PublicKey) -> Result<Self> {
+        match key {
+            PublicKey::Ed25519(key_bytes) => ed25519_dalek::VerifyingKey::from_bytes(key_bytes)
+                .map_err(|e| Error::InvalidKeyFormat(format!("Invalid Ed25519 public key: {e}"))),
+            PublicKey::Secp256k1(_) => Err(Error::InvalidKeyType(
+                "Expected an Ed25519 public key".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<&PublicKey> for k256::ecdsa::VerifyingKey {
+    type Error = Error;
+
+    fn try_from(key: &PublicKey) -> Result<Self> {
+        match key {
+            PublicKey::Secp256k1(key_bytes) => {
+                k256::ecdsa::VerifyingKey::from_sec1_bytes(key_bytes).map_err(|e| {
+                    Error::InvalidKeyFormat(format!("Invalid Secp256k1 public key: {e}"))
+                })
+            }
+            PublicKey::Ed25519(_) => Err(Error::InvalidKeyType(
+                "Expected a Secp256k1 public key".to_string(),
+            )),
+        }
+    }
+}
+
+impl From<ed25519_dalek::SigningKey> for PrivateKey {
+    fn from(key: ed25519_dalek::SigningKey) -> Self {
+        PrivateKey::Ed25519(key.to_bytes())
+    }
+}
+
+impl From<k256::ecdsa::SigningKey> for PrivateKey {
+    fn from(key: k256::ecdsa::SigningKey) -> Self {
+        PrivateKey::Secp256k1(key.to_bytes().into())
+    }
+}
+
+impl TryFrom<&PrivateKey> for ed25519_dalek::SigningKey {
+    type Error = Error;
+
+    fn try_from(key: &PrivateKey) -> Result<Self> {
+        match key {
+            PrivateKey::Ed25519(key_bytes) => Ok(ed25519_dalek::SigningKey::from_bytes(key_bytes)),
+            PrivateKey::Secp256k1(_) => Err(Error::InvalidKeyType(
+                "Expected an Ed25519 private key".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<&PrivateKey> for k256::ecdsa::SigningKey {
+    type Error = Error;
+
+    fn try_from(key: &PrivateKey) -> Result<Self> {
+        match key {
+            PrivateKey::Secp256k1(key_bytes) => k256::ecdsa::SigningKey::from_bytes(key_bytes)
+                .map_err(|e| {
+                    Error::InvalidKeyFormat(format!("Invalid Secp256k1 private key: {e}"))
+                }),
+            PrivateKey::Ed25519(_) => Err(Error::InvalidKeyType(
+                "Expected a Secp256k1 private key".to_string(),
+            )),
+        }
+    }
+}
+
+impl From<ed25519_dalek::SigningKey> for KeyPair {
+    fn from(key: ed25519_dalek::SigningKey) -> Self {
+        let public_key = key.verifying_key().into();
+        Self::from_parts(key.into(), public_key)
+    }
+}
+
+impl From<k256::ecdsa::SigningKey> for KeyPair {
+    fn from(key: k256::ecdsa::SigningKey) -> Self {
+        let public_key = key.verifying_key().into();
+        Self::from_parts(key.into(), public_key)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -397,4 +567,76 @@ mod tests {
         // Wrong message should fail
         assert!(keypair.verify(b"Wrong message", &signature).is_err());
     }
+
+    #[test]
+    fn test_dalek_interop_roundtrip() {
+        let signing_key = crate::crypto::ed25519::generate_signing_key();
+        let keypair = KeyPair::from(signing_key.clone());
+        assert_eq!(
+            keypair.public_key().to_bytes(),
+            signing_key.verifying_key().to_bytes().to_vec()
+        );
+
+        let restored = ed25519_dalek::SigningKey::try_from(keypair.private_key()).unwrap();
+        assert_eq!(restored.to_bytes(), signing_key.to_bytes());
+        assert!(k256::ecdsa::VerifyingKey::try_from(keypair.public_key()).is_err());
+    }
+
+    #[test]
+    fn test_k256_interop_roundtrip() {
+        let signing_key = crate::crypto::secp256k1::generate_signing_key();
+        let keypair = KeyPair::from(signing_key.clone());
+
+        let verifying_key = k256::ecdsa::VerifyingKey::try_from(keypair.public_key()).unwrap();
+        assert_eq!(verifying_key, signing_key.verifying_key());
+
+        let restored = k256::ecdsa::SigningKey::try_from(keypair.private_key()).unwrap();
+        assert_eq!(restored.to_bytes(), signing_key.to_bytes());
+        assert!(ed25519_dalek::SigningKey::try_from(keypair.private_key()).is_err());
+    }
+
+    #[test]
+    fn test_keypair_from_seed() {
+        let seed = [7u8; MIN_SEED_LENGTH];
+        for key_type in [KeyType::Ed25519, KeyType::Secp256k1] {
+            let keypair = KeyPair::from_seed(key_type, &seed).unwrap();
+            let again = KeyPair::from_seed(key_type, &seed).unwrap();
+            assert_eq!(keypair.key_type(), key_type);
+            assert_eq!(keypair.private_key_bytes(), again.private_key_bytes());
+            assert_eq!(keypair.key_id(), again.key_id());
+
+            let other = KeyPair::from_seed(key_type, &[8u8; MIN_SEED_LENGTH]).unwrap();
+            assert_ne!(keypair.private_key_bytes(), other.private_key_bytes());
+        }
+
+        // The key type is part of the derivation
+        let ed25519 = KeyPair::from_seed(KeyType::Ed25519, &seed).unwrap();
+        let secp256k1 = KeyPair::from_seed(KeyType::Secp256k1, &seed).unwrap();
+        assert_ne!(ed25519.private_key_bytes(), secp256k1.private_key_bytes());
+
+        assert!(KeyPair::from_seed(KeyType::Ed25519, &seed[..16]).is_err());
+    }
+
+    #[test]
+    fn test_keypair_wipe() {
+        let mut keypair = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        keypair.wipe();
+        assert_eq!(keypair.private_key_bytes(), vec![0u8; 32]);
+    }
+
+    #[test]
+    fn test_keypair_key_id_strategy() {
+        let keypair = KeyPair::generate(KeyType::Ed25519)
+            .unwrap()
+            .with_key_id_strategy(KeyIdStrategy::Sha256)
+            .unwrap();
+        assert_eq!(keypair.key_id().len(), 64);
+        assert_eq!(
+            keypair.key_id(),
+            keypair
+                .public_key()
+                .key_id_with(&KeyIdStrategy::Sha256)
+                .unwrap()
+        );
+    }
 }
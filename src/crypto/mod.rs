@@ -1,12 +1,18 @@
 //! Cryptographic primitives and key management
 
 pub mod ed25519;
+pub mod key_id;
 pub mod keys;
 pub mod secp256k1;
 pub mod signature;
+pub mod stream;
+pub mod wrap;
 
-pub use keys::{KeyPair, KeyType, PrivateKey, PublicKey};
+pub use key_id::KeyIdStrategy;
+pub use keys::{KeyPair, KeyType, PrivateKey, PublicKey, MIN_SEED_LENGTH};
 pub use signature::{Signature, Signer, Verifier};
+pub use stream::SignStream;
+pub use wrap::{WrapAlgorithm, WrappedKey};
 
 /// Supported key types
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
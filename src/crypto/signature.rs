@@ -1,6 +1,7 @@
 //! Signature types and traits
 
-use crate::error::Result;
+use super::keys::KeyType;
+use crate::error::{Error, Result};
 use base64::{engine::general_purpose, Engine as _};
 
 /// Signature abstraction
@@ -13,6 +14,18 @@ pub enum Signature {
 }
 
 impl Signature {
+    /// Decode a signature of the given key type, as produced by `to_bytes`
+    ///
+    /// Secp256k1 signatures may be DER or fixed-size `r || s`.
+    pub fn from_bytes(key_type: KeyType, bytes: &[u8]) -> Result<Self> {
+        match key_type {
+            KeyType::Ed25519 => super::ed25519::signature_from_bytes(bytes).map(Signature::Ed25519),
+            KeyType::Secp256k1 => {
+                super::secp256k1::signature_from_bytes(bytes).map(Signature::Secp256k1)
+            }
+        }
+    }
+
     /// Encode signature to bytes
     pub fn to_bytes(&self) -> Vec<u8> {
         match self {
@@ -35,6 +48,44 @@ impl Signature {
     }
 }
 
+impl From<ed25519_dalek::Signature> for Signature {
+    fn from(sig: ed25519_dalek::Signature) -> Self {
+        Signature::Ed25519(sig)
+    }
+}
+
+impl From<k256::ecdsa::Signature> for Signature {
+    fn from(sig: k256::ecdsa::Signature) -> Self {
+        Signature::Secp256k1(sig)
+    }
+}
+
+impl TryFrom<Signature> for ed25519_dalek::Signature {
+    type Error = Error;
+
+    fn try_from(sig: Signature) -> Result<Self> {
+        match sig {
+            Signature::Ed25519(sig) => Ok(sig),
+            Signature::Secp256k1(_) => Err(Error::InvalidKeyType(
+                "Expected an Ed25519 signature".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<Signature> for k256::ecdsa::Signature {
+    type Error = Error;
+
+    fn try_from(sig: Signature) -> Result<Self> {
+        match sig {
+            Signature::Secp256k1(sig) => Ok(sig),
+            Signature::Ed25519(_) => Err(Error::InvalidKeyType(
+                "Expected a Secp256k1 signature".to_string(),
+            )),
+        }
+    }
+}
+
 /// Trait for signing messages
 pub trait Signer {
     /// Sign a message
@@ -0,0 +1,95 @@
+//! Incremental signing of messages fed in chunks
+
+use crate::crypto::{KeyPair, PrivateKey, Signature, Signer};
+use crate::error::{Error, Result};
+use sha2::{Digest, Sha256};
+
+/// Message state accumulated by a [`SignStream`]
+enum StreamState {
+    /// Ed25519 hashes the message twice, so it has to be buffered
+    Buffered(Vec<u8>),
+    /// Secp256k1 signs the SHA-256 of the message, which is computed as it arrives
+    Hashed(Sha256),
+}
+
+/// Signer for messages fed in chunks
+///
+/// Produces the same signature as signing the concatenated chunks at once.
+pub struct SignStream {
+    keypair: KeyPair,
+    state: StreamState,
+}
+
+impl SignStream {
+    /// Start signing a message with `keypair`
+    pub fn new(keypair: KeyPair) -> Self {
+        let state = match keypair.private_key() {
+            PrivateKey::Ed25519(_) => StreamState::Buffered(Vec::new()),
+            PrivateKey::Secp256k1(_) => StreamState::Hashed(Sha256::new()),
+        };
+        Self { keypair, state }
+    }
+
+    /// Feed the next chunk of the message
+    pub fn update(&mut self, chunk: &[u8]) {
+        match &mut self.state {
+            StreamState::Buffered(buffer) => buffer.extend_from_slice(chunk),
+            StreamState::Hashed(hasher) => hasher.update(chunk),
+        }
+    }
+
+    /// Sign the message fed so far
+    pub fn finalize(self) -> Result<Signature> {
+        match (self.state, self.keypair.private_key()) {
+            (StreamState::Buffered(buffer), _) => self.keypair.sign(&buffer),
+            (StreamState::Hashed(hasher), PrivateKey::Secp256k1(key_bytes)) => {
+                use k256::ecdsa::signature::DigestSigner;
+                use k256::ecdsa::SigningKey;
+
+                let signing_key = SigningKey::from_bytes(key_bytes).map_err(|e| {
+                    Error::CryptoError(format!("Invalid Secp256k1 private key: {e}"))
+                })?;
+                let signature: k256::ecdsa::Signature = signing_key
+                    .try_sign_digest(hasher)
+                    .map_err(|e| Error::Signature(e.to_string()))?;
+                Ok(Signature::Secp256k1(signature))
+            }
+            (StreamState::Hashed(_), PrivateKey::Ed25519(_)) => Err(Error::InvalidKeyType(
+                "Ed25519 messages must be buffered".to_string(),
+            )),
+        }
+    }
+}
+
+impl KeyPair {
+    /// Start signing a message fed in chunks
+    pub fn sign_stream(&self) -> SignStream {
+        SignStream::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyType, Verifier};
+
+    #[test]
+    fn test_sign_stream_matches_one_shot() {
+        let message = b"a large payload split into several chunks";
+        for key_type in [KeyType::Ed25519, KeyType::Secp256k1] {
+            let keypair = KeyPair::generate(key_type).unwrap();
+            let mut stream = keypair.sign_stream();
+            for chunk in message.chunks(7) {
+                stream.update(chunk);
+            }
+            let signature = stream.finalize().unwrap();
+
+            keypair.verify(message, &signature).unwrap();
+            // Both algorithms are deterministic
+            assert_eq!(
+                signature.to_bytes(),
+                keypair.sign(message).unwrap().to_bytes()
+            );
+        }
+    }
+}
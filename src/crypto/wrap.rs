@@ -0,0 +1,344 @@
+//! Wrapping of private keys for escrow and transport
+//!
+//! A private key is wrapped either under a shared 256-bit key-encryption key
+//! with AES Key Wrap (RFC 3394), or for a recipient's X25519 public key with
+//! an ephemeral X25519 exchange, HKDF-SHA256 and ChaCha20-Poly1305.
+//!
+//! Ed25519 identity keys can act as recipients through their X25519 form,
+//! see [`x25519_public_key`] and [`x25519_secret_key`].
+
+use crate::crypto::{KeyPair, KeyType, PrivateKey, PublicKey};
+use crate::error::{Error, Result};
+use rand::rngs::OsRng;
+use zeroize::Zeroize;
+
+/// Domain separator for the recipient KEK derivation
+const WRAP_KDF_LABEL: &[u8] = b"sage-key-wrap-v1";
+
+/// Length of the wrapped key material
+const KEY_LENGTH: usize = 32;
+
+/// Length of the AES Key Wrap integrity block
+const AES_KW_OVERHEAD: usize = 8;
+
+/// Algorithms used to wrap a private key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WrapAlgorithm {
+    /// AES-256 Key Wrap under a shared key-encryption key
+    Aes256Kw,
+    /// ChaCha20-Poly1305 under a KEK derived from an ephemeral X25519 exchange
+    X25519ChaCha20Poly1305,
+}
+
+impl WrapAlgorithm {
+    fn to_byte(self) -> u8 {
+        match self {
+            WrapAlgorithm::Aes256Kw => 1,
+            WrapAlgorithm::X25519ChaCha20Poly1305 => 2,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self> {
+        match byte {
+            1 => Ok(WrapAlgorithm::Aes256Kw),
+            2 => Ok(WrapAlgorithm::X25519ChaCha20Poly1305),
+            other => Err(Error::InvalidKeyFormat(format!(
+                "Unknown key wrap algorithm: {other}"
+            ))),
+        }
+    }
+}
+
+/// A private key encrypted under another key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WrappedKey {
+    algorithm: WrapAlgorithm,
+    key_type: KeyType,
+    ephemeral_public_key: Option<[u8; 32]>,
+    ciphertext: Vec<u8>,
+}
+
+impl WrappedKey {
+    /// Wrap `key` under a 256-bit key-encryption key with AES Key Wrap
+    pub fn wrap_with_kek(key: &PrivateKey, kek: &[u8; 32]) -> Result<Self> {
+        let mut plaintext = key.to_bytes();
+        let mut ciphertext = vec![0u8; KEY_LENGTH + AES_KW_OVERHEAD];
+        let wrapped = aes_kw::KekAes256::from(*kek).wrap(&plaintext, &mut ciphertext);
+        plaintext.zeroize();
+        wrapped.map_err(|e| Error::CryptoError(format!("AES key wrap failed: {e}")))?;
+
+        Ok(Self {
+            algorithm: WrapAlgorithm::Aes256Kw,
+            key_type: key.key_type(),
+            ephemeral_public_key: None,
+            ciphertext,
+        })
+    }
+
+    /// Wrap `key` for the holder of the X25519 secret matching `recipient`
+    pub fn wrap_for_recipient(key: &PrivateKey, recipient: &[u8; 32]) -> Result<Self> {
+        use chacha20poly1305::aead::{Aead, Payload};
+
+        let ephemeral = x25519_dalek::EphemeralSecret::random_from_rng(OsRng);
+        let ephemeral_public = x25519_dalek::PublicKey::from(&ephemeral).to_bytes();
+        let shared = ephemeral.diffie_hellman(&x25519_dalek::PublicKey::from(*recipient));
+        if !shared.was_contributory() {
+            return Err(Error::InvalidInput(
+                "Recipient X25519 public key is a low-order point".to_string(),
+            ));
+        }
+
+        let mut wrapped = Self {
+            algorithm: WrapAlgorithm::X25519ChaCha20Poly1305,
+            key_type: key.key_type(),
+            ephemeral_public_key: Some(ephemeral_public),
+            ciphertext: Vec::new(),
+        };
+        let (cipher, nonce) = derive_cipher(
+            WRAP_KDF_LABEL,
+            shared.as_bytes(),
+            &ephemeral_public,
+            recipient,
+        )?;
+        let mut plaintext = key.to_bytes();
+        let sealed = cipher.encrypt(
+            &nonce,
+            Payload {
+                msg: &plaintext,
+                aad: &wrapped.header(),
+            },
+        );
+        plaintext.zeroize();
+        wrapped.ciphertext =
+            sealed.map_err(|_| Error::CryptoError("Key encryption failed".to_string()))?;
+        Ok(wrapped)
+    }
+
+    /// Unwrap a key wrapped with [`WrappedKey::wrap_with_kek`]
+    pub fn unwrap_with_kek(&self, kek: &[u8; 32]) -> Result<KeyPair> {
+        self.expect_algorithm(WrapAlgorithm::Aes256Kw)?;
+        if self.ciphertext.len() != KEY_LENGTH + AES_KW_OVERHEAD {
+            return Err(Error::InvalidKeyFormat(
+                "Wrapped key has an invalid length".to_string(),
+            ));
+        }
+
+        let mut plaintext = [0u8; KEY_LENGTH];
+        aes_kw::KekAes256::from(*kek)
+            .unwrap(&self.ciphertext, &mut plaintext)
+            .map_err(|_| Error::CryptoError("Key unwrap failed".to_string()))?;
+        let keypair = KeyPair::from_private_key_bytes(self.key_type, &plaintext);
+        plaintext.zeroize();
+        keypair
+    }
+
+    /// Unwrap a key wrapped with [`WrappedKey::wrap_for_recipient`] using the
+    /// recipient's X25519 secret
+    pub fn unwrap_with_secret(&self, secret: &[u8; 32]) -> Result<KeyPair> {
+        use chacha20poly1305::aead::{Aead, Payload};
+
+        self.expect_algorithm(WrapAlgorithm::X25519ChaCha20Poly1305)?;
+        let ephemeral_public = self.ephemeral_public_key.ok_or_else(|| {
+            Error::InvalidKeyFormat("Wrapped key has no ephemeral public key".to_string())
+        })?;
+
+        let secret = x25519_dalek::StaticSecret::from(*secret);
+        let recipient = x25519_dalek::PublicKey::from(&secret).to_bytes();
+        let shared = secret.diffie_hellman(&x25519_dalek::PublicKey::from(ephemeral_public));
+        let (cipher, nonce) = derive_cipher(
+            WRAP_KDF_LABEL,
+            shared.as_bytes(),
+            &ephemeral_public,
+            &recipient,
+        )?;
+        let mut plaintext = cipher
+            .decrypt(
+                &nonce,
+                Payload {
+                    msg: &self.ciphertext,
+                    aad: &self.header(),
+                },
+            )
+            .map_err(|_| Error::CryptoError("Key unwrap failed".to_string()))?;
+        let keypair = KeyPair::from_private_key_bytes(self.key_type, &plaintext);
+        plaintext.zeroize();
+        keypair
+    }
+
+    /// The algorithm the key is wrapped with
+    pub fn algorithm(&self) -> WrapAlgorithm {
+        self.algorithm
+    }
+
+    /// The type of the wrapped key
+    pub fn key_type(&self) -> KeyType {
+        self.key_type
+    }
+
+    /// Encode as algorithm, key type, ephemeral public key (if any) and ciphertext
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.header();
+        bytes.extend_from_slice(&self.ciphertext);
+        bytes
+    }
+
+    /// Decode a wrapped key encoded with [`WrappedKey::to_bytes`]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let [algorithm, key_type, rest @ ..] = bytes else {
+            return Err(Error::InvalidKeyFormat(
+                "Wrapped key is truncated".to_string(),
+            ));
+        };
+        let algorithm = WrapAlgorithm::from_byte(*algorithm)?;
+        let key_type = match key_type {
+            1 => KeyType::Ed25519,
+            2 => KeyType::Secp256k1,
+            other => {
+                return Err(Error::InvalidKeyType(format!(
+                    "Unknown wrapped key type: {other}"
+                )))
+            }
+        };
+
+        let (ephemeral_public_key, ciphertext) = match algorithm {
+            WrapAlgorithm::Aes256Kw => (None, rest),
+            WrapAlgorithm::X25519ChaCha20Poly1305 => {
+                if rest.len() < 32 {
+                    return Err(Error::InvalidKeyFormat(
+                        "Wrapped key is truncated".to_string(),
+                    ));
+                }
+                let (public_key, ciphertext) = rest.split_at(32);
+                let mut ephemeral = [0u8; 32];
+                ephemeral.copy_from_slice(public_key);
+                (Some(ephemeral), ciphertext)
+            }
+        };
+
+        Ok(Self {
+            algorithm,
+            key_type,
+            ephemeral_public_key,
+            ciphertext: ciphertext.to_vec(),
+        })
+    }
+
+    /// Encoded fields preceding the ciphertext, authenticated by the AEAD
+    fn header(&self) -> Vec<u8> {
+        let key_type = match self.key_type {
+            KeyType::Ed25519 => 1,
+            KeyType::Secp256k1 => 2,
+        };
+        let mut header = vec![self.algorithm.to_byte(), key_type];
+        if let Some(ephemeral) = &self.ephemeral_public_key {
+            header.extend_from_slice(ephemeral);
+        }
+        header
+    }
+
+    fn expect_algorithm(&self, algorithm: WrapAlgorithm) -> Result<()> {
+        if self.algorithm != algorithm {
+            return Err(Error::InvalidInput(format!(
+                "Key is wrapped with {:?}, not {algorithm:?}",
+                self.algorithm
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Derive the single-use cipher and nonce from an X25519 shared secret
+pub(crate) fn derive_cipher(
+    label: &[u8],
+    shared: &[u8; 32],
+    ephemeral_public: &[u8; 32],
+    recipient: &[u8; 32],
+) -> Result<(chacha20poly1305::ChaCha20Poly1305, chacha20poly1305::Nonce)> {
+    use chacha20poly1305::KeyInit;
+
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(ephemeral_public);
+    salt[32..].copy_from_slice(recipient);
+
+    let mut okm = [0u8; 44];
+    hkdf::Hkdf::<sha2::Sha256>::new(Some(&salt), shared)
+        .expand(label, &mut okm)
+        .map_err(|e| Error::CryptoError(format!("KEK derivation failed: {e}")))?;
+    let cipher = chacha20poly1305::ChaCha20Poly1305::new_from_slice(&okm[..32])
+        .map_err(|e| Error::CryptoError(format!("Invalid KEK: {e}")));
+    let nonce = *chacha20poly1305::Nonce::from_slice(&okm[32..]);
+    okm.zeroize();
+    Ok((cipher?, nonce))
+}
+
+/// X25519 form of an Ed25519 public key, for use as a wrapping recipient
+pub fn x25519_public_key(public_key: &PublicKey) -> Result<[u8; 32]> {
+    match public_key {
+        PublicKey::Ed25519(bytes) => {
+            let verifying_key = ed25519_dalek::VerifyingKey::from_bytes(bytes)
+                .map_err(|e| Error::InvalidKeyFormat(format!("Invalid Ed25519 key: {e}")))?;
+            Ok(verifying_key.to_montgomery().to_bytes())
+        }
+        PublicKey::Secp256k1(_) => Err(Error::InvalidKeyType(
+            "Only Ed25519 keys have an X25519 form".to_string(),
+        )),
+    }
+}
+
+/// X25519 form of an Ed25519 private key, matching [`x25519_public_key`]
+pub fn x25519_secret_key(private_key: &PrivateKey) -> Result<[u8; 32]> {
+    match private_key {
+        PrivateKey::Ed25519(bytes) => {
+            Ok(ed25519_dalek::SigningKey::from_bytes(bytes).to_scalar_bytes())
+        }
+        PrivateKey::Secp256k1(_) => Err(Error::InvalidKeyType(
+            "Only Ed25519 keys have an X25519 form".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::RngCore;
+
+    #[test]
+    fn test_wrap_with_kek() {
+        let mut kek = [0u8; 32];
+        OsRng.fill_bytes(&mut kek);
+        let keypair = KeyPair::generate(KeyType::Secp256k1).unwrap();
+
+        let wrapped = WrappedKey::wrap_with_kek(keypair.private_key(), &kek).unwrap();
+        let decoded = WrappedKey::from_bytes(&wrapped.to_bytes()).unwrap();
+        assert_eq!(decoded, wrapped);
+
+        let unwrapped = decoded.unwrap_with_kek(&kek).unwrap();
+        assert_eq!(unwrapped.private_key_bytes(), keypair.private_key_bytes());
+        assert!(decoded.unwrap_with_kek(&[0u8; 32]).is_err());
+        assert!(decoded.unwrap_with_secret(&kek).is_err());
+    }
+
+    #[test]
+    fn test_wrap_for_recipient() {
+        let recipient = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let other = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+        let recipient_public = x25519_public_key(recipient.public_key()).unwrap();
+        let wrapped =
+            WrappedKey::wrap_for_recipient(keypair.private_key(), &recipient_public).unwrap();
+        let decoded = WrappedKey::from_bytes(&wrapped.to_bytes()).unwrap();
+
+        let secret = x25519_secret_key(recipient.private_key()).unwrap();
+        let unwrapped = decoded.unwrap_with_secret(&secret).unwrap();
+        assert_eq!(unwrapped.key_id(), keypair.key_id());
+
+        let wrong = x25519_secret_key(other.private_key()).unwrap();
+        assert!(decoded.unwrap_with_secret(&wrong).is_err());
+
+        let mut tampered = wrapped.to_bytes();
+        tampered[1] = 2;
+        let tampered = WrappedKey::from_bytes(&tampered).unwrap();
+        assert!(tampered.unwrap_with_secret(&secret).is_err());
+    }
+}
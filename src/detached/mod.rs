@@ -0,0 +1,272 @@
+//! Detached signatures over files
+//!
+//! A [`DetachedSignature`] is stored next to the file it signs, e.g.
+//! `agent.wasm.sig`, so build artifacts referenced by SAGE messages can be
+//! signed out-of-band:
+//!
+//! ```text
+//! -----BEGIN SAGE SIGNATURE-----
+//! Key-Id: 1f2e3d4c5b6a7988
+//! Algorithm: ed25519
+//! Created: 1700000000
+//!
+//! 8b6Vq3nH0m2cA4rW...
+//! -----END SAGE SIGNATURE-----
+//! ```
+//!
+//! The signature covers the SHA-256 digest of the file together with the
+//! key ID, algorithm and creation time, canonicalized with JCS (RFC 8785).
+
+use crate::crypto::{KeyPair, PublicKey, Signature, Signer, Verifier};
+use crate::error::{Error, Result};
+use crate::jcs;
+use crate::rfc9421::{KeyResolver, SignatureAlgorithm};
+use base64::{
+    engine::general_purpose::{STANDARD, URL_SAFE_NO_PAD},
+    Engine as _,
+};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::Path;
+
+const BEGIN: &str = "-----BEGIN SAGE SIGNATURE-----";
+const END: &str = "-----END SAGE SIGNATURE-----";
+
+/// Line length of the armored base64 signature
+const LINE_LENGTH: usize = 64;
+
+/// A signature stored separately from the data it signs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DetachedSignature {
+    /// Key ID of the signing key
+    pub key_id: String,
+    /// RFC 9421 algorithm identifier, e.g. `ed25519`
+    pub alg: String,
+    /// Unix timestamp of signing
+    pub created: i64,
+    /// Signature bytes
+    pub signature: Vec<u8>,
+}
+
+impl DetachedSignature {
+    /// Sign `data` with `keypair`
+    pub fn sign(keypair: &KeyPair, data: &[u8]) -> Result<Self> {
+        Self::sign_digest(
+            keypair,
+            &Sha256::digest(data),
+            chrono::Utc::now().timestamp(),
+        )
+    }
+
+    /// Sign `data` with `keypair`, recording `created` as the signing time
+    pub fn sign_at(keypair: &KeyPair, data: &[u8], created: i64) -> Result<Self> {
+        Self::sign_digest(keypair, &Sha256::digest(data), created)
+    }
+
+    /// Sign everything read from `reader` with `keypair`
+    pub fn sign_reader<R: Read>(keypair: &KeyPair, reader: R) -> Result<Self> {
+        Self::sign_digest(
+            keypair,
+            &digest_reader(reader)?,
+            chrono::Utc::now().timestamp(),
+        )
+    }
+
+    /// Sign the file at `path` with `keypair`
+    pub fn sign_file(keypair: &KeyPair, path: impl AsRef<Path>) -> Result<Self> {
+        Self::sign_reader(keypair, std::fs::File::open(path)?)
+    }
+
+    fn sign_digest(keypair: &KeyPair, digest: &[u8], created: i64) -> Result<Self> {
+        let mut signature = Self {
+            key_id: keypair.key_id().to_string(),
+            alg: SignatureAlgorithm::for_key_type(keypair.key_type())
+                .identifier()
+                .to_string(),
+            created,
+            signature: Vec::new(),
+        };
+        signature.signature = keypair.sign(&signature.signing_input(digest)?)?.to_bytes();
+        Ok(signature)
+    }
+
+    /// Verify the signature over `data` with `public_key`
+    pub fn verify(&self, public_key: &PublicKey, data: &[u8]) -> Result<()> {
+        self.verify_digest(public_key, &Sha256::digest(data))
+    }
+
+    /// Verify the signature over everything read from `reader` with `public_key`
+    pub fn verify_reader<R: Read>(&self, public_key: &PublicKey, reader: R) -> Result<()> {
+        self.verify_digest(public_key, &digest_reader(reader)?)
+    }
+
+    /// Verify the signature over the file at `path` with `public_key`
+    pub fn verify_file(&self, public_key: &PublicKey, path: impl AsRef<Path>) -> Result<()> {
+        self.verify_reader(public_key, std::fs::File::open(path)?)
+    }
+
+    /// Verify the signature over `data` with the key `resolver` finds for `key_id`
+    pub fn verify_with(&self, resolver: &dyn KeyResolver, data: &[u8]) -> Result<()> {
+        self.verify(&resolver.resolve(&self.key_id)?, data)
+    }
+
+    fn verify_digest(&self, public_key: &PublicKey, digest: &[u8]) -> Result<()> {
+        let expected = SignatureAlgorithm::for_key_type(public_key.key_type());
+        if self.alg != expected.identifier() {
+            return Err(Error::Verification(format!(
+                "Signature algorithm {} does not match the {:?} key",
+                self.alg,
+                public_key.key_type()
+            )));
+        }
+
+        let signature = Signature::from_bytes(public_key.key_type(), &self.signature)?;
+        public_key.verify(&self.signing_input(digest)?, &signature)
+    }
+
+    /// Encode in the armored text format
+    pub fn to_armored(&self) -> String {
+        let encoded = STANDARD.encode(&self.signature);
+        let mut armored = format!(
+            "{BEGIN}\nKey-Id: {}\nAlgorithm: {}\nCreated: {}\n\n",
+            self.key_id, self.alg, self.created
+        );
+        // Base64 is ASCII, so chunking bytes never splits a character
+        for line in encoded.as_bytes().chunks(LINE_LENGTH) {
+            armored.push_str(&String::from_utf8_lossy(line));
+            armored.push('\n');
+        }
+        armored.push_str(END);
+        armored.push('\n');
+        armored
+    }
+
+    /// Parse the armored text format
+    pub fn from_armored(armored: &str) -> Result<Self> {
+        let invalid =
+            |reason: &str| Error::InvalidInput(format!("Invalid detached signature: {reason}"));
+
+        let mut lines = armored.lines().map(str::trim_end);
+        if lines.by_ref().find(|line| !line.is_empty()) != Some(BEGIN) {
+            return Err(invalid("missing BEGIN line"));
+        }
+
+        let (mut key_id, mut alg, mut created) = (None, None, None);
+        for line in lines.by_ref() {
+            if line.is_empty() {
+                break;
+            }
+            let (name, value) = line
+                .split_once(": ")
+                .ok_or_else(|| invalid("malformed header"))?;
+            match name {
+                "Key-Id" => key_id = Some(value.to_string()),
+                "Algorithm" => alg = Some(value.to_string()),
+                "Created" => {
+                    created = Some(value.parse().map_err(|_| invalid("malformed Created"))?)
+                }
+                // Unknown headers are ignored so the format can grow
+                _ => {}
+            }
+        }
+
+        let mut encoded = String::new();
+        let mut ended = false;
+        for line in lines {
+            if line == END {
+                ended = true;
+                break;
+            }
+            encoded.push_str(line.trim());
+        }
+        if !ended {
+            return Err(invalid("missing END line"));
+        }
+
+        Ok(Self {
+            key_id: key_id.ok_or_else(|| invalid("missing Key-Id"))?,
+            alg: alg.ok_or_else(|| invalid("missing Algorithm"))?,
+            created: created.ok_or_else(|| invalid("missing Created"))?,
+            signature: STANDARD.decode(encoded)?,
+        })
+    }
+
+    /// The bytes covered by the signature
+    fn signing_input(&self, digest: &[u8]) -> Result<Vec<u8>> {
+        let members = serde_json::json!({
+            "alg": self.alg,
+            "created": self.created,
+            "key_id": self.key_id,
+            "sha256": URL_SAFE_NO_PAD.encode(digest),
+        });
+        Ok(jcs::canonicalize(&members)?.into_bytes())
+    }
+}
+
+/// SHA-256 digest of everything read from `reader`
+fn digest_reader<R: Read>(mut reader: R) -> Result<Vec<u8>> {
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        match reader.read(&mut buffer)? {
+            0 => break,
+            n => hasher.update(&buffer[..n]),
+        }
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyType;
+    use crate::rfc9421::KeySet;
+
+    #[test]
+    fn test_sign_and_verify() {
+        for key_type in [KeyType::Ed25519, KeyType::Secp256k1] {
+            let keypair = KeyPair::generate(key_type).unwrap();
+            let data = vec![7u8; 20_000];
+            let signature = DetachedSignature::sign(&keypair, &data).unwrap();
+
+            let parsed = DetachedSignature::from_armored(&signature.to_armored()).unwrap();
+            assert_eq!(parsed, signature);
+            assert!(parsed.verify(keypair.public_key(), &data).is_ok());
+            assert!(parsed
+                .verify_reader(keypair.public_key(), data.as_slice())
+                .is_ok());
+
+            let key_set = KeySet::new().with_key(keypair.public_key().clone());
+            assert!(parsed.verify_with(&key_set, &data).is_ok());
+            assert!(parsed.verify(keypair.public_key(), b"other").is_err());
+        }
+    }
+
+    #[test]
+    fn test_sign_file() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let path = std::env::temp_dir().join(format!("sage-detached-{}", keypair.key_id()));
+        std::fs::write(&path, b"artifact").unwrap();
+
+        let signature = DetachedSignature::sign_file(&keypair, &path).unwrap();
+        assert!(signature.verify_file(keypair.public_key(), &path).is_ok());
+        assert!(signature.verify(keypair.public_key(), b"artifact").is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_tampered_metadata() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let signature = DetachedSignature::sign_at(&keypair, b"data", 1_700_000_000).unwrap();
+
+        let armored = signature
+            .to_armored()
+            .replace("Created: 1700000000", "Created: 1700000001");
+        let tampered = DetachedSignature::from_armored(&armored).unwrap();
+        assert!(tampered.verify(keypair.public_key(), b"data").is_err());
+
+        assert!(DetachedSignature::from_armored("not a signature").is_err());
+        let truncated = signature.to_armored().replace(END, "");
+        assert!(DetachedSignature::from_armored(&truncated).is_err());
+    }
+}
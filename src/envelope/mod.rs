@@ -0,0 +1,222 @@
+//! Signed JSON envelopes for non-HTTP transports
+//!
+//! An [`Envelope`] carries a payload with the `key_id`, `alg` and `created`
+//! time of its signature, so messages sent over queues or websockets can be
+//! verified the same way as RFC 9421 signed HTTP messages:
+//!
+//! ```json
+//! {"payload":"eyJ0YXNrIjoxfQ","key_id":"1f2e3d4c5b6a7988","alg":"ed25519","created":1700000000,"signature":"..."}
+//! ```
+//!
+//! The payload and signature are base64url encoded without padding. The
+//! signature covers the other four members as a JSON object canonicalized
+//! with JCS (RFC 8785).
+//!
+//! A [`SealedEnvelope`] additionally encrypts the envelope to its recipient.
+
+pub mod sealed;
+
+pub use sealed::SealedEnvelope;
+
+use crate::crypto::{KeyPair, PublicKey, Signature, Signer, Verifier};
+use crate::error::{Error, Result};
+use crate::jcs;
+use crate::rfc9421::{KeyResolver, SignatureAlgorithm};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde::{Deserialize, Serialize};
+
+/// A payload with its signature
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Envelope {
+    /// Payload, base64url encoded
+    pub payload: String,
+    /// Key ID of the signing key
+    pub key_id: String,
+    /// RFC 9421 algorithm identifier, e.g. `ed25519`
+    pub alg: String,
+    /// Unix timestamp of signing
+    pub created: i64,
+    /// Signature over the other members, base64url encoded
+    pub signature: String,
+}
+
+impl Envelope {
+    /// Sign `payload` with `keypair`
+    pub fn sign(keypair: &KeyPair, payload: &[u8]) -> Result<Self> {
+        Self::sign_at(keypair, payload, chrono::Utc::now().timestamp())
+    }
+
+    /// Sign `payload` with `keypair`, recording `created` as the signing time
+    pub fn sign_at(keypair: &KeyPair, payload: &[u8], created: i64) -> Result<Self> {
+        let mut envelope = Self {
+            payload: URL_SAFE_NO_PAD.encode(payload),
+            key_id: keypair.key_id().to_string(),
+            alg: SignatureAlgorithm::for_key_type(keypair.key_type())
+                .identifier()
+                .to_string(),
+            created,
+            signature: String::new(),
+        };
+        let signature = keypair.sign(&envelope.signing_input()?)?;
+        envelope.signature = URL_SAFE_NO_PAD.encode(signature.to_bytes());
+        Ok(envelope)
+    }
+
+    /// Verify the signature with `public_key` and return the decoded payload
+    pub fn verify(&self, public_key: &PublicKey) -> Result<Vec<u8>> {
+        let expected = SignatureAlgorithm::for_key_type(public_key.key_type());
+        if self.alg != expected.identifier() {
+            return Err(Error::Verification(format!(
+                "Envelope algorithm {} does not match the {:?} key",
+                self.alg,
+                public_key.key_type()
+            )));
+        }
+
+        let signature = URL_SAFE_NO_PAD.decode(&self.signature)?;
+        let signature = Signature::from_bytes(public_key.key_type(), &signature)?;
+        public_key.verify(&self.signing_input()?, &signature)?;
+        self.payload()
+    }
+
+    /// Verify the signature with the key `resolver` finds for `key_id`
+    pub fn verify_with(&self, resolver: &dyn KeyResolver) -> Result<Vec<u8>> {
+        self.verify(&resolver.resolve(&self.key_id)?)
+    }
+
+    /// Countersign this envelope with `keypair`
+    ///
+    /// The countersignature is an envelope whose payload is this envelope,
+    /// so it covers the original signature as well as the payload.
+    pub fn countersign(&self, keypair: &KeyPair) -> Result<Self> {
+        Self::sign(keypair, self.to_json()?.as_bytes())
+    }
+
+    /// Countersign this envelope with `keypair`, recording `created` as the signing time
+    pub fn countersign_at(&self, keypair: &KeyPair, created: i64) -> Result<Self> {
+        Self::sign_at(keypair, self.to_json()?.as_bytes(), created)
+    }
+
+    /// Decode the countersigned envelope carried as the payload, without verifying either signature
+    pub fn inner(&self) -> Result<Self> {
+        let payload = self.payload()?;
+        let json = std::str::from_utf8(&payload)
+            .map_err(|_| Error::Serialization("Envelope payload is not UTF-8".to_string()))?;
+        Self::from_json(json)
+    }
+
+    /// Verify this envelope and the `countersignatures` envelopes nested in it,
+    /// returning the innermost payload
+    pub fn verify_chain(
+        &self,
+        resolver: &dyn KeyResolver,
+        countersignatures: usize,
+    ) -> Result<Vec<u8>> {
+        let mut envelope = self.clone();
+        for _ in 0..countersignatures {
+            envelope.verify_with(resolver)?;
+            envelope = envelope.inner()?;
+        }
+        envelope.verify_with(resolver)
+    }
+
+    /// Decode the payload without verifying the signature
+    pub fn payload(&self) -> Result<Vec<u8>> {
+        Ok(URL_SAFE_NO_PAD.decode(&self.payload)?)
+    }
+
+    /// Serialize to JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| Error::Serialization(e.to_string()))
+    }
+
+    /// Parse from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| Error::Serialization(format!("Invalid envelope: {e}")))
+    }
+
+    /// The bytes covered by the signature
+    fn signing_input(&self) -> Result<Vec<u8>> {
+        let members = serde_json::json!({
+            "alg": self.alg,
+            "created": self.created,
+            "key_id": self.key_id,
+            "payload": self.payload,
+        });
+        Ok(jcs::canonicalize(&members)?.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyType;
+    use crate::rfc9421::KeySet;
+
+    #[test]
+    fn test_sign_and_verify() {
+        for key_type in [KeyType::Ed25519, KeyType::Secp256k1] {
+            let keypair = KeyPair::generate(key_type).unwrap();
+            let envelope = Envelope::sign(&keypair, b"{\"task\":1}").unwrap();
+
+            let parsed = Envelope::from_json(&envelope.to_json().unwrap()).unwrap();
+            assert_eq!(parsed, envelope);
+            assert_eq!(
+                parsed.verify(keypair.public_key()).unwrap(),
+                b"{\"task\":1}"
+            );
+
+            let key_set = KeySet::new().with_key(keypair.public_key().clone());
+            assert!(parsed.verify_with(&key_set).is_ok());
+
+            let other = KeyPair::generate(key_type).unwrap();
+            assert!(parsed.verify(other.public_key()).is_err());
+        }
+    }
+
+    #[test]
+    fn test_countersignatures() {
+        let agent = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let approver = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        let notary = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let key_set = KeySet::new()
+            .with_key(agent.public_key().clone())
+            .with_key(approver.public_key().clone())
+            .with_key(notary.public_key().clone());
+
+        let envelope = Envelope::sign(&agent, b"transfer").unwrap();
+        let approved = envelope.countersign(&approver).unwrap();
+        let notarized = approved.countersign(&notary).unwrap();
+
+        assert_eq!(notarized.key_id, notary.key_id());
+        assert_eq!(notarized.inner().unwrap(), approved);
+        assert_eq!(notarized.inner().unwrap().inner().unwrap(), envelope);
+        assert_eq!(notarized.verify_chain(&key_set, 2).unwrap(), b"transfer");
+        assert!(notarized.verify_chain(&key_set, 3).is_err());
+
+        // Replacing the original signature invalidates the countersignature
+        let mut forged = envelope.clone();
+        forged.signature = Envelope::sign(&notary, b"transfer").unwrap().signature;
+        let mut approved_forged = approved.clone();
+        approved_forged.payload = URL_SAFE_NO_PAD.encode(forged.to_json().unwrap());
+        assert!(approved_forged.verify(approver.public_key()).is_err());
+    }
+
+    #[test]
+    fn test_tampered_envelope() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let envelope = Envelope::sign_at(&keypair, b"payload", 1_700_000_000).unwrap();
+
+        let mut tampered = envelope.clone();
+        tampered.created += 1;
+        assert!(tampered.verify(keypair.public_key()).is_err());
+
+        let mut tampered = envelope.clone();
+        tampered.payload = URL_SAFE_NO_PAD.encode(b"other");
+        assert!(tampered.verify(keypair.public_key()).is_err());
+
+        let secp256k1 = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        assert!(envelope.verify(secp256k1.public_key()).is_err());
+    }
+}
@@ -0,0 +1,231 @@
+//! Sign-then-encrypt envelopes
+//!
+//! A [`SealedEnvelope`] is an [`Envelope`] encrypted to a recipient's Ed25519
+//! identity key through its X25519 form, with an ephemeral X25519 exchange,
+//! HKDF-SHA256 and ChaCha20-Poly1305. The signed payload starts with the
+//! recipient's X25519 key, so a recipient cannot re-encrypt the sender's
+//! signature to someone else.
+
+use super::Envelope;
+use crate::crypto::wrap::{derive_cipher, x25519_public_key, x25519_secret_key};
+use crate::crypto::{KeyPair, PublicKey};
+use crate::error::{Error, Result};
+use crate::rfc9421::KeyResolver;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use chacha20poly1305::aead::{Aead, Payload};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+/// Domain separator for the content key derivation
+const SEAL_KDF_LABEL: &[u8] = b"sage-sealed-envelope-v1";
+
+/// Length of an X25519 public key
+const X25519_KEY_LENGTH: usize = 32;
+
+/// A signed envelope encrypted to a single recipient
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SealedEnvelope {
+    /// Ephemeral X25519 public key, base64url encoded
+    pub epk: String,
+    /// Encrypted envelope, base64url encoded
+    pub ciphertext: String,
+}
+
+impl SealedEnvelope {
+    /// Sign `payload` with `sender` and encrypt it to the Ed25519 key `recipient`
+    pub fn seal(sender: &KeyPair, recipient: &PublicKey, payload: &[u8]) -> Result<Self> {
+        let recipient = x25519_public_key(recipient)?;
+        let mut signed = Vec::with_capacity(X25519_KEY_LENGTH + payload.len());
+        signed.extend_from_slice(&recipient);
+        signed.extend_from_slice(payload);
+        let mut envelope = Envelope::sign(sender, &signed)?.to_json()?.into_bytes();
+
+        let ephemeral = x25519_dalek::EphemeralSecret::random_from_rng(OsRng);
+        let ephemeral_public = x25519_dalek::PublicKey::from(&ephemeral).to_bytes();
+        let shared = ephemeral.diffie_hellman(&x25519_dalek::PublicKey::from(recipient));
+        if !shared.was_contributory() {
+            return Err(Error::InvalidInput(
+                "Recipient X25519 public key is a low-order point".to_string(),
+            ));
+        }
+
+        let (cipher, nonce) = derive_cipher(
+            SEAL_KDF_LABEL,
+            shared.as_bytes(),
+            &ephemeral_public,
+            &recipient,
+        )?;
+        let sealed = cipher.encrypt(
+            &nonce,
+            Payload {
+                msg: &envelope,
+                aad: &ephemeral_public,
+            },
+        );
+        envelope.zeroize();
+        signed.zeroize();
+        let ciphertext =
+            sealed.map_err(|_| Error::CryptoError("Envelope encryption failed".to_string()))?;
+
+        Ok(Self {
+            epk: URL_SAFE_NO_PAD.encode(ephemeral_public),
+            ciphertext: URL_SAFE_NO_PAD.encode(ciphertext),
+        })
+    }
+
+    /// Decrypt with the Ed25519 key `recipient` and verify the signature of `sender`
+    pub fn open(&self, recipient: &KeyPair, sender: &PublicKey) -> Result<Vec<u8>> {
+        let (envelope, recipient) = self.decrypt(recipient)?;
+        let signed = envelope.verify(sender)?;
+        strip_recipient(signed, &recipient)
+    }
+
+    /// Decrypt with `recipient` and verify the signature with the key
+    /// `resolver` finds, returning the sender's key ID and the payload
+    pub fn open_with(
+        &self,
+        recipient: &KeyPair,
+        resolver: &dyn KeyResolver,
+    ) -> Result<(String, Vec<u8>)> {
+        let (envelope, recipient) = self.decrypt(recipient)?;
+        let signed = envelope.verify_with(resolver)?;
+        Ok((envelope.key_id, strip_recipient(signed, &recipient)?))
+    }
+
+    /// Serialize to JSON
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string(self).map_err(|e| Error::Serialization(e.to_string()))
+    }
+
+    /// Parse from JSON
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json)
+            .map_err(|e| Error::Serialization(format!("Invalid sealed envelope: {e}")))
+    }
+
+    /// Decrypt the inner envelope, returning it with the recipient's X25519 key
+    fn decrypt(&self, recipient: &KeyPair) -> Result<(Envelope, [u8; X25519_KEY_LENGTH])> {
+        let ephemeral_public: [u8; X25519_KEY_LENGTH] =
+            URL_SAFE_NO_PAD.decode(&self.epk)?.try_into().map_err(|_| {
+                Error::InvalidInput("Ephemeral public key must be 32 bytes".to_string())
+            })?;
+        let ciphertext = URL_SAFE_NO_PAD.decode(&self.ciphertext)?;
+
+        let mut secret = x25519_secret_key(recipient.private_key())?;
+        let static_secret = x25519_dalek::StaticSecret::from(secret);
+        secret.zeroize();
+        let recipient_public = x25519_dalek::PublicKey::from(&static_secret).to_bytes();
+        let shared = static_secret.diffie_hellman(&x25519_dalek::PublicKey::from(ephemeral_public));
+
+        let (cipher, nonce) = derive_cipher(
+            SEAL_KDF_LABEL,
+            shared.as_bytes(),
+            &ephemeral_public,
+            &recipient_public,
+        )?;
+        let plaintext = cipher
+            .decrypt(
+                &nonce,
+                Payload {
+                    msg: &ciphertext,
+                    aad: &ephemeral_public,
+                },
+            )
+            .map_err(|_| Error::CryptoError("Envelope decryption failed".to_string()))?;
+        let json = std::str::from_utf8(&plaintext)
+            .map_err(|_| Error::Serialization("Sealed envelope is not UTF-8".to_string()))?;
+        Ok((Envelope::from_json(json)?, recipient_public))
+    }
+}
+
+/// Check that a signed payload was addressed to `recipient` and strip the address
+fn strip_recipient(mut signed: Vec<u8>, recipient: &[u8; X25519_KEY_LENGTH]) -> Result<Vec<u8>> {
+    if !signed.starts_with(recipient) {
+        return Err(Error::Verification(
+            "Envelope was signed for a different recipient".to_string(),
+        ));
+    }
+    Ok(signed.split_off(X25519_KEY_LENGTH))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyType;
+    use crate::rfc9421::KeySet;
+
+    #[test]
+    fn test_seal_and_open() {
+        let sender = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        let recipient = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+        let sealed = SealedEnvelope::seal(&sender, recipient.public_key(), b"secret task").unwrap();
+        let parsed = SealedEnvelope::from_json(&sealed.to_json().unwrap()).unwrap();
+        assert_eq!(parsed, sealed);
+        assert_eq!(
+            parsed.open(&recipient, sender.public_key()).unwrap(),
+            b"secret task"
+        );
+
+        let key_set = KeySet::new().with_key(sender.public_key().clone());
+        let (key_id, payload) = parsed.open_with(&recipient, &key_set).unwrap();
+        assert_eq!(key_id, sender.key_id());
+        assert_eq!(payload, b"secret task");
+
+        let other = KeyPair::generate(KeyType::Ed25519).unwrap();
+        assert!(parsed.open(&other, sender.public_key()).is_err());
+        assert!(parsed.open(&recipient, other.public_key()).is_err());
+    }
+
+    #[test]
+    fn test_tampered_ciphertext() {
+        let sender = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let recipient = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let sealed = SealedEnvelope::seal(&sender, recipient.public_key(), b"payload").unwrap();
+
+        let mut ciphertext = URL_SAFE_NO_PAD.decode(&sealed.ciphertext).unwrap();
+        ciphertext[0] ^= 1;
+        let tampered = SealedEnvelope {
+            ciphertext: URL_SAFE_NO_PAD.encode(ciphertext),
+            ..sealed
+        };
+        assert!(tampered.open(&recipient, sender.public_key()).is_err());
+    }
+
+    #[test]
+    fn test_forwarded_signature() {
+        let sender = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let recipient = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let third_party = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let sealed = SealedEnvelope::seal(&sender, recipient.public_key(), b"payload").unwrap();
+
+        // The recipient re-encrypts the sender's envelope to a third party
+        let (envelope, _) = sealed.decrypt(&recipient).unwrap();
+        let third_party_public = x25519_public_key(third_party.public_key()).unwrap();
+        let ephemeral = x25519_dalek::EphemeralSecret::random_from_rng(OsRng);
+        let ephemeral_public = x25519_dalek::PublicKey::from(&ephemeral).to_bytes();
+        let shared = ephemeral.diffie_hellman(&x25519_dalek::PublicKey::from(third_party_public));
+        let (cipher, nonce) = derive_cipher(
+            SEAL_KDF_LABEL,
+            shared.as_bytes(),
+            &ephemeral_public,
+            &third_party_public,
+        )
+        .unwrap();
+        let ciphertext = cipher
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: envelope.to_json().unwrap().as_bytes(),
+                    aad: &ephemeral_public,
+                },
+            )
+            .unwrap();
+        let forwarded = SealedEnvelope {
+            epk: URL_SAFE_NO_PAD.encode(ephemeral_public),
+            ciphertext: URL_SAFE_NO_PAD.encode(ciphertext),
+        };
+        assert!(forwarded.open(&third_party, sender.public_key()).is_err());
+    }
+}
@@ -36,6 +36,7 @@ impl From<SageKeyFormat> for KeyFormat {
 /// - `format` is a valid format
 /// - `out_data` is a valid pointer with sufficient space
 /// - `out_len` is a valid pointer
+#[cfg(feature = "ffi-private-export")]
 #[no_mangle]
 pub unsafe extern "C" fn sage_keypair_export_format(
     keypair: *const SageKeyPair,
@@ -43,26 +44,56 @@ pub unsafe extern "C" fn sage_keypair_export_format(
     out_data: *mut c_uchar,
     out_len: *mut size_t,
 ) -> SageResult {
-    if keypair.is_null() || out_data.is_null() || out_len.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if keypair.is_null() || out_data.is_null() || out_len.is_null() {
+            return null_argument();
+        }
 
-    let keypair = &(*keypair).inner;
-    let key_format = KeyFormat::from(format);
+        let keypair = &(*keypair).inner;
+        let key_format = KeyFormat::from(format);
 
-    match keypair.private_key().export(key_format) {
-        Ok(exported_data) => {
-            if exported_data.len() > *out_len {
+        match keypair.private_key().export(key_format) {
+            Ok(exported_data) => {
+                if exported_data.len() > *out_len {
+                    *out_len = exported_data.len();
+                    return fail(SageErrorCode::InvalidInput, "Output buffer too small");
+                }
+
+                ptr::copy_nonoverlapping(exported_data.as_ptr(), out_data, exported_data.len());
                 *out_len = exported_data.len();
-                return SageErrorCode::InvalidInput.into();
+                SageErrorCode::Success.into()
             }
+            Err(e) => error_result(e),
+        }
+    })
+}
+
+/// Export a key pair to a specific format, in a buffer allocated by the library
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `keypair` is a valid pointer
+/// - `format` is a valid format
+/// - `out_ptr` and `out_len` are valid pointers
+/// - the buffer is freed with `sage_buffer_free`
+#[cfg(feature = "ffi-private-export")]
+#[no_mangle]
+pub unsafe extern "C" fn sage_keypair_export_format_alloc(
+    keypair: *const SageKeyPair,
+    format: SageKeyFormat,
+    out_ptr: *mut *mut c_uchar,
+    out_len: *mut size_t,
+) -> SageResult {
+    guard(|| {
+        if keypair.is_null() || out_ptr.is_null() || out_len.is_null() {
+            return null_argument();
+        }
 
-            ptr::copy_nonoverlapping(exported_data.as_ptr(), out_data, exported_data.len());
-            *out_len = exported_data.len();
-            SageErrorCode::Success.into()
+        match (*keypair).inner.private_key().export(format.into()) {
+            Ok(exported_data) => bytes_to_c(exported_data, out_ptr, out_len),
+            Err(e) => error_result(e),
         }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    })
 }
 
 /// Import a key pair from a specific format
@@ -82,21 +113,23 @@ pub unsafe extern "C" fn sage_keypair_import_format(
     data_len: size_t,
     out_keypair: *mut *mut SageKeyPair,
 ) -> SageResult {
-    if data.is_null() || out_keypair.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if data.is_null() || out_keypair.is_null() {
+            return null_argument();
+        }
 
-    let data_slice = slice::from_raw_parts(data, data_len);
-    let _key_format = KeyFormat::from(format);
+        let data_slice = slice::from_raw_parts(data, data_len);
+        let _key_format = KeyFormat::from(format);
 
-    match KeyPair::from_private_key_bytes(key_type.into(), data_slice) {
-        Ok(keypair) => {
-            let boxed = Box::new(SageKeyPair { inner: keypair });
-            *out_keypair = Box::into_raw(boxed);
-            SageErrorCode::Success.into()
+        match KeyPair::from_private_key_bytes(key_type.into(), data_slice) {
+            Ok(keypair) => {
+                let boxed = Box::new(SageKeyPair { inner: keypair });
+                *out_keypair = Box::into_raw(boxed);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
         }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    })
 }
 
 /// Export a public key to a specific format
@@ -114,26 +147,55 @@ pub unsafe extern "C" fn sage_public_key_export_format(
     out_data: *mut c_uchar,
     out_len: *mut size_t,
 ) -> SageResult {
-    if public_key.is_null() || out_data.is_null() || out_len.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if public_key.is_null() || out_data.is_null() || out_len.is_null() {
+            return null_argument();
+        }
+
+        let public_key = &(*public_key).inner;
+        let key_format = KeyFormat::from(format);
 
-    let public_key = &(*public_key).inner;
-    let key_format = KeyFormat::from(format);
+        match public_key.export(key_format) {
+            Ok(exported_data) => {
+                if exported_data.len() > *out_len {
+                    *out_len = exported_data.len();
+                    return fail(SageErrorCode::InvalidInput, "Output buffer too small");
+                }
 
-    match public_key.export(key_format) {
-        Ok(exported_data) => {
-            if exported_data.len() > *out_len {
+                ptr::copy_nonoverlapping(exported_data.as_ptr(), out_data, exported_data.len());
                 *out_len = exported_data.len();
-                return SageErrorCode::InvalidInput.into();
+                SageErrorCode::Success.into()
             }
+            Err(e) => error_result(e),
+        }
+    })
+}
+
+/// Export a public key to a specific format, in a buffer allocated by the library
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `public_key` is a valid pointer
+/// - `format` is a valid format
+/// - `out_ptr` and `out_len` are valid pointers
+/// - the buffer is freed with `sage_buffer_free`
+#[no_mangle]
+pub unsafe extern "C" fn sage_public_key_export_format_alloc(
+    public_key: *const SagePublicKey,
+    format: SageKeyFormat,
+    out_ptr: *mut *mut c_uchar,
+    out_len: *mut size_t,
+) -> SageResult {
+    guard(|| {
+        if public_key.is_null() || out_ptr.is_null() || out_len.is_null() {
+            return null_argument();
+        }
 
-            ptr::copy_nonoverlapping(exported_data.as_ptr(), out_data, exported_data.len());
-            *out_len = exported_data.len();
-            SageErrorCode::Success.into()
+        match (*public_key).inner.export(format.into()) {
+            Ok(exported_data) => bytes_to_c(exported_data, out_ptr, out_len),
+            Err(e) => error_result(e),
         }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    })
 }
 
 /// Import a public key from a specific format
@@ -153,21 +215,23 @@ pub unsafe extern "C" fn sage_public_key_import_format(
     data_len: size_t,
     out_public_key: *mut *mut SagePublicKey,
 ) -> SageResult {
-    if data.is_null() || out_public_key.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if data.is_null() || out_public_key.is_null() {
+            return null_argument();
+        }
 
-    let data_slice = slice::from_raw_parts(data, data_len);
-    let _key_format = KeyFormat::from(format);
+        let data_slice = slice::from_raw_parts(data, data_len);
+        let _key_format = KeyFormat::from(format);
 
-    match PublicKey::from_bytes(key_type.into(), data_slice) {
-        Ok(public_key) => {
-            let boxed = Box::new(SagePublicKey { inner: public_key });
-            *out_public_key = Box::into_raw(boxed);
-            SageErrorCode::Success.into()
+        match PublicKey::from_bytes(key_type.into(), data_slice) {
+            Ok(public_key) => {
+                let boxed = Box::new(SagePublicKey { inner: public_key });
+                *out_public_key = Box::into_raw(boxed);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
         }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    })
 }
 
 /// Export a key pair to PEM format (convenience function)
@@ -176,25 +240,28 @@ pub unsafe extern "C" fn sage_public_key_import_format(
 /// The caller must ensure that:
 /// - `keypair` is a valid pointer
 /// - `out_pem` is a valid pointer to receive the PEM string
+#[cfg(feature = "ffi-private-export")]
 #[no_mangle]
 pub unsafe extern "C" fn sage_keypair_to_pem(
     keypair: *const SageKeyPair,
     out_pem: *mut *mut c_char,
 ) -> SageResult {
-    if keypair.is_null() || out_pem.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if keypair.is_null() || out_pem.is_null() {
+            return null_argument();
+        }
 
-    let keypair = &(*keypair).inner;
+        let keypair = &(*keypair).inner;
 
-    match keypair.private_key().export(KeyFormat::Pem) {
-        Ok(pem_data) => {
-            let pem_str = String::from_utf8_lossy(&pem_data);
-            *out_pem = string_to_c(&pem_str);
-            SageErrorCode::Success.into()
+        match keypair.private_key().export(KeyFormat::Pem) {
+            Ok(pem_data) => {
+                let pem_str = String::from_utf8_lossy(&pem_data);
+                *out_pem = string_to_c(&pem_str);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
         }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    })
 }
 
 /// Import a key pair from PEM format (convenience function)
@@ -210,23 +277,25 @@ pub unsafe extern "C" fn sage_keypair_from_pem(
     pem_data: *const c_char,
     out_keypair: *mut *mut SageKeyPair,
 ) -> SageResult {
-    if pem_data.is_null() || out_keypair.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if pem_data.is_null() || out_keypair.is_null() {
+            return null_argument();
+        }
 
-    let pem_str = match CStr::from_ptr(pem_data).to_str() {
-        Ok(s) => s,
-        Err(_) => return SageErrorCode::InvalidInput as SageResult,
-    };
+        let pem_str = match CStr::from_ptr(pem_data).to_str() {
+            Ok(s) => s,
+            Err(_) => return invalid_utf8(),
+        };
 
-    match KeyPair::from_private_key_bytes(key_type.into(), pem_str.as_bytes()) {
-        Ok(keypair) => {
-            let boxed = Box::new(SageKeyPair { inner: keypair });
-            *out_keypair = Box::into_raw(boxed);
-            SageErrorCode::Success.into()
+        match KeyPair::from_private_key_bytes(key_type.into(), pem_str.as_bytes()) {
+            Ok(keypair) => {
+                let boxed = Box::new(SageKeyPair { inner: keypair });
+                *out_keypair = Box::into_raw(boxed);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
         }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    })
 }
 
 /// Export a public key to PEM format (convenience function)
@@ -240,20 +309,22 @@ pub unsafe extern "C" fn sage_public_key_to_pem(
     public_key: *const SagePublicKey,
     out_pem: *mut *mut c_char,
 ) -> SageResult {
-    if public_key.is_null() || out_pem.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if public_key.is_null() || out_pem.is_null() {
+            return null_argument();
+        }
 
-    let public_key = &(*public_key).inner;
+        let public_key = &(*public_key).inner;
 
-    match public_key.export(KeyFormat::Pem) {
-        Ok(pem_data) => {
-            let pem_str = String::from_utf8_lossy(&pem_data);
-            *out_pem = string_to_c(&pem_str);
-            SageErrorCode::Success.into()
+        match public_key.export(KeyFormat::Pem) {
+            Ok(pem_data) => {
+                let pem_str = String::from_utf8_lossy(&pem_data);
+                *out_pem = string_to_c(&pem_str);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
         }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    })
 }
 
 /// Import a public key from PEM format (convenience function)
@@ -269,21 +340,23 @@ pub unsafe extern "C" fn sage_public_key_from_pem(
     pem_data: *const c_char,
     out_public_key: *mut *mut SagePublicKey,
 ) -> SageResult {
-    if pem_data.is_null() || out_public_key.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if pem_data.is_null() || out_public_key.is_null() {
+            return null_argument();
+        }
 
-    let pem_str = match CStr::from_ptr(pem_data).to_str() {
-        Ok(s) => s,
-        Err(_) => return SageErrorCode::InvalidInput as SageResult,
-    };
+        let pem_str = match CStr::from_ptr(pem_data).to_str() {
+            Ok(s) => s,
+            Err(_) => return invalid_utf8(),
+        };
 
-    match PublicKey::from_bytes(key_type.into(), pem_str.as_bytes()) {
-        Ok(public_key) => {
-            let boxed = Box::new(SagePublicKey { inner: public_key });
-            *out_public_key = Box::into_raw(boxed);
-            SageErrorCode::Success.into()
+        match PublicKey::from_bytes(key_type.into(), pem_str.as_bytes()) {
+            Ok(public_key) => {
+                let boxed = Box::new(SagePublicKey { inner: public_key });
+                *out_public_key = Box::into_raw(boxed);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
         }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    })
 }
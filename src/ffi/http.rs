@@ -1,9 +1,16 @@
 //! FFI functions for HTTP signature operations (RFC 9421)
 
 use super::*;
-use crate::rfc9421::{HttpSigner, HttpVerifier};
+use crate::rfc9421::policy::DEFAULT_CLOCK_SKEW;
+use crate::rfc9421::{
+    parse_signature_input, validate_components, Expiry, HttpSigner, HttpVerifier,
+    SignatureComponent, VerificationPolicy,
+};
 use ::http::Request;
+use std::borrow::Cow;
 use std::ffi::CStr;
+use std::mem;
+use std::time::Duration;
 
 /// Opaque handle for HTTP signer
 pub struct SageHttpSigner {
@@ -16,8 +23,11 @@ pub struct SageHttpVerifier {
 }
 
 /// HTTP request structure for FFI
+///
+/// `struct_size` must be set to `sizeof(SageHttpRequest)`.
 #[repr(C)]
 pub struct SageHttpRequest {
+    struct_size: size_t,
     method: *const c_char,
     uri: *const c_char,
     headers: *const SageHttpHeader,
@@ -27,8 +37,11 @@ pub struct SageHttpRequest {
 }
 
 /// HTTP response structure for FFI
+///
+/// `struct_size` must be set to `sizeof(SageHttpResponse)`.
 #[repr(C)]
 pub struct SageHttpResponse {
+    struct_size: size_t,
     status: c_int,
     headers: *const SageHttpHeader,
     headers_count: size_t,
@@ -37,12 +50,103 @@ pub struct SageHttpResponse {
 }
 
 /// HTTP header structure for FFI
+///
+/// `struct_size` must be set to `sizeof(SageHttpHeader)` on every element of
+/// a header array, including arrays the library fills in.
 #[repr(C)]
 pub struct SageHttpHeader {
+    struct_size: size_t,
     name: *const c_char,
     value: *const c_char,
 }
 
+/// Size of `SageHttpRequest` in ABI version 1
+const SAGE_HTTP_REQUEST_V1_SIZE: usize =
+    mem::offset_of!(SageHttpRequest, body_len) + mem::size_of::<size_t>();
+
+/// Size of `SageHttpHeader` in ABI version 1
+const SAGE_HTTP_HEADER_V1_SIZE: usize =
+    mem::offset_of!(SageHttpHeader, value) + mem::size_of::<*const c_char>();
+
+/// Get the element stride of a caller header array from its first element
+unsafe fn header_stride(
+    headers: *const SageHttpHeader,
+    count: size_t,
+) -> std::result::Result<usize, SageResult> {
+    if count == 0 {
+        return Ok(SAGE_HTTP_HEADER_V1_SIZE);
+    }
+    if headers.is_null() {
+        return Err(null_argument());
+    }
+    let stride = (*headers).struct_size;
+    check_struct_size("SageHttpHeader", stride, SAGE_HTTP_HEADER_V1_SIZE)?;
+    Ok(stride)
+}
+
+/// Get the `index`th element of a caller header array
+unsafe fn header_at(
+    headers: *mut SageHttpHeader,
+    stride: usize,
+    index: usize,
+) -> *mut SageHttpHeader {
+    (headers as *mut u8).add(index * stride) as *mut SageHttpHeader
+}
+
+/// Convert an FFI request to a Rust HTTP request
+unsafe fn build_request(
+    request: &SageHttpRequest,
+) -> std::result::Result<Request<Vec<u8>>, SageResult> {
+    check_struct_size(
+        "SageHttpRequest",
+        request.struct_size,
+        SAGE_HTTP_REQUEST_V1_SIZE,
+    )?;
+    if request.method.is_null() || request.uri.is_null() {
+        return Err(null_argument());
+    }
+
+    let method_str = CStr::from_ptr(request.method)
+        .to_str()
+        .map_err(|_| invalid_utf8())?;
+    let uri_str = CStr::from_ptr(request.uri)
+        .to_str()
+        .map_err(|_| invalid_utf8())?;
+
+    // Build HTTP request
+    let mut builder = Request::builder().method(method_str).uri(uri_str);
+
+    // Add headers
+    let stride = header_stride(request.headers, request.headers_count)?;
+    for i in 0..request.headers_count {
+        let header = &*header_at(request.headers as *mut SageHttpHeader, stride, i);
+        if header.name.is_null() || header.value.is_null() {
+            return Err(null_argument());
+        }
+        let name = CStr::from_ptr(header.name)
+            .to_str()
+            .map_err(|_| invalid_utf8())?;
+        let value = CStr::from_ptr(header.value)
+            .to_str()
+            .map_err(|_| invalid_utf8())?;
+        builder = builder.header(name, value);
+    }
+
+    // Add body
+    let body = if request.body.is_null() {
+        Vec::new()
+    } else {
+        slice::from_raw_parts(request.body, request.body_len).to_vec()
+    };
+
+    builder.body(body).map_err(|e| {
+        fail(
+            SageErrorCode::InvalidInput,
+            &format!("Invalid HTTP request: {e}"),
+        )
+    })
+}
+
 /// Create a new HTTP signer
 ///
 /// # Safety
@@ -54,15 +158,17 @@ pub unsafe extern "C" fn sage_http_signer_new(
     keypair: *const SageKeyPair,
     out_signer: *mut *mut SageHttpSigner,
 ) -> SageResult {
-    if keypair.is_null() || out_signer.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if keypair.is_null() || out_signer.is_null() {
+            return null_argument();
+        }
 
-    let keypair = &(*keypair).inner;
-    let signer = HttpSigner::new(keypair.clone());
-    let boxed = Box::new(SageHttpSigner { inner: signer });
-    *out_signer = Box::into_raw(boxed);
-    SageErrorCode::Success.into()
+        let keypair = &(*keypair).inner;
+        let signer = HttpSigner::new(keypair.clone());
+        let boxed = Box::new(SageHttpSigner { inner: signer });
+        *out_signer = Box::into_raw(boxed);
+        SageErrorCode::Success.into()
+    })
 }
 
 /// Free an HTTP signer
@@ -71,9 +177,123 @@ pub unsafe extern "C" fn sage_http_signer_new(
 /// The caller must ensure that `signer` is a valid pointer obtained from `sage_http_signer_new`.
 #[no_mangle]
 pub unsafe extern "C" fn sage_http_signer_free(signer: *mut SageHttpSigner) {
-    if !signer.is_null() {
-        let _ = Box::from_raw(signer);
-    }
+    guard_or((), || {
+        if !signer.is_null() {
+            let _ = Box::from_raw(signer);
+        }
+    })
+}
+
+/// Reconfigure a signer in place
+unsafe fn update_signer(
+    signer: *mut SageHttpSigner,
+    update: impl FnOnce(HttpSigner) -> HttpSigner,
+) -> SageResult {
+    let signer = &mut (*signer).inner;
+    *signer = update(signer.clone());
+    SageErrorCode::Success.into()
+}
+
+/// Set the components covered by signatures
+///
+/// `components` is a structured field inner list, e.g.
+/// `("@method" "@authority" "@path" "content-digest")`.
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `signer` is a valid pointer
+/// - `components` is a valid NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn sage_http_signer_set_components(
+    signer: *mut SageHttpSigner,
+    components: *const c_char,
+) -> SageResult {
+    guard(|| {
+        if signer.is_null() || components.is_null() {
+            return null_argument();
+        }
+
+        let components_str = match CStr::from_ptr(components).to_str() {
+            Ok(s) => s,
+            Err(_) => return invalid_utf8(),
+        };
+        let components = match parse_signature_input(components_str) {
+            Ok((components, _)) => components,
+            Err(e) => return error_result(e),
+        };
+        if components.is_empty() {
+            return fail(
+                SageErrorCode::InvalidInput,
+                "At least one component must be signed",
+            );
+        }
+        if let Err(e) = validate_components(&components, false) {
+            return error_result(e);
+        }
+
+        update_signer(signer, |s| s.with_default_components(components))
+    })
+}
+
+/// Set the lifetime of signatures in seconds; 0 omits the `expires` parameter
+///
+/// # Safety
+/// The caller must ensure that `signer` is a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sage_http_signer_set_ttl(
+    signer: *mut SageHttpSigner,
+    ttl_seconds: u64,
+) -> SageResult {
+    guard(|| {
+        if signer.is_null() {
+            return null_argument();
+        }
+
+        let ttl = (ttl_seconds > 0).then_some(Duration::from_secs(ttl_seconds));
+        update_signer(signer, |s| s.with_expiry_mode(Expiry::from(ttl)))
+    })
+}
+
+/// Enable or disable a random `nonce` parameter on every signature
+///
+/// # Safety
+/// The caller must ensure that `signer` is a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sage_http_signer_set_nonce(
+    signer: *mut SageHttpSigner,
+    enabled: bool,
+) -> SageResult {
+    guard(|| {
+        if signer.is_null() {
+            return null_argument();
+        }
+
+        update_signer(signer, |s| s.with_nonce(enabled))
+    })
+}
+
+/// Set the `tag` parameter of every signature
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `signer` is a valid pointer
+/// - `tag` is a valid NUL-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn sage_http_signer_set_tag(
+    signer: *mut SageHttpSigner,
+    tag: *const c_char,
+) -> SageResult {
+    guard(|| {
+        if signer.is_null() || tag.is_null() {
+            return null_argument();
+        }
+
+        let tag = match CStr::from_ptr(tag).to_str() {
+            Ok(s) => s,
+            Err(_) => return invalid_utf8(),
+        };
+        update_signer(signer, |s| s.with_tag(tag))
+    })
 }
 
 /// Sign an HTTP request
@@ -91,99 +311,67 @@ pub unsafe extern "C" fn sage_http_signer_sign_request(
     out_signed_headers: *mut SageHttpHeader,
     out_headers_count: *mut size_t,
 ) -> SageResult {
-    if signer.is_null()
-        || request.is_null()
-        || out_signed_headers.is_null()
-        || out_headers_count.is_null()
-    {
-        return SageErrorCode::InvalidInput.into();
-    }
-
-    let signer = &(*signer).inner;
-    let request = &*request;
-
-    // Convert FFI request to Rust HTTP request
-    let method_str = match CStr::from_ptr(request.method).to_str() {
-        Ok(s) => s,
-        Err(_) => return SageErrorCode::InvalidInput as SageResult,
-    };
-    let uri_str = match CStr::from_ptr(request.uri).to_str() {
-        Ok(s) => s,
-        Err(_) => return SageErrorCode::InvalidInput as SageResult,
-    };
-
-    // Build HTTP request
-    let mut builder = Request::builder().method(method_str).uri(uri_str);
+    guard(|| {
+        if signer.is_null()
+            || request.is_null()
+            || out_signed_headers.is_null()
+            || out_headers_count.is_null()
+        {
+            return null_argument();
+        }
 
-    // Add headers
-    let headers_slice = slice::from_raw_parts(request.headers, request.headers_count);
-    for header in headers_slice {
-        let name = match CStr::from_ptr(header.name).to_str() {
-            Ok(s) => s,
-            Err(_) => return SageErrorCode::InvalidInput as SageResult,
+        let signer = &(*signer).inner;
+        let http_request = match build_request(&*request) {
+            Ok(req) => req,
+            Err(code) => return code,
         };
-        let value = match CStr::from_ptr(header.value).to_str() {
-            Ok(s) => s,
-            Err(_) => return SageErrorCode::InvalidInput as SageResult,
+        let stride = match header_stride(out_signed_headers, *out_headers_count) {
+            Ok(stride) => stride,
+            Err(code) => return code,
         };
-        builder = builder.header(name, value);
-    }
 
-    // Add body
-    let body = if request.body.is_null() {
-        Vec::new()
-    } else {
-        slice::from_raw_parts(request.body, request.body_len).to_vec()
-    };
-
-    let http_request = match builder.body(body) {
-        Ok(req) => req,
-        Err(_) => return SageErrorCode::InvalidInput as SageResult,
-    };
-
-    // Sign the request
-    match signer.sign_request(http_request) {
-        Ok(signed_request) => {
-            // Extract signature headers
-            let headers = signed_request.headers();
-            let mut header_count = 0;
-
-            // Count signature-related headers
-            if headers.contains_key("signature") {
-                header_count += 1;
-            }
-            if headers.contains_key("signature-input") {
-                header_count += 1;
-            }
-
-            if header_count > *out_headers_count {
-                *out_headers_count = header_count;
-                return SageErrorCode::InvalidInput.into();
-            }
-
-            // Fill output headers
-            let mut idx = 0;
-            if let Some(sig_header) = headers.get("signature") {
-                let sig_name = string_to_c("signature");
-                let sig_value = string_to_c(sig_header.to_str().unwrap());
-                (*out_signed_headers.add(idx)).name = sig_name;
-                (*out_signed_headers.add(idx)).value = sig_value;
-                idx += 1;
+        // Sign the request
+        match signer.sign_request(http_request) {
+            Ok(signed_request) => {
+                // Extract signature headers
+                let headers = signed_request.headers();
+                let mut header_count = 0;
+
+                // Count signature-related headers
+                if headers.contains_key("signature") {
+                    header_count += 1;
+                }
+                if headers.contains_key("signature-input") {
+                    header_count += 1;
+                }
+
+                if header_count > *out_headers_count {
+                    *out_headers_count = header_count;
+                    return fail(SageErrorCode::InvalidInput, "Output buffer too small");
+                }
+
+                // Fill output headers
+                let mut idx = 0;
+                if let Some(sig_header) = headers.get("signature") {
+                    let out = header_at(out_signed_headers, stride, idx);
+                    (*out).name = string_to_c("signature");
+                    (*out).value = string_to_c(sig_header.to_str().unwrap());
+                    idx += 1;
+                }
+
+                if let Some(sig_input_header) = headers.get("signature-input") {
+                    let out = header_at(out_signed_headers, stride, idx);
+                    (*out).name = string_to_c("signature-input");
+                    (*out).value = string_to_c(sig_input_header.to_str().unwrap());
+                    idx += 1;
+                }
+
+                *out_headers_count = idx;
+                SageErrorCode::Success.into()
             }
-
-            if let Some(sig_input_header) = headers.get("signature-input") {
-                let sig_input_name = string_to_c("signature-input");
-                let sig_input_value = string_to_c(sig_input_header.to_str().unwrap());
-                (*out_signed_headers.add(idx)).name = sig_input_name;
-                (*out_signed_headers.add(idx)).value = sig_input_value;
-                idx += 1;
-            }
-
-            *out_headers_count = idx;
-            SageErrorCode::Success.into()
+            Err(e) => error_result(e),
         }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    })
 }
 
 /// Create a new HTTP verifier
@@ -197,15 +385,17 @@ pub unsafe extern "C" fn sage_http_verifier_new(
     public_key: *const SagePublicKey,
     out_verifier: *mut *mut SageHttpVerifier,
 ) -> SageResult {
-    if public_key.is_null() || out_verifier.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if public_key.is_null() || out_verifier.is_null() {
+            return null_argument();
+        }
 
-    let public_key = &(*public_key).inner;
-    let verifier = HttpVerifier::new(public_key.clone());
-    let boxed = Box::new(SageHttpVerifier { inner: verifier });
-    *out_verifier = Box::into_raw(boxed);
-    SageErrorCode::Success.into()
+        let public_key = &(*public_key).inner;
+        let verifier = HttpVerifier::new(public_key.clone());
+        let boxed = Box::new(SageHttpVerifier { inner: verifier });
+        *out_verifier = Box::into_raw(boxed);
+        SageErrorCode::Success.into()
+    })
 }
 
 /// Free an HTTP verifier
@@ -214,9 +404,130 @@ pub unsafe extern "C" fn sage_http_verifier_new(
 /// The caller must ensure that `verifier` is a valid pointer obtained from `sage_http_verifier_new`.
 #[no_mangle]
 pub unsafe extern "C" fn sage_http_verifier_free(verifier: *mut SageHttpVerifier) {
-    if !verifier.is_null() {
-        let _ = Box::from_raw(verifier);
+    guard_or((), || {
+        if !verifier.is_null() {
+            let _ = Box::from_raw(verifier);
+        }
+    })
+}
+
+/// Component bit for `@method` in `SageVerifyOptions::required_components`
+pub const SAGE_COMPONENT_METHOD: u32 = 1 << 0;
+/// Component bit for `@target-uri`
+pub const SAGE_COMPONENT_TARGET_URI: u32 = 1 << 1;
+/// Component bit for `@authority`
+pub const SAGE_COMPONENT_AUTHORITY: u32 = 1 << 2;
+/// Component bit for `@scheme`
+pub const SAGE_COMPONENT_SCHEME: u32 = 1 << 3;
+/// Component bit for `@request-target`
+pub const SAGE_COMPONENT_REQUEST_TARGET: u32 = 1 << 4;
+/// Component bit for `@path`
+pub const SAGE_COMPONENT_PATH: u32 = 1 << 5;
+/// Component bit for `@query`
+pub const SAGE_COMPONENT_QUERY: u32 = 1 << 6;
+/// Component bit for the `content-digest` header
+pub const SAGE_COMPONENT_CONTENT_DIGEST: u32 = 1 << 7;
+
+/// Requirements checked by `sage_http_verifier_verify_request_with_options`
+///
+/// Initialize with `sage_verify_options_default`, which also sets `struct_size`.
+#[repr(C)]
+pub struct SageVerifyOptions {
+    struct_size: size_t,
+    /// Reject signatures whose `created` is older than this many seconds; 0 for no limit
+    max_age_seconds: u64,
+    /// How far in the future `created` may be, in seconds
+    clock_skew_seconds: u64,
+    /// `SAGE_COMPONENT_*` bits the signature must cover
+    required_components: u32,
+    /// Key ID the signature must be made with, or NULL for any
+    required_key_id: *const c_char,
+}
+
+/// Size of `SageVerifyOptions` in ABI version 1
+const SAGE_VERIFY_OPTIONS_V1_SIZE: usize =
+    mem::offset_of!(SageVerifyOptions, required_key_id) + mem::size_of::<*const c_char>();
+
+/// Fill verification options with the defaults of the Rust API
+///
+/// # Safety
+/// The caller must ensure that `options` is a valid pointer.
+#[no_mangle]
+pub unsafe extern "C" fn sage_verify_options_default(
+    options: *mut SageVerifyOptions,
+) -> SageResult {
+    guard(|| {
+        if options.is_null() {
+            return null_argument();
+        }
+
+        *options = SageVerifyOptions {
+            struct_size: mem::size_of::<SageVerifyOptions>(),
+            max_age_seconds: 0,
+            clock_skew_seconds: DEFAULT_CLOCK_SKEW as u64,
+            required_components: 0,
+            required_key_id: ptr::null(),
+        };
+        SageErrorCode::Success.into()
+    })
+}
+
+/// Convert FFI verification options to a policy and an optional required key ID
+unsafe fn verify_options(
+    options: &SageVerifyOptions,
+) -> std::result::Result<(VerificationPolicy, Option<String>), SageResult> {
+    check_struct_size(
+        "SageVerifyOptions",
+        options.struct_size,
+        SAGE_VERIFY_OPTIONS_V1_SIZE,
+    )?;
+
+    let mut policy =
+        VerificationPolicy::new().clock_skew(Duration::from_secs(options.clock_skew_seconds));
+    if options.max_age_seconds > 0 {
+        policy = policy.max_age(Duration::from_secs(options.max_age_seconds));
+    }
+
+    let component_bits = [
+        (SAGE_COMPONENT_METHOD, SignatureComponent::Method),
+        (SAGE_COMPONENT_TARGET_URI, SignatureComponent::TargetUri),
+        (SAGE_COMPONENT_AUTHORITY, SignatureComponent::Authority),
+        (SAGE_COMPONENT_SCHEME, SignatureComponent::Scheme),
+        (
+            SAGE_COMPONENT_REQUEST_TARGET,
+            SignatureComponent::RequestTarget,
+        ),
+        (SAGE_COMPONENT_PATH, SignatureComponent::Path),
+        (SAGE_COMPONENT_QUERY, SignatureComponent::Query),
+        (
+            SAGE_COMPONENT_CONTENT_DIGEST,
+            SignatureComponent::content_digest(),
+        ),
+    ];
+    let known_bits = component_bits.iter().fold(0, |bits, (bit, _)| bits | bit);
+    if options.required_components & !known_bits != 0 {
+        return Err(fail(
+            SageErrorCode::InvalidInput,
+            "Unknown bits in required_components",
+        ));
     }
+    policy = policy.require_components(
+        component_bits
+            .into_iter()
+            .filter(|(bit, _)| options.required_components & bit != 0)
+            .map(|(_, component)| component),
+    );
+
+    let required_key_id = if options.required_key_id.is_null() {
+        None
+    } else {
+        let key_id = CStr::from_ptr(options.required_key_id)
+            .to_str()
+            .map_err(|_| invalid_utf8())?;
+        Some(key_id.to_string())
+    };
+
+    Ok((policy, required_key_id))
 }
 
 /// Verify an HTTP request signature
@@ -230,57 +541,57 @@ pub unsafe extern "C" fn sage_http_verifier_verify_request(
     verifier: *const SageHttpVerifier,
     request: *const SageHttpRequest,
 ) -> SageResult {
-    if verifier.is_null() || request.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
-
-    let verifier = &(*verifier).inner;
-    let request = &*request;
+    sage_http_verifier_verify_request_with_options(verifier, request, ptr::null())
+}
 
-    // Convert FFI request to Rust HTTP request
-    let method_str = match CStr::from_ptr(request.method).to_str() {
-        Ok(s) => s,
-        Err(_) => return SageErrorCode::InvalidInput as SageResult,
-    };
-    let uri_str = match CStr::from_ptr(request.uri).to_str() {
-        Ok(s) => s,
-        Err(_) => return SageErrorCode::InvalidInput as SageResult,
-    };
+/// Verify an HTTP request signature against additional requirements
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `verifier` is a valid pointer
+/// - `request` is a valid pointer to a properly initialized SageHttpRequest
+/// - `options` is NULL or a valid pointer to initialized SageVerifyOptions
+#[no_mangle]
+pub unsafe extern "C" fn sage_http_verifier_verify_request_with_options(
+    verifier: *const SageHttpVerifier,
+    request: *const SageHttpRequest,
+    options: *const SageVerifyOptions,
+) -> SageResult {
+    guard(|| {
+        if verifier.is_null() || request.is_null() {
+            return null_argument();
+        }
 
-    // Build HTTP request
-    let mut builder = Request::builder().method(method_str).uri(uri_str);
+        let http_request = match build_request(&*request) {
+            Ok(req) => req,
+            Err(code) => return code,
+        };
 
-    // Add headers
-    let headers_slice = slice::from_raw_parts(request.headers, request.headers_count);
-    for header in headers_slice {
-        let name = match CStr::from_ptr(header.name).to_str() {
-            Ok(s) => s,
-            Err(_) => return SageErrorCode::InvalidInput as SageResult,
+        let (policy, required_key_id) = if options.is_null() {
+            (None, None)
+        } else {
+            match verify_options(&*options) {
+                Ok((policy, key_id)) => (Some(policy), key_id),
+                Err(code) => return code,
+            }
         };
-        let value = match CStr::from_ptr(header.value).to_str() {
-            Ok(s) => s,
-            Err(_) => return SageErrorCode::InvalidInput as SageResult,
+        let verifier = match policy {
+            Some(policy) => Cow::Owned((*verifier).inner.clone().with_policy(policy)),
+            None => Cow::Borrowed(&(*verifier).inner),
         };
-        builder = builder.header(name, value);
-    }
-
-    // Add body
-    let body = if request.body.is_null() {
-        Vec::new()
-    } else {
-        slice::from_raw_parts(request.body, request.body_len).to_vec()
-    };
 
-    let http_request = match builder.body(body) {
-        Ok(req) => req,
-        Err(_) => return SageErrorCode::InvalidInput as SageResult,
-    };
-
-    // Verify the request
-    match verifier.verify_request(&http_request) {
-        Ok(()) => SageErrorCode::Success.into(),
-        Err(_) => SageErrorCode::VerificationFailed.into(),
-    }
+        // Verify the request
+        match verifier.verify_request_detailed(&http_request) {
+            Ok(report) => match required_key_id {
+                Some(key_id) if report.key_id != key_id => fail(
+                    SageErrorCode::VerificationFailed,
+                    &format!("Signature key ID {} is not {key_id}", report.key_id),
+                ),
+                _ => SageErrorCode::Success.into(),
+            },
+            Err(e) => fail(SageErrorCode::VerificationFailed, &e.to_string()),
+        }
+    })
 }
 
 /// Free HTTP headers allocated by this library
@@ -289,15 +600,193 @@ pub unsafe extern "C" fn sage_http_verifier_verify_request(
 /// The caller must ensure that `headers` is a valid pointer with `count` elements.
 #[no_mangle]
 pub unsafe extern "C" fn sage_http_headers_free(headers: *mut SageHttpHeader, count: size_t) {
-    if !headers.is_null() {
-        for i in 0..count {
-            let header = &mut *headers.add(i);
-            if !header.name.is_null() {
-                sage_string_free(header.name as *mut c_char);
-            }
-            if !header.value.is_null() {
-                sage_string_free(header.value as *mut c_char);
+    guard_or((), || {
+        if !headers.is_null() {
+            let Ok(stride) = header_stride(headers, count) else {
+                return;
+            };
+            for i in 0..count {
+                let header = &mut *header_at(headers, stride, i);
+                if !header.name.is_null() {
+                    sage_string_free(header.name as *mut c_char);
+                }
+                if !header.value.is_null() {
+                    sage_string_free(header.value as *mut c_char);
+                }
             }
         }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(name: &CString, value: &CString) -> SageHttpHeader {
+        SageHttpHeader {
+            struct_size: mem::size_of::<SageHttpHeader>(),
+            name: name.as_ptr(),
+            value: value.as_ptr(),
+        }
+    }
+
+    #[test]
+    fn test_http_sign_and_verify_with_struct_size() {
+        let method = CString::new("GET").unwrap();
+        let uri = CString::new("https://agent.example/status").unwrap();
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let signer = SageHttpSigner {
+            inner: HttpSigner::new(keypair.clone()),
+        };
+        let verifier = SageHttpVerifier {
+            inner: HttpVerifier::new(keypair.public_key().clone()),
+        };
+
+        let mut request = SageHttpRequest {
+            struct_size: mem::size_of::<SageHttpRequest>(),
+            method: method.as_ptr(),
+            uri: uri.as_ptr(),
+            headers: ptr::null(),
+            headers_count: 0,
+            body: ptr::null(),
+            body_len: 0,
+        };
+        let empty = CString::default();
+        let mut signed = [header(&empty, &empty), header(&empty, &empty)];
+        let mut count = signed.len();
+
+        unsafe {
+            assert_eq!(
+                sage_http_signer_sign_request(&signer, &request, signed.as_mut_ptr(), &mut count),
+                0
+            );
+            assert_eq!(count, 2);
+
+            request.headers = signed.as_ptr();
+            request.headers_count = count;
+            assert_eq!(sage_http_verifier_verify_request(&verifier, &request), 0);
+
+            // Callers built against an unknown layout are rejected
+            request.struct_size = 0;
+            assert_eq!(
+                sage_http_verifier_verify_request(&verifier, &request),
+                SageErrorCode::InvalidInput as SageResult
+            );
+
+            sage_http_headers_free(signed.as_mut_ptr(), count);
+        }
+    }
+
+    #[test]
+    fn test_http_verify_options() {
+        let method = CString::new("GET").unwrap();
+        let uri = CString::new("https://agent.example/status").unwrap();
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let signer = SageHttpSigner {
+            inner: HttpSigner::new(keypair.clone()),
+        };
+        let verifier = SageHttpVerifier {
+            inner: HttpVerifier::new(keypair.public_key().clone()),
+        };
+
+        let mut request = SageHttpRequest {
+            struct_size: mem::size_of::<SageHttpRequest>(),
+            method: method.as_ptr(),
+            uri: uri.as_ptr(),
+            headers: ptr::null(),
+            headers_count: 0,
+            body: ptr::null(),
+            body_len: 0,
+        };
+        let empty = CString::default();
+        let mut signed = [header(&empty, &empty), header(&empty, &empty)];
+        let mut count = signed.len();
+        let key_id = CString::new(keypair.key_id()).unwrap();
+        let other_key_id = CString::new("other").unwrap();
+
+        unsafe {
+            assert_eq!(
+                sage_http_signer_sign_request(&signer, &request, signed.as_mut_ptr(), &mut count),
+                0
+            );
+            request.headers = signed.as_ptr();
+            request.headers_count = count;
+
+            let mut options = mem::MaybeUninit::<SageVerifyOptions>::uninit();
+            assert_eq!(sage_verify_options_default(options.as_mut_ptr()), 0);
+            let mut options = options.assume_init();
+            options.max_age_seconds = 60;
+            options.required_components = SAGE_COMPONENT_METHOD | SAGE_COMPONENT_PATH;
+            options.required_key_id = key_id.as_ptr();
+            assert_eq!(
+                sage_http_verifier_verify_request_with_options(&verifier, &request, &options),
+                0
+            );
+
+            // Out-of-range durations saturate instead of wrapping negative
+            options.max_age_seconds = u64::MAX;
+            options.clock_skew_seconds = u64::MAX;
+            assert_eq!(
+                sage_http_verifier_verify_request_with_options(&verifier, &request, &options),
+                0
+            );
+
+            options.required_components |= SAGE_COMPONENT_CONTENT_DIGEST;
+            assert_eq!(
+                sage_http_verifier_verify_request_with_options(&verifier, &request, &options),
+                SageErrorCode::VerificationFailed as SageResult
+            );
+
+            options.required_components = SAGE_COMPONENT_METHOD;
+            options.required_key_id = other_key_id.as_ptr();
+            assert_eq!(
+                sage_http_verifier_verify_request_with_options(&verifier, &request, &options),
+                SageErrorCode::VerificationFailed as SageResult
+            );
+
+            sage_http_headers_free(signed.as_mut_ptr(), count);
+        }
+    }
+
+    #[test]
+    fn test_http_signer_setters() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let mut signer = SageHttpSigner {
+            inner: HttpSigner::new(keypair),
+        };
+        let components = CString::new("(\"@method\" \"@path\")").unwrap();
+        let tag = CString::new("sage").unwrap();
+
+        unsafe {
+            assert_eq!(
+                sage_http_signer_set_components(&mut signer, components.as_ptr()),
+                0
+            );
+            assert_eq!(sage_http_signer_set_ttl(&mut signer, 60), 0);
+            assert_eq!(sage_http_signer_set_nonce(&mut signer, true), 0);
+            assert_eq!(sage_http_signer_set_tag(&mut signer, tag.as_ptr()), 0);
+
+            let empty = CString::new("()").unwrap();
+            assert_eq!(
+                sage_http_signer_set_components(&mut signer, empty.as_ptr()),
+                SageErrorCode::InvalidInput as SageResult
+            );
+            let status = CString::new("(\"@status\")").unwrap();
+            assert_eq!(
+                sage_http_signer_set_components(&mut signer, status.as_ptr()),
+                SageErrorCode::InvalidInput as SageResult
+            );
+        }
+
+        let request = ::http::Request::builder()
+            .uri("https://agent.example/status")
+            .body(())
+            .unwrap();
+        let signed = signer.inner.sign_request(request).unwrap();
+        let input = signed.headers()["signature-input"].to_str().unwrap();
+        assert!(input.contains("(\"@method\" \"@path\")"));
+        assert!(input.contains("expires="));
+        assert!(input.contains("nonce="));
+        assert!(input.contains("tag=\"sage\""));
     }
 }
@@ -12,18 +12,53 @@ pub unsafe extern "C" fn sage_keypair_generate(
     key_type: SageKeyType,
     out_keypair: *mut *mut SageKeyPair,
 ) -> SageResult {
-    if out_keypair.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
-
-    match KeyPair::generate(key_type.into()) {
-        Ok(keypair) => {
-            let boxed = Box::new(SageKeyPair { inner: keypair });
-            *out_keypair = Box::into_raw(boxed);
-            SageErrorCode::Success.into()
-        }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    guard(|| {
+        if out_keypair.is_null() {
+            return null_argument();
+        }
+
+        match KeyPair::generate(key_type.into()) {
+            Ok(keypair) => {
+                let boxed = Box::new(SageKeyPair { inner: keypair });
+                *out_keypair = Box::into_raw(boxed);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
+        }
+    })
+}
+
+/// Derive a key pair deterministically from a seed
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `key_type` is a valid key type
+/// - `seed` is a valid pointer to at least 32 seed bytes
+/// - `seed_len` is the correct length
+/// - `out_keypair` is a valid pointer to a `*mut SageKeyPair`
+#[no_mangle]
+pub unsafe extern "C" fn sage_keypair_from_seed(
+    key_type: SageKeyType,
+    seed: *const c_uchar,
+    seed_len: size_t,
+    out_keypair: *mut *mut SageKeyPair,
+) -> SageResult {
+    guard(|| {
+        if seed.is_null() || out_keypair.is_null() {
+            return null_argument();
+        }
+
+        let seed_slice = slice::from_raw_parts(seed, seed_len);
+
+        match KeyPair::from_seed(key_type.into(), seed_slice) {
+            Ok(keypair) => {
+                let boxed = Box::new(SageKeyPair { inner: keypair });
+                *out_keypair = Box::into_raw(boxed);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
+        }
+    })
 }
 
 /// Free a key pair
@@ -32,9 +67,28 @@ pub unsafe extern "C" fn sage_keypair_generate(
 /// The caller must ensure that `keypair` is a valid pointer obtained from `sage_keypair_generate`.
 #[no_mangle]
 pub unsafe extern "C" fn sage_keypair_free(keypair: *mut SageKeyPair) {
-    if !keypair.is_null() {
-        let _ = Box::from_raw(keypair);
-    }
+    guard_or((), || {
+        if !keypair.is_null() {
+            let _ = Box::from_raw(keypair);
+        }
+    })
+}
+
+/// Zeroize the private key of a key pair and free it
+///
+/// Signers created from the key pair keep their own copy of the key until
+/// they are freed.
+///
+/// # Safety
+/// The caller must ensure that `keypair` is a valid pointer obtained from this library.
+#[no_mangle]
+pub unsafe extern "C" fn sage_keypair_destroy_secure(keypair: *mut SageKeyPair) {
+    guard_or((), || {
+        if !keypair.is_null() {
+            let mut keypair = Box::from_raw(keypair);
+            keypair.inner.wipe();
+        }
+    })
 }
 
 /// Get the public key from a key pair
@@ -48,15 +102,17 @@ pub unsafe extern "C" fn sage_keypair_get_public_key(
     keypair: *const SageKeyPair,
     out_public_key: *mut *mut SagePublicKey,
 ) -> SageResult {
-    if keypair.is_null() || out_public_key.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
-
-    let keypair = &(*keypair).inner;
-    let public_key = keypair.public_key().clone();
-    let boxed = Box::new(SagePublicKey { inner: public_key });
-    *out_public_key = Box::into_raw(boxed);
-    SageErrorCode::Success.into()
+    guard(|| {
+        if keypair.is_null() || out_public_key.is_null() {
+            return null_argument();
+        }
+
+        let keypair = &(*keypair).inner;
+        let public_key = keypair.public_key().clone();
+        let boxed = Box::new(SagePublicKey { inner: public_key });
+        *out_public_key = Box::into_raw(boxed);
+        SageErrorCode::Success.into()
+    })
 }
 
 /// Get the key ID from a key pair
@@ -72,26 +128,50 @@ pub unsafe extern "C" fn sage_keypair_get_key_id(
     out_key_id: *mut c_char,
     out_len: *mut size_t,
 ) -> SageResult {
-    if keypair.is_null() || out_key_id.is_null() || out_len.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if keypair.is_null() || out_key_id.is_null() || out_len.is_null() {
+            return null_argument();
+        }
 
-    let keypair = &(*keypair).inner;
-    let key_id = keypair.key_id();
-    let key_id_bytes = key_id.as_bytes();
+        let keypair = &(*keypair).inner;
+        let key_id = keypair.key_id();
+        let key_id_bytes = key_id.as_bytes();
 
-    if key_id_bytes.len() > *out_len {
+        if key_id_bytes.len() > *out_len {
+            *out_len = key_id_bytes.len();
+            return fail(SageErrorCode::InvalidInput, "Output buffer too small");
+        }
+
+        ptr::copy_nonoverlapping(
+            key_id_bytes.as_ptr(),
+            out_key_id as *mut u8,
+            key_id_bytes.len(),
+        );
         *out_len = key_id_bytes.len();
-        return SageErrorCode::InvalidInput.into();
-    }
-
-    ptr::copy_nonoverlapping(
-        key_id_bytes.as_ptr(),
-        out_key_id as *mut u8,
-        key_id_bytes.len(),
-    );
-    *out_len = key_id_bytes.len();
-    SageErrorCode::Success.into()
+        SageErrorCode::Success.into()
+    })
+}
+
+/// Get the key ID from a key pair as a string allocated by the library
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `keypair` is a valid pointer
+/// - `out_key_id` is a valid pointer
+/// - the string is freed with `sage_string_free`
+#[no_mangle]
+pub unsafe extern "C" fn sage_keypair_get_key_id_alloc(
+    keypair: *const SageKeyPair,
+    out_key_id: *mut *mut c_char,
+) -> SageResult {
+    guard(|| {
+        if keypair.is_null() || out_key_id.is_null() {
+            return null_argument();
+        }
+
+        *out_key_id = string_to_c((*keypair).inner.key_id());
+        SageErrorCode::Success.into()
+    })
 }
 
 /// Get the key type from a key pair
@@ -100,12 +180,14 @@ pub unsafe extern "C" fn sage_keypair_get_key_id(
 /// The caller must ensure that `keypair` is a valid pointer.
 #[no_mangle]
 pub unsafe extern "C" fn sage_keypair_get_type(keypair: *const SageKeyPair) -> SageKeyType {
-    if keypair.is_null() {
-        return SageKeyType::Ed25519; // Default, though this is an error case
-    }
+    guard_or(SageKeyType::Ed25519, || {
+        if keypair.is_null() {
+            return SageKeyType::Ed25519; // Default, though this is an error case
+        }
 
-    let keypair = &(*keypair).inner;
-    keypair.key_type().into()
+        let keypair = &(*keypair).inner;
+        keypair.key_type().into()
+    })
 }
 
 /// Export a key pair to bytes
@@ -117,6 +199,7 @@ pub unsafe extern "C" fn sage_keypair_get_type(keypair: *const SageKeyPair) -> S
 /// - `private_key_len` is a valid pointer
 /// - `out_public_key` is a valid pointer with sufficient space (32-33 bytes)
 /// - `public_key_len` is a valid pointer
+#[cfg(feature = "ffi-private-export")]
 #[no_mangle]
 pub unsafe extern "C" fn sage_keypair_export(
     keypair: *const SageKeyPair,
@@ -125,36 +208,38 @@ pub unsafe extern "C" fn sage_keypair_export(
     out_public_key: *mut c_uchar,
     public_key_len: *mut size_t,
 ) -> SageResult {
-    if keypair.is_null()
-        || out_private_key.is_null()
-        || private_key_len.is_null()
-        || out_public_key.is_null()
-        || public_key_len.is_null()
-    {
-        return SageErrorCode::InvalidInput.into();
-    }
-
-    let keypair = &(*keypair).inner;
-
-    // Export private key
-    let private_bytes = keypair.private_key_bytes();
-    if private_bytes.len() > *private_key_len {
+    guard(|| {
+        if keypair.is_null()
+            || out_private_key.is_null()
+            || private_key_len.is_null()
+            || out_public_key.is_null()
+            || public_key_len.is_null()
+        {
+            return null_argument();
+        }
+
+        let keypair = &(*keypair).inner;
+
+        // Export private key
+        let private_bytes = keypair.private_key_bytes();
+        if private_bytes.len() > *private_key_len {
+            *private_key_len = private_bytes.len();
+            return fail(SageErrorCode::InvalidInput, "Output buffer too small");
+        }
+        ptr::copy_nonoverlapping(private_bytes.as_ptr(), out_private_key, private_bytes.len());
         *private_key_len = private_bytes.len();
-        return SageErrorCode::InvalidInput.into();
-    }
-    ptr::copy_nonoverlapping(private_bytes.as_ptr(), out_private_key, private_bytes.len());
-    *private_key_len = private_bytes.len();
-
-    // Export public key
-    let public_bytes = keypair.public_key_bytes();
-    if public_bytes.len() > *public_key_len {
+
+        // Export public key
+        let public_bytes = keypair.public_key_bytes();
+        if public_bytes.len() > *public_key_len {
+            *public_key_len = public_bytes.len();
+            return fail(SageErrorCode::InvalidInput, "Output buffer too small");
+        }
+        ptr::copy_nonoverlapping(public_bytes.as_ptr(), out_public_key, public_bytes.len());
         *public_key_len = public_bytes.len();
-        return SageErrorCode::InvalidInput.into();
-    }
-    ptr::copy_nonoverlapping(public_bytes.as_ptr(), out_public_key, public_bytes.len());
-    *public_key_len = public_bytes.len();
 
-    SageErrorCode::Success.into()
+        SageErrorCode::Success.into()
+    })
 }
 
 /// Import a key pair from bytes
@@ -172,18 +257,20 @@ pub unsafe extern "C" fn sage_keypair_import(
     private_key_len: size_t,
     out_keypair: *mut *mut SageKeyPair,
 ) -> SageResult {
-    if private_key.is_null() || out_keypair.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if private_key.is_null() || out_keypair.is_null() {
+            return null_argument();
+        }
 
-    let private_key_slice = slice::from_raw_parts(private_key, private_key_len);
+        let private_key_slice = slice::from_raw_parts(private_key, private_key_len);
 
-    match KeyPair::from_private_key_bytes(key_type.into(), private_key_slice) {
-        Ok(keypair) => {
-            let boxed = Box::new(SageKeyPair { inner: keypair });
-            *out_keypair = Box::into_raw(boxed);
-            SageErrorCode::Success.into()
+        match KeyPair::from_private_key_bytes(key_type.into(), private_key_slice) {
+            Ok(keypair) => {
+                let boxed = Box::new(SageKeyPair { inner: keypair });
+                *out_keypair = Box::into_raw(boxed);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
         }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    })
 }
@@ -0,0 +1,73 @@
+//! FFI logging callback for surfacing internal warnings in the host application
+
+use super::*;
+use std::sync::RwLock;
+
+/// Severity of a log message
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SageLogLevel {
+    /// Failures the caller is expected to handle, e.g. rejected signatures
+    Warn = 0,
+    /// Unexpected failures, e.g. internal panics
+    Error = 1,
+}
+
+/// Log callback; `message` is only valid for the duration of the call
+pub type SageLogCallback = extern "C" fn(level: SageLogLevel, message: *const c_char);
+
+static LOG_CALLBACK: RwLock<Option<SageLogCallback>> = RwLock::new(None);
+
+/// Set the callback receiving log messages, or NULL to stop logging
+///
+/// The callback may be invoked from any thread that calls into the library
+/// and must not unwind.
+#[no_mangle]
+pub extern "C" fn sage_set_log_callback(callback: Option<SageLogCallback>) {
+    guard_or((), || {
+        if let Ok(mut current) = LOG_CALLBACK.write() {
+            *current = callback;
+        }
+    })
+}
+
+/// Pass a message to the log callback, if one is set
+pub(crate) fn emit(level: SageLogLevel, message: &str) {
+    let Some(callback) = LOG_CALLBACK.read().ok().and_then(|callback| *callback) else {
+        return;
+    };
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    callback(level, message.as_ptr());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    static MESSAGES: Mutex<Vec<(SageLogLevel, String)>> = Mutex::new(Vec::new());
+
+    extern "C" fn record(level: SageLogLevel, message: *const c_char) {
+        let message = unsafe { CStr::from_ptr(message) };
+        MESSAGES
+            .lock()
+            .unwrap()
+            .push((level, message.to_string_lossy().into_owned()));
+    }
+
+    #[test]
+    fn test_log_callback() {
+        sage_set_log_callback(Some(record));
+        let mut keypair = ptr::null_mut();
+        let result = unsafe {
+            sage_keypair_import(SageKeyType::Ed25519, [0u8; 3].as_ptr(), 3, &mut keypair)
+        };
+        sage_set_log_callback(None);
+
+        assert_ne!(result, 0);
+        let messages = MESSAGES.lock().unwrap();
+        assert!(messages
+            .iter()
+            .any(|(level, message)| *level == SageLogLevel::Warn && message.contains("32 bytes")));
+    }
+}
@@ -3,19 +3,24 @@
 use crate::crypto::{KeyPair, KeyType, PublicKey};
 use crate::error::Error;
 use libc::{c_char, c_int, c_uchar, size_t};
+use std::any::Any;
+use std::cell::RefCell;
 use std::ffi::{CStr, CString};
+use std::panic::{self, AssertUnwindSafe};
 use std::ptr;
 use std::slice;
 
 pub mod formats;
 pub mod http;
 pub mod keypair;
+pub mod log;
 pub mod signature;
 pub mod utils;
 
 pub use formats::*;
 pub use http::*;
 pub use keypair::*;
+pub use log::*;
 pub use signature::*;
 pub use utils::*;
 
@@ -116,21 +121,161 @@ pub struct SageSignature {
 /// Initialize the library (currently no-op, reserved for future use)
 #[no_mangle]
 pub extern "C" fn sage_init() -> SageResult {
-    SageErrorCode::Success.into()
+    guard(|| SageErrorCode::Success.into())
 }
 
 /// Get the version string of the library
 #[no_mangle]
 pub extern "C" fn sage_version() -> *const c_char {
-    static VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "\0");
-    VERSION.as_ptr() as *const c_char
+    guard_or(ptr::null(), || {
+        static VERSION: &str = concat!(env!("CARGO_PKG_VERSION"), "\0");
+        VERSION.as_ptr() as *const c_char
+    })
 }
 
-/// Get the last error message (thread-local)
+/// Version of the C ABI
+///
+/// Bumped on incompatible changes. Within a version, structs passed across
+/// the boundary only grow by appending fields, and carry a `struct_size` set
+/// by the caller so the library can tell which fields are present.
+pub const SAGE_ABI_VERSION: u32 = 1;
+
+/// Get the version of the C ABI the library implements
+///
+/// Callers should check this against the `SAGE_ABI_VERSION` they were built with.
+#[no_mangle]
+pub extern "C" fn sage_abi_version() -> u32 {
+    guard_or(0, || SAGE_ABI_VERSION)
+}
+
+/// Capability bit set when plaintext private key export is compiled in
+pub const SAGE_CAP_PRIVATE_KEY_EXPORT: u32 = 1 << 0;
+
+/// Get the optional capabilities compiled into the library, as `SAGE_CAP_*` bits
+#[no_mangle]
+pub extern "C" fn sage_capabilities() -> u32 {
+    guard_or(0, || {
+        let mut capabilities = 0;
+        if cfg!(feature = "ffi-private-export") {
+            capabilities |= SAGE_CAP_PRIVATE_KEY_EXPORT;
+        }
+        capabilities
+    })
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+/// Record the message of the last failure on the calling thread
+pub(crate) fn set_last_error(message: &str) {
+    // Interior NUL bytes cannot be represented in a C string
+    let message = CString::new(message.replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(message));
+}
+
+/// Record a failure and return its error code
+pub(crate) fn fail(code: SageErrorCode, message: &str) -> SageResult {
+    let level = match code {
+        SageErrorCode::InvalidInput | SageErrorCode::VerificationFailed => SageLogLevel::Warn,
+        _ => SageLogLevel::Error,
+    };
+    log::emit(level, message);
+    set_last_error(message);
+    code.into()
+}
+
+/// Record an [`Error`] and return the matching error code
+pub(crate) fn error_result(err: Error) -> SageResult {
+    let message = err.to_string();
+    fail(SageErrorCode::from(err), &message)
+}
+
+/// Record a null pointer argument and return `InvalidInput`
+pub(crate) fn null_argument() -> SageResult {
+    fail(SageErrorCode::InvalidInput, "Null pointer argument")
+}
+
+/// Record a string argument that is not valid UTF-8 and return `InvalidInput`
+pub(crate) fn invalid_utf8() -> SageResult {
+    fail(
+        SageErrorCode::InvalidInput,
+        "String argument is not valid UTF-8",
+    )
+}
+
+/// Run the body of an exported function, turning a panic into `UnknownError`
+///
+/// Unwinding across `extern "C"` is undefined behavior, so every exported
+/// function runs its body through this or [`guard_or`].
+pub(crate) fn guard<F: FnOnce() -> SageResult>(body: F) -> SageResult {
+    guard_or(SageErrorCode::UnknownError.into(), body)
+}
+
+/// Run the body of an exported function, returning `fallback` if it panics
+pub(crate) fn guard_or<T, F: FnOnce() -> T>(fallback: T, body: F) -> T {
+    match panic::catch_unwind(AssertUnwindSafe(body)) {
+        Ok(value) => value,
+        Err(payload) => {
+            let message = panic_message(payload.as_ref());
+            log::emit(SageLogLevel::Error, &message);
+            set_last_error(&message);
+            fallback
+        }
+    }
+}
+
+/// Extract the message of a panic payload
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned());
+    match message {
+        Some(message) => format!("Internal panic: {message}"),
+        None => "Internal panic".to_string(),
+    }
+}
+
+/// Check a caller's `struct_size` against the smallest layout this library understands
+///
+/// Larger sizes come from callers built against a newer minor layout; the
+/// appended fields are ignored.
+pub(crate) fn check_struct_size(
+    name: &str,
+    struct_size: size_t,
+    min_size: usize,
+) -> std::result::Result<(), SageResult> {
+    if struct_size < min_size {
+        return Err(fail(
+            SageErrorCode::InvalidInput,
+            &format!("{name}.struct_size is {struct_size}, expected at least {min_size}"),
+        ));
+    }
+    Ok(())
+}
+
+/// Get the message of the last failure on the calling thread, or NULL if none
+///
+/// The string is owned by the library and stays valid until the next failing
+/// call or `sage_clear_last_error` on the same thread.
 #[no_mangle]
 pub extern "C" fn sage_last_error() -> *const c_char {
-    // TODO: Implement thread-local error storage
-    ptr::null()
+    guard_or(ptr::null(), || {
+        LAST_ERROR.with(|last| {
+            last.borrow()
+                .as_ref()
+                .map_or(ptr::null(), |message| message.as_ptr())
+        })
+    })
+}
+
+/// Clear the last error message of the calling thread
+#[no_mangle]
+pub extern "C" fn sage_clear_last_error() {
+    guard_or((), || {
+        LAST_ERROR.with(|last| *last.borrow_mut() = None);
+    })
 }
 
 #[cfg(test)]
@@ -149,6 +294,300 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_last_error() {
+        sage_clear_last_error();
+        assert!(sage_last_error().is_null());
+
+        let code = error_result(Error::Verification("bad signature".to_string()));
+        assert_eq!(code, SageErrorCode::VerificationFailed as SageResult);
+        let message = unsafe { CStr::from_ptr(sage_last_error()) };
+        assert_eq!(
+            message.to_str().unwrap(),
+            "Signature verification failed: bad signature"
+        );
+
+        let mut keypair = ptr::null_mut();
+        let code =
+            unsafe { sage_keypair_import(SageKeyType::Ed25519, ptr::null(), 0, &mut keypair) };
+        assert_eq!(code, SageErrorCode::InvalidInput as SageResult);
+        assert!(!sage_last_error().is_null());
+
+        sage_clear_last_error();
+        assert!(sage_last_error().is_null());
+    }
+
+    #[test]
+    fn test_alloc_exports() {
+        unsafe {
+            let mut keypair = ptr::null_mut();
+            assert_eq!(sage_keypair_generate(SageKeyType::Ed25519, &mut keypair), 0);
+            let message = b"hello";
+            let mut signature = ptr::null_mut();
+            assert_eq!(
+                sage_sign(keypair, message.as_ptr(), message.len(), &mut signature),
+                0
+            );
+
+            let mut bytes = ptr::null_mut();
+            let mut len = 0;
+            assert_eq!(
+                sage_signature_export_alloc(signature, &mut bytes, &mut len),
+                0
+            );
+            assert_eq!(
+                slice::from_raw_parts(bytes, len),
+                (*signature).inner.to_bytes().as_slice()
+            );
+            sage_buffer_free(bytes, len);
+
+            let mut key_id = ptr::null_mut();
+            assert_eq!(sage_keypair_get_key_id_alloc(keypair, &mut key_id), 0);
+            assert_eq!(
+                CStr::from_ptr(key_id).to_str().unwrap(),
+                (*keypair).inner.key_id()
+            );
+            sage_string_free(key_id);
+
+            sage_signature_free(signature);
+            sage_keypair_free(keypair);
+        }
+    }
+
+    #[test]
+    fn test_signature_import() {
+        unsafe {
+            let mut keypair = ptr::null_mut();
+            assert_eq!(
+                sage_keypair_generate(SageKeyType::Secp256k1, &mut keypair),
+                0
+            );
+            let mut public_key = ptr::null_mut();
+            assert_eq!(sage_keypair_get_public_key(keypair, &mut public_key), 0);
+            let message = b"hello";
+            let mut signature = ptr::null_mut();
+            assert_eq!(
+                sage_sign(keypair, message.as_ptr(), message.len(), &mut signature),
+                0
+            );
+
+            let bytes = (*signature).inner.to_bytes();
+            let mut imported = ptr::null_mut();
+            assert_eq!(
+                sage_signature_import(
+                    SageKeyType::Secp256k1,
+                    bytes.as_ptr(),
+                    bytes.len(),
+                    &mut imported
+                ),
+                0
+            );
+            assert_eq!(
+                sage_verify_with_public_key(public_key, message.as_ptr(), message.len(), imported),
+                0
+            );
+
+            let mut invalid = ptr::null_mut();
+            assert_ne!(
+                sage_signature_import(SageKeyType::Ed25519, bytes.as_ptr(), 3, &mut invalid),
+                0
+            );
+            assert!(invalid.is_null());
+
+            sage_signature_free(imported);
+            sage_signature_free(signature);
+            sage_public_key_free(public_key);
+            sage_keypair_free(keypair);
+        }
+    }
+
+    #[test]
+    fn test_guard_catches_panic() {
+        let result = guard(|| panic!("boom"));
+        assert_eq!(result, SageErrorCode::UnknownError as SageResult);
+        let message = unsafe { CStr::from_ptr(sage_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "Internal panic: boom");
+
+        assert!(guard_or(ptr::null::<c_char>(), || panic!("{}", 42)).is_null());
+        let message = unsafe { CStr::from_ptr(sage_last_error()) };
+        assert_eq!(message.to_str().unwrap(), "Internal panic: 42");
+
+        assert_eq!(guard(|| SageErrorCode::Success.into()), 0);
+    }
+
+    #[test]
+    fn test_sign_stream() {
+        unsafe {
+            let mut keypair = ptr::null_mut();
+            assert_eq!(sage_keypair_generate(SageKeyType::Ed25519, &mut keypair), 0);
+            let mut stream = ptr::null_mut();
+            assert_eq!(sage_sign_stream_new(keypair, &mut stream), 0);
+            for chunk in [&b"hello "[..], &b"world"[..]] {
+                assert_eq!(
+                    sage_sign_stream_update(stream, chunk.as_ptr(), chunk.len()),
+                    0
+                );
+            }
+            let mut signature = ptr::null_mut();
+            assert_eq!(sage_sign_stream_finalize(stream, &mut signature), 0);
+
+            let message = b"hello world";
+            assert_eq!(
+                sage_verify_with_keypair(keypair, message.as_ptr(), message.len(), signature),
+                0
+            );
+
+            sage_signature_free(signature);
+            sage_keypair_free(keypair);
+        }
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        unsafe {
+            let mut keypair = ptr::null_mut();
+            assert_eq!(sage_keypair_generate(SageKeyType::Ed25519, &mut keypair), 0);
+            let mut public_key = ptr::null_mut();
+            assert_eq!(sage_keypair_get_public_key(keypair, &mut public_key), 0);
+            let message = b"hello";
+            let mut signature = ptr::null_mut();
+            assert_eq!(
+                sage_sign(keypair, message.as_ptr(), message.len(), &mut signature),
+                0
+            );
+
+            let item = |message: &[u8]| SageVerifyItem {
+                struct_size: std::mem::size_of::<SageVerifyItem>(),
+                public_key,
+                message: message.as_ptr(),
+                message_len: message.len(),
+                signature,
+                result: -1,
+            };
+            let mut items = [item(message), item(b"tampered"), item(message)];
+            assert_eq!(
+                sage_verify_batch(items.as_mut_ptr(), items.len()),
+                SageErrorCode::VerificationFailed as SageResult
+            );
+            let results: Vec<SageResult> = items.iter().map(|item| item.result).collect();
+            assert_eq!(
+                results,
+                vec![0, SageErrorCode::VerificationFailed as SageResult, 0]
+            );
+            assert!(CStr::from_ptr(sage_last_error())
+                .to_str()
+                .unwrap()
+                .starts_with("1 of 3 signatures failed verification"));
+
+            sage_signature_free(signature);
+            sage_public_key_free(public_key);
+            sage_keypair_free(keypair);
+        }
+    }
+
+    #[test]
+    fn test_destroy_secure_and_capabilities() {
+        unsafe {
+            let mut keypair = ptr::null_mut();
+            assert_eq!(sage_keypair_generate(SageKeyType::Ed25519, &mut keypair), 0);
+            sage_keypair_destroy_secure(keypair);
+            sage_keypair_destroy_secure(ptr::null_mut());
+        }
+
+        assert_eq!(
+            sage_capabilities() & SAGE_CAP_PRIVATE_KEY_EXPORT != 0,
+            cfg!(feature = "ffi-private-export")
+        );
+    }
+
+    #[test]
+    fn test_public_key_get_key_id() {
+        unsafe {
+            let mut keypair = ptr::null_mut();
+            assert_eq!(
+                sage_keypair_generate(SageKeyType::Secp256k1, &mut keypair),
+                0
+            );
+            let mut public_key = ptr::null_mut();
+            assert_eq!(sage_keypair_get_public_key(keypair, &mut public_key), 0);
+
+            let mut key_id = [0 as c_char; 64];
+            let mut len = key_id.len();
+            assert_eq!(
+                sage_public_key_get_key_id(public_key, key_id.as_mut_ptr(), &mut len),
+                0
+            );
+            let key_id = slice::from_raw_parts(key_id.as_ptr() as *const u8, len);
+            assert_eq!(key_id, (*keypair).inner.key_id().as_bytes());
+
+            let mut short = [0 as c_char; 4];
+            let mut short_len = short.len();
+            assert_ne!(
+                sage_public_key_get_key_id(public_key, short.as_mut_ptr(), &mut short_len),
+                0
+            );
+            assert_eq!(short_len, len);
+
+            sage_public_key_free(public_key);
+            sage_keypair_free(keypair);
+        }
+    }
+
+    #[test]
+    fn test_sign_into() {
+        unsafe {
+            let mut keypair = ptr::null_mut();
+            assert_eq!(sage_keypair_generate(SageKeyType::Ed25519, &mut keypair), 0);
+            let message = b"hello";
+
+            let mut sig_bytes = [0u8; 64];
+            let mut len = sig_bytes.len();
+            assert_eq!(
+                sage_sign_into(
+                    keypair,
+                    message.as_ptr(),
+                    message.len(),
+                    sig_bytes.as_mut_ptr(),
+                    &mut len
+                ),
+                0
+            );
+            assert_eq!(len, 64);
+
+            let mut signature = ptr::null_mut();
+            assert_eq!(
+                sage_signature_import(
+                    SageKeyType::Ed25519,
+                    sig_bytes.as_ptr(),
+                    len,
+                    &mut signature
+                ),
+                0
+            );
+            assert_eq!(
+                sage_verify_with_keypair(keypair, message.as_ptr(), message.len(), signature),
+                0
+            );
+
+            let mut short = [0u8; 16];
+            let mut short_len = short.len();
+            assert_ne!(
+                sage_sign_into(
+                    keypair,
+                    message.as_ptr(),
+                    message.len(),
+                    short.as_mut_ptr(),
+                    &mut short_len
+                ),
+                0
+            );
+            assert_eq!(short_len, 64);
+
+            sage_signature_free(signature);
+            sage_keypair_free(keypair);
+        }
+    }
+
     #[test]
     fn test_key_type_conversion() {
         assert_eq!(KeyType::from(SageKeyType::Ed25519), KeyType::Ed25519);
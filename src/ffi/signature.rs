@@ -1,7 +1,7 @@
 //! FFI functions for signature operations
 
 use super::*;
-use crate::crypto::{Signer, Verifier};
+use crate::crypto::{SignStream, Signature, Signer, Verifier};
 
 /// Sign a message
 ///
@@ -18,21 +18,166 @@ pub unsafe extern "C" fn sage_sign(
     message_len: size_t,
     out_signature: *mut *mut SageSignature,
 ) -> SageResult {
-    if keypair.is_null() || message.is_null() || out_signature.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if keypair.is_null() || message.is_null() || out_signature.is_null() {
+            return null_argument();
+        }
 
-    let keypair = &(*keypair).inner;
-    let message_slice = slice::from_raw_parts(message, message_len);
+        let keypair = &(*keypair).inner;
+        let message_slice = slice::from_raw_parts(message, message_len);
 
-    match keypair.sign(message_slice) {
-        Ok(signature) => {
-            let boxed = Box::new(SageSignature { inner: signature });
-            *out_signature = Box::into_raw(boxed);
-            SageErrorCode::Success.into()
+        match keypair.sign(message_slice) {
+            Ok(signature) => {
+                let boxed = Box::new(SageSignature { inner: signature });
+                *out_signature = Box::into_raw(boxed);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
         }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    })
+}
+
+/// Sign a message straight into a caller-provided buffer
+///
+/// On input `out_len` holds the buffer size; on output the signature length.
+/// Ed25519 signatures are 64 bytes; Secp256k1 DER signatures at most 72.
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `keypair` is a valid pointer
+/// - `message` is a valid pointer to `message_len` bytes
+/// - `out_signature` is a valid pointer with `*out_len` bytes of space
+/// - `out_len` is a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn sage_sign_into(
+    keypair: *const SageKeyPair,
+    message: *const c_uchar,
+    message_len: size_t,
+    out_signature: *mut c_uchar,
+    out_len: *mut size_t,
+) -> SageResult {
+    guard(|| {
+        if keypair.is_null() || message.is_null() || out_signature.is_null() || out_len.is_null() {
+            return null_argument();
+        }
+
+        let message_slice = slice::from_raw_parts(message, message_len);
+        let sig_bytes = match (*keypair).inner.sign(message_slice) {
+            Ok(signature) => signature.to_bytes(),
+            Err(e) => return error_result(e),
+        };
+
+        if sig_bytes.len() > *out_len {
+            *out_len = sig_bytes.len();
+            return fail(SageErrorCode::InvalidInput, "Output buffer too small");
+        }
+
+        ptr::copy_nonoverlapping(sig_bytes.as_ptr(), out_signature, sig_bytes.len());
+        *out_len = sig_bytes.len();
+        SageErrorCode::Success.into()
+    })
+}
+
+/// Opaque handle for an incremental signing operation
+pub struct SageSignStream {
+    inner: SignStream,
+}
+
+/// Start signing a message fed in chunks
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `keypair` is a valid pointer
+/// - `out_stream` is a valid pointer to a `*mut SageSignStream`
+#[no_mangle]
+pub unsafe extern "C" fn sage_sign_stream_new(
+    keypair: *const SageKeyPair,
+    out_stream: *mut *mut SageSignStream,
+) -> SageResult {
+    guard(|| {
+        if keypair.is_null() || out_stream.is_null() {
+            return null_argument();
+        }
+
+        let boxed = Box::new(SageSignStream {
+            inner: (*keypair).inner.sign_stream(),
+        });
+        *out_stream = Box::into_raw(boxed);
+        SageErrorCode::Success.into()
+    })
+}
+
+/// Feed the next chunk of the message
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `stream` is a valid pointer
+/// - `data` is a valid pointer to `data_len` bytes
+#[no_mangle]
+pub unsafe extern "C" fn sage_sign_stream_update(
+    stream: *mut SageSignStream,
+    data: *const c_uchar,
+    data_len: size_t,
+) -> SageResult {
+    guard(|| {
+        if stream.is_null() || (data.is_null() && data_len > 0) {
+            return null_argument();
+        }
+        if data_len == 0 {
+            return SageErrorCode::Success.into();
+        }
+
+        (*stream)
+            .inner
+            .update(slice::from_raw_parts(data, data_len));
+        SageErrorCode::Success.into()
+    })
+}
+
+/// Sign the message fed so far and free the stream
+///
+/// The stream is freed whether or not signing succeeds.
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `stream` is a valid pointer obtained from `sage_sign_stream_new`
+/// - `out_signature` is a valid pointer to a `*mut SageSignature`
+#[no_mangle]
+pub unsafe extern "C" fn sage_sign_stream_finalize(
+    stream: *mut SageSignStream,
+    out_signature: *mut *mut SageSignature,
+) -> SageResult {
+    guard(|| {
+        if stream.is_null() {
+            return null_argument();
+        }
+        let stream = Box::from_raw(stream);
+        if out_signature.is_null() {
+            return null_argument();
+        }
+
+        match stream.inner.finalize() {
+            Ok(signature) => {
+                let boxed = Box::new(SageSignature { inner: signature });
+                *out_signature = Box::into_raw(boxed);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
+        }
+    })
+}
+
+/// Free a stream without signing
+///
+/// # Safety
+/// The caller must ensure that `stream` is a valid pointer obtained from `sage_sign_stream_new`.
+#[no_mangle]
+pub unsafe extern "C" fn sage_sign_stream_free(stream: *mut SageSignStream) {
+    guard_or((), || {
+        if !stream.is_null() {
+            let _ = Box::from_raw(stream);
+        }
+    })
 }
 
 /// Verify a signature with a key pair
@@ -50,18 +195,20 @@ pub unsafe extern "C" fn sage_verify_with_keypair(
     message_len: size_t,
     signature: *const SageSignature,
 ) -> SageResult {
-    if keypair.is_null() || message.is_null() || signature.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if keypair.is_null() || message.is_null() || signature.is_null() {
+            return null_argument();
+        }
 
-    let keypair = &(*keypair).inner;
-    let message_slice = slice::from_raw_parts(message, message_len);
-    let signature = &(*signature).inner;
+        let keypair = &(*keypair).inner;
+        let message_slice = slice::from_raw_parts(message, message_len);
+        let signature = &(*signature).inner;
 
-    match keypair.verify(message_slice, signature) {
-        Ok(()) => SageErrorCode::Success.into(),
-        Err(_) => SageErrorCode::VerificationFailed.into(),
-    }
+        match keypair.verify(message_slice, signature) {
+            Ok(()) => SageErrorCode::Success.into(),
+            Err(e) => fail(SageErrorCode::VerificationFailed, &e.to_string()),
+        }
+    })
 }
 
 /// Verify a signature with a public key
@@ -79,29 +226,112 @@ pub unsafe extern "C" fn sage_verify_with_public_key(
     message_len: size_t,
     signature: *const SageSignature,
 ) -> SageResult {
-    if public_key.is_null() || message.is_null() || signature.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if public_key.is_null() || message.is_null() || signature.is_null() {
+            return null_argument();
+        }
+
+        let public_key = &(*public_key).inner;
+        let message_slice = slice::from_raw_parts(message, message_len);
+        let signature = &(*signature).inner;
 
-    let public_key = &(*public_key).inner;
-    let message_slice = slice::from_raw_parts(message, message_len);
-    let signature = &(*signature).inner;
+        match public_key.verify(message_slice, signature) {
+            Ok(()) => SageErrorCode::Success.into(),
+            Err(e) => fail(SageErrorCode::VerificationFailed, &e.to_string()),
+        }
+    })
+}
+
+/// One verification of a batch
+///
+/// `struct_size` must be set to `sizeof(SageVerifyItem)`. The library writes
+/// the outcome of the item to `result`.
+#[repr(C)]
+pub struct SageVerifyItem {
+    struct_size: size_t,
+    public_key: *const SagePublicKey,
+    message: *const c_uchar,
+    message_len: size_t,
+    signature: *const SageSignature,
+    result: SageResult,
+}
+
+/// Size of `SageVerifyItem` in ABI version 1
+const SAGE_VERIFY_ITEM_V1_SIZE: usize =
+    std::mem::offset_of!(SageVerifyItem, result) + std::mem::size_of::<SageResult>();
+
+/// Verify one item of a batch
+unsafe fn verify_item(item: &SageVerifyItem) -> SageResult {
+    if item.public_key.is_null() || item.message.is_null() || item.signature.is_null() {
+        return null_argument();
+    }
 
-    match public_key.verify(message_slice, signature) {
+    let message_slice = slice::from_raw_parts(item.message, item.message_len);
+    match (*item.public_key)
+        .inner
+        .verify(message_slice, &(*item.signature).inner)
+    {
         Ok(()) => SageErrorCode::Success.into(),
-        Err(_) => SageErrorCode::VerificationFailed.into(),
+        Err(e) => fail(SageErrorCode::VerificationFailed, &e.to_string()),
     }
 }
 
+/// Verify a batch of signatures, writing each outcome to the item's `result`
+///
+/// Returns success only if every item verified. Otherwise returns
+/// `VerificationFailed`, and the last error describes the last failed item.
+///
+/// # Safety
+/// The caller must ensure that `items` is a valid pointer to `count` items,
+/// each with valid pointers.
+#[no_mangle]
+pub unsafe extern "C" fn sage_verify_batch(
+    items: *mut SageVerifyItem,
+    count: size_t,
+) -> SageResult {
+    guard(|| {
+        if count == 0 {
+            return SageErrorCode::Success.into();
+        }
+        if items.is_null() {
+            return null_argument();
+        }
+        let stride = (*items).struct_size;
+        if let Err(code) = check_struct_size("SageVerifyItem", stride, SAGE_VERIFY_ITEM_V1_SIZE) {
+            return code;
+        }
+
+        let mut failed = 0;
+        for i in 0..count {
+            let item = &mut *((items as *mut u8).add(i * stride) as *mut SageVerifyItem);
+            item.result = verify_item(item);
+            if item.result != SageResult::from(SageErrorCode::Success) {
+                failed += 1;
+            }
+        }
+
+        if failed > 0 {
+            let last_error = CStr::from_ptr(sage_last_error()).to_string_lossy();
+            return fail(
+                SageErrorCode::VerificationFailed,
+                &format!("{failed} of {count} signatures failed verification: {last_error}"),
+            );
+        }
+        SageErrorCode::Success.into()
+    })
+}
+
 /// Free a signature
 ///
 /// # Safety
 /// The caller must ensure that `signature` is a valid pointer obtained from `sage_sign`.
 #[no_mangle]
 pub unsafe extern "C" fn sage_signature_free(signature: *mut SageSignature) {
-    if !signature.is_null() {
-        let _ = Box::from_raw(signature);
-    }
+    guard_or((), || {
+        if !signature.is_null() {
+            let _ = Box::from_raw(signature);
+        }
+    })
 }
 
 /// Export a signature to bytes
@@ -117,21 +347,78 @@ pub unsafe extern "C" fn sage_signature_export(
     out_bytes: *mut c_uchar,
     out_len: *mut size_t,
 ) -> SageResult {
-    if signature.is_null() || out_bytes.is_null() || out_len.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if signature.is_null() || out_bytes.is_null() || out_len.is_null() {
+            return null_argument();
+        }
+
+        let signature = &(*signature).inner;
+        let sig_bytes = signature.to_bytes();
 
-    let signature = &(*signature).inner;
-    let sig_bytes = signature.to_bytes();
+        if sig_bytes.len() > *out_len {
+            *out_len = sig_bytes.len();
+            return fail(SageErrorCode::InvalidInput, "Output buffer too small");
+        }
 
-    if sig_bytes.len() > *out_len {
+        ptr::copy_nonoverlapping(sig_bytes.as_ptr(), out_bytes, sig_bytes.len());
         *out_len = sig_bytes.len();
-        return SageErrorCode::InvalidInput.into();
-    }
+        SageErrorCode::Success.into()
+    })
+}
+
+/// Export a signature to a buffer allocated by the library
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `signature` is a valid pointer
+/// - `out_ptr` and `out_len` are valid pointers
+/// - the buffer is freed with `sage_buffer_free`
+#[no_mangle]
+pub unsafe extern "C" fn sage_signature_export_alloc(
+    signature: *const SageSignature,
+    out_ptr: *mut *mut c_uchar,
+    out_len: *mut size_t,
+) -> SageResult {
+    guard(|| {
+        if signature.is_null() || out_ptr.is_null() || out_len.is_null() {
+            return null_argument();
+        }
+
+        bytes_to_c((*signature).inner.to_bytes(), out_ptr, out_len)
+    })
+}
+
+/// Import a signature from bytes
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `key_type` is a valid key type
+/// - `bytes` is a valid pointer to signature bytes
+/// - `bytes_len` is the correct length
+/// - `out_signature` is a valid pointer to a `*mut SageSignature`
+#[no_mangle]
+pub unsafe extern "C" fn sage_signature_import(
+    key_type: SageKeyType,
+    bytes: *const c_uchar,
+    bytes_len: size_t,
+    out_signature: *mut *mut SageSignature,
+) -> SageResult {
+    guard(|| {
+        if bytes.is_null() || out_signature.is_null() {
+            return null_argument();
+        }
+
+        let bytes_slice = slice::from_raw_parts(bytes, bytes_len);
 
-    ptr::copy_nonoverlapping(sig_bytes.as_ptr(), out_bytes, sig_bytes.len());
-    *out_len = sig_bytes.len();
-    SageErrorCode::Success.into()
+        match Signature::from_bytes(key_type.into(), bytes_slice) {
+            Ok(signature) => {
+                let boxed = Box::new(SageSignature { inner: signature });
+                *out_signature = Box::into_raw(boxed);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
+        }
+    })
 }
 
 /// Free a public key
@@ -140,9 +427,47 @@ pub unsafe extern "C" fn sage_signature_export(
 /// The caller must ensure that `public_key` is a valid pointer.
 #[no_mangle]
 pub unsafe extern "C" fn sage_public_key_free(public_key: *mut SagePublicKey) {
-    if !public_key.is_null() {
-        let _ = Box::from_raw(public_key);
-    }
+    guard_or((), || {
+        if !public_key.is_null() {
+            let _ = Box::from_raw(public_key);
+        }
+    })
+}
+
+/// Get the key ID of a public key
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `public_key` is a valid pointer
+/// - `out_key_id` is a valid pointer with sufficient space (at least 64 bytes)
+/// - `out_len` is a valid pointer
+#[no_mangle]
+pub unsafe extern "C" fn sage_public_key_get_key_id(
+    public_key: *const SagePublicKey,
+    out_key_id: *mut c_char,
+    out_len: *mut size_t,
+) -> SageResult {
+    guard(|| {
+        if public_key.is_null() || out_key_id.is_null() || out_len.is_null() {
+            return null_argument();
+        }
+
+        let key_id = (*public_key).inner.key_id();
+        let key_id_bytes = key_id.as_bytes();
+
+        if key_id_bytes.len() > *out_len {
+            *out_len = key_id_bytes.len();
+            return fail(SageErrorCode::InvalidInput, "Output buffer too small");
+        }
+
+        ptr::copy_nonoverlapping(
+            key_id_bytes.as_ptr(),
+            out_key_id as *mut u8,
+            key_id_bytes.len(),
+        );
+        *out_len = key_id_bytes.len();
+        SageErrorCode::Success.into()
+    })
 }
 
 /// Export a public key to bytes
@@ -158,21 +483,45 @@ pub unsafe extern "C" fn sage_public_key_export(
     out_bytes: *mut c_uchar,
     out_len: *mut size_t,
 ) -> SageResult {
-    if public_key.is_null() || out_bytes.is_null() || out_len.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if public_key.is_null() || out_bytes.is_null() || out_len.is_null() {
+            return null_argument();
+        }
+
+        let public_key = &(*public_key).inner;
+        let key_bytes = public_key.to_bytes();
 
-    let public_key = &(*public_key).inner;
-    let key_bytes = public_key.to_bytes();
+        if key_bytes.len() > *out_len {
+            *out_len = key_bytes.len();
+            return fail(SageErrorCode::InvalidInput, "Output buffer too small");
+        }
 
-    if key_bytes.len() > *out_len {
+        ptr::copy_nonoverlapping(key_bytes.as_ptr(), out_bytes, key_bytes.len());
         *out_len = key_bytes.len();
-        return SageErrorCode::InvalidInput.into();
-    }
+        SageErrorCode::Success.into()
+    })
+}
 
-    ptr::copy_nonoverlapping(key_bytes.as_ptr(), out_bytes, key_bytes.len());
-    *out_len = key_bytes.len();
-    SageErrorCode::Success.into()
+/// Export a public key to a buffer allocated by the library
+///
+/// # Safety
+/// The caller must ensure that:
+/// - `public_key` is a valid pointer
+/// - `out_ptr` and `out_len` are valid pointers
+/// - the buffer is freed with `sage_buffer_free`
+#[no_mangle]
+pub unsafe extern "C" fn sage_public_key_export_alloc(
+    public_key: *const SagePublicKey,
+    out_ptr: *mut *mut c_uchar,
+    out_len: *mut size_t,
+) -> SageResult {
+    guard(|| {
+        if public_key.is_null() || out_ptr.is_null() || out_len.is_null() {
+            return null_argument();
+        }
+
+        bytes_to_c((*public_key).inner.to_bytes(), out_ptr, out_len)
+    })
 }
 
 /// Import a public key from bytes
@@ -190,18 +539,20 @@ pub unsafe extern "C" fn sage_public_key_import(
     bytes_len: size_t,
     out_public_key: *mut *mut SagePublicKey,
 ) -> SageResult {
-    if bytes.is_null() || out_public_key.is_null() {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if bytes.is_null() || out_public_key.is_null() {
+            return null_argument();
+        }
 
-    let bytes_slice = slice::from_raw_parts(bytes, bytes_len);
+        let bytes_slice = slice::from_raw_parts(bytes, bytes_len);
 
-    match PublicKey::from_bytes(key_type.into(), bytes_slice) {
-        Ok(public_key) => {
-            let boxed = Box::new(SagePublicKey { inner: public_key });
-            *out_public_key = Box::into_raw(boxed);
-            SageErrorCode::Success.into()
+        match PublicKey::from_bytes(key_type.into(), bytes_slice) {
+            Ok(public_key) => {
+                let boxed = Box::new(SagePublicKey { inner: public_key });
+                *out_public_key = Box::into_raw(boxed);
+                SageErrorCode::Success.into()
+            }
+            Err(e) => error_result(e),
         }
-        Err(e) => SageErrorCode::from(e).into(),
-    }
+    })
 }
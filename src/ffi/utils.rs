@@ -8,9 +8,11 @@ use super::*;
 /// The caller must ensure that `str` is a valid pointer allocated by this library.
 #[no_mangle]
 pub unsafe extern "C" fn sage_string_free(str: *mut c_char) {
-    if !str.is_null() {
-        let _ = CString::from_raw(str);
-    }
+    guard_or((), || {
+        if !str.is_null() {
+            let _ = CString::from_raw(str);
+        }
+    })
 }
 
 /// Allocate and copy a string for C
@@ -24,6 +26,36 @@ pub(crate) unsafe fn string_to_c(s: &str) -> *mut c_char {
     }
 }
 
+/// Hand a byte buffer over to C
+///
+/// # Safety
+/// `out_ptr` and `out_len` must be valid pointers. The caller must free the
+/// buffer with `sage_buffer_free`.
+pub(crate) unsafe fn bytes_to_c(
+    bytes: Vec<u8>,
+    out_ptr: *mut *mut c_uchar,
+    out_len: *mut size_t,
+) -> SageResult {
+    let boxed = bytes.into_boxed_slice();
+    *out_len = boxed.len();
+    *out_ptr = Box::into_raw(boxed) as *mut c_uchar;
+    SageErrorCode::Success.into()
+}
+
+/// Free a buffer returned by one of the `*_alloc` functions
+///
+/// # Safety
+/// The caller must ensure that `ptr` and `len` were returned together by a
+/// `*_alloc` function of this library, and that the buffer is freed only once.
+#[no_mangle]
+pub unsafe extern "C" fn sage_buffer_free(ptr: *mut c_uchar, len: size_t) {
+    guard_or((), || {
+        if !ptr.is_null() {
+            let _ = Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len));
+        }
+    })
+}
+
 /// Generate a random nonce
 ///
 /// # Safety
@@ -32,16 +64,21 @@ pub(crate) unsafe fn string_to_c(s: &str) -> *mut c_char {
 /// - `len` is the desired length of the nonce
 #[no_mangle]
 pub unsafe extern "C" fn sage_generate_nonce(out_nonce: *mut c_uchar, len: size_t) -> SageResult {
-    if out_nonce.is_null() || len == 0 {
-        return SageErrorCode::InvalidInput.into();
-    }
+    guard(|| {
+        if out_nonce.is_null() {
+            return null_argument();
+        }
+        if len == 0 {
+            return fail(SageErrorCode::InvalidInput, "Nonce length must be positive");
+        }
 
-    use rand::RngCore;
-    let mut rng = rand::thread_rng();
-    let nonce_slice = slice::from_raw_parts_mut(out_nonce, len);
-    rng.fill_bytes(nonce_slice);
+        use rand::RngCore;
+        let mut rng = rand::thread_rng();
+        let nonce_slice = slice::from_raw_parts_mut(out_nonce, len);
+        rng.fill_bytes(nonce_slice);
 
-    SageErrorCode::Success.into()
+        SageErrorCode::Success.into()
+    })
 }
 
 /// Clear sensitive memory
@@ -52,15 +89,17 @@ pub unsafe extern "C" fn sage_generate_nonce(out_nonce: *mut c_uchar, len: size_
 /// - `len` is the correct length
 #[no_mangle]
 pub unsafe extern "C" fn sage_secure_zero(ptr: *mut c_uchar, len: size_t) {
-    if !ptr.is_null() && len > 0 {
-        let slice = slice::from_raw_parts_mut(ptr, len);
+    guard_or((), || {
+        if !ptr.is_null() && len > 0 {
+            let slice = slice::from_raw_parts_mut(ptr, len);
 
-        // Use volatile writes to prevent optimization
-        for i in 0..len {
-            std::ptr::write_volatile(slice.as_mut_ptr().add(i), 0);
-        }
+            // Use volatile writes to prevent optimization
+            for i in 0..len {
+                std::ptr::write_volatile(slice.as_mut_ptr().add(i), 0);
+            }
 
-        // Additional fence to ensure ordering
-        std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
-    }
+            // Additional fence to ensure ordering
+            std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+        }
+    })
 }
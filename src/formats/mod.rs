@@ -3,11 +3,17 @@
 //! This module provides functionality to import and export cryptographic keys
 //! in various formats including JWK, PEM, and raw bytes.
 
-use crate::crypto::{KeyPair, PrivateKey, PublicKey};
+use crate::crypto::{KeyPair, KeyType, PrivateKey, PublicKey};
 use crate::error::{Error, Result};
 use base64::{engine::general_purpose, Engine as _};
 use serde::{Deserialize, Serialize};
 
+pub mod multikey;
+pub use multikey::{
+    keypair_from_multikey, multikey_verification_method, private_key_to_multikey,
+    public_key_from_multikey, public_key_to_multikey,
+};
+
 /// Supported key formats
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum KeyFormat {
@@ -41,10 +47,93 @@ pub trait KeyExporter {
     /// Export to JWK format
     fn to_jwk(&self) -> Result<serde_json::Value>;
 
+    /// Export to JWK format with additional metadata members
+    ///
+    /// Metadata never replaces the key material members (`kty`, `crv`, `x`, `y`, `d`).
+    fn to_jwk_with(&self, metadata: &JwkMetadata) -> Result<serde_json::Value> {
+        let mut jwk = self.to_jwk()?;
+        let members =
+            serde_json::to_value(metadata).map_err(|e| Error::Serialization(e.to_string()))?;
+        if let (Some(jwk), serde_json::Value::Object(members)) = (jwk.as_object_mut(), members) {
+            for (name, value) in members {
+                if !KEY_MATERIAL_MEMBERS.contains(&name.as_str()) {
+                    jwk.insert(name, value);
+                }
+            }
+        }
+        Ok(jwk)
+    }
+
     /// Export to PEM format
     fn to_pem(&self) -> Result<String>;
 }
 
+/// JWK members that carry key material and cannot be set through [`JwkMetadata`]
+const KEY_MATERIAL_MEMBERS: [&str; 5] = ["kty", "crv", "x", "y", "d"];
+
+/// Optional JWK members describing how a key is used (RFC 7517 Section 4)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct JwkMetadata {
+    /// Intended algorithm, e.g. `EdDSA`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alg: Option<String>,
+    /// Intended use, `sig` or `enc`
+    #[serde(rename = "use", skip_serializing_if = "Option::is_none")]
+    pub key_use: Option<String>,
+    /// Permitted operations, e.g. `["sign", "verify"]`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_ops: Option<Vec<String>>,
+    /// Any other members, preserved as is
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl JwkMetadata {
+    /// Create empty metadata
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the `alg` member
+    pub fn with_alg(mut self, alg: impl Into<String>) -> Self {
+        self.alg = Some(alg.into());
+        self
+    }
+
+    /// Set the `use` member
+    pub fn with_use(mut self, key_use: impl Into<String>) -> Self {
+        self.key_use = Some(key_use.into());
+        self
+    }
+
+    /// Set the `key_ops` member
+    pub fn with_key_ops(mut self, key_ops: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.key_ops = Some(key_ops.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Add an arbitrary member
+    pub fn with_member(mut self, name: impl Into<String>, value: serde_json::Value) -> Self {
+        self.extra.insert(name.into(), value);
+        self
+    }
+
+    /// Read the metadata of an imported JWK, keeping unknown members
+    ///
+    /// Key material and `kid` are not included.
+    pub fn from_jwk(jwk: &serde_json::Value) -> Result<Self> {
+        let mut members = jwk
+            .as_object()
+            .cloned()
+            .ok_or_else(|| Error::InvalidKeyFormat("JWK must be a JSON object".to_string()))?;
+        for name in KEY_MATERIAL_MEMBERS.iter().chain(&["kid"]) {
+            members.remove(*name);
+        }
+        serde_json::from_value(serde_json::Value::Object(members))
+            .map_err(|e| Error::InvalidKeyFormat(format!("Invalid JWK metadata: {e}")))
+    }
+}
+
 /// JWK representation for Ed25519 keys
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Ed25519Jwk {
@@ -60,6 +149,9 @@ pub struct Ed25519Jwk {
     /// Key ID - optional
     #[serde(skip_serializing_if = "Option::is_none")]
     pub kid: Option<String>,
+    /// Metadata and unknown members
+    #[serde(flatten)]
+    pub metadata: JwkMetadata,
 }
 
 /// JWK representation for Secp256k1 keys
@@ -79,6 +171,9 @@ pub struct Secp256k1Jwk {
     /// Key ID - optional
     #[serde(skip_serializing_if = "Option::is_none")]
     pub kid: Option<String>,
+    /// Metadata and unknown members
+    #[serde(flatten)]
+    pub metadata: JwkMetadata,
 }
 
 impl KeyExporter for PublicKey {
@@ -89,13 +184,7 @@ impl KeyExporter for PublicKey {
                 serde_json::to_vec(&jwk).map_err(|e| Error::Serialization(e.to_string()))
             }
             KeyFormat::Pem => Ok(self.to_pem()?.into_bytes()),
-            KeyFormat::Der => {
-                // For DER format, export as PEM then convert to DER
-                // TODO: Implement proper DER export
-                Err(Error::Unsupported(
-                    "DER format not yet implemented".to_string(),
-                ))
-            }
+            KeyFormat::Der => spki_der(self),
             KeyFormat::Raw => Ok(self.to_bytes()),
         }
     }
@@ -109,17 +198,11 @@ impl KeyExporter for PublicKey {
                     x: general_purpose::URL_SAFE_NO_PAD.encode(key_bytes),
                     d: None,
                     kid: Some(self.key_id()),
+                    metadata: JwkMetadata::default(),
                 };
                 serde_json::to_value(jwk).map_err(|e| Error::Serialization(e.to_string()))
             }
-            PublicKey::Secp256k1(_key_bytes) => {
-                // For secp256k1, we need to decode the compressed public key
-                // In a real implementation, this would use the k256 library to properly decode
-                // For now, we'll return an error as this requires more complex handling
-                Err(Error::Unsupported(
-                    "Secp256k1 JWK export not yet implemented".to_string(),
-                ))
-            }
+            PublicKey::Secp256k1(key_bytes) => secp256k1_jwk(key_bytes, None, self.key_id()),
         }
     }
 
@@ -151,13 +234,7 @@ impl KeyExporter for PrivateKey {
                 serde_json::to_vec(&jwk).map_err(|e| Error::Serialization(e.to_string()))
             }
             KeyFormat::Pem => Ok(self.to_pem()?.into_bytes()),
-            KeyFormat::Der => {
-                // For DER format, export as PKCS#8 DER
-                // TODO: Implement proper DER export
-                Err(Error::Unsupported(
-                    "DER format not yet implemented".to_string(),
-                ))
-            }
+            KeyFormat::Der => pkcs8_der(self),
             KeyFormat::Raw => Ok(self.to_bytes()),
         }
     }
@@ -172,12 +249,18 @@ impl KeyExporter for PrivateKey {
                     x: general_purpose::URL_SAFE_NO_PAD.encode(&self.public_key().to_bytes()[..32]),
                     d: Some(general_purpose::URL_SAFE_NO_PAD.encode(key_bytes)),
                     kid: Some(self.public_key().key_id()),
+                    metadata: JwkMetadata::default(),
                 };
                 serde_json::to_value(jwk).map_err(|e| Error::Serialization(e.to_string()))
             }
-            PrivateKey::Secp256k1(_) => Err(Error::Unsupported(
-                "Secp256k1 JWK export not yet implemented".to_string(),
-            )),
+            PrivateKey::Secp256k1(key_bytes) => {
+                let public_key = self.public_key();
+                secp256k1_jwk(
+                    &public_key.to_bytes(),
+                    Some(general_purpose::URL_SAFE_NO_PAD.encode(key_bytes)),
+                    public_key.key_id(),
+                )
+            }
         }
     }
 
@@ -215,6 +298,332 @@ impl KeyExporter for KeyPair {
     }
 }
 
+/// DER `AlgorithmIdentifier` for Ed25519 (RFC 8410)
+const ED25519_ALGORITHM: &[u8] = &[0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70];
+
+/// DER `AlgorithmIdentifier` for `id-ecPublicKey` on secp256k1 (RFC 5480)
+const SECP256K1_ALGORITHM: &[u8] = &[
+    0x30, 0x10, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x05, 0x2b, 0x81, 0x04,
+    0x00, 0x0a,
+];
+
+/// Encode a DER element with the given tag
+fn der(tag: u8, contents: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    let len = contents.len();
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|b| *b == 0)
+            .collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend_from_slice(&bytes);
+    }
+    out.extend_from_slice(contents);
+    out
+}
+
+/// Encode a DER `BIT STRING` with no unused bits
+fn der_bit_string(bytes: &[u8]) -> Vec<u8> {
+    der(0x03, &[&[0x00], bytes].concat())
+}
+
+/// Uncompressed SEC1 encoding of a secp256k1 public key
+fn secp256k1_uncompressed(public_key: &[u8]) -> Result<Vec<u8>> {
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+
+    let point = k256::PublicKey::from_sec1_bytes(public_key)
+        .map_err(|e| Error::InvalidKeyFormat(format!("Invalid secp256k1 point: {e}")))?
+        .to_encoded_point(false);
+    Ok(point.as_bytes().to_vec())
+}
+
+/// Encode a public key as a DER `SubjectPublicKeyInfo`
+fn spki_der(public_key: &PublicKey) -> Result<Vec<u8>> {
+    let (algorithm, key) = match public_key {
+        PublicKey::Ed25519(key_bytes) => (ED25519_ALGORITHM, key_bytes.to_vec()),
+        PublicKey::Secp256k1(key_bytes) => {
+            (SECP256K1_ALGORITHM, secp256k1_uncompressed(key_bytes)?)
+        }
+    };
+    Ok(der(0x30, &[algorithm, &der_bit_string(&key)].concat()))
+}
+
+/// Encode a private key as a DER PKCS#8 `PrivateKeyInfo`
+///
+/// Secp256k1 keys wrap an RFC 5915 `ECPrivateKey` that includes the public key.
+fn pkcs8_der(private_key: &PrivateKey) -> Result<Vec<u8>> {
+    let (algorithm, inner) = match private_key {
+        PrivateKey::Ed25519(key_bytes) => (ED25519_ALGORITHM, der(0x04, key_bytes)),
+        PrivateKey::Secp256k1(key_bytes) => {
+            let public_key = secp256k1_uncompressed(&private_key.public_key().to_bytes())?;
+            let ec_private_key = [
+                &[0x02, 0x01, 0x01][..],
+                &der(0x04, key_bytes),
+                &der(0xa1, &der_bit_string(&public_key)),
+            ]
+            .concat();
+            (SECP256K1_ALGORITHM, der(0x30, &ec_private_key))
+        }
+    };
+    let version = [0x02, 0x01, 0x00];
+    Ok(der(
+        0x30,
+        &[&version[..], algorithm, &der(0x04, &inner)].concat(),
+    ))
+}
+
+/// Split a DER element with the given tag off the front of `data`
+///
+/// Returns the element's contents and the remaining bytes.
+fn der_take(tag: u8, data: &[u8]) -> Result<(&[u8], &[u8])> {
+    let malformed = || Error::InvalidKeyFormat("Malformed DER".to_string());
+    let (&found, rest) = data.split_first().ok_or_else(malformed)?;
+    if found != tag {
+        return Err(Error::InvalidKeyFormat(format!(
+            "Expected DER tag {tag:#04x}, got {found:#04x}"
+        )));
+    }
+    let (&len, rest) = rest.split_first().ok_or_else(malformed)?;
+    let (len, rest) = if len < 0x80 {
+        (len as usize, rest)
+    } else {
+        let count = (len & 0x7f) as usize;
+        if count == 0 || count > 4 || rest.len() < count {
+            return Err(malformed());
+        }
+        let (len_bytes, rest) = rest.split_at(count);
+        let len = len_bytes
+            .iter()
+            .fold(0usize, |len, byte| (len << 8) | *byte as usize);
+        (len, rest)
+    };
+    if rest.len() < len {
+        return Err(malformed());
+    }
+    Ok(rest.split_at(len))
+}
+
+/// Key type named by the contents of a DER `AlgorithmIdentifier`
+fn algorithm_key_type(algorithm: &[u8]) -> Result<KeyType> {
+    let algorithm = der(0x30, algorithm);
+    if algorithm == ED25519_ALGORITHM {
+        Ok(KeyType::Ed25519)
+    } else if algorithm == SECP256K1_ALGORITHM {
+        Ok(KeyType::Secp256k1)
+    } else {
+        Err(Error::InvalidKeyType(
+            "Unsupported key algorithm".to_string(),
+        ))
+    }
+}
+
+/// Parse a public key from raw bytes, telling the key type by length
+///
+/// Ed25519 keys are 32 bytes; secp256k1 keys are 33 or 65 byte SEC1 points.
+fn public_key_from_raw(bytes: &[u8]) -> Result<PublicKey> {
+    match bytes.len() {
+        32 => PublicKey::from_bytes(KeyType::Ed25519, bytes),
+        33 | 65 => k256::PublicKey::from_sec1_bytes(bytes)
+            .map(Into::into)
+            .map_err(|e| Error::InvalidKeyFormat(format!("Invalid secp256k1 point: {e}"))),
+        len => Err(Error::InvalidKeyFormat(format!(
+            "Cannot tell the key type of a {len} byte public key"
+        ))),
+    }
+}
+
+/// Parse a DER `SubjectPublicKeyInfo`
+fn public_key_from_spki(data: &[u8]) -> Result<PublicKey> {
+    let (spki, _) = der_take(0x30, data)?;
+    let (algorithm, rest) = der_take(0x30, spki)?;
+    let (bits, _) = der_take(0x03, rest)?;
+    let key = bits
+        .strip_prefix(&[0x00])
+        .ok_or_else(|| Error::InvalidKeyFormat("Invalid public key bit string".to_string()))?;
+    match algorithm_key_type(algorithm)? {
+        KeyType::Ed25519 => PublicKey::from_bytes(KeyType::Ed25519, key),
+        KeyType::Secp256k1 => public_key_from_raw(key),
+    }
+}
+
+/// Parse the scalar out of an RFC 5915 `ECPrivateKey`
+fn secp256k1_from_ec_private_key(data: &[u8]) -> Result<KeyPair> {
+    let (ec_private_key, _) = der_take(0x30, data)?;
+    let (_, rest) = der_take(0x02, ec_private_key)?;
+    let (scalar, _) = der_take(0x04, rest)?;
+    KeyPair::from_private_key_bytes(KeyType::Secp256k1, scalar)
+}
+
+/// Parse a DER PKCS#8 `PrivateKeyInfo`
+fn keypair_from_pkcs8(data: &[u8]) -> Result<KeyPair> {
+    let (info, _) = der_take(0x30, data)?;
+    let (_, rest) = der_take(0x02, info)?;
+    let (algorithm, rest) = der_take(0x30, rest)?;
+    let (private_key, _) = der_take(0x04, rest)?;
+    match algorithm_key_type(algorithm)? {
+        KeyType::Ed25519 => {
+            let (seed, _) = der_take(0x04, private_key)?;
+            KeyPair::from_private_key_bytes(KeyType::Ed25519, seed)
+        }
+        KeyType::Secp256k1 => secp256k1_from_ec_private_key(private_key),
+    }
+}
+
+/// Parse a JSON-encoded JWK
+fn jwk_from_slice(data: &[u8]) -> Result<serde_json::Value> {
+    serde_json::from_slice(data).map_err(|e| Error::InvalidKeyFormat(format!("Invalid JWK: {e}")))
+}
+
+impl KeyImporter for KeyPair {
+    /// Import a public key; raw keys are told apart by length
+    ///
+    /// PEM accepts both SPKI and the raw contents written by [`KeyExporter::to_pem`].
+    fn import_public_key(data: &[u8], format: KeyFormat) -> Result<PublicKey> {
+        match format {
+            KeyFormat::Jwk => public_key_from_jwk(&jwk_from_slice(data)?),
+            KeyFormat::Der => public_key_from_spki(data),
+            KeyFormat::Pem => {
+                let pem = pem::parse(data)?;
+                if pem.tag != "PUBLIC KEY" {
+                    return Err(Error::InvalidKeyFormat(format!(
+                        "Expected a PUBLIC KEY PEM block, got {}",
+                        pem.tag
+                    )));
+                }
+                match pem.contents.first() {
+                    Some(0x30) => public_key_from_spki(&pem.contents),
+                    _ => public_key_from_raw(&pem.contents),
+                }
+            }
+            KeyFormat::Raw => public_key_from_raw(data),
+        }
+    }
+
+    fn import_private_key(data: &[u8], format: KeyFormat) -> Result<PrivateKey> {
+        Self::import_key_pair(data, format).map(|keypair| keypair.private_key().clone())
+    }
+
+    /// Import a key pair from a private key
+    ///
+    /// PEM accepts PKCS#8 and SEC1 as well as the raw contents written by
+    /// [`KeyExporter::to_pem`]. Raw private keys do not identify their key
+    /// type, so use [`KeyPair::from_private_key_bytes`] for those.
+    fn import_key_pair(data: &[u8], format: KeyFormat) -> Result<KeyPair> {
+        match format {
+            KeyFormat::Jwk => keypair_from_jwk(&jwk_from_slice(data)?),
+            KeyFormat::Der => keypair_from_pkcs8(data),
+            KeyFormat::Pem => {
+                let pem = pem::parse(data)?;
+                match (pem.tag.as_str(), pem.contents.len()) {
+                    ("PRIVATE KEY", 32) => {
+                        KeyPair::from_private_key_bytes(KeyType::Ed25519, &pem.contents)
+                    }
+                    ("PRIVATE KEY", _) => keypair_from_pkcs8(&pem.contents),
+                    ("EC PRIVATE KEY", 32) => {
+                        KeyPair::from_private_key_bytes(KeyType::Secp256k1, &pem.contents)
+                    }
+                    ("EC PRIVATE KEY", _) => secp256k1_from_ec_private_key(&pem.contents),
+                    (tag, _) => Err(Error::InvalidKeyFormat(format!(
+                        "Expected a PRIVATE KEY PEM block, got {tag}"
+                    ))),
+                }
+            }
+            KeyFormat::Raw => Err(Error::InvalidInput(
+                "Raw private keys need a key type".to_string(),
+            )),
+        }
+    }
+}
+
+/// Build an `EC`/`secp256k1` JWK from a SEC1-encoded public key
+fn secp256k1_jwk(public_key: &[u8], d: Option<String>, kid: String) -> Result<serde_json::Value> {
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+
+    let point = k256::PublicKey::from_sec1_bytes(public_key)
+        .map_err(|e| Error::InvalidKeyFormat(format!("Invalid secp256k1 point: {e}")))?
+        .to_encoded_point(false);
+    let (Some(x), Some(y)) = (point.x(), point.y()) else {
+        return Err(Error::InvalidKeyFormat(
+            "secp256k1 public key is the identity point".to_string(),
+        ));
+    };
+
+    let jwk = Secp256k1Jwk {
+        kty: "EC".to_string(),
+        crv: "secp256k1".to_string(),
+        x: general_purpose::URL_SAFE_NO_PAD.encode(x),
+        y: general_purpose::URL_SAFE_NO_PAD.encode(y),
+        d,
+        kid: Some(kid),
+        metadata: JwkMetadata::default(),
+    };
+    serde_json::to_value(jwk).map_err(|e| Error::Serialization(e.to_string()))
+}
+
+/// Parse a public key from a JWK (`OKP`/`Ed25519` or `EC`/`secp256k1`)
+///
+/// Private members are ignored.
+pub fn public_key_from_jwk(jwk: &serde_json::Value) -> Result<PublicKey> {
+    let member = |name: &str| {
+        jwk.get(name)
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| Error::InvalidKeyFormat(format!("JWK is missing the {name} member")))
+    };
+    let coordinate = |name: &str| -> Result<Vec<u8>> {
+        general_purpose::URL_SAFE_NO_PAD
+            .decode(member(name)?)
+            .map_err(|e| Error::InvalidKeyFormat(format!("Invalid JWK {name}: {e}")))
+    };
+
+    match (member("kty")?, member("crv")?) {
+        ("OKP", "Ed25519") => PublicKey::from_bytes(KeyType::Ed25519, &coordinate("x")?),
+        ("EC", "secp256k1") => {
+            let (x, y) = (coordinate("x")?, coordinate("y")?);
+            if x.len() != 32 || y.len() != 32 {
+                return Err(Error::InvalidKeyFormat(
+                    "secp256k1 JWK coordinates must be 32 bytes".to_string(),
+                ));
+            }
+            let mut uncompressed = Vec::with_capacity(65);
+            uncompressed.push(0x04);
+            uncompressed.extend_from_slice(&x);
+            uncompressed.extend_from_slice(&y);
+            let key = k256::PublicKey::from_sec1_bytes(&uncompressed)
+                .map_err(|e| Error::InvalidKeyFormat(format!("Invalid secp256k1 point: {e}")))?;
+            Ok(key.into())
+        }
+        (kty, crv) => Err(Error::InvalidKeyType(format!(
+            "Unsupported JWK key type {kty}/{crv}"
+        ))),
+    }
+}
+
+/// Parse a key pair from a private JWK
+///
+/// The public members must match the key derived from `d`.
+pub fn keypair_from_jwk(jwk: &serde_json::Value) -> Result<KeyPair> {
+    let public_key = public_key_from_jwk(jwk)?;
+    let d = jwk
+        .get("d")
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::InvalidKeyFormat("JWK is missing the d member".to_string()))?;
+    let d = general_purpose::URL_SAFE_NO_PAD
+        .decode(d)
+        .map_err(|e| Error::InvalidKeyFormat(format!("Invalid JWK d: {e}")))?;
+
+    let keypair = KeyPair::from_private_key_bytes(public_key.key_type(), &d)?;
+    if keypair.public_key().to_bytes() != public_key.to_bytes() {
+        return Err(Error::InvalidKeyFormat(
+            "JWK d does not match its public key".to_string(),
+        ));
+    }
+    Ok(keypair)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -224,4 +633,144 @@ mod tests {
         assert_eq!(KeyFormat::Jwk, KeyFormat::Jwk);
         assert_ne!(KeyFormat::Jwk, KeyFormat::Pem);
     }
+
+    #[test]
+    fn test_public_key_from_jwk() {
+        use k256::elliptic_curve::sec1::ToEncodedPoint;
+
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let jwk = keypair.public_key().to_jwk().unwrap();
+        let imported = public_key_from_jwk(&jwk).unwrap();
+        assert_eq!(imported.to_bytes(), keypair.public_key_bytes());
+
+        let keypair = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        let point = k256::PublicKey::from_sec1_bytes(&keypair.public_key_bytes())
+            .unwrap()
+            .to_encoded_point(false);
+        let jwk = serde_json::json!({
+            "kty": "EC",
+            "crv": "secp256k1",
+            "x": general_purpose::URL_SAFE_NO_PAD.encode(point.x().unwrap()),
+            "y": general_purpose::URL_SAFE_NO_PAD.encode(point.y().unwrap()),
+        });
+        let imported = public_key_from_jwk(&jwk).unwrap();
+        assert_eq!(imported.to_bytes(), keypair.public_key_bytes());
+
+        let jwk = serde_json::json!({"kty": "RSA", "crv": "none", "n": "AQAB"});
+        assert!(public_key_from_jwk(&jwk).is_err());
+    }
+
+    #[test]
+    fn test_keypair_jwk_round_trip() {
+        for key_type in [KeyType::Ed25519, KeyType::Secp256k1] {
+            let keypair = KeyPair::generate(key_type).unwrap();
+
+            let public_jwk = keypair.public_key().to_jwk().unwrap();
+            assert!(public_jwk.get("d").is_none());
+            assert_eq!(
+                public_key_from_jwk(&public_jwk).unwrap().to_bytes(),
+                keypair.public_key_bytes()
+            );
+
+            let private_jwk = keypair.to_jwk().unwrap();
+            let imported = keypair_from_jwk(&private_jwk).unwrap();
+            assert_eq!(imported.private_key_bytes(), keypair.private_key_bytes());
+            assert!(keypair_from_jwk(&public_jwk).is_err());
+        }
+
+        let mut mismatched = KeyPair::generate(KeyType::Ed25519)
+            .unwrap()
+            .to_jwk()
+            .unwrap();
+        mismatched["x"] = KeyPair::generate(KeyType::Ed25519)
+            .unwrap()
+            .public_key()
+            .to_jwk()
+            .unwrap()["x"]
+            .clone();
+        assert!(keypair_from_jwk(&mismatched).is_err());
+    }
+
+    #[test]
+    fn test_der_export() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let spki = keypair.public_key().export(KeyFormat::Der).unwrap();
+        assert_eq!(spki[..12], hex::decode("302a300506032b6570032100").unwrap());
+        assert_eq!(spki[12..], keypair.public_key_bytes());
+        let pkcs8 = keypair.export(KeyFormat::Der).unwrap();
+        assert_eq!(
+            pkcs8[..16],
+            hex::decode("302e020100300506032b657004220420").unwrap()
+        );
+        assert_eq!(pkcs8[16..], keypair.private_key_bytes());
+
+        let keypair = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        let spki = keypair.public_key().export(KeyFormat::Der).unwrap();
+        assert_eq!(spki.len(), 88);
+        assert_eq!(spki[..2], [0x30, 0x56]);
+        assert_eq!(
+            spki[23..],
+            secp256k1_uncompressed(&keypair.public_key_bytes()).unwrap()
+        );
+        let pkcs8 = keypair.export(KeyFormat::Der).unwrap();
+        assert_eq!(pkcs8.len(), 135);
+        assert_eq!(pkcs8[..3], [0x30, 0x81, 0x84]);
+        assert_eq!(pkcs8[33..65], keypair.private_key_bytes());
+    }
+
+    #[test]
+    fn test_import_round_trip() {
+        for key_type in [KeyType::Ed25519, KeyType::Secp256k1] {
+            let keypair = KeyPair::generate(key_type).unwrap();
+            for format in [KeyFormat::Jwk, KeyFormat::Pem, KeyFormat::Der] {
+                let exported = keypair.export(format).unwrap();
+                let imported = KeyPair::import_key_pair(&exported, format).unwrap();
+                assert_eq!(imported.private_key_bytes(), keypair.private_key_bytes());
+
+                let exported = keypair.public_key().export(format).unwrap();
+                let imported = KeyPair::import_public_key(&exported, format).unwrap();
+                assert_eq!(imported.to_bytes(), keypair.public_key_bytes());
+            }
+
+            let raw = keypair.public_key().export(KeyFormat::Raw).unwrap();
+            let imported = KeyPair::import_public_key(&raw, KeyFormat::Raw).unwrap();
+            assert_eq!(imported.key_type(), key_type);
+            assert!(
+                KeyPair::import_key_pair(&keypair.private_key_bytes(), KeyFormat::Raw).is_err()
+            );
+        }
+
+        assert!(KeyPair::import_public_key(&[0x30, 0x05, 0x00], KeyFormat::Der).is_err());
+    }
+
+    #[test]
+    fn test_jwk_metadata() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let metadata = JwkMetadata::new()
+            .with_alg("EdDSA")
+            .with_use("sig")
+            .with_key_ops(["verify"])
+            .with_member("x5u", serde_json::json!("https://example.com/cert"))
+            .with_member("x", serde_json::json!("ignored"));
+
+        let jwk = keypair.public_key().to_jwk_with(&metadata).unwrap();
+        assert_eq!(jwk["alg"], "EdDSA");
+        assert_eq!(jwk["use"], "sig");
+        assert_eq!(jwk["key_ops"], serde_json::json!(["verify"]));
+        assert_eq!(jwk["x5u"], "https://example.com/cert");
+        assert_eq!(jwk["x"], keypair.public_key().to_jwk().unwrap()["x"]);
+
+        // Unknown members survive a round trip through the JWK struct
+        let parsed: Ed25519Jwk = serde_json::from_value(jwk.clone()).unwrap();
+        assert_eq!(parsed.metadata.key_use.as_deref(), Some("sig"));
+        assert_eq!(serde_json::to_value(&parsed).unwrap(), jwk);
+
+        let imported = JwkMetadata::from_jwk(&jwk).unwrap();
+        assert_eq!(imported.extra.len(), 1);
+        assert_eq!(imported.alg.as_deref(), Some("EdDSA"));
+        assert_eq!(
+            public_key_from_jwk(&jwk).unwrap().to_bytes(),
+            keypair.public_key().to_bytes()
+        );
+    }
 }
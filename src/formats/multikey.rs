@@ -0,0 +1,181 @@
+//! W3C Multikey encoding
+//!
+//! Multikey values are base58btc multibase strings (`z` prefix) of a
+//! multicodec header followed by the key bytes, as used by `did:key` and the
+//! `publicKeyMultibase`/`secretKeyMultibase` members of DID documents.
+//! Secp256k1 public keys are encoded in compressed SEC1 form.
+
+use crate::crypto::{KeyPair, KeyType, PrivateKey, PublicKey};
+use crate::error::{Error, Result};
+use zeroize::Zeroize;
+
+/// Multicodec header of `ed25519-pub` (0xed)
+const ED25519_PUB: [u8; 2] = [0xed, 0x01];
+
+/// Multicodec header of `secp256k1-pub` (0xe7)
+const SECP256K1_PUB: [u8; 2] = [0xe7, 0x01];
+
+/// Multicodec header of `ed25519-priv` (0x1300)
+const ED25519_PRIV: [u8; 2] = [0x80, 0x26];
+
+/// Multicodec header of `secp256k1-priv` (0x1301)
+const SECP256K1_PRIV: [u8; 2] = [0x81, 0x26];
+
+/// Encode a public key as a `publicKeyMultibase` value
+pub fn public_key_to_multikey(public_key: &PublicKey) -> String {
+    let header = match public_key.key_type() {
+        KeyType::Ed25519 => ED25519_PUB,
+        KeyType::Secp256k1 => SECP256K1_PUB,
+    };
+    encode(&header, &public_key.to_bytes())
+}
+
+/// Encode a private key as a `secretKeyMultibase` value
+pub fn private_key_to_multikey(private_key: &PrivateKey) -> String {
+    let header = match private_key.key_type() {
+        KeyType::Ed25519 => ED25519_PRIV,
+        KeyType::Secp256k1 => SECP256K1_PRIV,
+    };
+    let mut key = private_key.to_bytes();
+    let multikey = encode(&header, &key);
+    key.zeroize();
+    multikey
+}
+
+/// Parse a `publicKeyMultibase` value
+pub fn public_key_from_multikey(multikey: &str) -> Result<PublicKey> {
+    let (header, key) = decode(multikey)?;
+    match header {
+        ED25519_PUB => PublicKey::from_bytes(KeyType::Ed25519, &key),
+        SECP256K1_PUB => PublicKey::from_bytes(KeyType::Secp256k1, &key),
+        _ => Err(unsupported(header)),
+    }
+}
+
+/// Parse a `secretKeyMultibase` value into a key pair
+pub fn keypair_from_multikey(multikey: &str) -> Result<KeyPair> {
+    let (header, mut key) = decode(multikey)?;
+    let keypair = match header {
+        ED25519_PRIV => KeyPair::from_private_key_bytes(KeyType::Ed25519, &key),
+        SECP256K1_PRIV => KeyPair::from_private_key_bytes(KeyType::Secp256k1, &key),
+        _ => Err(unsupported(header)),
+    };
+    key.zeroize();
+    keypair
+}
+
+/// Multikey verification method of a DID document
+pub fn multikey_verification_method(
+    id: &str,
+    controller: &str,
+    public_key: &PublicKey,
+) -> serde_json::Value {
+    serde_json::json!({
+        "id": id,
+        "type": "Multikey",
+        "controller": controller,
+        "publicKeyMultibase": public_key_to_multikey(public_key),
+    })
+}
+
+fn encode(header: &[u8; 2], key: &[u8]) -> String {
+    let mut bytes = Vec::with_capacity(header.len() + key.len());
+    bytes.extend_from_slice(header);
+    bytes.extend_from_slice(key);
+    let multikey = format!("z{}", bs58::encode(&bytes).into_string());
+    bytes.zeroize();
+    multikey
+}
+
+/// Split a multibase string into its multicodec header and key bytes
+///
+/// Every supported multicodec takes two bytes as a varint.
+fn decode(multikey: &str) -> Result<([u8; 2], Vec<u8>)> {
+    let encoded = multikey.strip_prefix('z').ok_or_else(|| {
+        Error::InvalidKeyFormat("Multikey must be base58btc multibase".to_string())
+    })?;
+    let mut bytes = bs58::decode(encoded)
+        .into_vec()
+        .map_err(|e| Error::InvalidKeyFormat(format!("Invalid Multikey base58: {e}")))?;
+    if bytes.len() < 2 {
+        return Err(Error::InvalidKeyFormat("Multikey is too short".to_string()));
+    }
+    let key = bytes.split_off(2);
+    Ok(([bytes[0], bytes[1]], key))
+}
+
+fn unsupported(header: [u8; 2]) -> Error {
+    Error::InvalidKeyType(format!(
+        "Unsupported Multikey codec {}",
+        hex::encode(header)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ed25519_multikey() {
+        // Key pair from the Data Integrity EdDSA Cryptosuites examples
+        let keypair =
+            keypair_from_multikey("z3u2en7t5LR2WtQH5PfFqMqwVHBeXouLzo6haApm8XHqvjxq").unwrap();
+        assert_eq!(
+            hex::encode(keypair.private_key_bytes()),
+            "c96ef9ea10c5e414c471723aff9de72c35fa5b70fae97e8832ecac7d2e2b8ed6"
+        );
+        assert_eq!(
+            public_key_to_multikey(keypair.public_key()),
+            "z6MkrJVnaZkeFzdQyMZu1cgjg7k1pZZ6pvBQ7XJPt4swbTQ2"
+        );
+        assert_eq!(
+            private_key_to_multikey(keypair.private_key()),
+            "z3u2en7t5LR2WtQH5PfFqMqwVHBeXouLzo6haApm8XHqvjxq"
+        );
+
+        let public_key =
+            public_key_from_multikey("z6MkrJVnaZkeFzdQyMZu1cgjg7k1pZZ6pvBQ7XJPt4swbTQ2").unwrap();
+        assert_eq!(public_key.to_bytes(), keypair.public_key_bytes());
+    }
+
+    #[test]
+    fn test_secp256k1_multikey() {
+        let keypair = KeyPair::from_private_key_bytes(KeyType::Secp256k1, &[1; 32]).unwrap();
+        let multikey = public_key_to_multikey(keypair.public_key());
+        assert_eq!(
+            multikey,
+            "zQ3shgVXZLaMzm5S5x7XzGUG6YFHFLtoEMiv9ao2Bqa7hGyg2"
+        );
+        assert_eq!(
+            public_key_from_multikey(&multikey).unwrap().to_bytes(),
+            keypair.public_key_bytes()
+        );
+
+        let secret = private_key_to_multikey(keypair.private_key());
+        assert_eq!(secret, "z3vLU4oxmMuk5hntHbupuqenVWfFEwiUniigZDvuqdPViY4G");
+        assert_eq!(
+            keypair_from_multikey(&secret).unwrap().private_key_bytes(),
+            keypair.private_key_bytes()
+        );
+    }
+
+    #[test]
+    fn test_invalid_multikey() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let multikey = public_key_to_multikey(keypair.public_key());
+
+        // Wrong multibase, public key where a secret is expected, and bad base58
+        assert!(public_key_from_multikey(&multikey.replacen('z', "u", 1)).is_err());
+        assert!(keypair_from_multikey(&multikey).is_err());
+        assert!(public_key_from_multikey("z0OIl").is_err());
+        assert!(public_key_from_multikey("z").is_err());
+
+        let method = multikey_verification_method(
+            "did:example:123#key-1",
+            "did:example:123",
+            keypair.public_key(),
+        );
+        assert_eq!(method["type"], "Multikey");
+        assert_eq!(method["publicKeyMultibase"], multikey);
+    }
+}
@@ -0,0 +1,616 @@
+//! Noise handshakes between agents
+//!
+//! Implements the `Noise_XX_25519_ChaChaPoly_SHA256` and
+//! `Noise_IK_25519_ChaChaPoly_SHA256` patterns of the Noise Protocol
+//! Framework, with Ed25519 identity keys acting as Noise static keys through
+//! their X25519 form. Each side sends its Ed25519 public key ahead of the
+//! payload of the message carrying its static key, so a finished handshake
+//! yields the peer's identity alongside the transport keys.
+//!
+//! Use XX when neither side knows the other's key in advance, and IK when
+//! the initiator already knows the responder's key, e.g. from its DID
+//! document, to save a round trip. A finished handshake starts a
+//! forward-secret [`Session`].
+
+pub mod session;
+
+pub use session::Session;
+
+use crate::crypto::wrap::{x25519_public_key, x25519_secret_key};
+use crate::crypto::{KeyPair, KeyType, PublicKey};
+use crate::error::{Error, Result};
+use chacha20poly1305::aead::{Aead, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, KeyInit};
+use rand::rngs::OsRng;
+use sha2::{Digest, Sha256};
+use x25519_dalek::StaticSecret;
+use zeroize::Zeroize;
+
+/// Prologue binding handshakes to this protocol
+const PROLOGUE: &[u8] = b"sage-handshake-v1";
+
+/// Length of X25519 keys, hashes and cipher keys
+const KEY_LENGTH: usize = 32;
+
+/// Length of the ChaCha20-Poly1305 tag
+const TAG_LENGTH: usize = 16;
+
+/// Maximum length of a Noise message
+pub const MAX_MESSAGE_LENGTH: usize = 65535;
+
+/// Supported handshake patterns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HandshakePattern {
+    /// Both static keys are transmitted during the handshake
+    XX,
+    /// The initiator knows the responder's static key in advance
+    IK,
+}
+
+impl HandshakePattern {
+    fn protocol_name(self) -> &'static [u8] {
+        match self {
+            HandshakePattern::XX => b"Noise_XX_25519_ChaChaPoly_SHA256",
+            HandshakePattern::IK => b"Noise_IK_25519_ChaChaPoly_SHA256",
+        }
+    }
+
+    fn messages(self) -> &'static [&'static [Token]] {
+        use Token::*;
+        match self {
+            HandshakePattern::XX => &[&[E], &[E, EE, S, ES], &[S, SE]],
+            HandshakePattern::IK => &[&[E, ES, S, SS], &[E, EE, SE]],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Token {
+    E,
+    S,
+    EE,
+    ES,
+    SE,
+    SS,
+}
+
+/// Keys for the transport phase of a finished handshake
+pub struct TransportKeys {
+    /// Key for messages to the peer
+    pub send: [u8; 32],
+    /// Key for messages from the peer
+    pub receive: [u8; 32],
+    /// Hash of the handshake transcript, identical on both sides
+    pub handshake_hash: [u8; 32],
+    /// Ed25519 identity of the peer
+    pub remote_identity: PublicKey,
+    /// Whether this side initiated the handshake
+    pub initiator: bool,
+}
+
+impl std::fmt::Debug for TransportKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TransportKeys")
+            .field("handshake_hash", &hex::encode(self.handshake_hash))
+            .field("remote_identity", &self.remote_identity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for TransportKeys {
+    fn drop(&mut self) {
+        self.send.zeroize();
+        self.receive.zeroize();
+    }
+}
+
+/// State of an in-progress handshake
+pub struct Handshake {
+    pattern: HandshakePattern,
+    initiator: bool,
+    symmetric: SymmetricState,
+    identity: PublicKey,
+    static_secret: StaticSecret,
+    static_public: [u8; 32],
+    ephemeral_secret: Option<StaticSecret>,
+    remote_static: Option<[u8; 32]>,
+    remote_ephemeral: Option<[u8; 32]>,
+    remote_identity: Option<PublicKey>,
+    message_index: usize,
+}
+
+impl Handshake {
+    /// Start an XX handshake as the initiator
+    pub fn xx_initiator(identity: &KeyPair) -> Result<Self> {
+        Self::new(HandshakePattern::XX, true, identity, None)
+    }
+
+    /// Start an XX handshake as the responder
+    pub fn xx_responder(identity: &KeyPair) -> Result<Self> {
+        Self::new(HandshakePattern::XX, false, identity, None)
+    }
+
+    /// Start an IK handshake with the responder whose identity key is `responder`
+    pub fn ik_initiator(identity: &KeyPair, responder: &PublicKey) -> Result<Self> {
+        Self::new(HandshakePattern::IK, true, identity, Some(responder))
+    }
+
+    /// Start an IK handshake as the responder
+    pub fn ik_responder(identity: &KeyPair) -> Result<Self> {
+        Self::new(HandshakePattern::IK, false, identity, None)
+    }
+
+    fn new(
+        pattern: HandshakePattern,
+        initiator: bool,
+        identity: &KeyPair,
+        responder: Option<&PublicKey>,
+    ) -> Result<Self> {
+        let mut secret = x25519_secret_key(identity.private_key())?;
+        let static_secret = StaticSecret::from(secret);
+        secret.zeroize();
+        let static_public = x25519_dalek::PublicKey::from(&static_secret).to_bytes();
+
+        let mut symmetric = SymmetricState::new(pattern.protocol_name());
+        symmetric.mix_hash(PROLOGUE);
+
+        let mut handshake = Self {
+            pattern,
+            initiator,
+            symmetric,
+            identity: identity.public_key().clone(),
+            static_secret,
+            static_public,
+            ephemeral_secret: None,
+            remote_static: None,
+            remote_ephemeral: None,
+            remote_identity: responder.cloned(),
+            message_index: 0,
+        };
+
+        // IK pre-message: <- s
+        if pattern == HandshakePattern::IK {
+            let responder_static = match responder {
+                Some(responder) => x25519_public_key(responder)?,
+                None => static_public,
+            };
+            handshake.remote_static = responder.map(|_| responder_static);
+            handshake.symmetric.mix_hash(&responder_static);
+        }
+        Ok(handshake)
+    }
+
+    /// The handshake pattern
+    pub fn pattern(&self) -> HandshakePattern {
+        self.pattern
+    }
+
+    /// Whether it is this side's turn to write a message
+    pub fn is_my_turn(&self) -> bool {
+        !self.is_finished() && (self.message_index % 2 == 0) == self.initiator
+    }
+
+    /// Whether all handshake messages have been exchanged
+    pub fn is_finished(&self) -> bool {
+        self.message_index == self.pattern.messages().len()
+    }
+
+    /// Ed25519 identity of the peer, once it is known
+    pub fn remote_identity(&self) -> Option<&PublicKey> {
+        self.remote_identity.as_ref()
+    }
+
+    /// Write the next handshake message, carrying `payload`
+    ///
+    /// Payloads of the first messages are not encrypted, or only encrypted
+    /// to a peer that is not authenticated yet; see the Noise specification
+    /// for the guarantees of each message.
+    pub fn write_message(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        if !self.is_my_turn() {
+            return Err(Error::InvalidInput(
+                "Not this side's turn to write a handshake message".to_string(),
+            ));
+        }
+
+        let tokens = self.pattern.messages()[self.message_index];
+        let mut message = Vec::new();
+        let mut sends_static = false;
+        for token in tokens {
+            match token {
+                Token::E => {
+                    let secret = StaticSecret::random_from_rng(OsRng);
+                    let public = x25519_dalek::PublicKey::from(&secret).to_bytes();
+                    self.symmetric.mix_hash(&public);
+                    message.extend_from_slice(&public);
+                    self.ephemeral_secret = Some(secret);
+                }
+                Token::S => {
+                    let encrypted = self.symmetric.encrypt_and_hash(&self.static_public)?;
+                    message.extend_from_slice(&encrypted);
+                    sends_static = true;
+                }
+                _ => self.mix_dh(*token)?,
+            }
+        }
+
+        let mut plaintext = Vec::with_capacity(KEY_LENGTH + payload.len());
+        if sends_static {
+            plaintext.extend_from_slice(&self.identity.to_bytes());
+        }
+        plaintext.extend_from_slice(payload);
+        message.extend_from_slice(&self.symmetric.encrypt_and_hash(&plaintext)?);
+        if message.len() > MAX_MESSAGE_LENGTH {
+            return Err(Error::InvalidInput(
+                "Handshake message exceeds the Noise message length".to_string(),
+            ));
+        }
+
+        self.message_index += 1;
+        Ok(message)
+    }
+
+    /// Read the peer's next handshake message and return its payload
+    pub fn read_message(&mut self, message: &[u8]) -> Result<Vec<u8>> {
+        if self.is_finished() || self.is_my_turn() {
+            return Err(Error::InvalidInput(
+                "Not this side's turn to read a handshake message".to_string(),
+            ));
+        }
+        if message.len() > MAX_MESSAGE_LENGTH {
+            return Err(Error::InvalidInput(
+                "Handshake message exceeds the Noise message length".to_string(),
+            ));
+        }
+
+        let tokens = self.pattern.messages()[self.message_index];
+        let mut rest = message;
+        let mut receives_static = false;
+        for token in tokens {
+            match token {
+                Token::E => {
+                    let public = take_key(&mut rest, KEY_LENGTH)?;
+                    let public: [u8; 32] = public.try_into().expect("length checked");
+                    self.symmetric.mix_hash(&public);
+                    self.remote_ephemeral = Some(public);
+                }
+                Token::S => {
+                    let length = KEY_LENGTH + self.symmetric.tag_length();
+                    let encrypted = take_key(&mut rest, length)?;
+                    let public = self.symmetric.decrypt_and_hash(encrypted)?;
+                    self.remote_static = Some(public.try_into().expect("length checked"));
+                    receives_static = true;
+                }
+                _ => self.mix_dh(*token)?,
+            }
+        }
+
+        let mut payload = self.symmetric.decrypt_and_hash(rest)?;
+        if receives_static {
+            payload = self.take_remote_identity(payload)?;
+        }
+
+        self.message_index += 1;
+        Ok(payload)
+    }
+
+    /// Hash of the handshake transcript so far
+    pub fn handshake_hash(&self) -> [u8; 32] {
+        self.symmetric.h
+    }
+
+    /// Finish the handshake and derive the transport keys
+    pub fn into_transport_keys(self) -> Result<TransportKeys> {
+        if !self.is_finished() {
+            return Err(Error::InvalidInput("Handshake is not finished".to_string()));
+        }
+        let remote_identity = self.remote_identity.clone().ok_or_else(|| {
+            Error::Verification("Peer identity was not authenticated".to_string())
+        })?;
+
+        let (initiator_key, responder_key) = self.symmetric.split()?;
+        let (send, receive) = if self.initiator {
+            (initiator_key, responder_key)
+        } else {
+            (responder_key, initiator_key)
+        };
+        Ok(TransportKeys {
+            send,
+            receive,
+            handshake_hash: self.symmetric.h,
+            remote_identity,
+            initiator: self.initiator,
+        })
+    }
+
+    /// Finish the handshake and start a session over it
+    pub fn into_session(self) -> Result<Session> {
+        Session::new(self.into_transport_keys()?)
+    }
+
+    /// Split the peer's Ed25519 identity off a payload and check it matches its static key
+    fn take_remote_identity(&mut self, mut payload: Vec<u8>) -> Result<Vec<u8>> {
+        if payload.len() < KEY_LENGTH {
+            return Err(Error::Verification(
+                "Handshake payload has no identity key".to_string(),
+            ));
+        }
+        let rest = payload.split_off(KEY_LENGTH);
+        let identity = PublicKey::from_bytes(KeyType::Ed25519, &payload)?;
+        if Some(x25519_public_key(&identity)?) != self.remote_static {
+            return Err(Error::Verification(
+                "Identity key does not match the handshake static key".to_string(),
+            ));
+        }
+        self.remote_identity = Some(identity);
+        Ok(rest)
+    }
+
+    /// Mix a Diffie-Hellman result into the chaining key
+    fn mix_dh(&mut self, token: Token) -> Result<()> {
+        let missing = || Error::InvalidInput("Handshake key is missing".to_string());
+        let ephemeral = self.ephemeral_secret.as_ref().ok_or_else(missing);
+        let remote_ephemeral = self.remote_ephemeral.ok_or_else(missing);
+        let remote_static = self.remote_static.ok_or_else(missing);
+
+        // `es` is DH(e, rs) for the initiator and DH(s, re) for the responder
+        let (secret, public) = match (token, self.initiator) {
+            (Token::EE, _) => (ephemeral?, remote_ephemeral?),
+            (Token::SS, _) => (&self.static_secret, remote_static?),
+            (Token::ES, true) | (Token::SE, false) => (ephemeral?, remote_static?),
+            (Token::ES, false) | (Token::SE, true) => (&self.static_secret, remote_ephemeral?),
+            (Token::E | Token::S, _) => unreachable!("not a DH token"),
+        };
+
+        let shared = secret.diffie_hellman(&x25519_dalek::PublicKey::from(public));
+        if !shared.was_contributory() {
+            return Err(Error::InvalidInput(
+                "Peer X25519 key is a low-order point".to_string(),
+            ));
+        }
+        self.symmetric.mix_key(shared.as_bytes())
+    }
+}
+
+/// Take `length` bytes off the front of a handshake message
+fn take_key<'a>(rest: &mut &'a [u8], length: usize) -> Result<&'a [u8]> {
+    if rest.len() < length {
+        return Err(Error::InvalidInput(
+            "Handshake message is truncated".to_string(),
+        ));
+    }
+    let (key, tail) = rest.split_at(length);
+    *rest = tail;
+    Ok(key)
+}
+
+/// Noise `SymmetricState` with its `CipherState`
+struct SymmetricState {
+    ck: [u8; 32],
+    h: [u8; 32],
+    k: Option<[u8; 32]>,
+    n: u64,
+}
+
+impl SymmetricState {
+    fn new(protocol_name: &[u8]) -> Self {
+        let mut h = [0u8; 32];
+        if protocol_name.len() <= KEY_LENGTH {
+            h[..protocol_name.len()].copy_from_slice(protocol_name);
+        } else {
+            h.copy_from_slice(&Sha256::digest(protocol_name));
+        }
+        Self {
+            ck: h,
+            h,
+            k: None,
+            n: 0,
+        }
+    }
+
+    fn mix_hash(&mut self, data: &[u8]) {
+        let mut hasher = Sha256::new();
+        hasher.update(self.h);
+        hasher.update(data);
+        self.h = hasher.finalize().into();
+    }
+
+    fn mix_key(&mut self, input_key_material: &[u8]) -> Result<()> {
+        let [ck, k] = kdf(&self.ck, input_key_material)?;
+        self.ck = ck;
+        self.k = Some(k);
+        self.n = 0;
+        Ok(())
+    }
+
+    fn tag_length(&self) -> usize {
+        if self.k.is_some() {
+            TAG_LENGTH
+        } else {
+            0
+        }
+    }
+
+    fn encrypt_and_hash(&mut self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let ciphertext = match &self.k {
+            Some(k) => encrypt(k, self.n, &self.h, plaintext)?,
+            None => plaintext.to_vec(),
+        };
+        if self.k.is_some() {
+            self.n += 1;
+        }
+        self.mix_hash(&ciphertext);
+        Ok(ciphertext)
+    }
+
+    fn decrypt_and_hash(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        let plaintext = match &self.k {
+            Some(k) => decrypt(k, self.n, &self.h, ciphertext)?,
+            None => ciphertext.to_vec(),
+        };
+        if self.k.is_some() {
+            self.n += 1;
+        }
+        self.mix_hash(ciphertext);
+        Ok(plaintext)
+    }
+
+    /// Derive the initiator and responder transport keys
+    fn split(&self) -> Result<([u8; 32], [u8; 32])> {
+        let [initiator, responder] = kdf(&self.ck, &[])?;
+        Ok((initiator, responder))
+    }
+}
+
+impl Drop for SymmetricState {
+    fn drop(&mut self) {
+        self.ck.zeroize();
+        self.k.zeroize();
+    }
+}
+
+/// Noise `HKDF` with two outputs, which is RFC 5869 HKDF with empty info
+fn kdf(chaining_key: &[u8; 32], input_key_material: &[u8]) -> Result<[[u8; 32]; 2]> {
+    let mut okm = [0u8; 2 * KEY_LENGTH];
+    hkdf::Hkdf::<Sha256>::new(Some(chaining_key), input_key_material)
+        .expand(&[], &mut okm)
+        .map_err(|e| Error::CryptoError(format!("Key derivation failed: {e}")))?;
+    let mut outputs = [[0u8; 32]; 2];
+    outputs[0].copy_from_slice(&okm[..KEY_LENGTH]);
+    outputs[1].copy_from_slice(&okm[KEY_LENGTH..]);
+    okm.zeroize();
+    Ok(outputs)
+}
+
+/// Noise ChaChaPoly nonce: 32 zero bits followed by the little-endian counter
+fn nonce(n: u64) -> chacha20poly1305::Nonce {
+    let mut nonce = [0u8; 12];
+    nonce[4..].copy_from_slice(&n.to_le_bytes());
+    nonce.into()
+}
+
+/// Encrypt with ChaCha20-Poly1305 under key `k` and counter `n`
+pub(crate) fn encrypt(k: &[u8; 32], n: u64, ad: &[u8], plaintext: &[u8]) -> Result<Vec<u8>> {
+    ChaCha20Poly1305::new(k.into())
+        .encrypt(
+            &nonce(n),
+            Payload {
+                msg: plaintext,
+                aad: ad,
+            },
+        )
+        .map_err(|_| Error::CryptoError("Encryption failed".to_string()))
+}
+
+/// Decrypt with ChaCha20-Poly1305 under key `k` and counter `n`
+pub(crate) fn decrypt(k: &[u8; 32], n: u64, ad: &[u8], ciphertext: &[u8]) -> Result<Vec<u8>> {
+    ChaCha20Poly1305::new(k.into())
+        .decrypt(
+            &nonce(n),
+            Payload {
+                msg: ciphertext,
+                aad: ad,
+            },
+        )
+        .map_err(|_| Error::CryptoError("Decryption failed".to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(mut initiator: Handshake, mut responder: Handshake) -> (TransportKeys, TransportKeys) {
+        let mut round = 0;
+        while !initiator.is_finished() {
+            let (writer, reader) = if initiator.is_my_turn() {
+                (&mut initiator, &mut responder)
+            } else {
+                (&mut responder, &mut initiator)
+            };
+            let payload = format!("message {round}");
+            let message = writer.write_message(payload.as_bytes()).unwrap();
+            assert_eq!(reader.read_message(&message).unwrap(), payload.as_bytes());
+            round += 1;
+        }
+        assert!(responder.is_finished());
+        assert_eq!(initiator.handshake_hash(), responder.handshake_hash());
+        (
+            initiator.into_transport_keys().unwrap(),
+            responder.into_transport_keys().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_xx_handshake() {
+        let alice = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let bob = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+        let (a, b) = run(
+            Handshake::xx_initiator(&alice).unwrap(),
+            Handshake::xx_responder(&bob).unwrap(),
+        );
+        assert_eq!(a.send, b.receive);
+        assert_eq!(a.receive, b.send);
+        assert_ne!(a.send, a.receive);
+        assert_eq!(a.handshake_hash, b.handshake_hash);
+        assert_eq!(&a.remote_identity, bob.public_key());
+        assert_eq!(&b.remote_identity, alice.public_key());
+    }
+
+    #[test]
+    fn test_ik_handshake() {
+        let alice = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let bob = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+        let (a, b) = run(
+            Handshake::ik_initiator(&alice, bob.public_key()).unwrap(),
+            Handshake::ik_responder(&bob).unwrap(),
+        );
+        assert_eq!(a.send, b.receive);
+        assert_eq!(a.receive, b.send);
+        assert_eq!(&a.remote_identity, bob.public_key());
+        assert_eq!(&b.remote_identity, alice.public_key());
+    }
+
+    #[test]
+    fn test_ik_wrong_responder() {
+        let alice = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let bob = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let mallory = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+        let mut initiator = Handshake::ik_initiator(&alice, bob.public_key()).unwrap();
+        let mut responder = Handshake::ik_responder(&mallory).unwrap();
+        let message = initiator.write_message(b"hello").unwrap();
+        assert!(responder.read_message(&message).is_err());
+    }
+
+    #[test]
+    fn test_tampered_message() {
+        let alice = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let bob = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+        let mut initiator = Handshake::xx_initiator(&alice).unwrap();
+        let mut responder = Handshake::xx_responder(&bob).unwrap();
+        let message = initiator.write_message(b"").unwrap();
+        responder.read_message(&message).unwrap();
+
+        let mut message = responder.write_message(b"").unwrap();
+        let last = message.len() - 1;
+        message[last] ^= 1;
+        assert!(initiator.read_message(&message).is_err());
+    }
+
+    #[test]
+    fn test_turns_and_key_types() {
+        let alice = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let mut responder = Handshake::xx_responder(&alice).unwrap();
+        assert!(!responder.is_my_turn());
+        assert!(responder.write_message(b"").is_err());
+        assert!(Handshake::xx_initiator(&alice)
+            .unwrap()
+            .into_transport_keys()
+            .is_err());
+
+        let secp256k1 = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        assert!(Handshake::xx_initiator(&secp256k1).is_err());
+    }
+}
@@ -0,0 +1,361 @@
+//! Forward-secret sessions over a finished handshake
+//!
+//! A [`Session`] runs a Double Ratchet over the handshake's transport keys:
+//! every message is encrypted under a fresh key from a hash chain, and each
+//! change of direction mixes a new ephemeral X25519 exchange into the root
+//! key. Keys are deleted once used, so a compromise exposes neither earlier
+//! messages nor, once the peers have exchanged new ephemeral keys, later ones.
+//!
+//! Messages are `ratchet key || previous chain length || message number ||
+//! ciphertext`, with the header and handshake hash as associated data.
+//! Messages may arrive out of order, up to [`MAX_SKIP`] messages late.
+
+use super::{decrypt, encrypt, kdf, TransportKeys, KEY_LENGTH, TAG_LENGTH};
+use crate::crypto::PublicKey;
+use crate::error::{Error, Result};
+use rand::rngs::OsRng;
+use std::collections::HashMap;
+use x25519_dalek::StaticSecret;
+use zeroize::Zeroize;
+
+/// Maximum number of skipped message keys kept for late messages
+pub const MAX_SKIP: usize = 1000;
+
+/// Length of the cleartext message header
+const HEADER_LENGTH: usize = KEY_LENGTH + 8;
+
+/// An encrypted channel with a peer
+#[derive(Clone)]
+pub struct Session {
+    root_key: [u8; 32],
+    ratchet_secret: StaticSecret,
+    ratchet_public: [u8; 32],
+    remote_ratchet: Option<[u8; 32]>,
+    send_chain: [u8; 32],
+    receive_chain: Option<[u8; 32]>,
+    send_count: u32,
+    receive_count: u32,
+    previous_send_count: u32,
+    skipped: HashMap<([u8; 32], u32), [u8; 32]>,
+    handshake_hash: [u8; 32],
+    remote_identity: PublicKey,
+}
+
+impl Session {
+    /// Start a session from the transport keys of a finished handshake
+    ///
+    /// The responder's first ratchet key is derived from the transport keys,
+    /// so the initiator can ratchet with its first message and either side
+    /// can send first.
+    pub fn new(keys: TransportKeys) -> Result<Self> {
+        let (initiator_key, responder_key) = if keys.initiator {
+            (keys.send, keys.receive)
+        } else {
+            (keys.receive, keys.send)
+        };
+        let mut input_key_material = [0u8; 2 * KEY_LENGTH];
+        input_key_material[..KEY_LENGTH].copy_from_slice(&initiator_key);
+        input_key_material[KEY_LENGTH..].copy_from_slice(&responder_key);
+        let [root_key, mut responder_seed] = kdf(&keys.handshake_hash, &input_key_material)?;
+        input_key_material.zeroize();
+        let responder_secret = StaticSecret::from(responder_seed);
+        responder_seed.zeroize();
+        let responder_public = x25519_dalek::PublicKey::from(&responder_secret).to_bytes();
+
+        if !keys.initiator {
+            return Ok(Self {
+                root_key,
+                ratchet_secret: responder_secret,
+                ratchet_public: responder_public,
+                remote_ratchet: None,
+                send_chain: keys.send,
+                receive_chain: None,
+                send_count: 0,
+                receive_count: 0,
+                previous_send_count: 0,
+                skipped: HashMap::new(),
+                handshake_hash: keys.handshake_hash,
+                remote_identity: keys.remote_identity.clone(),
+            });
+        }
+
+        let ratchet_secret = StaticSecret::random_from_rng(OsRng);
+        let ratchet_public = x25519_dalek::PublicKey::from(&ratchet_secret).to_bytes();
+        let [root_key, send_chain] = kdf(&root_key, &dh(&ratchet_secret, &responder_public)?)?;
+        Ok(Self {
+            root_key,
+            ratchet_secret,
+            ratchet_public,
+            remote_ratchet: Some(responder_public),
+            send_chain,
+            // The responder sends under its first ratchet key before ratcheting
+            receive_chain: Some(keys.receive),
+            send_count: 0,
+            receive_count: 0,
+            previous_send_count: 0,
+            skipped: HashMap::new(),
+            handshake_hash: keys.handshake_hash,
+            remote_identity: keys.remote_identity.clone(),
+        })
+    }
+
+    /// Ed25519 identity of the peer
+    pub fn remote_identity(&self) -> &PublicKey {
+        &self.remote_identity
+    }
+
+    /// Hash of the handshake the session was established with
+    pub fn handshake_hash(&self) -> [u8; 32] {
+        self.handshake_hash
+    }
+
+    /// Encrypt a message to the peer
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let send_count = self
+            .send_count
+            .checked_add(1)
+            .ok_or_else(|| Error::CryptoError("Session sending chain is exhausted".to_string()))?;
+        let [send_chain, mut message_key] = kdf(&self.send_chain, &[])?;
+
+        let mut message = Vec::with_capacity(HEADER_LENGTH + plaintext.len() + TAG_LENGTH);
+        message.extend_from_slice(&self.ratchet_public);
+        message.extend_from_slice(&self.previous_send_count.to_be_bytes());
+        message.extend_from_slice(&self.send_count.to_be_bytes());
+        let ciphertext = encrypt(&message_key, 0, &self.associated_data(&message), plaintext);
+        message_key.zeroize();
+        message.extend_from_slice(&ciphertext?);
+
+        self.send_chain = send_chain;
+        self.send_count = send_count;
+        Ok(message)
+    }
+
+    /// Decrypt a message from the peer
+    ///
+    /// The session is left unchanged if the message does not authenticate.
+    pub fn decrypt(&mut self, message: &[u8]) -> Result<Vec<u8>> {
+        if message.len() < HEADER_LENGTH + TAG_LENGTH {
+            return Err(Error::InvalidInput(
+                "Session message is truncated".to_string(),
+            ));
+        }
+        let (header, ciphertext) = message.split_at(HEADER_LENGTH);
+        let mut ratchet = [0u8; KEY_LENGTH];
+        ratchet.copy_from_slice(&header[..KEY_LENGTH]);
+        let mut count = [0u8; 4];
+        count.copy_from_slice(&header[KEY_LENGTH..KEY_LENGTH + 4]);
+        let previous_count = u32::from_be_bytes(count);
+        count.copy_from_slice(&header[KEY_LENGTH + 4..]);
+        let number = u32::from_be_bytes(count);
+
+        let mut next = self.clone();
+        let mut message_key = next.message_key(ratchet, previous_count, number)?;
+        let plaintext = decrypt(&message_key, 0, &next.associated_data(header), ciphertext);
+        message_key.zeroize();
+        let plaintext = plaintext?;
+
+        *self = next;
+        Ok(plaintext)
+    }
+
+    /// Find or derive the key of message `number` under `ratchet`
+    fn message_key(
+        &mut self,
+        ratchet: [u8; 32],
+        previous_count: u32,
+        number: u32,
+    ) -> Result<[u8; 32]> {
+        if let Some(message_key) = self.skipped.remove(&(ratchet, number)) {
+            return Ok(message_key);
+        }
+
+        if self.remote_ratchet != Some(ratchet) {
+            self.skip_to(previous_count)?;
+            self.ratchet(ratchet)?;
+        }
+        self.skip_to(number)?;
+
+        let receive_chain = self
+            .receive_chain
+            .ok_or_else(|| Error::CryptoError("Session has no receiving chain".to_string()))?;
+        let [receive_chain, message_key] = kdf(&receive_chain, &[])?;
+        self.receive_chain = Some(receive_chain);
+        self.receive_count = self.receive_count.saturating_add(1);
+        Ok(message_key)
+    }
+
+    /// Store the keys of messages of the receiving chain up to `number` for late delivery
+    fn skip_to(&mut self, number: u32) -> Result<()> {
+        let (Some(mut receive_chain), Some(ratchet)) = (self.receive_chain, self.remote_ratchet)
+        else {
+            return Ok(());
+        };
+        if number <= self.receive_count {
+            return Ok(());
+        }
+        if (number - self.receive_count) as usize + self.skipped.len() > MAX_SKIP {
+            return Err(Error::CryptoError(
+                "Too many skipped session messages".to_string(),
+            ));
+        }
+
+        while self.receive_count < number {
+            let [next, message_key] = kdf(&receive_chain, &[])?;
+            self.skipped
+                .insert((ratchet, self.receive_count), message_key);
+            receive_chain = next;
+            self.receive_count += 1;
+        }
+        self.receive_chain = Some(receive_chain);
+        Ok(())
+    }
+
+    /// Ratchet to the peer's new key and a fresh key of our own
+    fn ratchet(&mut self, remote_ratchet: [u8; 32]) -> Result<()> {
+        let [root_key, receive_chain] =
+            kdf(&self.root_key, &dh(&self.ratchet_secret, &remote_ratchet)?)?;
+
+        let ratchet_secret = StaticSecret::random_from_rng(OsRng);
+        let [root_key, send_chain] = kdf(&root_key, &dh(&ratchet_secret, &remote_ratchet)?)?;
+
+        self.ratchet_public = x25519_dalek::PublicKey::from(&ratchet_secret).to_bytes();
+        self.ratchet_secret = ratchet_secret;
+        self.remote_ratchet = Some(remote_ratchet);
+        self.root_key = root_key;
+        self.receive_chain = Some(receive_chain);
+        self.send_chain = send_chain;
+        self.previous_send_count = self.send_count;
+        self.send_count = 0;
+        self.receive_count = 0;
+        Ok(())
+    }
+
+    fn associated_data(&self, header: &[u8]) -> Vec<u8> {
+        let mut associated_data = self.handshake_hash.to_vec();
+        associated_data.extend_from_slice(&header[..HEADER_LENGTH]);
+        associated_data
+    }
+}
+
+impl std::fmt::Debug for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Session")
+            .field("handshake_hash", &hex::encode(self.handshake_hash))
+            .field("remote_identity", &self.remote_identity)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        self.root_key.zeroize();
+        self.send_chain.zeroize();
+        self.receive_chain.zeroize();
+        for message_key in self.skipped.values_mut() {
+            message_key.zeroize();
+        }
+    }
+}
+
+/// X25519 exchange with a peer ratchet key
+fn dh(secret: &StaticSecret, public: &[u8; 32]) -> Result<[u8; 32]> {
+    let shared = secret.diffie_hellman(&x25519_dalek::PublicKey::from(*public));
+    if !shared.was_contributory() {
+        return Err(Error::InvalidInput(
+            "Peer ratchet key is a low-order point".to_string(),
+        ));
+    }
+    Ok(shared.to_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType};
+    use crate::handshake::Handshake;
+
+    fn sessions() -> (Session, Session) {
+        let alice = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let bob = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let mut initiator = Handshake::ik_initiator(&alice, bob.public_key()).unwrap();
+        let mut responder = Handshake::ik_responder(&bob).unwrap();
+        let message = initiator.write_message(b"").unwrap();
+        responder.read_message(&message).unwrap();
+        let message = responder.write_message(b"").unwrap();
+        initiator.read_message(&message).unwrap();
+        (
+            initiator.into_session().unwrap(),
+            responder.into_session().unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_conversation() {
+        let (mut alice, mut bob) = sessions();
+
+        // Either side may send first
+        let message = bob.encrypt(b"ready").unwrap();
+        assert_eq!(alice.decrypt(&message).unwrap(), b"ready");
+
+        for round in 0..5 {
+            let ratchet = alice.ratchet_public;
+            for i in 0..3 {
+                let text = format!("alice {round} {i}");
+                let message = alice.encrypt(text.as_bytes()).unwrap();
+                assert_eq!(bob.decrypt(&message).unwrap(), text.as_bytes());
+            }
+            let message = bob.encrypt(b"ack").unwrap();
+            assert_eq!(alice.decrypt(&message).unwrap(), b"ack");
+
+            // Every round trip replaces the ephemeral ratchet keys
+            assert_ne!(alice.ratchet_public, ratchet);
+        }
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let (mut alice, mut bob) = sessions();
+
+        let first = alice.encrypt(b"first").unwrap();
+        let second = alice.encrypt(b"second").unwrap();
+        let third = alice.encrypt(b"third").unwrap();
+        assert_eq!(bob.decrypt(&third).unwrap(), b"third");
+        assert_eq!(bob.decrypt(&first).unwrap(), b"first");
+
+        // A reply ratchets Alice before the late message arrives
+        let reply = bob.encrypt(b"reply").unwrap();
+        assert_eq!(alice.decrypt(&reply).unwrap(), b"reply");
+        let after = alice.encrypt(b"after").unwrap();
+        assert_eq!(bob.decrypt(&after).unwrap(), b"after");
+        assert_eq!(bob.decrypt(&second).unwrap(), b"second");
+    }
+
+    #[test]
+    fn test_replay_and_tampering() {
+        let (mut alice, mut bob) = sessions();
+
+        let message = alice.encrypt(b"once").unwrap();
+        assert_eq!(bob.decrypt(&message).unwrap(), b"once");
+        assert!(bob.decrypt(&message).is_err());
+
+        let mut tampered = alice.encrypt(b"payload").unwrap();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(bob.decrypt(&tampered).is_err());
+        tampered[last] ^= 1;
+        assert_eq!(bob.decrypt(&tampered).unwrap(), b"payload");
+
+        // A message from another session does not disturb this one
+        let (mut other, _) = sessions();
+        assert!(bob.decrypt(&other.encrypt(b"foreign").unwrap()).is_err());
+        let message = alice.encrypt(b"still here").unwrap();
+        assert_eq!(bob.decrypt(&message).unwrap(), b"still here");
+    }
+
+    #[test]
+    fn test_identities() {
+        let (alice, bob) = sessions();
+        assert_eq!(alice.handshake_hash(), bob.handshake_hash());
+        assert_ne!(alice.remote_identity(), bob.remote_identity());
+    }
+}
@@ -0,0 +1,229 @@
+//! Windows CNG signer
+//!
+//! Keys are persisted P-256 keys of a CNG key storage provider, either the
+//! software provider or the platform provider, which keeps them in the TPM.
+
+use super::key_id_for_sec1;
+use crate::error::{Error, Result};
+use crate::rfc9421::{CustomSigner, SignatureAlgorithm};
+use sha2::{Digest, Sha256};
+use std::ptr;
+use windows_sys::core::{HRESULT, PCWSTR};
+use windows_sys::Win32::Security::Cryptography::{
+    NCryptCreatePersistedKey, NCryptExportKey, NCryptFinalizeKey, NCryptFreeObject, NCryptOpenKey,
+    NCryptOpenStorageProvider, NCryptSignHash, BCRYPT_ECCPUBLIC_BLOB, BCRYPT_ECDSA_P256_ALGORITHM,
+    MS_KEY_STORAGE_PROVIDER, MS_PLATFORM_CRYPTO_PROVIDER, NCRYPT_KEY_HANDLE, NCRYPT_PROV_HANDLE,
+    NCRYPT_SILENT_FLAG,
+};
+
+/// `NTE_BAD_KEYSET`, returned when a named key does not exist
+const NTE_BAD_KEYSET: HRESULT = 0x8009_0016_u32 as HRESULT;
+
+/// Size of the `BCRYPT_ECCKEY_BLOB` header preceding the public point
+const ECC_BLOB_HEADER_LENGTH: usize = 8;
+
+/// Size of the X and Y coordinates of a P-256 point
+const P256_POINT_LENGTH: usize = 64;
+
+/// Size of a P-256 signature in IEEE P1363 (`r || s`) form
+const P256_SIGNATURE_LENGTH: usize = 64;
+
+/// CNG key storage providers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CngProvider {
+    /// Microsoft Software Key Storage Provider
+    Software,
+    /// Microsoft Platform Crypto Provider, backed by the TPM
+    Platform,
+}
+
+impl CngProvider {
+    fn name(&self) -> PCWSTR {
+        match self {
+            CngProvider::Software => MS_KEY_STORAGE_PROVIDER,
+            CngProvider::Platform => MS_PLATFORM_CRYPTO_PROVIDER,
+        }
+    }
+}
+
+/// Owned NCrypt handle, freed on drop
+struct Handle(usize);
+
+impl Drop for Handle {
+    fn drop(&mut self) {
+        if self.0 != 0 {
+            // SAFETY: the handle was returned by NCrypt and is freed once
+            unsafe {
+                NCryptFreeObject(self.0);
+            }
+        }
+    }
+}
+
+/// Signer for a P-256 key persisted by a CNG provider
+pub struct CngSigner {
+    key: Handle,
+    public_key: Vec<u8>,
+    key_id: String,
+}
+
+impl CngSigner {
+    /// Create a persisted key named `name`
+    pub fn generate(provider: CngProvider, name: &str) -> Result<Self> {
+        let provider = open_provider(provider)?;
+        let name = wide(name);
+        let mut key: NCRYPT_KEY_HANDLE = 0;
+        // SAFETY: the provider handle is open and the out pointer and
+        // NUL-terminated strings are valid for the call
+        check(
+            unsafe {
+                NCryptCreatePersistedKey(
+                    provider.0,
+                    &mut key,
+                    BCRYPT_ECDSA_P256_ALGORITHM,
+                    name.as_ptr(),
+                    0,
+                    0,
+                )
+            },
+            Error::KeyGeneration,
+        )?;
+        let key = Handle(key);
+        // SAFETY: the key handle is open and not finalized yet
+        check(
+            unsafe { NCryptFinalizeKey(key.0, NCRYPT_SILENT_FLAG) },
+            Error::KeyGeneration,
+        )?;
+        Self::from_key(key)
+    }
+
+    /// Open the persisted key named `name`
+    pub fn open(provider: CngProvider, name: &str) -> Result<Option<Self>> {
+        let provider = open_provider(provider)?;
+        let name = wide(name);
+        let mut key: NCRYPT_KEY_HANDLE = 0;
+        // SAFETY: the provider handle is open and the out pointer and
+        // NUL-terminated name are valid for the call
+        let status = unsafe { NCryptOpenKey(provider.0, &mut key, name.as_ptr(), 0, 0) };
+        if status == NTE_BAD_KEYSET {
+            return Ok(None);
+        }
+        check(status, Error::Other)?;
+        Self::from_key(Handle(key)).map(Some)
+    }
+
+    /// Open the persisted key named `name`, creating it if there is none
+    pub fn open_or_generate(provider: CngProvider, name: &str) -> Result<Self> {
+        match Self::open(provider, name)? {
+            Some(signer) => Ok(signer),
+            None => Self::generate(provider, name),
+        }
+    }
+
+    fn from_key(key: Handle) -> Result<Self> {
+        let mut blob = [0u8; ECC_BLOB_HEADER_LENGTH + P256_POINT_LENGTH];
+        let mut length = 0u32;
+        // SAFETY: the key handle is open and the output buffer is valid for
+        // `blob.len()` bytes
+        check(
+            unsafe {
+                NCryptExportKey(
+                    key.0,
+                    0,
+                    BCRYPT_ECCPUBLIC_BLOB,
+                    ptr::null(),
+                    blob.as_mut_ptr(),
+                    blob.len() as u32,
+                    &mut length,
+                    0,
+                )
+            },
+            Error::InvalidKeyFormat,
+        )?;
+        if length as usize != blob.len() {
+            return Err(Error::InvalidKeyFormat(
+                "Unexpected CNG public key blob length".to_string(),
+            ));
+        }
+
+        // BCRYPT_ECCKEY_BLOB is followed by X and Y
+        let mut public_key = vec![0x04];
+        public_key.extend_from_slice(&blob[ECC_BLOB_HEADER_LENGTH..]);
+        Ok(Self {
+            key,
+            key_id: key_id_for_sec1(&public_key),
+            public_key,
+        })
+    }
+
+    /// Use `key_id` as the `keyid` of signatures instead of the derived key ID
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = key_id.into();
+        self
+    }
+
+    /// The uncompressed SEC1 public key
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
+
+impl CustomSigner for CngSigner {
+    fn identifier(&self) -> &'static str {
+        SignatureAlgorithm::EcdsaP256Sha256.identifier()
+    }
+
+    fn key_id(&self) -> String {
+        self.key_id.clone()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let digest = Sha256::digest(message);
+        let mut signature = vec![0u8; P256_SIGNATURE_LENGTH];
+        let mut length = 0u32;
+        // SAFETY: the key handle is open, the digest and output buffers are
+        // valid for their lengths, and ECDSA takes no padding info
+        check(
+            unsafe {
+                NCryptSignHash(
+                    self.key.0,
+                    ptr::null(),
+                    digest.as_ptr(),
+                    digest.len() as u32,
+                    signature.as_mut_ptr(),
+                    signature.len() as u32,
+                    &mut length,
+                    NCRYPT_SILENT_FLAG,
+                )
+            },
+            Error::Signature,
+        )?;
+        // CNG already produces the IEEE P1363 form required by RFC 9421
+        signature.truncate(length as usize);
+        Ok(signature)
+    }
+}
+
+fn open_provider(provider: CngProvider) -> Result<Handle> {
+    let mut handle: NCRYPT_PROV_HANDLE = 0;
+    // SAFETY: the out pointer is valid and the provider name is a static
+    // NUL-terminated string
+    check(
+        unsafe { NCryptOpenStorageProvider(&mut handle, provider.name(), 0) },
+        Error::Other,
+    )?;
+    Ok(Handle(handle))
+}
+
+/// Map a failed `HRESULT` to an error
+fn check(status: HRESULT, error: fn(String) -> Error) -> Result<()> {
+    if status < 0 {
+        return Err(error(format!("CNG error 0x{:08x}", status as u32)));
+    }
+    Ok(())
+}
+
+/// NUL-terminated UTF-16 string
+fn wide(value: &str) -> Vec<u16> {
+    value.encode_utf16().chain(std::iter::once(0)).collect()
+}
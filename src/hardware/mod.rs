@@ -0,0 +1,111 @@
+//! Signers backed by hardware-protected keys
+//!
+//! The private key never leaves the device; signing happens in the secure
+//! element. Each backend is a [`CustomSigner`](crate::rfc9421::CustomSigner),
+//! so it plugs into [`HttpSigner::from_custom_signer`](crate::rfc9421::HttpSigner::from_custom_signer)
+//! to emit RFC 9421 signatures.
+//!
+//! - `secure-enclave`: Apple Secure Enclave (P-256), on macOS and iOS
+//! - `cng`: Windows CNG, in software or in the platform TPM (P-256)
+//! - `tpm`: TPM 2.0 through the TSS2 ESAPI, e.g. on Linux servers (P-256)
+//!
+//! Their `ecdsa-p256-sha256` signatures are verified by registering a
+//! [`P256Verifier`] with the `HttpVerifier`, enabled by each backend or by
+//! the `p256` feature alone.
+
+#[cfg(all(feature = "cng", windows))]
+pub mod cng;
+#[cfg(all(feature = "secure-enclave", target_vendor = "apple"))]
+pub mod secure_enclave;
+#[cfg(feature = "tpm")]
+pub mod tpm;
+#[cfg(feature = "p256")]
+pub mod verifier;
+
+#[cfg(all(feature = "cng", windows))]
+pub use cng::{CngProvider, CngSigner};
+#[cfg(all(feature = "secure-enclave", target_vendor = "apple"))]
+pub use secure_enclave::SecureEnclaveSigner;
+#[cfg(feature = "tpm")]
+pub use tpm::TpmSigner;
+#[cfg(feature = "p256")]
+pub use verifier::P256Verifier;
+
+use crate::crypto::key_id::DEFAULT_KEY_ID_LENGTH;
+use crate::error::{Error, Result};
+use sha2::{Digest, Sha256};
+
+/// Length of a P-256 scalar
+const P256_SCALAR_LENGTH: usize = 32;
+
+/// Default key ID of a SEC1-encoded public key, as for [`PublicKey::key_id`](crate::crypto::PublicKey::key_id)
+pub fn key_id_for_sec1(public_key: &[u8]) -> String {
+    hex::encode(&Sha256::digest(public_key)[..DEFAULT_KEY_ID_LENGTH])
+}
+
+/// Convert a DER-encoded P-256 ECDSA signature to the fixed-size `r || s`
+/// form required by `ecdsa-p256-sha256` (RFC 9421 Section 3.3.4)
+pub fn p256_signature_from_der(der: &[u8]) -> Result<[u8; 2 * P256_SCALAR_LENGTH]> {
+    let (contents, rest) = der_take(0x30, der)?;
+    if !rest.is_empty() {
+        return Err(invalid_signature());
+    }
+
+    let mut raw = [0u8; 2 * P256_SCALAR_LENGTH];
+    let mut rest = contents;
+    for half in raw.chunks_exact_mut(P256_SCALAR_LENGTH) {
+        let (integer, tail) = der_take(0x02, rest)?;
+        let start = integer
+            .iter()
+            .position(|&b| b != 0)
+            .unwrap_or(integer.len());
+        let integer = &integer[start..];
+        if integer.len() > P256_SCALAR_LENGTH {
+            return Err(invalid_signature());
+        }
+        half[P256_SCALAR_LENGTH - integer.len()..].copy_from_slice(integer);
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        return Err(invalid_signature());
+    }
+    Ok(raw)
+}
+
+/// Split a DER element with a short-form length off `data`
+fn der_take(tag: u8, data: &[u8]) -> Result<(&[u8], &[u8])> {
+    match data {
+        [found, length, rest @ ..]
+            if *found == tag && *length < 0x80 && usize::from(*length) <= rest.len() =>
+        {
+            Ok(rest.split_at(usize::from(*length)))
+        }
+        _ => Err(invalid_signature()),
+    }
+}
+
+fn invalid_signature() -> Error {
+    Error::Signature("Invalid DER ECDSA signature".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_p256_signature_from_der() {
+        // r has a leading zero for its sign bit, s is shorter than 32 bytes
+        let mut der = vec![0x30, 0x44, 0x02, 0x21, 0x00];
+        der.extend_from_slice(&[0x80; 32]);
+        der.extend_from_slice(&[0x02, 0x1f]);
+        der.extend_from_slice(&[0x11; 31]);
+
+        let raw = p256_signature_from_der(&der).unwrap();
+        assert_eq!(raw[..32], [0x80; 32]);
+        assert_eq!(raw[32], 0);
+        assert_eq!(raw[33..], [0x11; 31]);
+
+        assert!(p256_signature_from_der(&der[..der.len() - 1]).is_err());
+        assert!(p256_signature_from_der(&[0x30, 0x00]).is_err());
+    }
+}
@@ -0,0 +1,116 @@
+//! Apple Secure Enclave signer
+//!
+//! Keys are P-256, generated inside the Secure Enclave and stored in the
+//! data protection keychain under a label. Only a reference to the key is
+//! ever available to the process.
+
+use super::{key_id_for_sec1, p256_signature_from_der};
+use crate::error::{Error, Result};
+use crate::rfc9421::{CustomSigner, SignatureAlgorithm};
+use security_framework::item::{
+    ItemClass, ItemSearchOptions, KeyClass, Location, Reference, SearchResult,
+};
+use security_framework::key::{Algorithm, GenerateKeyOptions, KeyType, SecKey, Token};
+
+/// `errSecItemNotFound`
+const ERR_SEC_ITEM_NOT_FOUND: i32 = -25300;
+
+/// Signer for a P-256 key held in the Secure Enclave
+pub struct SecureEnclaveSigner {
+    key: SecKey,
+    public_key: Vec<u8>,
+    key_id: String,
+}
+
+impl SecureEnclaveSigner {
+    /// Generate a key in the Secure Enclave, stored in the keychain under `label`
+    pub fn generate(label: &str) -> Result<Self> {
+        let options = GenerateKeyOptions::default()
+            .set_key_type(KeyType::ec())
+            .set_size_in_bits(256)
+            .set_token(Token::SecureEnclave)
+            .set_location(Location::DataProtectionKeychain)
+            .set_label(label)
+            .to_dictionary();
+        let key = SecKey::generate(options)
+            .map_err(|e| Error::KeyGeneration(format!("Secure Enclave: {e}")))?;
+        Self::from_key(key)
+    }
+
+    /// Load the key stored in the keychain under `label`
+    pub fn load(label: &str) -> Result<Option<Self>> {
+        let results = match ItemSearchOptions::new()
+            .class(ItemClass::key())
+            .key_class(KeyClass::private())
+            .label(label)
+            .load_refs(true)
+            .limit(1)
+            .search()
+        {
+            Ok(results) => results,
+            Err(e) if e.code() == ERR_SEC_ITEM_NOT_FOUND => return Ok(None),
+            Err(e) => return Err(Error::Other(format!("Keychain search failed: {e}"))),
+        };
+
+        match results.into_iter().next() {
+            Some(SearchResult::Ref(Reference::Key(key))) => Self::from_key(key).map(Some),
+            Some(_) => Err(Error::InvalidKeyType(format!(
+                "Keychain item {label} is not a key"
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// Load the key stored under `label`, generating it if there is none
+    pub fn load_or_generate(label: &str) -> Result<Self> {
+        match Self::load(label)? {
+            Some(signer) => Ok(signer),
+            None => Self::generate(label),
+        }
+    }
+
+    fn from_key(key: SecKey) -> Result<Self> {
+        let public_key = key
+            .public_key()
+            .and_then(|public_key| public_key.external_representation())
+            .ok_or_else(|| {
+                Error::InvalidKeyFormat("Secure Enclave key has no public key".to_string())
+            })?
+            .bytes()
+            .to_vec();
+        Ok(Self {
+            key,
+            key_id: key_id_for_sec1(&public_key),
+            public_key,
+        })
+    }
+
+    /// Use `key_id` as the `keyid` of signatures instead of the derived key ID
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = key_id.into();
+        self
+    }
+
+    /// The uncompressed SEC1 public key
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
+
+impl CustomSigner for SecureEnclaveSigner {
+    fn identifier(&self) -> &'static str {
+        SignatureAlgorithm::EcdsaP256Sha256.identifier()
+    }
+
+    fn key_id(&self) -> String {
+        self.key_id.clone()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let der = self
+            .key
+            .create_signature(Algorithm::ECDSASignatureMessageX962SHA256, message)
+            .map_err(|e| Error::Signature(format!("Secure Enclave: {e}")))?;
+        Ok(p256_signature_from_der(&der)?.to_vec())
+    }
+}
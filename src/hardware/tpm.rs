@@ -0,0 +1,209 @@
+//! TPM 2.0 signer
+//!
+//! Signs with a P-256 key that never leaves the TPM: either a persistent key
+//! provisioned ahead of time, or the owner hierarchy primary key, which the
+//! TPM re-derives from its seed so it survives reboots without storage.
+//! Signatures are verified with a [`P256Verifier`](super::P256Verifier)
+//! trusting [`TpmSigner::public_key`].
+
+use super::key_id_for_sec1;
+use crate::error::{Error, Result};
+use crate::rfc9421::{CustomSigner, SignatureAlgorithm};
+use sha2::{Digest as _, Sha256};
+use std::sync::Mutex;
+use tss_esapi::constants::tss::{TPM2_RH_NULL, TPM2_ST_HASHCHECK};
+use tss_esapi::handles::{KeyHandle, PersistentTpmHandle, TpmHandle};
+use tss_esapi::interface_types::algorithm::HashingAlgorithm;
+use tss_esapi::interface_types::ecc::EccCurve;
+use tss_esapi::interface_types::resource_handles::Hierarchy;
+use tss_esapi::structures::{
+    Digest, EccScheme, HashScheme, HashcheckTicket, Public, Signature, SignatureScheme,
+};
+use tss_esapi::tss2_esys::TPMT_TK_HASHCHECK;
+use tss_esapi::utils::create_unrestricted_signing_ecc_public;
+use tss_esapi::{Context, TctiNameConf};
+
+/// Length of a P-256 coordinate or scalar
+const P256_SCALAR_LENGTH: usize = 32;
+
+/// Signer for a P-256 key held in a TPM 2.0
+pub struct TpmSigner {
+    context: Mutex<Context>,
+    key: KeyHandle,
+    public_key: Vec<u8>,
+    key_id: String,
+}
+
+impl TpmSigner {
+    /// Use the owner hierarchy P-256 signing primary key
+    ///
+    /// The TPM is selected by the `TPM2TOOLS_TCTI` environment variable.
+    pub fn primary() -> Result<Self> {
+        let mut context = open_context()?;
+        let template = create_unrestricted_signing_ecc_public(
+            EccScheme::EcDsa(HashScheme::new(HashingAlgorithm::Sha256)),
+            EccCurve::NistP256,
+        )
+        .map_err(tpm_error(Error::KeyGeneration))?;
+        let primary = context
+            .execute_with_nullauth_session(|ctx| {
+                ctx.create_primary(Hierarchy::Owner, template, None, None, None, None)
+            })
+            .map_err(tpm_error(Error::KeyGeneration))?;
+        Self::from_parts(context, primary.key_handle, &primary.out_public)
+    }
+
+    /// Use the P-256 signing key at persistent handle `handle`, e.g. `0x8101_0001`
+    pub fn persistent(handle: u32) -> Result<Self> {
+        let mut context = open_context()?;
+        let handle = PersistentTpmHandle::new(handle).map_err(tpm_error(Error::InvalidInput))?;
+        let object = context
+            .tr_from_tpm_public(TpmHandle::Persistent(handle))
+            .map_err(tpm_error(Error::Other))?;
+        let key = KeyHandle::from(object);
+        let (public, _, _) = context.read_public(key).map_err(tpm_error(Error::Other))?;
+        Self::from_parts(context, key, &public)
+    }
+
+    fn from_parts(context: Context, key: KeyHandle, public: &Public) -> Result<Self> {
+        let Public::Ecc { unique, .. } = public else {
+            return Err(Error::InvalidKeyType(
+                "TPM key is not an ECC key".to_string(),
+            ));
+        };
+
+        let mut public_key = vec![0x04];
+        for coordinate in [unique.x().value(), unique.y().value()] {
+            if !push_scalar(&mut public_key, coordinate) {
+                return Err(Error::InvalidKeyType(
+                    "TPM key is not a P-256 key".to_string(),
+                ));
+            }
+        }
+        Ok(Self {
+            context: Mutex::new(context),
+            key,
+            key_id: key_id_for_sec1(&public_key),
+            public_key,
+        })
+    }
+
+    /// Use `key_id` as the `keyid` of signatures instead of the derived key ID
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = key_id.into();
+        self
+    }
+
+    /// The uncompressed SEC1 public key
+    pub fn public_key(&self) -> &[u8] {
+        &self.public_key
+    }
+}
+
+impl CustomSigner for TpmSigner {
+    fn identifier(&self) -> &'static str {
+        SignatureAlgorithm::EcdsaP256Sha256.identifier()
+    }
+
+    fn key_id(&self) -> String {
+        self.key_id.clone()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let digest = Digest::try_from(Sha256::digest(message).to_vec())
+            .map_err(tpm_error(Error::Signature))?;
+        // The key is unrestricted, so no proof that the TPM computed the digest is needed
+        let validation = HashcheckTicket::try_from(TPMT_TK_HASHCHECK {
+            tag: TPM2_ST_HASHCHECK,
+            hierarchy: TPM2_RH_NULL,
+            digest: Default::default(),
+        })
+        .map_err(tpm_error(Error::Signature))?;
+
+        let mut context = self
+            .context
+            .lock()
+            .map_err(|_| Error::Other("TPM context lock poisoned".to_string()))?;
+        let signature = context
+            .execute_with_nullauth_session(|ctx| {
+                ctx.sign(self.key, digest, SignatureScheme::Null, validation)
+            })
+            .map_err(tpm_error(Error::Signature))?;
+
+        let Signature::EcDsa(signature) = signature else {
+            return Err(Error::Signature(
+                "TPM returned a non-ECDSA signature".to_string(),
+            ));
+        };
+        let mut raw = Vec::with_capacity(2 * P256_SCALAR_LENGTH);
+        for scalar in [
+            signature.signature_r().value(),
+            signature.signature_s().value(),
+        ] {
+            if !push_scalar(&mut raw, scalar) {
+                return Err(Error::Signature("TPM signature is not P-256".to_string()));
+            }
+        }
+        Ok(raw)
+    }
+}
+
+/// Append a big-endian value left-padded to a P-256 scalar, if it fits
+fn push_scalar(out: &mut Vec<u8>, value: &[u8]) -> bool {
+    if value.len() > P256_SCALAR_LENGTH {
+        return false;
+    }
+    out.resize(out.len() + P256_SCALAR_LENGTH - value.len(), 0);
+    out.extend_from_slice(value);
+    true
+}
+
+fn open_context() -> Result<Context> {
+    let tcti = TctiNameConf::from_environment_variable().map_err(tpm_error(Error::Other))?;
+    Context::new(tcti).map_err(tpm_error(Error::Other))
+}
+
+/// Map a TSS error to the given error variant
+fn tpm_error(variant: fn(String) -> Error) -> impl Fn(tss_esapi::Error) -> Error {
+    move |e| variant(format!("TPM: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType};
+    use crate::hardware::P256Verifier;
+    use crate::rfc9421::{AlgorithmRegistry, HttpSigner, HttpVerifier, VerificationPolicy};
+    use std::sync::Arc;
+
+    #[test]
+    #[ignore = "requires a TPM 2.0, selected by TPM2TOOLS_TCTI"]
+    fn test_tpm_http_signature() {
+        let signer = TpmSigner::primary().unwrap();
+        let mut verifier = P256Verifier::new();
+        verifier
+            .insert_with_id(signer.key_id(), signer.public_key())
+            .unwrap();
+        let mut algorithms = AlgorithmRegistry::new();
+        algorithms.register(Arc::new(verifier)).unwrap();
+
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("https://agent.example/tasks")
+            .body(())
+            .unwrap();
+        let signed = HttpSigner::from_custom_signer(Arc::new(signer))
+            .sign_request(request)
+            .unwrap();
+
+        let unrelated = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let report = HttpVerifier::new(unrelated.public_key().clone())
+            .with_algorithms(algorithms)
+            .with_policy(
+                VerificationPolicy::new().allow_algorithm(SignatureAlgorithm::EcdsaP256Sha256),
+            )
+            .verify_request_detailed(&signed)
+            .unwrap();
+        assert_eq!(report.algorithm, SignatureAlgorithm::EcdsaP256Sha256);
+    }
+}
@@ -0,0 +1,166 @@
+//! Verification of `ecdsa-p256-sha256` signatures
+//!
+//! P-256 keys are not a [`PublicKey`](crate::crypto::PublicKey) type, so
+//! signatures from hardware-backed signers are verified through the
+//! [`AlgorithmRegistry`](crate::rfc9421::AlgorithmRegistry) extension point.
+
+use super::key_id_for_sec1;
+use crate::error::{Error, Result};
+use crate::rfc9421::{CustomVerifier, SignatureAlgorithm};
+use p256::ecdsa::signature::Verifier as _;
+use p256::ecdsa::{Signature, VerifyingKey};
+use std::collections::HashMap;
+use std::fmt;
+
+/// Verifier for `ecdsa-p256-sha256` signatures by trusted SEC1 public keys
+///
+/// Keys are looked up by the signature's `keyid`. Register the verifier with
+/// [`HttpVerifier::with_algorithms`](crate::rfc9421::HttpVerifier::with_algorithms)
+/// and allow [`SignatureAlgorithm::EcdsaP256Sha256`] on the verification policy.
+#[derive(Clone, Default)]
+pub struct P256Verifier {
+    keys: HashMap<String, VerifyingKey>,
+}
+
+impl P256Verifier {
+    /// Create a verifier trusting no keys
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust a SEC1 public key under its default key ID, which is returned
+    pub fn insert(&mut self, public_key: &[u8]) -> Result<String> {
+        let key_id = key_id_for_sec1(public_key);
+        self.insert_with_id(key_id.clone(), public_key)?;
+        Ok(key_id)
+    }
+
+    /// Trust a SEC1 public key under an explicit key ID
+    pub fn insert_with_id(&mut self, key_id: impl Into<String>, public_key: &[u8]) -> Result<()> {
+        let key = VerifyingKey::from_sec1_bytes(public_key)
+            .map_err(|_| Error::InvalidKeyFormat("Invalid P-256 public key".to_string()))?;
+        self.keys.insert(key_id.into(), key);
+        Ok(())
+    }
+
+    /// Add a SEC1 public key under its default key ID
+    pub fn with_key(mut self, public_key: &[u8]) -> Result<Self> {
+        self.insert(public_key)?;
+        Ok(self)
+    }
+}
+
+impl CustomVerifier for P256Verifier {
+    fn identifier(&self) -> &'static str {
+        SignatureAlgorithm::EcdsaP256Sha256.identifier()
+    }
+
+    fn verify(&self, key_id: Option<&str>, message: &[u8], signature: &[u8]) -> Result<()> {
+        let key_id = key_id.ok_or_else(|| {
+            Error::Verification("Signature is missing the keyid parameter".to_string())
+        })?;
+        let key = self
+            .keys
+            .get(key_id)
+            .ok_or_else(|| Error::Verification(format!("Unknown key ID: {key_id}")))?;
+        let signature = Signature::try_from(signature)
+            .map_err(|_| Error::Verification("Invalid P-256 signature".to_string()))?;
+        key.verify(message, &signature)
+            .map_err(|_| Error::Verification("Signature verification failed".to_string()))
+    }
+}
+
+impl fmt::Debug for P256Verifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.keys.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rfc9421::{
+        AlgorithmRegistry, CustomSigner, HttpSigner, HttpVerifier, VerificationPolicy,
+    };
+    use p256::ecdsa::signature::Signer as _;
+    use p256::ecdsa::SigningKey;
+    use std::sync::Arc;
+
+    /// Software stand-in for a hardware-backed P-256 signer
+    struct SoftwareSigner(SigningKey);
+
+    impl SoftwareSigner {
+        fn public_key(&self) -> Vec<u8> {
+            self.0
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes()
+                .to_vec()
+        }
+    }
+
+    impl CustomSigner for SoftwareSigner {
+        fn identifier(&self) -> &'static str {
+            SignatureAlgorithm::EcdsaP256Sha256.identifier()
+        }
+
+        fn key_id(&self) -> String {
+            key_id_for_sec1(&self.public_key())
+        }
+
+        fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+            let signature: Signature = self.0.sign(message);
+            Ok(signature.as_ref().to_vec())
+        }
+    }
+
+    #[test]
+    fn test_p256_http_signature() {
+        let signer = SoftwareSigner(SigningKey::random(&mut rand::rngs::OsRng));
+        let public_key = signer.public_key();
+        let request = http::Request::builder()
+            .method("POST")
+            .uri("https://agent.example/tasks")
+            .body(())
+            .unwrap();
+        let signed = HttpSigner::from_custom_signer(Arc::new(signer))
+            .sign_request(request)
+            .unwrap();
+
+        let mut algorithms = AlgorithmRegistry::new();
+        algorithms
+            .register(Arc::new(P256Verifier::new().with_key(&public_key).unwrap()))
+            .unwrap();
+        let policy = VerificationPolicy::new().allow_algorithm(SignatureAlgorithm::EcdsaP256Sha256);
+        let unrelated = crate::crypto::KeyPair::generate(crate::crypto::KeyType::Ed25519).unwrap();
+        let verifier = HttpVerifier::new(unrelated.public_key().clone())
+            .with_algorithms(algorithms.clone())
+            .with_policy(policy.clone());
+        let report = verifier.verify_request_detailed(&signed).unwrap();
+        assert_eq!(report.algorithm, SignatureAlgorithm::EcdsaP256Sha256);
+        assert_eq!(report.key_id, key_id_for_sec1(&public_key));
+
+        // Other P-256 keys are not trusted
+        let other = SoftwareSigner(SigningKey::random(&mut rand::rngs::OsRng));
+        let mut untrusted = AlgorithmRegistry::new();
+        untrusted
+            .register(Arc::new(
+                P256Verifier::new().with_key(&other.public_key()).unwrap(),
+            ))
+            .unwrap();
+        let verifier = HttpVerifier::new(unrelated.public_key().clone())
+            .with_algorithms(untrusted)
+            .with_policy(policy);
+        assert!(verifier.verify_request(&signed).is_err());
+
+        // A tampered request fails
+        let mut tampered = signed;
+        *tampered.method_mut() = http::Method::GET;
+        let verifier = HttpVerifier::new(unrelated.public_key().clone())
+            .with_algorithms(algorithms)
+            .with_policy(
+                VerificationPolicy::new().allow_algorithm(SignatureAlgorithm::EcdsaP256Sha256),
+            );
+        assert!(verifier.verify_request(&tampered).is_err());
+    }
+}
@@ -0,0 +1,333 @@
+//! Agent identities
+//!
+//! An [`AgentIdentity`] bundles an agent's DID with its key pairs, each used
+//! for one or more [`KeyPurpose`]s, and free-form metadata. It produces the
+//! agent's DID document and RFC 9421 signers whose `keyid` is the DID URL of
+//! the signing key, e.g. `did:sage:ethereum:0xabc#1f2e3d4c5b6a7988`.
+
+use crate::crypto::{KeyIdStrategy, KeyPair, PublicKey};
+use crate::error::{Error, Result};
+use crate::formats::{public_key_from_jwk, KeyExporter};
+use crate::rfc9421::{HttpSigner, HttpVerifier, KeySet};
+use serde_json::{json, Map, Value};
+
+/// JSON-LD contexts of generated DID documents
+const DID_CONTEXTS: [&str; 2] = [
+    "https://www.w3.org/ns/did/v1",
+    "https://w3id.org/security/suites/jws-2020/v1",
+];
+
+/// Verification relationships a key can be used for (DID Core Section 5.3)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyPurpose {
+    /// Authenticating as the agent, e.g. RFC 9421 request signatures
+    Authentication,
+    /// Issuing claims, e.g. verifiable credentials
+    AssertionMethod,
+    /// Invoking capabilities
+    CapabilityInvocation,
+    /// Delegating capabilities to others
+    CapabilityDelegation,
+}
+
+impl KeyPurpose {
+    /// All purposes, in DID document order
+    pub const ALL: [KeyPurpose; 4] = [
+        KeyPurpose::Authentication,
+        KeyPurpose::AssertionMethod,
+        KeyPurpose::CapabilityInvocation,
+        KeyPurpose::CapabilityDelegation,
+    ];
+
+    /// Name of the verification relationship in a DID document
+    pub fn relationship(&self) -> &'static str {
+        match self {
+            KeyPurpose::Authentication => "authentication",
+            KeyPurpose::AssertionMethod => "assertionMethod",
+            KeyPurpose::CapabilityInvocation => "capabilityInvocation",
+            KeyPurpose::CapabilityDelegation => "capabilityDelegation",
+        }
+    }
+}
+
+/// A DID with its key pairs and metadata
+#[derive(Debug, Clone)]
+pub struct AgentIdentity {
+    did: String,
+    keys: Vec<(KeyPurpose, KeyPair)>,
+    services: Vec<Value>,
+    metadata: Map<String, Value>,
+}
+
+impl AgentIdentity {
+    /// Create an identity for `did` without keys
+    pub fn new(did: impl Into<String>) -> Result<Self> {
+        let did = did.into();
+        let mut parts = did.splitn(3, ':');
+        let valid = parts.next() == Some("did")
+            && parts.next().is_some_and(|method| {
+                !method.is_empty()
+                    && method
+                        .bytes()
+                        .all(|b| b.is_ascii_lowercase() || b.is_ascii_digit())
+            })
+            && parts.next().is_some_and(|id| !id.is_empty())
+            && !did.contains(['#', '?', '/']);
+        if !valid {
+            return Err(Error::InvalidInput(format!("Invalid DID: {did}")));
+        }
+
+        Ok(Self {
+            did,
+            keys: Vec::new(),
+            services: Vec::new(),
+            metadata: Map::new(),
+        })
+    }
+
+    /// Use `keypair` for `purpose`, replacing any key previously used for it
+    ///
+    /// The same key pair can be added for several purposes.
+    pub fn with_key(mut self, purpose: KeyPurpose, keypair: KeyPair) -> Self {
+        self.keys.retain(|(existing, _)| *existing != purpose);
+        self.keys.push((purpose, keypair));
+        self
+    }
+
+    /// Add a service endpoint to the DID document
+    ///
+    /// `id` is a fragment, e.g. `agent`, or a full DID URL.
+    pub fn with_service(
+        mut self,
+        id: impl AsRef<str>,
+        service_type: impl Into<String>,
+        endpoint: impl Into<String>,
+    ) -> Self {
+        self.services.push(json!({
+            "id": self.did_url(id.as_ref()),
+            "type": service_type.into(),
+            "serviceEndpoint": endpoint.into(),
+        }));
+        self
+    }
+
+    /// Set a metadata member, e.g. the agent's display name
+    pub fn with_metadata(mut self, name: impl Into<String>, value: Value) -> Self {
+        self.metadata.insert(name.into(), value);
+        self
+    }
+
+    /// The agent's DID
+    pub fn did(&self) -> &str {
+        &self.did
+    }
+
+    /// Metadata members
+    pub fn metadata(&self) -> &Map<String, Value> {
+        &self.metadata
+    }
+
+    /// The key pair used for `purpose`
+    pub fn key(&self, purpose: KeyPurpose) -> Option<&KeyPair> {
+        self.keys
+            .iter()
+            .find(|(existing, _)| *existing == purpose)
+            .map(|(_, keypair)| keypair)
+    }
+
+    /// The DID URL identifying a public key, `<did>#<key id>`
+    pub fn verification_method_id(&self, public_key: &PublicKey) -> String {
+        format!("{}#{}", self.did, public_key.key_id())
+    }
+
+    /// Distinct public keys of the identity, in the order they were added
+    fn public_keys(&self) -> Vec<&PublicKey> {
+        let mut public_keys: Vec<&PublicKey> = Vec::new();
+        for (_, keypair) in &self.keys {
+            let public_key = keypair.public_key();
+            if !public_keys
+                .iter()
+                .any(|existing| existing.key_id() == public_key.key_id())
+            {
+                public_keys.push(public_key);
+            }
+        }
+        public_keys
+    }
+
+    /// Produce the DID document, with `JsonWebKey2020` verification methods
+    pub fn did_document(&self) -> Result<Value> {
+        let mut verification_methods = Vec::new();
+        for public_key in self.public_keys() {
+            let mut jwk = public_key.to_jwk()?;
+            if let Some(jwk) = jwk.as_object_mut() {
+                jwk.remove("kid");
+            }
+            verification_methods.push(json!({
+                "id": self.verification_method_id(public_key),
+                "type": "JsonWebKey2020",
+                "controller": self.did,
+                "publicKeyJwk": jwk,
+            }));
+        }
+
+        let mut document = json!({
+            "@context": DID_CONTEXTS,
+            "id": self.did,
+            "verificationMethod": verification_methods,
+        });
+        for purpose in KeyPurpose::ALL {
+            if let Some(keypair) = self.key(purpose) {
+                document[purpose.relationship()] =
+                    json!([self.verification_method_id(keypair.public_key())]);
+            }
+        }
+        if !self.services.is_empty() {
+            document["service"] = Value::Array(self.services.clone());
+        }
+        Ok(document)
+    }
+
+    /// Create an HTTP signer for the key used for `purpose`
+    ///
+    /// Signatures carry the key's DID URL as their `keyid`.
+    pub fn signer(&self, purpose: KeyPurpose) -> Result<HttpSigner> {
+        let keypair = self.key(purpose).ok_or_else(|| {
+            Error::InvalidInput(format!(
+                "{} has no {} key",
+                self.did,
+                purpose.relationship()
+            ))
+        })?;
+        let did = self.did.clone();
+        let keypair = keypair.clone().with_key_id_strategy(KeyIdStrategy::custom(
+            move |public_key: &PublicKey| format!("{did}#{}", public_key.key_id()),
+        ))?;
+        Ok(HttpSigner::new(keypair))
+    }
+
+    /// Create an HTTP signer for the [`KeyPurpose::Authentication`] key
+    pub fn http_signer(&self) -> Result<HttpSigner> {
+        self.signer(KeyPurpose::Authentication)
+    }
+
+    /// Public keys of the identity indexed by DID URL
+    pub fn key_set(&self) -> KeySet {
+        let mut key_set = KeySet::new();
+        for public_key in self.public_keys() {
+            key_set.insert_with_id(self.verification_method_id(public_key), public_key.clone());
+        }
+        key_set
+    }
+
+    /// Create an HTTP verifier for signatures made by this identity
+    pub fn verifier(&self) -> HttpVerifier {
+        HttpVerifier::from_key_set(self.key_set())
+    }
+
+    /// Resolve a DID URL fragment or relative reference against the DID
+    fn did_url(&self, id: &str) -> String {
+        if id.starts_with("did:") {
+            id.to_string()
+        } else {
+            format!("{}#{}", self.did, id.trim_start_matches('#'))
+        }
+    }
+}
+
+/// Collect the `publicKeyJwk` verification methods of a DID document,
+/// indexed by their absolute DID URL
+///
+/// Verification methods in other representations are skipped.
+pub fn key_set_from_did_document(document: &Value) -> Result<KeySet> {
+    let did = document
+        .get("id")
+        .and_then(Value::as_str)
+        .ok_or_else(|| Error::InvalidInput("DID document has no id".to_string()))?;
+    let methods = document
+        .get("verificationMethod")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let mut key_set = KeySet::new();
+    for method in methods {
+        let (Some(id), Some(jwk)) = (
+            method.get("id").and_then(Value::as_str),
+            method.get("publicKeyJwk"),
+        ) else {
+            continue;
+        };
+        let id = match id.strip_prefix('#') {
+            Some(fragment) => format!("{did}#{fragment}"),
+            None => id.to_string(),
+        };
+        key_set.insert_with_id(id, public_key_from_jwk(jwk)?);
+    }
+    Ok(key_set)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyType;
+
+    #[test]
+    fn test_did_document() {
+        let authentication = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let assertion = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        let identity = AgentIdentity::new("did:sage:ethereum:0xabc")
+            .unwrap()
+            .with_key(KeyPurpose::Authentication, authentication.clone())
+            .with_key(KeyPurpose::CapabilityInvocation, authentication.clone())
+            .with_key(KeyPurpose::AssertionMethod, assertion)
+            .with_service("agent", "SageAgent", "https://agent.example")
+            .with_metadata("name", json!("Example agent"));
+
+        let document = identity.did_document().unwrap();
+        let auth_id = identity.verification_method_id(authentication.public_key());
+        assert_eq!(document["id"], "did:sage:ethereum:0xabc");
+        assert_eq!(document["verificationMethod"].as_array().unwrap().len(), 2);
+        assert_eq!(document["authentication"][0], auth_id);
+        assert_eq!(document["capabilityInvocation"][0], auth_id);
+        assert!(document.get("capabilityDelegation").is_none());
+        assert_eq!(
+            document["service"][0]["id"],
+            "did:sage:ethereum:0xabc#agent"
+        );
+        assert_eq!(identity.metadata()["name"], "Example agent");
+
+        let key_set = key_set_from_did_document(&document).unwrap();
+        assert_eq!(key_set.len(), 2);
+        assert!(key_set.get(&auth_id).is_some());
+
+        assert!(AgentIdentity::new("not-a-did").is_err());
+        assert!(AgentIdentity::new("did:sage:abc#key").is_err());
+    }
+
+    #[test]
+    fn test_identity_signer_and_verifier() {
+        let identity = AgentIdentity::new("did:key:agent").unwrap().with_key(
+            KeyPurpose::Authentication,
+            KeyPair::generate(KeyType::Ed25519).unwrap(),
+        );
+
+        let request = http::Request::builder()
+            .method("GET")
+            .uri("https://agent.example/tasks")
+            .body(())
+            .unwrap();
+        let signed = identity
+            .http_signer()
+            .unwrap()
+            .sign_request(request)
+            .unwrap();
+
+        let report = identity
+            .verifier()
+            .verify_request_detailed(&signed)
+            .unwrap();
+        assert!(report.key_id.starts_with("did:key:agent#"));
+        assert!(identity.signer(KeyPurpose::AssertionMethod).is_err());
+    }
+}
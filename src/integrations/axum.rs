@@ -0,0 +1,123 @@
+//! `axum` extractor for requests with a verified signature
+//!
+//! ```ignore
+//! async fn handler(signature: VerifiedSignature) -> String {
+//!     format!("hello {}", signature.key_id())
+//! }
+//!
+//! let app = Router::new()
+//!     .route("/", get(handler))
+//!     .with_state(SignatureVerifier::from_resolver(Arc::new(key_set)));
+//! ```
+//!
+//! Use your own state type by implementing `FromRef<State>` for [`SignatureVerifier`].
+
+use crate::error::Error;
+use crate::rfc9421::{HttpVerifier, KeyResolver, VerificationReport};
+use ::axum::async_trait;
+use ::axum::extract::{FromRef, FromRequestParts};
+use ::axum::http::{request::Parts, StatusCode};
+use ::axum::response::{IntoResponse, Response};
+use std::sync::Arc;
+
+/// Shared [`HttpVerifier`] used by the [`VerifiedSignature`] extractor
+#[derive(Clone)]
+pub struct SignatureVerifier(Arc<HttpVerifier>);
+
+impl SignatureVerifier {
+    /// Verify requests with `verifier`
+    pub fn new(verifier: HttpVerifier) -> Self {
+        Self(Arc::new(verifier))
+    }
+
+    /// Verify requests with keys looked up by the signature's `keyid`
+    pub fn from_resolver(resolver: Arc<dyn KeyResolver>) -> Self {
+        Self::new(HttpVerifier::from_resolver(resolver))
+    }
+}
+
+/// Extractor for the verified signature of a request
+///
+/// Requests failing verification are rejected with `401 Unauthorized`.
+/// The extractor does not read the body; check the `Content-Digest` header
+/// against it if the signature covers it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedSignature(pub VerificationReport);
+
+impl VerifiedSignature {
+    /// Key ID of the caller
+    pub fn key_id(&self) -> &str {
+        &self.0.key_id
+    }
+
+    /// Details of the verified signature
+    pub fn report(&self) -> &VerificationReport {
+        &self.0
+    }
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for VerifiedSignature
+where
+    SignatureVerifier: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = SignatureRejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let verifier = SignatureVerifier::from_ref(state);
+        verifier
+            .0
+            .verify_message(&*parts)
+            .map(VerifiedSignature)
+            .map_err(SignatureRejection)
+    }
+}
+
+/// Rejection for requests whose signature failed verification
+#[derive(Debug)]
+pub struct SignatureRejection(pub Error);
+
+impl IntoResponse for SignatureRejection {
+    fn into_response(self) -> Response {
+        (StatusCode::UNAUTHORIZED, self.0.to_string()).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType};
+    use crate::rfc9421::{HttpSigner, KeySet};
+    use ::axum::http::Request;
+
+    #[tokio::test]
+    async fn test_verified_signature_extractor() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let keys = KeySet::new().with_key(keypair.public_key().clone());
+        let state = SignatureVerifier::from_resolver(Arc::new(keys));
+
+        let request = || {
+            Request::builder()
+                .method("GET")
+                .uri("https://agent.example/status")
+                .body(())
+                .unwrap()
+        };
+        let (mut unsigned, _) = request().into_parts();
+        let signed = HttpSigner::new(keypair.clone())
+            .sign_request(request())
+            .unwrap();
+        let (mut parts, _) = signed.into_parts();
+
+        let signature = VerifiedSignature::from_request_parts(&mut parts, &state)
+            .await
+            .unwrap();
+        assert_eq!(signature.key_id(), keypair.key_id());
+
+        let rejection = VerifiedSignature::from_request_parts(&mut unsigned, &state)
+            .await
+            .unwrap_err();
+        assert_eq!(rejection.into_response().status(), StatusCode::UNAUTHORIZED);
+    }
+}
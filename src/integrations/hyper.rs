@@ -0,0 +1,205 @@
+//! Signing and verification helpers for `hyper` clients and servers
+//!
+//! [`sign_request`] buffers the request body to compute its `Content-Digest`
+//! before signing. [`verify_request`] and [`verify_response`] check the
+//! signature headers right away and wrap the body in a [`DigestVerifyingBody`],
+//! which checks the `Content-Digest` as the body is streamed. Digest
+//! algorithms are restricted by the verifier's policy.
+
+use crate::error::{Error, Result};
+use crate::rfc9421::{
+    ContentDigest, ContentDigestBuilder, DigestAlgorithm, HttpSigner, HttpVerifier,
+    VerificationReport,
+};
+use ::hyper::body::{Buf, HttpBody, SizeHint};
+use ::hyper::{Body, HeaderMap, Request, Response};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Boxed error returned by [`DigestVerifyingBody`]
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Read the body of a request, set its `Content-Digest` and sign it
+///
+/// The signature covers the signer's default components and `content-digest`.
+pub async fn sign_request<B>(signer: &HttpSigner, request: Request<B>) -> Result<Request<Body>>
+where
+    B: HttpBody,
+    B::Error: std::fmt::Display,
+{
+    let (parts, body) = request.into_parts();
+    let body = ::hyper::body::to_bytes(body)
+        .await
+        .map_err(|e| Error::Other(format!("Failed to read request body: {e}")))?;
+
+    let request = signer.sign_request_with_body(Request::from_parts(parts, body))?;
+    Ok(request.map(Body::from))
+}
+
+/// Verify the signature of a request and check its `Content-Digest` while the body is read
+pub fn verify_request<B>(
+    verifier: &HttpVerifier,
+    request: Request<B>,
+) -> Result<(Request<DigestVerifyingBody<B>>, VerificationReport)> {
+    let report = verifier.verify_request_detailed(&request)?;
+    let (parts, body) = request.into_parts();
+    let body = DigestVerifyingBody::from_headers(body, &parts.headers)?;
+    Ok((Request::from_parts(parts, body), report))
+}
+
+/// Verify the signature of a response and check its `Content-Digest` while the body is read
+pub fn verify_response<B>(
+    verifier: &HttpVerifier,
+    response: Response<B>,
+) -> Result<(Response<DigestVerifyingBody<B>>, VerificationReport)> {
+    let report = verifier.verify_response_detailed(&response)?;
+    let (parts, body) = response.into_parts();
+    let body = DigestVerifyingBody::from_headers(body, &parts.headers)?;
+    Ok((Response::from_parts(parts, body), report))
+}
+
+/// Body that checks a `Content-Digest` as it is streamed
+///
+/// A mismatch is reported as an error in place of the end of the body, so
+/// consumers must read the body to completion before trusting it.
+pub struct DigestVerifyingBody<B> {
+    inner: B,
+    digest: Option<(ContentDigestBuilder, ContentDigest)>,
+}
+
+impl<B> DigestVerifyingBody<B> {
+    /// Check `inner` against `expected`
+    ///
+    /// `expected` must include a built-in digest algorithm.
+    pub fn new(inner: B, expected: ContentDigest) -> Result<Self> {
+        let algorithms: Vec<DigestAlgorithm> = expected
+            .algorithms()
+            .filter_map(DigestAlgorithm::from_identifier)
+            .collect();
+        if algorithms.is_empty() {
+            return Err(Error::Unsupported(
+                "Content-digest has no supported algorithm".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            inner,
+            digest: Some((ContentDigestBuilder::with_algorithms(&algorithms), expected)),
+        })
+    }
+
+    /// Check `inner` against the `Content-Digest` header, if present
+    pub fn from_headers(inner: B, headers: &HeaderMap) -> Result<Self> {
+        if !headers.contains_key("content-digest") {
+            return Ok(Self {
+                inner,
+                digest: None,
+            });
+        }
+        Self::new(inner, ContentDigest::from_headers(headers)?)
+    }
+
+    /// Unwrap the inner body
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+}
+
+impl<B> HttpBody for DigestVerifyingBody<B>
+where
+    B: HttpBody + Unpin,
+    B::Error: Into<BoxError>,
+{
+    type Data = B::Data;
+    type Error = BoxError;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<std::result::Result<Self::Data, Self::Error>>> {
+        let this = &mut *self;
+        match Pin::new(&mut this.inner).poll_data(cx) {
+            Poll::Pending => Poll::Pending,
+            Poll::Ready(Some(Ok(chunk))) => {
+                if let Some((builder, _)) = &mut this.digest {
+                    builder.update(chunk.chunk());
+                }
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e.into()))),
+            Poll::Ready(None) => match this.digest.take() {
+                Some((builder, expected)) => match builder.verify(&expected) {
+                    Ok(()) => Poll::Ready(None),
+                    Err(e) => Poll::Ready(Some(Err(e.into()))),
+                },
+                None => Poll::Ready(None),
+            },
+        }
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<std::result::Result<Option<HeaderMap>, Self::Error>> {
+        Pin::new(&mut self.inner)
+            .poll_trailers(cx)
+            .map_err(Into::into)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        // Keep the body open until the digest has been checked
+        self.digest.is_none() && self.inner.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.inner.size_hint()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType};
+    use crate::rfc9421::SignatureComponent;
+
+    fn request(body: &'static str) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("https://agent.example/tasks")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_verify_hyper_request() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let signer = HttpSigner::new(keypair.clone());
+        let verifier = HttpVerifier::new(keypair.public_key().clone());
+
+        let signed = sign_request(&signer, request("{\"task\":\"ping\"}"))
+            .await
+            .unwrap();
+        let (received, report) = verify_request(&verifier, signed).unwrap();
+        assert!(report.covers(&SignatureComponent::content_digest()));
+        let body = ::hyper::body::to_bytes(received.into_body()).await.unwrap();
+        assert_eq!(&body[..], b"{\"task\":\"ping\"}");
+
+        // Swap the body after signing; the signature still verifies but the digest does not
+        let signed = sign_request(&signer, request("{\"task\":\"ping\"}"))
+            .await
+            .unwrap();
+        let (parts, _) = signed.into_parts();
+        let tampered = Request::from_parts(parts, Body::from("{\"task\":\"drop\"}"));
+        let (received, _) = verify_request(&verifier, tampered).unwrap();
+        assert!(::hyper::body::to_bytes(received.into_body()).await.is_err());
+
+        // A digest algorithm the policy does not allow is rejected up front
+        let verifier = verifier.with_policy(
+            crate::rfc9421::VerificationPolicy::new().allow_digest_algorithm("sha-512"),
+        );
+        let signed = sign_request(&signer, request("{\"task\":\"ping\"}"))
+            .await
+            .unwrap();
+        assert!(verify_request(&verifier, signed).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//! Key resolution from a remote JWK Set, e.g. an agent's well-known endpoint
+//!
+//! [`KeyResolver::resolve`] is synchronous, so [`RemoteKeySet`] serves it from
+//! a cache. Call [`RemoteKeySet::prefetch`] with an incoming message before
+//! verifying it to make sure the keys it names are cached and fresh.
+//!
+//! ```ignore
+//! let keys = Arc::new(RemoteKeySet::new("https://agent.example/.well-known/jwks.json"));
+//! let verifier = HttpVerifier::from_resolver(keys.clone());
+//!
+//! keys.prefetch(&request).await?;
+//! let report = verifier.verify_request_detailed(&request)?;
+//! ```
+
+use crate::crypto::PublicKey;
+use crate::error::{Error, Result};
+use crate::rfc9421::{
+    extract_signature_headers, parse_signature_input, signature_labels, HttpMessage, KeyResolver,
+    KeySet,
+};
+use ::reqwest::header::{ETAG, IF_NONE_MATCH};
+use ::reqwest::{Client, StatusCode};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+/// Default time a fetched JWK Set is trusted before revalidation
+pub const DEFAULT_TTL: Duration = Duration::from_secs(300);
+
+/// Minimum time between fetches triggered by unknown key IDs
+pub const MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Default)]
+struct Cache {
+    keys: KeySet,
+    etag: Option<String>,
+    fetched_at: Option<Instant>,
+}
+
+/// [`KeyResolver`] backed by a JWK Set fetched over HTTP
+///
+/// The set is refetched once the TTL has passed, revalidating with
+/// `If-None-Match` when the server sent an `ETag`, and when a message names
+/// an unknown key, to pick up rotated keys.
+pub struct RemoteKeySet {
+    client: Client,
+    url: String,
+    ttl: Duration,
+    cache: RwLock<Cache>,
+}
+
+impl RemoteKeySet {
+    /// Fetch keys from the JWK Set at `url`
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            url: url.into(),
+            ttl: DEFAULT_TTL,
+            cache: RwLock::new(Cache::default()),
+        }
+    }
+
+    /// Use a preconfigured client, e.g. with timeouts or TLS settings
+    pub fn with_client(mut self, client: Client) -> Self {
+        self.client = client;
+        self
+    }
+
+    /// Set how long a fetched JWK Set is trusted before revalidation
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Check whether the cached keys are missing or older than the TTL
+    pub fn is_stale(&self) -> bool {
+        match self.fetched_at() {
+            Some(fetched_at) => fetched_at.elapsed() >= self.ttl,
+            None => true,
+        }
+    }
+
+    /// Fetch the JWK Set, revalidating the cached copy if the server sent an `ETag`
+    pub async fn refresh(&self) -> Result<()> {
+        let mut request = self.client.get(&self.url);
+        if let Some(etag) = self.read_cache()?.etag.clone() {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+
+        let response = request.send().await.map_err(fetch_error)?;
+        match response.status() {
+            StatusCode::NOT_MODIFIED => {
+                self.write_cache()?.fetched_at = Some(Instant::now());
+                Ok(())
+            }
+            status if status.is_success() => {
+                let etag = response
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|v| v.to_str().ok())
+                    .map(str::to_string);
+                let body = response.bytes().await.map_err(fetch_error)?;
+                let jwks: serde_json::Value = serde_json::from_slice(&body)
+                    .map_err(|e| Error::InvalidKeyFormat(format!("Invalid JWK Set: {e}")))?;
+                let keys = KeySet::from_jwks(&jwks)?;
+
+                *self.write_cache()? = Cache {
+                    keys,
+                    etag,
+                    fetched_at: Some(Instant::now()),
+                };
+                Ok(())
+            }
+            status => Err(Error::Other(format!(
+                "Fetching JWK Set from {} failed with status {status}",
+                self.url
+            ))),
+        }
+    }
+
+    /// Look up a key, fetching the JWK Set if it is stale or does not contain the key
+    pub async fn resolve_async(&self, key_id: &str) -> Result<PublicKey> {
+        if self.is_stale() {
+            self.refresh().await?;
+        } else if self.read_cache()?.keys.get(key_id).is_none() {
+            let recently_fetched = self
+                .fetched_at()
+                .is_some_and(|fetched_at| fetched_at.elapsed() < MIN_REFRESH_INTERVAL);
+            if !recently_fetched {
+                self.refresh().await?;
+            }
+        }
+        self.resolve(key_id)
+    }
+
+    /// Make sure the keys named by every signature on a message are cached
+    pub async fn prefetch<M: HttpMessage + ?Sized>(&self, message: &M) -> Result<()> {
+        let mut key_ids = Vec::new();
+        for label in signature_labels(message)? {
+            let (_, sig_input) = extract_signature_headers(message, &label)?;
+            let (_, params) = parse_signature_input(&sig_input)?;
+            if let Some(key_id) = params.key_id {
+                key_ids.push(key_id);
+            }
+        }
+
+        for key_id in key_ids {
+            self.resolve_async(&key_id).await?;
+        }
+        Ok(())
+    }
+
+    fn fetched_at(&self) -> Option<Instant> {
+        self.cache.read().ok().and_then(|cache| cache.fetched_at)
+    }
+
+    fn read_cache(&self) -> Result<std::sync::RwLockReadGuard<'_, Cache>> {
+        self.cache
+            .read()
+            .map_err(|_| Error::Other("JWK Set cache lock poisoned".to_string()))
+    }
+
+    fn write_cache(&self) -> Result<std::sync::RwLockWriteGuard<'_, Cache>> {
+        self.cache
+            .write()
+            .map_err(|_| Error::Other("JWK Set cache lock poisoned".to_string()))
+    }
+}
+
+impl KeyResolver for RemoteKeySet {
+    /// Look up a key in the cached JWK Set
+    ///
+    /// Does not fetch; keys of a stale set are still returned.
+    fn resolve(&self, key_id: &str) -> Result<PublicKey> {
+        self.read_cache()?.keys.resolve(key_id)
+    }
+}
+
+fn fetch_error(error: ::reqwest::Error) -> Error {
+    Error::Other(format!("Fetching JWK Set failed: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType};
+    use crate::formats::KeyExporter;
+    use crate::rfc9421::{HttpSigner, HttpVerifier};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    /// Serve a JWK Set with an ETag, answering revalidations with 304
+    async fn serve_jwks(jwks: String, full_responses: Arc<AtomicUsize>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut buffer = vec![0; 4096];
+                let read = stream.read(&mut buffer).await.unwrap();
+                let request = String::from_utf8_lossy(&buffer[..read]).to_ascii_lowercase();
+
+                let response = if request.contains("if-none-match: \"v1\"") {
+                    "HTTP/1.1 304 Not Modified\r\netag: \"v1\"\r\nconnection: close\r\ncontent-length: 0\r\n\r\n"
+                        .to_string()
+                } else {
+                    full_responses.fetch_add(1, Ordering::SeqCst);
+                    format!(
+                        "HTTP/1.1 200 OK\r\netag: \"v1\"\r\nconnection: close\r\ncontent-length: {}\r\n\r\n{jwks}",
+                        jwks.len()
+                    )
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        format!("http://{address}/.well-known/jwks.json")
+    }
+
+    #[tokio::test]
+    async fn test_remote_key_set() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let jwks = serde_json::json!({ "keys": [keypair.public_key().to_jwk().unwrap()] });
+        let full_responses = Arc::new(AtomicUsize::new(0));
+        let url = serve_jwks(jwks.to_string(), full_responses.clone()).await;
+
+        let keys = Arc::new(RemoteKeySet::new(url).with_ttl(Duration::ZERO));
+        let verifier = HttpVerifier::from_resolver(keys.clone());
+        let request = HttpSigner::new(keypair.clone())
+            .sign_request(
+                http::Request::builder()
+                    .uri("https://agent.example/status")
+                    .body(())
+                    .unwrap(),
+            )
+            .unwrap();
+
+        // Nothing is cached before the first fetch
+        assert!(verifier.verify_request(&request).is_err());
+        keys.prefetch(&request).await.unwrap();
+        verifier.verify_request(&request).unwrap();
+
+        // A zero TTL forces revalidation, which the ETag turns into a 304
+        keys.prefetch(&request).await.unwrap();
+        verifier.verify_request(&request).unwrap();
+        assert_eq!(full_responses.load(Ordering::SeqCst), 1);
+
+        assert!(keys.resolve_async("unknown").await.is_err());
+    }
+}
@@ -0,0 +1,29 @@
+//! Integrations with HTTP client and server frameworks
+//!
+//! Each integration is enabled by the cargo feature of the same name:
+//!
+//! - `reqwest`: [`reqwest::SigningClient`] signs outgoing requests, and
+//!   [`jwks::RemoteKeySet`] resolves keys from a remote JWK Set
+//! - `tower`: [`tower::SignLayer`] and [`tower::VerifyLayer`] sign and verify
+//!   requests passing through a service stack
+//! - `axum`: [`axum::VerifiedSignature`] extracts the verified signature of a request
+//! - `hyper`: helpers to sign and verify `hyper` messages, checking the
+//!   `Content-Digest` of streamed bodies
+//! - `tonic`: gRPC interceptors carrying signatures in request metadata,
+//!   bound to the called method through [`tonic::GrpcPathLayer`]
+//! - `warp`: [`warp::verified_signature`] filter for verified requests
+
+#[cfg(feature = "axum")]
+pub mod axum;
+#[cfg(feature = "hyper")]
+pub mod hyper;
+#[cfg(feature = "reqwest")]
+pub mod jwks;
+#[cfg(feature = "reqwest")]
+pub mod reqwest;
+#[cfg(feature = "tonic")]
+pub mod tonic;
+#[cfg(feature = "tower")]
+pub mod tower;
+#[cfg(feature = "warp")]
+pub mod warp;
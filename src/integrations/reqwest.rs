@@ -0,0 +1,165 @@
+//! Signing of outgoing `reqwest` requests
+//!
+//! ```ignore
+//! let client = SigningClient::new(reqwest::Client::new(), HttpSigner::new(keypair));
+//! let response = client.send(client.post("https://agent.example/tasks").json(&task)).await?;
+//! ```
+
+use crate::error::{Error, Result};
+use crate::rfc9421::{HttpMessage, HttpSigner};
+use ::reqwest::{Client, IntoUrl, Method, Request, RequestBuilder, Response};
+use std::sync::Arc;
+
+impl HttpMessage for Request {
+    fn method(&self) -> Option<&str> {
+        Some(Request::method(self).as_str())
+    }
+
+    fn target_uri(&self) -> Option<String> {
+        Some(self.url().to_string())
+    }
+
+    fn status(&self) -> Option<u16> {
+        None
+    }
+
+    fn header_values(&self, name: &str) -> Vec<&[u8]> {
+        self.headers()
+            .get_all(name)
+            .iter()
+            .map(|v| v.as_bytes())
+            .collect()
+    }
+}
+
+/// `reqwest` client that signs every request with an [`HttpSigner`] before sending it
+///
+/// Requests with a body also get a `Content-Digest` header covered by the
+/// signature, unless disabled with [`SigningClient::with_content_digest`].
+#[derive(Clone)]
+pub struct SigningClient {
+    client: Client,
+    signer: Arc<HttpSigner>,
+    content_digest: bool,
+}
+
+impl SigningClient {
+    /// Wrap a client so that its requests are signed by `signer`
+    pub fn new(client: Client, signer: HttpSigner) -> Self {
+        Self {
+            client,
+            signer: Arc::new(signer),
+            content_digest: true,
+        }
+    }
+
+    /// Add and sign a `Content-Digest` header for request bodies (enabled by default)
+    pub fn with_content_digest(mut self, enabled: bool) -> Self {
+        self.content_digest = enabled;
+        self
+    }
+
+    /// Get the wrapped client
+    pub fn client(&self) -> &Client {
+        &self.client
+    }
+
+    /// Start building a request; send it with [`SigningClient::send`]
+    pub fn request(&self, method: Method, url: impl IntoUrl) -> RequestBuilder {
+        self.client.request(method, url)
+    }
+
+    /// Start building a GET request
+    pub fn get(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::GET, url)
+    }
+
+    /// Start building a POST request
+    pub fn post(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::POST, url)
+    }
+
+    /// Start building a PUT request
+    pub fn put(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::PUT, url)
+    }
+
+    /// Start building a DELETE request
+    pub fn delete(&self, url: impl IntoUrl) -> RequestBuilder {
+        self.request(Method::DELETE, url)
+    }
+
+    /// Build, sign and send a request
+    pub async fn send(&self, builder: RequestBuilder) -> Result<Response> {
+        let request = builder.build().map_err(request_error)?;
+        self.execute(request).await
+    }
+
+    /// Sign and send a request
+    pub async fn execute(&self, mut request: Request) -> Result<Response> {
+        self.sign(&mut request)?;
+        self.client.execute(request).await.map_err(request_error)
+    }
+
+    /// Sign a request in place without sending it
+    ///
+    /// Streaming bodies cannot be digested and are rejected while the
+    /// `Content-Digest` header is enabled.
+    pub fn sign(&self, request: &mut Request) -> Result<()> {
+        let digest = match request.body() {
+            Some(body) if self.content_digest => {
+                let body = body.as_bytes().ok_or_else(|| {
+                    Error::Unsupported("Cannot compute the digest of a streaming body".to_string())
+                })?;
+                Some(self.signer.content_digest(body))
+            }
+            _ => None,
+        };
+
+        let headers = match digest {
+            Some(digest) => {
+                digest.insert_into(request.headers_mut())?;
+                self.signer.sign_message_with_digest(&*request)?
+            }
+            None => self.signer.sign_message(&*request)?,
+        };
+        headers.insert_into(request.headers_mut())
+    }
+}
+
+fn request_error(error: ::reqwest::Error) -> Error {
+    Error::Other(format!("HTTP request failed: {error}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType};
+    use crate::rfc9421::{ContentDigest, HttpVerifier, SignatureComponent};
+
+    #[test]
+    fn test_sign_reqwest_request() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let client = SigningClient::new(Client::new(), HttpSigner::new(keypair.clone()));
+        let verifier = HttpVerifier::new(keypair.public_key().clone());
+
+        let mut request = client
+            .post("https://agent.example/tasks?id=1")
+            .body("{\"task\":\"ping\"}")
+            .build()
+            .unwrap();
+        client.sign(&mut request).unwrap();
+
+        let report = verifier.verify_message(&request).unwrap();
+        assert!(report.covers(&SignatureComponent::content_digest()));
+        ContentDigest::from_headers(request.headers())
+            .unwrap()
+            .verify(b"{\"task\":\"ping\"}")
+            .unwrap();
+
+        let mut request = client.get("https://agent.example/status").build().unwrap();
+        client.sign(&mut request).unwrap();
+        let report = verifier.verify_message(&request).unwrap();
+        assert!(!report.covers(&SignatureComponent::content_digest()));
+    }
+}
@@ -0,0 +1,254 @@
+//! `tower` middleware for signing and verifying requests
+//!
+//! [`SignLayer`] signs requests on their way out of a client stack.
+//! [`VerifyLayer`] verifies requests entering a server stack, stores the
+//! [`VerificationReport`] in the request extensions and rejects requests
+//! that fail verification.
+//!
+//! `VerifyLayer` does not read request bodies; check the `Content-Digest`
+//! header against the body in the handler if the signature covers it.
+
+use crate::error::Error;
+use crate::rfc9421::{HttpSigner, HttpVerifier, VerificationReport};
+use http::{Request, Response, StatusCode};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// Boxed error returned by [`SignService`]
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// Layer that signs every request with an [`HttpSigner`]
+#[derive(Clone)]
+pub struct SignLayer {
+    signer: Arc<HttpSigner>,
+}
+
+impl SignLayer {
+    /// Sign requests with `signer`
+    pub fn new(signer: HttpSigner) -> Self {
+        Self {
+            signer: Arc::new(signer),
+        }
+    }
+}
+
+impl<S> Layer<S> for SignLayer {
+    type Service = SignService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        SignService {
+            inner,
+            signer: self.signer.clone(),
+        }
+    }
+}
+
+/// Service created by [`SignLayer`]
+#[derive(Clone)]
+pub struct SignService<S> {
+    inner: S,
+    signer: Arc<HttpSigner>,
+}
+
+impl<S, B> Service<Request<B>> for SignService<S>
+where
+    S: Service<Request<B>>,
+    S::Error: Into<BoxError>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = BoxFuture<Result<S::Response, BoxError>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        if let Err(e) = self.signer.sign_request_mut(&mut request) {
+            return Box::pin(async move { Err(e.into()) });
+        }
+
+        let future = self.inner.call(request);
+        Box::pin(async move { future.await.map_err(Into::into) })
+    }
+}
+
+/// Builds the response for a request that failed verification
+pub trait Rejection<B>: Clone {
+    /// Turn a verification error into a response
+    fn reject(&self, error: Error) -> Response<B>;
+}
+
+impl<B, F> Rejection<B> for F
+where
+    F: Fn(Error) -> Response<B> + Clone,
+{
+    fn reject(&self, error: Error) -> Response<B> {
+        self(error)
+    }
+}
+
+/// Default [`Rejection`]: `401 Unauthorized` with an empty body
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Unauthorized;
+
+impl<B: Default> Rejection<B> for Unauthorized {
+    fn reject(&self, _error: Error) -> Response<B> {
+        let mut response = Response::new(B::default());
+        *response.status_mut() = StatusCode::UNAUTHORIZED;
+        response
+    }
+}
+
+/// Layer that verifies every request with an [`HttpVerifier`]
+///
+/// Verified requests carry a [`VerificationReport`] extension.
+#[derive(Clone)]
+pub struct VerifyLayer<R = Unauthorized> {
+    verifier: Arc<HttpVerifier>,
+    rejection: R,
+}
+
+impl VerifyLayer {
+    /// Verify requests with `verifier`, rejecting failures with `401 Unauthorized`
+    pub fn new(verifier: HttpVerifier) -> Self {
+        Self {
+            verifier: Arc::new(verifier),
+            rejection: Unauthorized,
+        }
+    }
+}
+
+impl<R> VerifyLayer<R> {
+    /// Build the response for rejected requests, e.g. with a JSON error body
+    pub fn with_rejection<T>(self, rejection: T) -> VerifyLayer<T> {
+        VerifyLayer {
+            verifier: self.verifier,
+            rejection,
+        }
+    }
+}
+
+impl<S, R: Clone> Layer<S> for VerifyLayer<R> {
+    type Service = VerifyService<S, R>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        VerifyService {
+            inner,
+            verifier: self.verifier.clone(),
+            rejection: self.rejection.clone(),
+        }
+    }
+}
+
+/// Service created by [`VerifyLayer`]
+#[derive(Clone)]
+pub struct VerifyService<S, R = Unauthorized> {
+    inner: S,
+    verifier: Arc<HttpVerifier>,
+    rejection: R,
+}
+
+impl<S, R, B, ResBody> Service<Request<B>> for VerifyService<S, R>
+where
+    S: Service<Request<B>, Response = Response<ResBody>>,
+    S::Future: Send + 'static,
+    S::Error: Send + 'static,
+    R: Rejection<ResBody>,
+    ResBody: Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<Result<Response<ResBody>, S::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<B>) -> Self::Future {
+        match self.verifier.verify_request_detailed(&request) {
+            Ok(report) => {
+                request
+                    .extensions_mut()
+                    .insert::<VerificationReport>(report);
+                Box::pin(self.inner.call(request))
+            }
+            Err(e) => {
+                let response = self.rejection.reject(e);
+                Box::pin(async move { Ok(response) })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType};
+    use std::convert::Infallible;
+    use std::future::{ready, Ready};
+
+    /// Responds with the key ID of the verified signature, if any
+    #[derive(Clone)]
+    struct Echo;
+
+    impl Service<Request<()>> for Echo {
+        type Response = Response<String>;
+        type Error = Infallible;
+        type Future = Ready<Result<Response<String>, Infallible>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Infallible>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<()>) -> Self::Future {
+            let key_id = request
+                .extensions()
+                .get::<VerificationReport>()
+                .map(|report| report.key_id.clone())
+                .unwrap_or_default();
+            ready(Ok(Response::new(key_id)))
+        }
+    }
+
+    fn request() -> Request<()> {
+        Request::builder()
+            .method("GET")
+            .uri("https://agent.example/status")
+            .body(())
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sign_and_verify_layers() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let verify = VerifyLayer::new(HttpVerifier::new(keypair.public_key().clone()));
+
+        let mut client = SignLayer::new(HttpSigner::new(keypair.clone())).layer(verify.layer(Echo));
+        let response = client.call(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.body(), keypair.key_id());
+
+        let mut server = verify.layer(Echo);
+        let response = server.call(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+        let mut server = verify
+            .with_rejection(|e: Error| {
+                let mut response = Response::new(e.to_string());
+                *response.status_mut() = StatusCode::FORBIDDEN;
+                response
+            })
+            .layer(Echo);
+        let response = server.call(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        assert!(!response.body().is_empty());
+    }
+}
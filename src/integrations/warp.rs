@@ -0,0 +1,126 @@
+//! `warp` filter for requests with a verified signature
+//!
+//! ```ignore
+//! let routes = warp::path("status")
+//!     .and(verified_signature(Arc::new(verifier)))
+//!     .map(|report: VerificationReport| format!("hello {}", report.key_id))
+//!     .recover(handle_rejection);
+//! ```
+
+use crate::error::Error;
+use crate::rfc9421::{HttpVerifier, VerificationReport};
+use ::warp::http::{HeaderMap, Method, StatusCode};
+use ::warp::path::FullPath;
+use ::warp::reject::{Reject, Rejection};
+use ::warp::{Filter, Reply};
+use std::sync::Arc;
+
+/// Rejection for requests whose signature failed verification
+#[derive(Debug)]
+pub struct SignatureRejection(pub Error);
+
+impl Reject for SignatureRejection {}
+
+/// Filter verifying the signature of a request and extracting its [`VerificationReport`]
+///
+/// The filter does not read the body; check the `Content-Digest` header
+/// against it if the signature covers it.
+pub fn verified_signature(
+    verifier: Arc<HttpVerifier>,
+) -> impl Filter<Extract = (VerificationReport,), Error = Rejection> + Clone {
+    let query = ::warp::query::raw()
+        .or(::warp::any().map(String::new))
+        .unify();
+
+    ::warp::any()
+        .map(move || verifier.clone())
+        .and(::warp::method())
+        .and(::warp::path::full())
+        .and(query)
+        .and(::warp::header::headers_cloned())
+        .and_then(
+            |verifier: Arc<HttpVerifier>,
+             method: Method,
+             path: FullPath,
+             query: String,
+             headers: HeaderMap| async move {
+                verify(&verifier, method, path.as_str(), &query, headers)
+                    .map_err(|e| ::warp::reject::custom(SignatureRejection(e)))
+            },
+        )
+}
+
+/// Reassemble the request from its parts and verify it
+fn verify(
+    verifier: &HttpVerifier,
+    method: Method,
+    path: &str,
+    query: &str,
+    headers: HeaderMap,
+) -> crate::error::Result<VerificationReport> {
+    let uri = if query.is_empty() {
+        path.to_string()
+    } else {
+        format!("{path}?{query}")
+    };
+
+    let mut request = http::Request::builder()
+        .method(method)
+        .uri(uri)
+        .body(())
+        .map_err(|e| Error::InvalidInput(format!("Invalid request: {e}")))?;
+    *request.headers_mut() = headers;
+    verifier.verify_request_detailed(&request)
+}
+
+/// Turn a [`SignatureRejection`] into a `401 Unauthorized` response, for use with `recover`
+pub async fn handle_rejection(rejection: Rejection) -> Result<impl Reply, Rejection> {
+    match rejection.find::<SignatureRejection>() {
+        Some(SignatureRejection(e)) => Ok(::warp::reply::with_status(
+            e.to_string(),
+            StatusCode::UNAUTHORIZED,
+        )),
+        None => Err(rejection),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType};
+    use crate::rfc9421::HttpSigner;
+
+    #[tokio::test]
+    async fn test_verified_signature_filter() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let filter = verified_signature(Arc::new(HttpVerifier::new(keypair.public_key().clone())));
+
+        let signed = HttpSigner::new(keypair.clone())
+            .sign_request(
+                http::Request::builder()
+                    .uri("https://agent.example/status?verbose=1")
+                    .body(())
+                    .unwrap(),
+            )
+            .unwrap();
+        let request = |path: &str| {
+            signed.headers().iter().fold(
+                ::warp::test::request()
+                    .path(path)
+                    .header("host", "agent.example"),
+                |request, (name, value)| request.header(name, value),
+            )
+        };
+
+        let report = request("/status?verbose=1").filter(&filter).await.unwrap();
+        assert_eq!(report.key_id, keypair.key_id());
+
+        let rejection = request("/admin?verbose=1")
+            .filter(&filter)
+            .await
+            .unwrap_err();
+        assert!(rejection.find::<SignatureRejection>().is_some());
+        let response = handle_rejection(rejection).await.unwrap().into_response();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+    }
+}
@@ -0,0 +1,385 @@
+//! JNI bindings for Java and Android
+//!
+//! Every function takes and returns `byte[]`, `String` and `String[]` values
+//! only; keys are passed as raw bytes instead of native handles. The natives
+//! belong to `io.sage.crypto.SageCrypto` (see `examples/java`)
+//! and failures are thrown as `io.sage.crypto.SageCryptoException`.
+//!
+//! Key types use the C API values: `0` for Ed25519 and `1` for Secp256k1.
+//! Headers are flattened into `String[]` as `name, value, name, value, ...`.
+
+// JNI resolves natives by their `Java_<package>_<class>_<method>` names
+#![allow(non_snake_case)]
+
+use crate::crypto::{KeyPair, KeyType, PublicKey, Signature, Signer, Verifier};
+use crate::error::{Error, Result};
+use crate::rfc9421::{ContentDigest, HttpSigner, HttpVerifier};
+use ::jni::objects::{JByteArray, JClass, JObject, JObjectArray, JString};
+use ::jni::sys::{jboolean, jbyteArray, jint, jobjectArray, jstring, JNI_FALSE, JNI_TRUE};
+use ::jni::JNIEnv;
+use http::Request;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Java exception class thrown on failure
+const EXCEPTION_CLASS: &str = "io/sage/crypto/SageCryptoException";
+
+/// Convert a JNI key type value
+fn key_type(value: jint) -> Result<KeyType> {
+    match value {
+        0 => Ok(KeyType::Ed25519),
+        1 => Ok(KeyType::Secp256k1),
+        _ => Err(Error::InvalidInput(format!("Unknown key type: {value}"))),
+    }
+}
+
+/// Wrap a JNI error
+fn jni_error(err: ::jni::errors::Error) -> Error {
+    Error::Other(format!("JNI error: {err}"))
+}
+
+/// Run `f`, throwing a `SageCryptoException` and returning `fallback` on error or panic
+fn run<'local, T>(
+    env: &mut JNIEnv<'local>,
+    fallback: T,
+    f: impl FnOnce(&mut JNIEnv<'local>) -> Result<T>,
+) -> T {
+    let message = match panic::catch_unwind(AssertUnwindSafe(|| f(env))) {
+        Ok(Ok(value)) => return value,
+        Ok(Err(err)) => err.to_string(),
+        Err(_) => "Internal panic".to_string(),
+    };
+    // A Java exception may already be pending, in which case it is kept
+    if !env.exception_check().unwrap_or(true) {
+        let _ = env.throw_new(EXCEPTION_CLASS, message);
+    }
+    fallback
+}
+
+fn read_bytes(env: &mut JNIEnv, array: &JByteArray) -> Result<Vec<u8>> {
+    if array.is_null() {
+        return Err(Error::InvalidInput("Null byte array".to_string()));
+    }
+    env.convert_byte_array(array).map_err(jni_error)
+}
+
+fn read_string(env: &mut JNIEnv, string: &JString) -> Result<String> {
+    if string.is_null() {
+        return Err(Error::InvalidInput("Null string".to_string()));
+    }
+    Ok(env.get_string(string).map_err(jni_error)?.into())
+}
+
+fn read_strings(env: &mut JNIEnv, array: &JObjectArray) -> Result<Vec<String>> {
+    if array.is_null() {
+        return Ok(Vec::new());
+    }
+    let len = env.get_array_length(array).map_err(jni_error)?;
+    let mut strings = Vec::with_capacity(len as usize);
+    for i in 0..len {
+        let element = JString::from(env.get_object_array_element(array, i).map_err(jni_error)?);
+        strings.push(read_string(env, &element)?);
+    }
+    Ok(strings)
+}
+
+fn new_bytes(env: &mut JNIEnv, bytes: &[u8]) -> Result<jbyteArray> {
+    Ok(env
+        .byte_array_from_slice(bytes)
+        .map_err(jni_error)?
+        .into_raw())
+}
+
+fn new_strings(env: &mut JNIEnv, strings: &[String]) -> Result<jobjectArray> {
+    let array = env
+        .new_object_array(strings.len() as i32, "java/lang/String", JObject::null())
+        .map_err(jni_error)?;
+    for (i, string) in strings.iter().enumerate() {
+        let element = env.new_string(string).map_err(jni_error)?;
+        env.set_object_array_element(&array, i as i32, element)
+            .map_err(jni_error)?;
+    }
+    Ok(array.into_raw())
+}
+
+/// Build a request from flattened `name, value` header pairs
+fn build_request(
+    method: &str,
+    uri: &str,
+    headers: &[String],
+    body: Vec<u8>,
+) -> Result<Request<Vec<u8>>> {
+    if headers.len() % 2 != 0 {
+        return Err(Error::InvalidInput(
+            "Headers must be name/value pairs".to_string(),
+        ));
+    }
+
+    let mut builder = Request::builder().method(method).uri(uri);
+    for pair in headers.chunks(2) {
+        builder = builder.header(pair[0].as_str(), pair[1].as_str());
+    }
+    builder
+        .body(body)
+        .map_err(|e| Error::InvalidInput(format!("Invalid HTTP request: {e}")))
+}
+
+/// Sign a request and return the signature headers as flattened pairs
+fn sign_http_request(
+    keypair: KeyPair,
+    method: &str,
+    uri: &str,
+    headers: &[String],
+    body: Option<Vec<u8>>,
+) -> Result<Vec<String>> {
+    let signer = HttpSigner::new(keypair);
+    let has_body = body.is_some();
+    let request = build_request(method, uri, headers, body.unwrap_or_default())?;
+    let signed = if has_body {
+        signer.sign_request_with_body(request)?
+    } else {
+        signer.sign_request(request)?
+    };
+
+    let mut pairs = Vec::new();
+    for name in ["content-digest", "signature-input", "signature"] {
+        if name == "content-digest" && !has_body {
+            continue;
+        }
+        if let Some(value) = signed.headers().get(name) {
+            let value = value
+                .to_str()
+                .map_err(|e| Error::HttpSignature(format!("Invalid {name} header: {e}")))?;
+            pairs.push(name.to_string());
+            pairs.push(value.to_string());
+        }
+    }
+    Ok(pairs)
+}
+
+/// Verify a request signature, and its `content-digest` when a body is given
+fn verify_http_request(
+    public_key: PublicKey,
+    method: &str,
+    uri: &str,
+    headers: &[String],
+    body: Option<Vec<u8>>,
+) -> Result<bool> {
+    let has_body = body.is_some();
+    let request = build_request(method, uri, headers, body.unwrap_or_default())?;
+    let result = HttpVerifier::new(public_key)
+        .verify_request(&request)
+        .and_then(|()| {
+            if has_body {
+                ContentDigest::verify_request(&request)
+            } else {
+                Ok(())
+            }
+        });
+    match result {
+        Ok(()) => Ok(true),
+        Err(Error::Verification(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Generate a private key
+#[no_mangle]
+pub extern "system" fn Java_io_sage_crypto_SageCrypto_generatePrivateKey<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    key_type_value: jint,
+) -> jbyteArray {
+    run(&mut env, std::ptr::null_mut(), |env| {
+        let keypair = KeyPair::generate(key_type(key_type_value)?)?;
+        new_bytes(env, &keypair.private_key_bytes())
+    })
+}
+
+/// Get the public key for a private key
+#[no_mangle]
+pub extern "system" fn Java_io_sage_crypto_SageCrypto_publicKey<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    key_type_value: jint,
+    private_key: JByteArray<'local>,
+) -> jbyteArray {
+    run(&mut env, std::ptr::null_mut(), |env| {
+        let private_key = read_bytes(env, &private_key)?;
+        let keypair = KeyPair::from_private_key_bytes(key_type(key_type_value)?, &private_key)?;
+        new_bytes(env, &keypair.public_key_bytes())
+    })
+}
+
+/// Get the key ID of a public key
+#[no_mangle]
+pub extern "system" fn Java_io_sage_crypto_SageCrypto_keyId<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    key_type_value: jint,
+    public_key: JByteArray<'local>,
+) -> jstring {
+    run(&mut env, std::ptr::null_mut(), |env| {
+        let public_key = read_bytes(env, &public_key)?;
+        let public_key = PublicKey::from_bytes(key_type(key_type_value)?, &public_key)?;
+        Ok(env
+            .new_string(public_key.key_id())
+            .map_err(jni_error)?
+            .into_raw())
+    })
+}
+
+/// Sign a message
+#[no_mangle]
+pub extern "system" fn Java_io_sage_crypto_SageCrypto_sign<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    key_type_value: jint,
+    private_key: JByteArray<'local>,
+    message: JByteArray<'local>,
+) -> jbyteArray {
+    run(&mut env, std::ptr::null_mut(), |env| {
+        let private_key = read_bytes(env, &private_key)?;
+        let message = read_bytes(env, &message)?;
+        let keypair = KeyPair::from_private_key_bytes(key_type(key_type_value)?, &private_key)?;
+        new_bytes(env, &keypair.sign(&message)?.to_bytes())
+    })
+}
+
+/// Verify a signature, returning `false` if it does not match
+#[no_mangle]
+pub extern "system" fn Java_io_sage_crypto_SageCrypto_verify<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    key_type_value: jint,
+    public_key: JByteArray<'local>,
+    message: JByteArray<'local>,
+    signature: JByteArray<'local>,
+) -> jboolean {
+    run(&mut env, JNI_FALSE, |env| {
+        let key_type = key_type(key_type_value)?;
+        let public_key = PublicKey::from_bytes(key_type, &read_bytes(env, &public_key)?)?;
+        let message = read_bytes(env, &message)?;
+        let signature = Signature::from_bytes(key_type, &read_bytes(env, &signature)?)?;
+        match public_key.verify(&message, &signature) {
+            Ok(()) => Ok(JNI_TRUE),
+            Err(Error::Verification(_)) => Ok(JNI_FALSE),
+            Err(e) => Err(e),
+        }
+    })
+}
+
+/// Sign an HTTP request (RFC 9421) and return the headers to add to it
+///
+/// `body` may be null; when present a `content-digest` header is added and signed.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "system" fn Java_io_sage_crypto_SageCrypto_signHttpRequest<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    key_type_value: jint,
+    private_key: JByteArray<'local>,
+    method: JString<'local>,
+    uri: JString<'local>,
+    headers: JObjectArray<'local>,
+    body: JByteArray<'local>,
+) -> jobjectArray {
+    run(&mut env, std::ptr::null_mut(), |env| {
+        let private_key = read_bytes(env, &private_key)?;
+        let keypair = KeyPair::from_private_key_bytes(key_type(key_type_value)?, &private_key)?;
+        let method = read_string(env, &method)?;
+        let uri = read_string(env, &uri)?;
+        let headers = read_strings(env, &headers)?;
+        let body = if body.is_null() {
+            None
+        } else {
+            Some(read_bytes(env, &body)?)
+        };
+
+        let pairs = sign_http_request(keypair, &method, &uri, &headers, body)?;
+        new_strings(env, &pairs)
+    })
+}
+
+/// Verify the signature of an HTTP request (RFC 9421), returning `false` if it does not match
+///
+/// When `body` is not null it must also match the signed `content-digest` header.
+#[no_mangle]
+#[allow(clippy::too_many_arguments)]
+pub extern "system" fn Java_io_sage_crypto_SageCrypto_verifyHttpRequest<'local>(
+    mut env: JNIEnv<'local>,
+    _class: JClass<'local>,
+    key_type_value: jint,
+    public_key: JByteArray<'local>,
+    method: JString<'local>,
+    uri: JString<'local>,
+    headers: JObjectArray<'local>,
+    body: JByteArray<'local>,
+) -> jboolean {
+    run(&mut env, JNI_FALSE, |env| {
+        let public_key = read_bytes(env, &public_key)?;
+        let public_key = PublicKey::from_bytes(key_type(key_type_value)?, &public_key)?;
+        let method = read_string(env, &method)?;
+        let uri = read_string(env, &uri)?;
+        let headers = read_strings(env, &headers)?;
+        let body = if body.is_null() {
+            None
+        } else {
+            Some(read_bytes(env, &body)?)
+        };
+
+        let verified = verify_http_request(public_key, &method, &uri, &headers, body)?;
+        Ok(if verified { JNI_TRUE } else { JNI_FALSE })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_type_values() {
+        assert_eq!(key_type(0).unwrap(), KeyType::Ed25519);
+        assert_eq!(key_type(1).unwrap(), KeyType::Secp256k1);
+        assert!(key_type(2).is_err());
+    }
+
+    #[test]
+    fn test_sign_http_request_pairs() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let public_key = keypair.public_key().clone();
+        let mut headers = vec!["host".to_string(), "agent.example".to_string()];
+
+        let pairs = sign_http_request(
+            keypair,
+            "POST",
+            "https://agent.example/tasks",
+            &headers,
+            Some(b"{}".to_vec()),
+        )
+        .unwrap();
+        assert_eq!(pairs.len(), 6);
+        assert_eq!(pairs[0], "content-digest");
+
+        headers.extend(pairs);
+        let uri = "https://agent.example/tasks";
+        assert!(verify_http_request(
+            public_key.clone(),
+            "POST",
+            uri,
+            &headers,
+            Some(b"{}".to_vec())
+        )
+        .unwrap());
+        assert!(!verify_http_request(
+            public_key.clone(),
+            "POST",
+            uri,
+            &headers,
+            Some(b"{\"x\":1}".to_vec())
+        )
+        .unwrap());
+        assert!(!verify_http_request(public_key, "PUT", uri, &headers, None).unwrap());
+
+        assert!(build_request("GET", "/", &["host".to_string()], Vec::new()).is_err());
+    }
+}
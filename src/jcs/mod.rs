@@ -0,0 +1,179 @@
+//! JSON Canonicalization Scheme (RFC 8785)
+//!
+//! Semantically equal JSON documents canonicalize to the same bytes,
+//! whatever their member order or whitespace, so their signatures match.
+
+use crate::crypto::{KeyPair, PublicKey, Signature, Signer, Verifier};
+use crate::error::{Error, Result};
+use serde::Serialize;
+use serde_json::Value;
+
+/// Canonicalize a JSON value
+///
+/// Object members are sorted by their UTF-16 code units and numbers are
+/// serialized like ECMAScript's `Number.prototype.toString`.
+pub fn canonicalize(value: &Value) -> Result<String> {
+    let mut out = String::new();
+    write_value(&mut out, value)?;
+    Ok(out)
+}
+
+/// Canonicalize any serializable value
+pub fn to_canonical_string<T: Serialize + ?Sized>(value: &T) -> Result<String> {
+    let value = serde_json::to_value(value).map_err(|e| Error::Serialization(e.to_string()))?;
+    canonicalize(&value)
+}
+
+/// Sign the canonical form of `value`
+pub fn sign_json<T: Serialize + ?Sized>(keypair: &KeyPair, value: &T) -> Result<Signature> {
+    keypair.sign(to_canonical_string(value)?.as_bytes())
+}
+
+/// Verify a signature made with [`sign_json`] over any equivalent document
+pub fn verify_json<T: Serialize + ?Sized>(
+    public_key: &PublicKey,
+    value: &T,
+    signature: &Signature,
+) -> Result<()> {
+    public_key.verify(to_canonical_string(value)?.as_bytes(), signature)
+}
+
+fn write_value(out: &mut String, value: &Value) -> Result<()> {
+    match value {
+        Value::Null => out.push_str("null"),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Number(number) => {
+            let number = number.as_f64().ok_or_else(|| {
+                Error::Serialization(format!("Number {number} is not an IEEE 754 double"))
+            })?;
+            out.push_str(&format_number(number)?);
+        }
+        Value::String(string) => write_string(out, string)?,
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_value(out, item)?;
+            }
+            out.push(']');
+        }
+        Value::Object(members) => {
+            let mut members: Vec<(&String, &Value)> = members.iter().collect();
+            members.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            out.push('{');
+            for (i, (name, member)) in members.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_string(out, name)?;
+                out.push(':');
+                write_value(out, member)?;
+            }
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+/// Write a string with the minimal escaping of RFC 8785 Section 3.2.2.2,
+/// which serde_json already implements
+fn write_string(out: &mut String, string: &str) -> Result<()> {
+    let escaped = serde_json::to_string(string).map_err(|e| Error::Serialization(e.to_string()))?;
+    out.push_str(&escaped);
+    Ok(())
+}
+
+/// Format a number like ECMAScript `Number.prototype.toString` (RFC 8785 Section 3.2.2.3)
+fn format_number(number: f64) -> Result<String> {
+    if !number.is_finite() {
+        return Err(Error::Serialization(
+            "JSON numbers must be finite".to_string(),
+        ));
+    }
+    if number == 0.0 {
+        return Ok("0".to_string());
+    }
+
+    // Shortest round-trip digits, e.g. "-1.2345e3"
+    let scientific = format!("{number:e}");
+    let (mantissa, exponent) = scientific
+        .split_once('e')
+        .ok_or_else(|| Error::Serialization(format!("Unexpected float format {scientific}")))?;
+    let exponent: i32 = exponent
+        .parse()
+        .map_err(|_| Error::Serialization(format!("Unexpected float format {scientific}")))?;
+    let (sign, mantissa) = match mantissa.strip_prefix('-') {
+        Some(mantissa) => ("-", mantissa),
+        None => ("", mantissa),
+    };
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+
+    // ECMAScript's k (digit count) and n (decimal point position)
+    let k = digits.len() as i32;
+    let n = exponent + 1;
+    let formatted = if k <= n && n <= 21 {
+        format!("{digits}{}", "0".repeat((n - k) as usize))
+    } else if 0 < n && n <= 21 {
+        let (integer, fraction) = digits.split_at(n as usize);
+        format!("{integer}.{fraction}")
+    } else if -6 < n && n <= 0 {
+        format!("0.{}{digits}", "0".repeat((-n) as usize))
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let fraction = if rest.is_empty() {
+            String::new()
+        } else {
+            format!(".{rest}")
+        };
+        let exponent_sign = if n - 1 < 0 { '-' } else { '+' };
+        format!("{first}{fraction}e{exponent_sign}{}", (n - 1).abs())
+    };
+    Ok(format!("{sign}{formatted}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyType;
+    use serde_json::json;
+
+    #[test]
+    fn test_canonicalize() {
+        let value: Value = serde_json::from_str(
+            r#"{ "numbers": [333333333.33333329, 1E30, 4.50, 2e-3, 0.000001, 1e-7, -0, 100],
+                 "string": "€$\u000F\u000aA'\u0042\u0022\u005c\\\"\/",
+                 "literals": [null, true, false] }"#,
+        )
+        .unwrap();
+        // Example from RFC 8785 Section 3.2.4, plus extra numbers
+        assert_eq!(
+            canonicalize(&value).unwrap(),
+            r#"{"literals":[null,true,false],"numbers":[333333333.3333333,1e+30,4.5,0.002,0.000001,1e-7,0,100],"string":"€$\u000f\nA'B\"\\\\\"/"}"#
+        );
+    }
+
+    #[test]
+    fn test_member_order_by_utf16() {
+        // U+1F600 sorts before U+FB33 in UTF-16, but after it in UTF-8
+        let value = json!({"\u{fb33}": 1, "\u{1f600}": 2, "a": 3});
+        assert_eq!(
+            canonicalize(&value).unwrap(),
+            "{\"a\":3,\"\u{1f600}\":2,\"\u{fb33}\":1}"
+        );
+    }
+
+    #[test]
+    fn test_sign_and_verify_json() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let document: Value = serde_json::from_str(r#"{"b": [1, 2.0], "a": "x"}"#).unwrap();
+        let signature = sign_json(&keypair, &document).unwrap();
+
+        let reordered: Value = serde_json::from_str(r#"{ "a":"x","b":[1,2] }"#).unwrap();
+        assert!(verify_json(keypair.public_key(), &reordered, &signature).is_ok());
+
+        let changed = json!({"a": "y", "b": [1, 2]});
+        assert!(verify_json(keypair.public_key(), &changed, &signature).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+//! JSON Web Signatures (RFC 7515) in compact serialization
+//!
+//! Supports `EdDSA` (RFC 8037) for Ed25519 keys and `ES256K` (RFC 8812) for
+//! Secp256k1 keys, using the same [`KeyPair`] and [`PublicKey`] types as the
+//! rest of the crate.
+
+use crate::crypto::{KeyPair, KeyType, PublicKey, Signature, Signer, Verifier};
+use crate::error::{Error, Result};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use serde_json::{json, Map, Value};
+
+/// JWS signature algorithms
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JwsAlgorithm {
+    /// EdDSA with Ed25519
+    EdDSA,
+    /// ECDSA with Secp256k1 and SHA-256
+    ES256K,
+}
+
+impl JwsAlgorithm {
+    /// Get the `alg` header value
+    pub fn name(&self) -> &'static str {
+        match self {
+            JwsAlgorithm::EdDSA => "EdDSA",
+            JwsAlgorithm::ES256K => "ES256K",
+        }
+    }
+
+    /// Look up an algorithm by its `alg` header value
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "EdDSA" => Some(JwsAlgorithm::EdDSA),
+            "ES256K" => Some(JwsAlgorithm::ES256K),
+            _ => None,
+        }
+    }
+
+    /// The algorithm used to sign with a key type
+    pub fn for_key_type(key_type: KeyType) -> Self {
+        match key_type {
+            KeyType::Ed25519 => JwsAlgorithm::EdDSA,
+            KeyType::Secp256k1 => JwsAlgorithm::ES256K,
+        }
+    }
+}
+
+/// A decoded JWS
+#[derive(Debug, Clone, PartialEq)]
+pub struct Jws {
+    /// Protected header
+    pub header: Map<String, Value>,
+    /// Decoded payload
+    pub payload: Vec<u8>,
+}
+
+impl Jws {
+    /// Get the `kid` header value
+    pub fn key_id(&self) -> Option<&str> {
+        self.header.get("kid").and_then(Value::as_str)
+    }
+
+    /// Get the algorithm named by the `alg` header
+    pub fn algorithm(&self) -> Result<JwsAlgorithm> {
+        let alg = self
+            .header
+            .get("alg")
+            .and_then(Value::as_str)
+            .ok_or_else(|| Error::InvalidInput("JWS header is missing alg".to_string()))?;
+        JwsAlgorithm::from_name(alg)
+            .ok_or_else(|| Error::Unsupported(format!("Unsupported JWS algorithm: {alg}")))
+    }
+}
+
+/// Sign a payload, producing a compact JWS with `alg` and `kid` headers
+pub fn sign(keypair: &KeyPair, payload: &[u8]) -> Result<String> {
+    sign_with_header(keypair, Map::new(), payload)
+}
+
+/// Sign a payload with additional protected header parameters, e.g. `typ`
+///
+/// `alg` is always set from the key type; `kid` defaults to the key ID.
+pub fn sign_with_header(
+    keypair: &KeyPair,
+    mut header: Map<String, Value>,
+    payload: &[u8],
+) -> Result<String> {
+    let algorithm = JwsAlgorithm::for_key_type(keypair.key_type());
+    header.insert("alg".to_string(), json!(algorithm.name()));
+    header
+        .entry("kid")
+        .or_insert_with(|| json!(keypair.key_id()));
+
+    let header = serde_json::to_vec(&header).map_err(|e| Error::Serialization(e.to_string()))?;
+    let signing_input = format!(
+        "{}.{}",
+        URL_SAFE_NO_PAD.encode(header),
+        URL_SAFE_NO_PAD.encode(payload)
+    );
+
+    let signature = match keypair.sign(signing_input.as_bytes())? {
+        Signature::Ed25519(signature) => signature.to_bytes().to_vec(),
+        // JWS uses the fixed-size r || s encoding rather than DER
+        Signature::Secp256k1(signature) => signature.as_ref().to_vec(),
+    };
+
+    Ok(format!(
+        "{signing_input}.{}",
+        URL_SAFE_NO_PAD.encode(signature)
+    ))
+}
+
+/// Verify a compact JWS and return its header and payload
+///
+/// The `alg` header must match the key type; tokens with `crit` extensions are rejected.
+pub fn verify(public_key: &PublicKey, token: &str) -> Result<Jws> {
+    let (jws, signing_input, signature) = split(token)?;
+
+    if jws.header.contains_key("crit") {
+        return Err(Error::Unsupported(
+            "JWS critical header parameters are not supported".to_string(),
+        ));
+    }
+
+    let algorithm = jws.algorithm()?;
+    if algorithm != JwsAlgorithm::for_key_type(public_key.key_type()) {
+        return Err(Error::Verification(format!(
+            "JWS algorithm {} does not match the {:?} key",
+            algorithm.name(),
+            public_key.key_type()
+        )));
+    }
+
+    let signature = match algorithm {
+        JwsAlgorithm::EdDSA => {
+            let bytes: [u8; 64] = signature
+                .as_slice()
+                .try_into()
+                .map_err(|_| Error::InvalidInput("EdDSA signature must be 64 bytes".to_string()))?;
+            Signature::Ed25519(ed25519_dalek::Signature::from_bytes(&bytes))
+        }
+        JwsAlgorithm::ES256K => {
+            if signature.len() != 64 {
+                return Err(Error::InvalidInput(
+                    "ES256K signature must be 64 bytes".to_string(),
+                ));
+            }
+            Signature::Secp256k1(
+                k256::ecdsa::Signature::try_from(signature.as_slice())
+                    .map_err(|e| Error::InvalidInput(format!("Invalid ES256K signature: {e}")))?,
+            )
+        }
+    };
+
+    public_key.verify(signing_input.as_bytes(), &signature)?;
+    Ok(jws)
+}
+
+/// Decode a compact JWS without verifying it, e.g. to read `kid` before choosing a key
+pub fn decode_unverified(token: &str) -> Result<Jws> {
+    split(token).map(|(jws, _, _)| jws)
+}
+
+/// Split a compact JWS into its decoded parts and signing input
+fn split(token: &str) -> Result<(Jws, &str, Vec<u8>)> {
+    let invalid = || Error::InvalidInput("Compact JWS must have three parts".to_string());
+    let (signing_input, signature) = token.rsplit_once('.').ok_or_else(invalid)?;
+    let (header, payload) = signing_input.split_once('.').ok_or_else(invalid)?;
+    if payload.contains('.') {
+        return Err(invalid());
+    }
+
+    let header: Map<String, Value> = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header)?)
+        .map_err(|e| Error::Serialization(format!("Invalid JWS header: {e}")))?;
+    let payload = URL_SAFE_NO_PAD.decode(payload)?;
+    let signature = URL_SAFE_NO_PAD.decode(signature)?;
+
+    Ok((Jws { header, payload }, signing_input, signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_and_verify() {
+        for key_type in [KeyType::Ed25519, KeyType::Secp256k1] {
+            let keypair = KeyPair::generate(key_type).unwrap();
+            let token = sign(&keypair, b"{\"sub\":\"agent\"}").unwrap();
+
+            let jws = verify(keypair.public_key(), &token).unwrap();
+            assert_eq!(jws.payload, b"{\"sub\":\"agent\"}");
+            assert_eq!(jws.key_id(), Some(keypair.key_id()));
+            assert_eq!(
+                jws.algorithm().unwrap(),
+                JwsAlgorithm::for_key_type(key_type)
+            );
+
+            let other = KeyPair::generate(key_type).unwrap();
+            assert!(verify(other.public_key(), &token).is_err());
+        }
+    }
+
+    #[test]
+    fn test_reject_tampered_and_mismatched_tokens() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let token = sign(&keypair, b"payload").unwrap();
+
+        let (signing_input, signature) = token.rsplit_once('.').unwrap();
+        let (header, _) = signing_input.split_once('.').unwrap();
+        let tampered = format!("{header}.{}.{signature}", URL_SAFE_NO_PAD.encode(b"other"));
+        assert!(verify(keypair.public_key(), &tampered).is_err());
+
+        let secp = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        assert!(verify(secp.public_key(), &token).is_err());
+
+        let mut header = Map::new();
+        header.insert("crit".to_string(), json!(["exp"]));
+        let critical = sign_with_header(&keypair, header, b"payload").unwrap();
+        assert!(verify(keypair.public_key(), &critical).is_err());
+
+        assert!(verify(keypair.public_key(), "a.b").is_err());
+        assert!(decode_unverified(&token).is_ok());
+    }
+}
@@ -0,0 +1,216 @@
+//! Audit trail of private key use
+//!
+//! Hooks registered with [`KeyStore::with_audit_hook`] are called after every
+//! operation that creates, hands out, uses or discards private key material.
+
+use super::policy::signature_base_params;
+use super::{read_state, KeyStore, KeyStoreState, StoredKey};
+use crate::crypto::Signer;
+use crate::error::{Error, Result};
+use crate::rfc9421::{CustomSigner, SignatureAlgorithm};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// Operation recorded by an [`AuditEvent`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditAction {
+    /// A key pair was generated in the store
+    Generated,
+    /// An existing key pair was added to the store
+    Imported,
+    /// A private key was handed out of the store
+    Exported,
+    /// A message was signed with the key
+    Signed,
+    /// The key became the current key for its label
+    Rotated,
+    /// The key was revoked
+    Revoked,
+    /// The key was taken out of the store
+    Removed,
+    /// The key was removed and its private key wiped
+    Destroyed,
+}
+
+/// Record of one operation on a stored key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    /// What was done
+    pub action: AuditAction,
+    /// Key ID of the affected key
+    pub key_id: String,
+    /// Label of the affected key, if any
+    pub label: Option<String>,
+    /// Unix timestamp of the operation
+    pub timestamp: i64,
+}
+
+/// Receives [`AuditEvent`]s, e.g. to forward them to a SIEM
+///
+/// Hooks run synchronously on the calling thread and must not call back into
+/// the key store.
+pub trait AuditHook: Send + Sync {
+    /// Record an event
+    fn record(&self, event: &AuditEvent);
+}
+
+impl<F: Fn(&AuditEvent) + Send + Sync> AuditHook for F {
+    fn record(&self, event: &AuditEvent) {
+        self(event)
+    }
+}
+
+/// Registered audit hooks
+#[derive(Clone, Default)]
+pub(super) struct AuditLog {
+    hooks: Vec<Arc<dyn AuditHook>>,
+}
+
+impl AuditLog {
+    /// Send an event to every hook
+    pub(super) fn emit(&self, action: AuditAction, key_id: &str, label: Option<&str>) {
+        if self.hooks.is_empty() {
+            return;
+        }
+        let event = AuditEvent {
+            action,
+            key_id: key_id.to_string(),
+            label: label.map(str::to_string),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+        for hook in &self.hooks {
+            hook.record(&event);
+        }
+    }
+}
+
+impl fmt::Debug for AuditLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditLog")
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+/// Signer for the current key of a label that records every signature
+///
+/// The key, its status and validity and its policy are read from the store
+/// at every signature, so rotation, revocation and policy changes apply to
+/// signers created before them.
+pub(super) struct StoreSigner {
+    state: Arc<RwLock<KeyStoreState>>,
+    label: String,
+    audit: AuditLog,
+}
+
+impl StoreSigner {
+    pub(super) fn new(state: Arc<RwLock<KeyStoreState>>, label: &str, audit: AuditLog) -> Self {
+        Self {
+            state,
+            label: label.to_string(),
+            audit,
+        }
+    }
+
+    /// Read the current key for the label
+    fn current<T>(&self, read: impl FnOnce(&StoredKey) -> T) -> Result<T> {
+        let state = read_state(&self.state)?;
+        state.current_key(&self.label).map(read)
+    }
+}
+
+impl CustomSigner for StoreSigner {
+    fn identifier(&self) -> &'static str {
+        // A label without a key fails at signing, whatever algorithm is named here
+        self.current(|stored| stored.keypair.key_type())
+            .map(|key_type| SignatureAlgorithm::for_key_type(key_type).identifier())
+            .unwrap_or_default()
+    }
+
+    fn key_id(&self) -> String {
+        self.current(|stored| stored.keypair.key_id().to_string())
+            .unwrap_or_default()
+    }
+
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let params = signature_base_params(message)?;
+        let now = chrono::Utc::now().timestamp();
+        let (key_id, signature) = {
+            let state = read_state(&self.state)?;
+            let stored = state.current_key(&self.label)?;
+            stored.check_signing(now)?;
+            let keypair = &stored.keypair;
+            let algorithm = SignatureAlgorithm::for_key_type(keypair.key_type());
+            if params.key_id.as_deref() != Some(keypair.key_id())
+                || params.alg.as_deref() != Some(algorithm.identifier())
+            {
+                return Err(Error::InvalidInput(format!(
+                    "Key for label {} changed while signing",
+                    self.label
+                )));
+            }
+            stored
+                .policy
+                .check_signature_params(keypair.key_id(), &params, now)?;
+            (keypair.key_id().to_string(), keypair.sign(message)?)
+        };
+        self.audit
+            .emit(AuditAction::Signed, &key_id, Some(&self.label));
+        Ok(signature.to_bytes())
+    }
+}
+
+impl KeyStore {
+    /// Call `hook` for every operation on private key material
+    pub fn with_audit_hook(mut self, hook: impl AuditHook + 'static) -> Self {
+        self.audit.hooks.push(Arc::new(hook));
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType};
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[test]
+    fn test_audit_events() {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        let store = KeyStore::new().with_audit_hook(move |event: &AuditEvent| {
+            recorded.lock().unwrap().push(event.action);
+        });
+
+        let key_id = store.generate_with_label("http", KeyType::Ed25519).unwrap();
+        store.get(&key_id).unwrap();
+        store.get("unknown").unwrap();
+
+        let request = http::Request::builder()
+            .method("GET")
+            .uri("https://agent.example/tasks")
+            .body(())
+            .unwrap();
+        store.signer("http").unwrap().sign_request(request).unwrap();
+        store.sign(&key_id, b"message").unwrap();
+
+        let next = KeyPair::generate(KeyType::Ed25519).unwrap();
+        store.rotate("http", next, Duration::from_secs(60)).unwrap();
+        store.revoke(&key_id).unwrap();
+        assert!(store.destroy(&key_id).unwrap());
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                AuditAction::Generated,
+                AuditAction::Exported,
+                AuditAction::Signed,
+                AuditAction::Signed,
+                AuditAction::Rotated,
+                AuditAction::Revoked,
+                AuditAction::Destroyed,
+            ]
+        );
+    }
+}
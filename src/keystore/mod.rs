@@ -0,0 +1,353 @@
+//! Storage of agent key pairs
+//!
+//! [`KeyStore`] keeps key pairs in memory, indexed by key ID and optional
+//! label, and resolves public keys for RFC 9421 verification. Keys can be
+//! rotated with an overlapping grace period, see [`KeyStore::rotate`], and
+//! every use of private key material can be audited, see
+//! [`KeyStore::with_audit_hook`]. A [`KeyPolicy`] restricts what a key may
+//! be used for, and [`KeyStorage`] persists keys, e.g. in the OS keyring.
+
+pub mod audit;
+#[cfg(feature = "keyring")]
+pub mod os_keyring;
+pub mod policy;
+pub mod rotation;
+pub mod storage;
+
+pub use audit::{AuditAction, AuditEvent, AuditHook};
+#[cfg(feature = "keyring")]
+pub use os_keyring::OsKeyring;
+pub use policy::{KeyOperation, KeyPolicy};
+pub use rotation::{KeyInfo, KeyStatus};
+pub use storage::KeyStorage;
+
+use crate::crypto::{KeyPair, KeyType, PublicKey, Signature, Signer};
+use crate::error::{Error, Result};
+use crate::rfc9421::{KeyResolver, KeySet};
+use audit::AuditLog;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Key pair held by a [`KeyStore`]
+#[derive(Debug, Clone)]
+struct StoredKey {
+    keypair: KeyPair,
+    label: Option<String>,
+    status: KeyStatus,
+    not_before: Option<i64>,
+    not_after: Option<i64>,
+    policy: KeyPolicy,
+}
+
+#[derive(Debug, Default)]
+struct KeyStoreState {
+    keys: HashMap<String, StoredKey>,
+    labels: HashMap<String, String>,
+}
+
+/// Thread-safe in-memory store of key pairs
+///
+/// Keys are indexed by their key ID and, optionally, by a unique label such
+/// as `"http-signing"`.
+#[derive(Debug, Default)]
+pub struct KeyStore {
+    state: Arc<RwLock<KeyStoreState>>,
+    audit: AuditLog,
+}
+
+/// Lock the state of a store for reading
+fn read_state(state: &RwLock<KeyStoreState>) -> Result<RwLockReadGuard<'_, KeyStoreState>> {
+    state
+        .read()
+        .map_err(|_| Error::Other("Key store lock poisoned".to_string()))
+}
+
+impl KeyStore {
+    /// Create an empty key store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read(&self) -> Result<RwLockReadGuard<'_, KeyStoreState>> {
+        read_state(&self.state)
+    }
+
+    fn write(&self) -> Result<RwLockWriteGuard<'_, KeyStoreState>> {
+        self.state
+            .write()
+            .map_err(|_| Error::Other("Key store lock poisoned".to_string()))
+    }
+
+    /// Add a key pair, returning its key ID
+    ///
+    /// A key pair with the same key ID replaces the stored one.
+    pub fn insert(&self, keypair: KeyPair) -> Result<String> {
+        self.insert_entry(keypair, None, AuditAction::Imported)
+    }
+
+    /// Add a key pair under a label, returning its key ID
+    ///
+    /// Fails if the label is already used by another key.
+    pub fn insert_with_label(&self, label: impl Into<String>, keypair: KeyPair) -> Result<String> {
+        self.insert_entry(keypair, Some(label.into()), AuditAction::Imported)
+    }
+
+    /// Generate a key pair in the store, returning its key ID
+    pub fn generate(&self, key_type: KeyType) -> Result<String> {
+        self.insert_entry(KeyPair::generate(key_type)?, None, AuditAction::Generated)
+    }
+
+    /// Generate a key pair in the store under a label, returning its key ID
+    pub fn generate_with_label(
+        &self,
+        label: impl Into<String>,
+        key_type: KeyType,
+    ) -> Result<String> {
+        self.insert_entry(
+            KeyPair::generate(key_type)?,
+            Some(label.into()),
+            AuditAction::Generated,
+        )
+    }
+
+    fn insert_entry(
+        &self,
+        keypair: KeyPair,
+        label: Option<String>,
+        action: AuditAction,
+    ) -> Result<String> {
+        let key_id = keypair.key_id().to_string();
+        let mut state = self.write()?;
+
+        if let Some(label) = &label {
+            if state.labels.get(label).is_some_and(|id| *id != key_id) {
+                return Err(Error::InvalidInput(format!(
+                    "Label {label} is already in use"
+                )));
+            }
+        }
+        let previous = state.keys.insert(
+            key_id.clone(),
+            StoredKey {
+                keypair,
+                label: label.clone(),
+                status: KeyStatus::Active,
+                not_before: None,
+                not_after: None,
+                policy: KeyPolicy::default(),
+            },
+        );
+        if let Some(old_label) = previous.and_then(|stored| stored.label) {
+            state.labels.remove(&old_label);
+        }
+        if let Some(label) = &label {
+            state.labels.insert(label.clone(), key_id.clone());
+        }
+        drop(state);
+
+        self.audit.emit(action, &key_id, label.as_deref());
+        Ok(key_id)
+    }
+
+    /// Get the key pair with the given key ID
+    ///
+    /// Fails if the key's policy forbids [`KeyOperation::Export`].
+    pub fn get(&self, key_id: &str) -> Result<Option<KeyPair>> {
+        let stored = self.read()?.keys.get(key_id).cloned();
+        stored.map(|stored| self.export(stored)).transpose()
+    }
+
+    /// Get the key pair with the given label
+    pub fn get_by_label(&self, label: &str) -> Result<Option<KeyPair>> {
+        let stored = {
+            let state = self.read()?;
+            state
+                .labels
+                .get(label)
+                .and_then(|key_id| state.keys.get(key_id))
+                .cloned()
+        };
+        stored.map(|stored| self.export(stored)).transpose()
+    }
+
+    /// Hand a stored key pair out of the store
+    fn export(&self, stored: StoredKey) -> Result<KeyPair> {
+        stored.policy.check(
+            stored.keypair.key_id(),
+            KeyOperation::Export,
+            chrono::Utc::now().timestamp(),
+        )?;
+        self.audit.emit(
+            AuditAction::Exported,
+            stored.keypair.key_id(),
+            stored.label.as_deref(),
+        );
+        Ok(stored.keypair)
+    }
+
+    /// Sign `message` with the key with the given key ID, if it may sign now
+    pub fn sign(&self, key_id: &str, message: &[u8]) -> Result<Signature> {
+        let stored = self
+            .read()?
+            .keys
+            .get(key_id)
+            .cloned()
+            .ok_or_else(|| Error::InvalidInput(format!("Unknown key ID: {key_id}")))?;
+        let now = chrono::Utc::now().timestamp();
+        stored.check_signing(now)?;
+        stored.policy.check(key_id, KeyOperation::Sign, now)?;
+        let signature = stored.keypair.sign(message)?;
+        self.audit
+            .emit(AuditAction::Signed, key_id, stored.label.as_deref());
+        Ok(signature)
+    }
+
+    /// Get the public key with the given key ID
+    pub fn public_key(&self, key_id: &str) -> Result<Option<PublicKey>> {
+        Ok(self
+            .read()?
+            .keys
+            .get(key_id)
+            .map(|stored| stored.keypair.public_key().clone()))
+    }
+
+    /// Remove a key pair, returning it if it was stored
+    pub fn remove(&self, key_id: &str) -> Result<Option<KeyPair>> {
+        let removed = self.take(key_id)?;
+        if let Some(stored) = &removed {
+            self.audit
+                .emit(AuditAction::Removed, key_id, stored.label.as_deref());
+        }
+        Ok(removed.map(|stored| stored.keypair))
+    }
+
+    /// Remove a key pair and wipe its private key, returning whether it was stored
+    pub fn destroy(&self, key_id: &str) -> Result<bool> {
+        let Some(mut stored) = self.take(key_id)? else {
+            return Ok(false);
+        };
+        stored.keypair.wipe();
+        self.audit
+            .emit(AuditAction::Destroyed, key_id, stored.label.as_deref());
+        Ok(true)
+    }
+
+    fn take(&self, key_id: &str) -> Result<Option<StoredKey>> {
+        let mut state = self.write()?;
+        let Some(stored) = state.keys.remove(key_id) else {
+            return Ok(None);
+        };
+        if let Some(label) = &stored.label {
+            state.labels.remove(label);
+        }
+        Ok(Some(stored))
+    }
+
+    /// Key IDs of all stored keys, sorted
+    pub fn key_ids(&self) -> Result<Vec<String>> {
+        let mut key_ids: Vec<String> = self.read()?.keys.keys().cloned().collect();
+        key_ids.sort();
+        Ok(key_ids)
+    }
+
+    /// Public keys of all stored keys, sorted by key ID
+    pub fn public_keys(&self) -> Result<Vec<(String, PublicKey)>> {
+        let mut public_keys: Vec<(String, PublicKey)> = self
+            .read()?
+            .keys
+            .iter()
+            .map(|(key_id, stored)| (key_id.clone(), stored.keypair.public_key().clone()))
+            .collect();
+        public_keys.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(public_keys)
+    }
+
+    /// Snapshot of the stored public keys as a [`KeySet`]
+    pub fn key_set(&self) -> Result<KeySet> {
+        let mut key_set = KeySet::new();
+        for (key_id, public_key) in self.public_keys()? {
+            key_set.insert_with_id(key_id, public_key);
+        }
+        Ok(key_set)
+    }
+
+    /// Number of stored keys
+    pub fn len(&self) -> usize {
+        self.read()
+            .map(|state| state.keys.len())
+            .unwrap_or_default()
+    }
+
+    /// Check whether the store is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Resolves keys that are valid for verification now
+///
+/// Revoked keys, keys outside their validity window and retiring keys past
+/// their grace period are rejected.
+impl KeyResolver for KeyStore {
+    fn resolve(&self, key_id: &str) -> Result<PublicKey> {
+        self.resolve_at(key_id, chrono::Utc::now().timestamp())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyType;
+
+    #[test]
+    fn test_insert_and_lookup() {
+        let store = KeyStore::new();
+        let first = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let second = KeyPair::generate(KeyType::Secp256k1).unwrap();
+
+        let first_id = store.insert(first.clone()).unwrap();
+        let second_id = store
+            .insert_with_label("http-signing", second.clone())
+            .unwrap();
+        assert_eq!(first_id, first.key_id());
+        assert_eq!(store.len(), 2);
+
+        let found = store.get_by_label("http-signing").unwrap().unwrap();
+        assert_eq!(found.private_key_bytes(), second.private_key_bytes());
+        assert!(store.get(&first_id).unwrap().is_some());
+        assert!(store.get_by_label("unknown").unwrap().is_none());
+
+        let mut expected = vec![first_id.clone(), second_id.clone()];
+        expected.sort();
+        assert_eq!(store.key_ids().unwrap(), expected);
+        assert_eq!(store.key_set().unwrap().len(), 2);
+
+        let other = KeyPair::generate(KeyType::Ed25519).unwrap();
+        assert!(store.insert_with_label("http-signing", other).is_err());
+
+        assert!(store.remove(&second_id).unwrap().is_some());
+        assert!(store.get_by_label("http-signing").unwrap().is_none());
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_key_store_resolver() {
+        use crate::rfc9421::{HttpSigner, HttpVerifier};
+        use std::sync::Arc;
+
+        let store = Arc::new(KeyStore::new());
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        store.insert(keypair.clone()).unwrap();
+
+        let request = http::Request::builder()
+            .method("GET")
+            .uri("https://agent.example/tasks")
+            .body(())
+            .unwrap();
+        let signed = HttpSigner::new(keypair).sign_request(request).unwrap();
+
+        let verifier = HttpVerifier::from_resolver(store.clone());
+        assert!(verifier.verify_request(&signed).is_ok());
+        assert!(store.resolve("unknown").is_err());
+    }
+}
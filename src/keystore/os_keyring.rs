@@ -0,0 +1,73 @@
+//! Key storage in the operating system's credential store
+//!
+//! Uses the macOS Keychain, Windows Credential Manager or the Secret Service
+//! on Linux. Each key pair is stored as a private JWK under the service name,
+//! with its key ID as the account.
+
+use super::KeyStorage;
+use crate::crypto::KeyPair;
+use crate::error::{Error, Result};
+use crate::formats::{keypair_from_jwk, KeyExporter};
+
+/// Default service name of stored credentials
+pub const DEFAULT_SERVICE: &str = "sage-crypto-core";
+
+/// [`KeyStorage`] backed by the OS credential store
+#[derive(Debug, Clone)]
+pub struct OsKeyring {
+    service: String,
+}
+
+impl OsKeyring {
+    /// Store keys under [`DEFAULT_SERVICE`]
+    pub fn new() -> Self {
+        Self::with_service(DEFAULT_SERVICE)
+    }
+
+    /// Store keys under a custom service name, e.g. one per application
+    pub fn with_service(service: impl Into<String>) -> Self {
+        Self {
+            service: service.into(),
+        }
+    }
+
+    fn entry(&self, key_id: &str) -> Result<keyring::Entry> {
+        keyring::Entry::new(&self.service, key_id)
+            .map_err(|e| Error::Other(format!("OS keyring error: {e}")))
+    }
+}
+
+impl Default for OsKeyring {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KeyStorage for OsKeyring {
+    fn store(&self, keypair: &KeyPair) -> Result<String> {
+        let jwk = keypair.to_jwk()?.to_string();
+        self.entry(keypair.key_id())?
+            .set_password(&jwk)
+            .map_err(|e| Error::Other(format!("OS keyring error: {e}")))?;
+        Ok(keypair.key_id().to_string())
+    }
+
+    fn load(&self, key_id: &str) -> Result<Option<KeyPair>> {
+        let jwk = match self.entry(key_id)?.get_password() {
+            Ok(jwk) => jwk,
+            Err(keyring::Error::NoEntry) => return Ok(None),
+            Err(e) => return Err(Error::Other(format!("OS keyring error: {e}"))),
+        };
+        let jwk: serde_json::Value =
+            serde_json::from_str(&jwk).map_err(|e| Error::InvalidKeyFormat(e.to_string()))?;
+        keypair_from_jwk(&jwk).map(Some)
+    }
+
+    fn delete(&self, key_id: &str) -> Result<bool> {
+        match self.entry(key_id)?.delete_password() {
+            Ok(()) => Ok(true),
+            Err(keyring::Error::NoEntry) => Ok(false),
+            Err(e) => Err(Error::Other(format!("OS keyring error: {e}"))),
+        }
+    }
+}
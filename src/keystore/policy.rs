@@ -0,0 +1,209 @@
+//! Usage policies for stored keys
+//!
+//! A [`KeyPolicy`] limits what a key may be used for, so that a key meant
+//! for RFC 9421 signatures cannot be used to sign arbitrary payloads such as
+//! blockchain transactions, even by a compromised code path.
+
+use crate::error::{Error, Result};
+use crate::rfc9421::{parse_signature_input, SignatureParams};
+
+/// Operations a stored key can be used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyOperation {
+    /// RFC 9421 HTTP message signatures through [`KeyStore::signer`](super::KeyStore::signer)
+    HttpSign,
+    /// Signatures over arbitrary messages through [`KeyStore::sign`](super::KeyStore::sign)
+    Sign,
+    /// Handing the private key out of the store
+    Export,
+}
+
+/// Restrictions on the use of a stored key
+///
+/// The default policy allows every operation. Changes through
+/// [`KeyStore::set_policy`](super::KeyStore::set_policy) also apply to
+/// signers created before them.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeyPolicy {
+    operations: Option<Vec<KeyOperation>>,
+    tags: Option<Vec<String>>,
+    not_after: Option<i64>,
+}
+
+impl KeyPolicy {
+    /// Create a policy that allows every operation
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allow only the given operations
+    pub fn with_operations(mut self, operations: impl IntoIterator<Item = KeyOperation>) -> Self {
+        self.operations = Some(operations.into_iter().collect());
+        self
+    }
+
+    /// Allow only HTTP signatures carrying one of the given `tag` parameters
+    pub fn with_tags<I, S>(mut self, tags: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.tags = Some(tags.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Forbid signing after the Unix timestamp `not_after`
+    ///
+    /// Unlike [`KeyStore::set_validity`](super::KeyStore::set_validity), this
+    /// does not stop the key from verifying signatures made before.
+    pub fn with_not_after(mut self, not_after: i64) -> Self {
+        self.not_after = Some(not_after);
+        self
+    }
+
+    /// Check whether the policy allows `operation`
+    pub fn allows(&self, operation: KeyOperation) -> bool {
+        match &self.operations {
+            Some(operations) => operations.contains(&operation),
+            None => true,
+        }
+    }
+
+    /// Check that the policy allows `operation` at `now`
+    pub(super) fn check(&self, key_id: &str, operation: KeyOperation, now: i64) -> Result<()> {
+        if !self.allows(operation) {
+            return Err(Error::InvalidInput(format!(
+                "Key {key_id} may not be used for {operation:?}"
+            )));
+        }
+        if operation != KeyOperation::Export && self.not_after.is_some_and(|end| now > end) {
+            return Err(Error::InvalidInput(format!(
+                "Key {key_id} may no longer sign"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that the policy allows an HTTP signature with `params`
+    pub(super) fn check_signature_params(
+        &self,
+        key_id: &str,
+        params: &SignatureParams,
+        now: i64,
+    ) -> Result<()> {
+        self.check(key_id, KeyOperation::HttpSign, now)?;
+        let Some(tags) = &self.tags else {
+            return Ok(());
+        };
+
+        let tag = params.tag.as_ref();
+        if !tag.is_some_and(|tag| tags.contains(tag)) {
+            return Err(Error::InvalidInput(format!(
+                "Key {key_id} may not sign with tag {}",
+                tag.map_or("(none)", String::as_str)
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Parameters of the `@signature-params` line ending a signature base
+pub(super) fn signature_base_params(signature_base: &[u8]) -> Result<SignatureParams> {
+    let base = std::str::from_utf8(signature_base)
+        .map_err(|_| Error::InvalidInput("Signature base is not UTF-8".to_string()))?;
+    let params_line = base.rsplit('\n').next().unwrap_or(base);
+    let input = params_line
+        .strip_prefix("\"@signature-params\": ")
+        .ok_or_else(|| {
+            Error::InvalidInput("Signature base has no @signature-params".to_string())
+        })?;
+    let (_, params) = parse_signature_input(input)?;
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType};
+    use crate::keystore::KeyStore;
+
+    fn request() -> http::Request<()> {
+        http::Request::builder()
+            .method("GET")
+            .uri("https://agent.example/tasks")
+            .body(())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_operation_policy() {
+        let store = KeyStore::new();
+        let key_id = store
+            .insert_with_label("http", KeyPair::generate(KeyType::Secp256k1).unwrap())
+            .unwrap();
+        store
+            .set_policy(
+                &key_id,
+                KeyPolicy::new().with_operations([KeyOperation::HttpSign]),
+            )
+            .unwrap();
+
+        assert!(store.sign(&key_id, b"transaction").is_err());
+        assert!(store.get(&key_id).is_err());
+        assert!(store
+            .signer("http")
+            .unwrap()
+            .sign_request(request())
+            .is_ok());
+    }
+
+    #[test]
+    fn test_tag_and_expiry_policy() {
+        let store = KeyStore::new();
+        let key_id = store
+            .insert_with_label("http", KeyPair::generate(KeyType::Ed25519).unwrap())
+            .unwrap();
+        store
+            .set_policy(&key_id, KeyPolicy::new().with_tags(["sage-agent"]))
+            .unwrap();
+
+        let signer = store.signer("http").unwrap();
+        assert!(signer.sign_request(request()).is_err());
+        let tagged = signer.with_tag("sage-agent");
+        assert!(tagged.sign_request(request()).is_ok());
+        assert!(tagged.with_tag("other").sign_request(request()).is_err());
+
+        store
+            .set_policy(&key_id, KeyPolicy::new().with_not_after(0))
+            .unwrap();
+        assert!(store.sign(&key_id, b"message").is_err());
+        assert!(store.get(&key_id).unwrap().is_some());
+        assert!(tagged.sign_request(request()).is_err());
+    }
+
+    #[test]
+    fn test_policy_change_after_signer() {
+        let store = KeyStore::new();
+        let key_id = store
+            .insert_with_label("http", KeyPair::generate(KeyType::Ed25519).unwrap())
+            .unwrap();
+        let signer = store.signer("http").unwrap().with_tag("sage-agent");
+        assert!(signer.sign_request(request()).is_ok());
+
+        store
+            .set_policy(&key_id, KeyPolicy::new().with_tags(["other"]))
+            .unwrap();
+        assert!(signer.sign_request(request()).is_err());
+
+        store
+            .set_policy(
+                &key_id,
+                KeyPolicy::new().with_operations([KeyOperation::Sign]),
+            )
+            .unwrap();
+        assert!(signer.sign_request(request()).is_err());
+
+        store.set_policy(&key_id, KeyPolicy::new()).unwrap();
+        assert!(signer.sign_request(request()).is_ok());
+    }
+}
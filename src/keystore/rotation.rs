@@ -0,0 +1,369 @@
+//! Key rotation with overlapping validity
+//!
+//! Rotating a label makes the new key the one used for signing, while the
+//! previous key keeps verifying for a grace period so in-flight messages
+//! signed with it are still accepted.
+
+use super::audit::StoreSigner;
+use super::{AuditAction, KeyOperation, KeyPolicy, KeyStore, KeyStoreState, StoredKey};
+use crate::crypto::{KeyPair, PublicKey};
+use crate::error::{Error, Result};
+use crate::rfc9421::HttpSigner;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Lifecycle state of a stored key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStatus {
+    /// Used for signing and verification
+    Active,
+    /// Replaced by a newer key; verifies until the given Unix timestamp
+    Retiring {
+        /// End of the grace period
+        until: i64,
+    },
+    /// Neither signs nor verifies
+    Revoked,
+}
+
+/// Status and validity of a stored key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyInfo {
+    /// Key ID
+    pub key_id: String,
+    /// Label, if the key is the current key for one
+    pub label: Option<String>,
+    /// Lifecycle state
+    pub status: KeyStatus,
+    /// Unix timestamp before which the key is not valid
+    pub not_before: Option<i64>,
+    /// Unix timestamp after which the key is not valid
+    pub not_after: Option<i64>,
+    /// Usage policy
+    pub policy: KeyPolicy,
+}
+
+impl StoredKey {
+    /// Whether `now` falls within the validity window
+    fn is_valid_at(&self, now: i64) -> bool {
+        !self.not_before.is_some_and(|not_before| now < not_before)
+            && !self.not_after.is_some_and(|not_after| now > not_after)
+    }
+
+    /// Check that the key may sign at `now`
+    pub(super) fn check_signing(&self, now: i64) -> Result<()> {
+        if self.status != KeyStatus::Active {
+            return Err(Error::InvalidInput(format!(
+                "Key {} is not active",
+                self.keypair.key_id()
+            )));
+        }
+        if !self.is_valid_at(now) {
+            return Err(Error::InvalidInput(format!(
+                "Key {} is outside its validity window",
+                self.keypair.key_id()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that the key may verify at `now`
+    fn check_verification(&self, now: i64) -> Result<()> {
+        let key_id = self.keypair.key_id();
+        match self.status {
+            KeyStatus::Revoked => {
+                return Err(Error::Verification(format!(
+                    "Key {key_id} has been revoked"
+                )))
+            }
+            KeyStatus::Retiring { until } if now > until => {
+                return Err(Error::Verification(format!(
+                    "Key {key_id} is past its grace period"
+                )))
+            }
+            _ => {}
+        }
+        if !self.is_valid_at(now) {
+            return Err(Error::Verification(format!(
+                "Key {key_id} is outside its validity window"
+            )));
+        }
+        Ok(())
+    }
+
+    fn info(&self) -> KeyInfo {
+        KeyInfo {
+            key_id: self.keypair.key_id().to_string(),
+            label: self.label.clone(),
+            status: self.status,
+            not_before: self.not_before,
+            not_after: self.not_after,
+            policy: self.policy.clone(),
+        }
+    }
+}
+
+impl KeyStoreState {
+    /// The current key for `label`, whether or not it may sign
+    pub(super) fn current_key(&self, label: &str) -> Result<&StoredKey> {
+        self.labels
+            .get(label)
+            .and_then(|key_id| self.keys.get(key_id))
+            .ok_or_else(|| Error::InvalidInput(format!("No key for label {label}")))
+    }
+}
+
+impl KeyStore {
+    /// Make `keypair` the current key for `label`, returning its key ID
+    ///
+    /// The key previously holding the label becomes [`KeyStatus::Retiring`]
+    /// and keeps verifying for `grace`.
+    pub fn rotate(&self, label: &str, keypair: KeyPair, grace: Duration) -> Result<String> {
+        self.rotate_at(label, keypair, grace, chrono::Utc::now().timestamp())
+    }
+
+    fn rotate_at(
+        &self,
+        label: &str,
+        keypair: KeyPair,
+        grace: Duration,
+        now: i64,
+    ) -> Result<String> {
+        let key_id = keypair.key_id().to_string();
+        let mut state = self.write()?;
+
+        if let Some(previous_id) = state.labels.remove(label) {
+            if previous_id != key_id {
+                if let Some(previous) = state.keys.get_mut(&previous_id) {
+                    previous.label = None;
+                    if previous.status == KeyStatus::Active {
+                        previous.status = KeyStatus::Retiring {
+                            until: now + grace.as_secs() as i64,
+                        };
+                    }
+                }
+            }
+        }
+        state.keys.insert(
+            key_id.clone(),
+            StoredKey {
+                keypair,
+                label: Some(label.to_string()),
+                status: KeyStatus::Active,
+                not_before: None,
+                not_after: None,
+                policy: KeyPolicy::default(),
+            },
+        );
+        state.labels.insert(label.to_string(), key_id.clone());
+        drop(state);
+
+        self.audit.emit(AuditAction::Rotated, &key_id, Some(label));
+        Ok(key_id)
+    }
+
+    /// Revoke a key, so it neither signs nor verifies
+    pub fn revoke(&self, key_id: &str) -> Result<()> {
+        let mut label = None;
+        self.update(key_id, |stored| {
+            stored.status = KeyStatus::Revoked;
+            label = stored.label.clone();
+        })?;
+        self.audit
+            .emit(AuditAction::Revoked, key_id, label.as_deref());
+        Ok(())
+    }
+
+    /// Limit a key to the Unix timestamps `not_before..=not_after`
+    pub fn set_validity(
+        &self,
+        key_id: &str,
+        not_before: Option<i64>,
+        not_after: Option<i64>,
+    ) -> Result<()> {
+        self.update(key_id, |stored| {
+            stored.not_before = not_before;
+            stored.not_after = not_after;
+        })
+    }
+
+    /// Restrict the use of a key to `policy`
+    pub fn set_policy(&self, key_id: &str, policy: KeyPolicy) -> Result<()> {
+        self.update(key_id, |stored| stored.policy = policy)
+    }
+
+    fn update(&self, key_id: &str, change: impl FnOnce(&mut StoredKey)) -> Result<()> {
+        let mut state = self.write()?;
+        let stored = state
+            .keys
+            .get_mut(key_id)
+            .ok_or_else(|| Error::InvalidInput(format!("Unknown key ID: {key_id}")))?;
+        change(stored);
+        Ok(())
+    }
+
+    /// Get the status and validity of a key
+    pub fn info(&self, key_id: &str) -> Result<Option<KeyInfo>> {
+        Ok(self.read()?.keys.get(key_id).map(StoredKey::info))
+    }
+
+    /// Get the current key for `label` if it may sign now
+    pub fn active_key(&self, label: &str) -> Result<KeyPair> {
+        self.active_key_at(label, chrono::Utc::now().timestamp())
+    }
+
+    fn active_key_at(&self, label: &str, now: i64) -> Result<KeyPair> {
+        self.export(self.signing_key_at(label, now)?)
+    }
+
+    fn signing_key_at(&self, label: &str, now: i64) -> Result<StoredKey> {
+        let state = self.read()?;
+        let stored = state.current_key(label)?;
+        stored.check_signing(now)?;
+        Ok(stored.clone())
+    }
+
+    /// Create an HTTP signer for the current key of `label`
+    ///
+    /// The private key stays in the store. At every signature the signer
+    /// uses the key then current for `label`, checks its status, validity and
+    /// policy, and records the signature with the audit hooks.
+    pub fn signer(&self, label: &str) -> Result<HttpSigner> {
+        let now = chrono::Utc::now().timestamp();
+        {
+            let state = self.read()?;
+            let stored = state.current_key(label)?;
+            stored.check_signing(now)?;
+            stored
+                .policy
+                .check(stored.keypair.key_id(), KeyOperation::HttpSign, now)?;
+        }
+        let signer = StoreSigner::new(self.state.clone(), label, self.audit.clone());
+        Ok(HttpSigner::from_custom_signer(Arc::new(signer)))
+    }
+
+    /// Resolve a public key that may verify at `now`
+    pub(super) fn resolve_at(&self, key_id: &str, now: i64) -> Result<PublicKey> {
+        let state = self.read()?;
+        let stored = state
+            .keys
+            .get(key_id)
+            .ok_or_else(|| Error::Verification(format!("Unknown key ID: {key_id}")))?;
+        stored.check_verification(now)?;
+        Ok(stored.keypair.public_key().clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyType;
+
+    #[test]
+    fn test_rotation_grace_period() {
+        let store = KeyStore::new();
+        let now = 1_700_000_000;
+        let old = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let new = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+        let old_id = store
+            .rotate_at("http", old, Duration::from_secs(60), now)
+            .unwrap();
+        let new_id = store
+            .rotate_at("http", new, Duration::from_secs(60), now)
+            .unwrap();
+
+        assert_eq!(store.active_key_at("http", now).unwrap().key_id(), new_id);
+        assert_eq!(
+            store.info(&old_id).unwrap().unwrap().status,
+            KeyStatus::Retiring { until: now + 60 }
+        );
+        assert!(store.resolve_at(&old_id, now + 60).is_ok());
+        assert!(store.resolve_at(&old_id, now + 61).is_err());
+        assert!(store.resolve_at(&new_id, now + 61).is_ok());
+    }
+
+    #[test]
+    fn test_revocation_and_validity() {
+        let store = KeyStore::new();
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let key_id = store
+            .rotate_at("http", keypair, Duration::from_secs(60), 100)
+            .unwrap();
+
+        store.set_validity(&key_id, Some(100), Some(200)).unwrap();
+        assert!(store.active_key_at("http", 99).is_err());
+        assert!(store.active_key_at("http", 150).is_ok());
+        assert!(store.resolve_at(&key_id, 201).is_err());
+
+        store.revoke(&key_id).unwrap();
+        assert!(store.active_key_at("http", 150).is_err());
+        assert!(matches!(
+            store.resolve_at(&key_id, 150),
+            Err(Error::Verification(_))
+        ));
+        assert!(store.revoke("unknown").is_err());
+    }
+
+    #[test]
+    fn test_signer_follows_store() {
+        use crate::rfc9421::HttpVerifier;
+
+        let request = || {
+            http::Request::builder()
+                .method("GET")
+                .uri("https://agent.example/tasks")
+                .body(())
+                .unwrap()
+        };
+        let store = Arc::new(KeyStore::new());
+        let old_id = store
+            .rotate(
+                "http",
+                KeyPair::generate(KeyType::Ed25519).unwrap(),
+                Duration::from_secs(60),
+            )
+            .unwrap();
+        let signer = store.signer("http").unwrap();
+        let verifier = HttpVerifier::from_resolver(store.clone());
+        assert!(verifier
+            .verify_request(&signer.sign_request(request()).unwrap())
+            .is_ok());
+
+        // An existing signer picks up the rotated key
+        let new_id = store
+            .rotate(
+                "http",
+                KeyPair::generate(KeyType::Secp256k1).unwrap(),
+                Duration::from_secs(60),
+            )
+            .unwrap();
+        let signed = signer.sign_request(request()).unwrap();
+        let report = verifier.verify_request_detailed(&signed).unwrap();
+        assert_eq!(report.key_id, new_id);
+        assert_ne!(report.key_id, old_id);
+
+        // Revoking or destroying the key stops the signer
+        store.revoke(&new_id).unwrap();
+        assert!(signer.sign_request(request()).is_err());
+        assert!(store.destroy(&new_id).unwrap());
+        assert!(signer.sign_request(request()).is_err());
+    }
+
+    #[test]
+    fn test_signer_validity_changes() {
+        let store = KeyStore::new();
+        let key_id = store
+            .insert_with_label("http", KeyPair::generate(KeyType::Ed25519).unwrap())
+            .unwrap();
+        let signer = store.signer("http").unwrap();
+        let request = http::Request::builder()
+            .method("GET")
+            .uri("https://agent.example/tasks")
+            .body(())
+            .unwrap();
+
+        store.set_validity(&key_id, None, Some(0)).unwrap();
+        assert!(signer.sign_request(request).is_err());
+    }
+}
@@ -0,0 +1,82 @@
+//! Persistent storage backends for key pairs
+//!
+//! [`KeyStorage`] abstracts where private keys live between runs. The
+//! in-memory [`KeyStore`] implements it, and the `keyring` feature adds
+//! `OsKeyring`, backed by the operating system's credential store.
+
+use super::KeyStore;
+use crate::crypto::KeyPair;
+use crate::error::{Error, Result};
+
+/// Stores and loads key pairs by key ID
+pub trait KeyStorage: Send + Sync {
+    /// Store a key pair under its key ID, replacing any stored one, and return the key ID
+    fn store(&self, keypair: &KeyPair) -> Result<String>;
+
+    /// Load the key pair with the given key ID
+    fn load(&self, key_id: &str) -> Result<Option<KeyPair>>;
+
+    /// Delete the key pair with the given key ID, returning whether it was stored
+    fn delete(&self, key_id: &str) -> Result<bool>;
+}
+
+impl KeyStorage for KeyStore {
+    fn store(&self, keypair: &KeyPair) -> Result<String> {
+        self.insert(keypair.clone())
+    }
+
+    fn load(&self, key_id: &str) -> Result<Option<KeyPair>> {
+        self.get(key_id)
+    }
+
+    fn delete(&self, key_id: &str) -> Result<bool> {
+        self.destroy(key_id)
+    }
+}
+
+impl KeyStore {
+    /// Load the key pair with the given key ID from `storage` into this store
+    ///
+    /// Returns whether the key was found.
+    pub fn load_from(&self, storage: &dyn KeyStorage, key_id: &str) -> Result<bool> {
+        let Some(keypair) = storage.load(key_id)? else {
+            return Ok(false);
+        };
+        self.insert(keypair)?;
+        Ok(true)
+    }
+
+    /// Save the key pair with the given key ID from this store to `storage`
+    pub fn save_to(&self, storage: &dyn KeyStorage, key_id: &str) -> Result<()> {
+        let keypair = self
+            .get(key_id)?
+            .ok_or_else(|| Error::InvalidInput(format!("Unknown key ID: {key_id}")))?;
+        storage.store(&keypair)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyType;
+
+    #[test]
+    fn test_key_store_as_storage() {
+        let backend = KeyStore::new();
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let key_id = backend.store(&keypair).unwrap();
+
+        let store = KeyStore::new();
+        assert!(store.load_from(&backend, &key_id).unwrap());
+        assert!(!store.load_from(&backend, "unknown").unwrap());
+        assert_eq!(
+            store.get(&key_id).unwrap().unwrap().private_key_bytes(),
+            keypair.private_key_bytes()
+        );
+
+        assert!(backend.delete(&key_id).unwrap());
+        store.save_to(&backend, &key_id).unwrap();
+        assert!(backend.load(&key_id).unwrap().is_some());
+    }
+}
@@ -0,0 +1,103 @@
+//! AWS KMS signer for secp256k1 keys
+//!
+//! Uses asymmetric KMS keys with the `ECC_SECG_P256K1` key spec and the
+//! `ECDSA_SHA_256` signing algorithm.
+
+use super::{RemoteSigner, SignFuture};
+use crate::crypto::{KeyPair, PublicKey};
+use crate::error::{Error, Result};
+use crate::formats::{KeyFormat, KeyImporter};
+use crate::rfc9421::SignatureAlgorithm;
+use aws_sdk_kms::primitives::Blob;
+use aws_sdk_kms::types::{KeySpec, MessageType, SigningAlgorithmSpec};
+use aws_sdk_kms::Client;
+use sha2::{Digest, Sha256};
+
+/// Signer for a secp256k1 key held in AWS KMS
+#[derive(Debug, Clone)]
+pub struct AwsKmsSigner {
+    client: Client,
+    kms_key_id: String,
+    public_key: PublicKey,
+    key_id: String,
+}
+
+impl AwsKmsSigner {
+    /// Create a signer for the KMS key `kms_key_id` (key ID, ARN or alias)
+    ///
+    /// Fetches the public key, whose SAGE key ID is used as the `keyid`.
+    pub async fn new(client: Client, kms_key_id: impl Into<String>) -> Result<Self> {
+        let kms_key_id = kms_key_id.into();
+        let output = client
+            .get_public_key()
+            .key_id(&kms_key_id)
+            .send()
+            .await
+            .map_err(|e| Error::Other(format!("KMS GetPublicKey failed: {e}")))?;
+
+        if output.key_spec() != Some(&KeySpec::EccSecgP256K1) {
+            return Err(Error::InvalidKeyType(format!(
+                "KMS key {kms_key_id} is not an ECC_SECG_P256K1 key"
+            )));
+        }
+        let spki = output
+            .public_key()
+            .ok_or_else(|| Error::InvalidKeyFormat("KMS returned no public key".to_string()))?;
+        let public_key = KeyPair::import_public_key(spki.as_ref(), KeyFormat::Der)?;
+
+        Ok(Self {
+            client,
+            kms_key_id,
+            key_id: public_key.key_id(),
+            public_key,
+        })
+    }
+
+    /// Use `key_id` as the `keyid` of signatures instead of the derived key ID
+    pub fn with_key_id(mut self, key_id: impl Into<String>) -> Self {
+        self.key_id = key_id.into();
+        self
+    }
+
+    /// The public key of the KMS key
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    async fn sign_digest(&self, message: &[u8]) -> Result<Vec<u8>> {
+        let digest = Sha256::digest(message);
+        let output = self
+            .client
+            .sign()
+            .key_id(&self.kms_key_id)
+            .message(Blob::new(digest.to_vec()))
+            .message_type(MessageType::Digest)
+            .signing_algorithm(SigningAlgorithmSpec::EcdsaSha256)
+            .send()
+            .await
+            .map_err(|e| Error::Signature(format!("KMS Sign failed: {e}")))?;
+
+        let der = output
+            .signature()
+            .ok_or_else(|| Error::Signature("KMS returned no signature".to_string()))?;
+        let signature = k256::ecdsa::Signature::from_der(der.as_ref())
+            .map_err(|e| Error::Signature(format!("Invalid KMS signature: {e}")))?;
+        // KMS may return high-S signatures, which secp256k1 verifiers reject
+        let signature = signature.normalize_s().unwrap_or(signature);
+        Ok(signature.to_der().as_bytes().to_vec())
+    }
+}
+
+impl RemoteSigner for AwsKmsSigner {
+    fn key_id(&self) -> String {
+        self.key_id.clone()
+    }
+
+    fn algorithm(&self) -> SignatureAlgorithm {
+        SignatureAlgorithm::EcdsaSecp256k1Sha256
+    }
+
+    fn sign<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a> {
+        Box::pin(self.sign_digest(message))
+    }
+}
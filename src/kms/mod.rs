@@ -0,0 +1,79 @@
+//! Signing with keys held by remote services
+//!
+//! A [`RemoteSigner`] keeps the private key in a cloud KMS or HSM and signs
+//! asynchronously. Use it for RFC 9421 signatures with
+//! [`HttpSigner::from_remote_signer`](crate::rfc9421::HttpSigner::from_remote_signer).
+
+#[cfg(feature = "aws-kms")]
+pub mod aws;
+
+#[cfg(feature = "aws-kms")]
+pub use aws::AwsKmsSigner;
+
+use crate::error::Result;
+use crate::rfc9421::SignatureAlgorithm;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Future returned by [`RemoteSigner::sign`]
+pub type SignFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<u8>>> + Send + 'a>>;
+
+/// Signs with a key held by a remote service
+pub trait RemoteSigner: Send + Sync {
+    /// The `keyid` of the signing key
+    fn key_id(&self) -> String;
+
+    /// The algorithm of produced signatures
+    fn algorithm(&self) -> SignatureAlgorithm;
+
+    /// Sign `message`, returning the signature in the encoding of
+    /// [`Signature::to_bytes`](crate::crypto::Signature::to_bytes)
+    fn sign<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType, Signer};
+    use crate::rfc9421::{HttpSigner, HttpVerifier};
+    use std::sync::Arc;
+
+    /// Remote signer backed by a local key pair
+    struct LocalRemote(KeyPair);
+
+    impl RemoteSigner for LocalRemote {
+        fn key_id(&self) -> String {
+            self.0.key_id().to_string()
+        }
+
+        fn algorithm(&self) -> SignatureAlgorithm {
+            SignatureAlgorithm::for_key_type(self.0.key_type())
+        }
+
+        fn sign<'a>(&'a self, message: &'a [u8]) -> SignFuture<'a> {
+            Box::pin(async move { Ok(self.0.sign(message)?.to_bytes()) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_remote_signer_http_signature() {
+        let keypair = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        let public_key = keypair.public_key().clone();
+        let signer = HttpSigner::from_remote_signer(Arc::new(LocalRemote(keypair)));
+
+        let request = || {
+            http::Request::builder()
+                .method("POST")
+                .uri("https://agent.example/tasks")
+                .body(())
+                .unwrap()
+        };
+        assert!(signer.sign_request(request()).is_err());
+
+        let signed = signer.sign_request_async(request()).await.unwrap();
+        let report = HttpVerifier::new(public_key)
+            .verify_request_detailed(&signed)
+            .unwrap();
+        assert_eq!(report.algorithm, SignatureAlgorithm::EcdsaSecp256k1Sha256);
+    }
+}
@@ -5,19 +5,54 @@
 //! and RFC 9421 HTTP Message Signatures support.
 
 #![warn(missing_docs)]
-#![cfg_attr(not(feature = "ffi"), deny(unsafe_code))]
+#![cfg_attr(
+    not(any(
+        feature = "ffi-core",
+        feature = "cng",
+        feature = "jni",
+        feature = "napi",
+        feature = "uniffi"
+    )),
+    deny(unsafe_code)
+)]
 
+pub mod cbor;
+pub mod chain;
 pub mod crypto;
+pub mod detached;
+pub mod envelope;
 pub mod error;
 pub mod formats;
+pub mod handshake;
+pub mod hardware;
+pub mod identity;
+pub mod integrations;
+pub mod jcs;
+pub mod jose;
+pub mod keystore;
+pub mod kms;
+pub mod minisign;
 pub mod rfc9421;
+pub mod vc;
 
-#[cfg(feature = "ffi")]
+#[cfg(feature = "ffi-core")]
 pub mod ffi;
 
 #[cfg(feature = "wasm")]
 pub mod wasm;
 
+#[cfg(feature = "jni")]
+pub mod java;
+
+#[cfg(feature = "napi")]
+pub mod node;
+
+#[cfg(feature = "uniffi")]
+pub mod mobile;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
 // Re-export main types
 pub use crypto::{KeyPair, KeyType, PrivateKey, PublicKey, Signature};
 pub use error::{Error, Result};
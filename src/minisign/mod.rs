@@ -0,0 +1,411 @@
+//! minisign-compatible signatures
+//!
+//! Ed25519 SAGE keys can sign artifacts in the format of the
+//! [minisign](https://jedisct1.github.io/minisign/) tool, so they verify with
+//! `minisign -V -p agent.pub -m artifact`:
+//!
+//! ```text
+//! untrusted comment: signature from SAGE secret key
+//! RUQ...
+//! trusted comment: timestamp:1700000000	file:artifact
+//! ...
+//! ```
+//!
+//! Signatures are created over the BLAKE2b-512 hash of the data (`ED`);
+//! verification also accepts legacy signatures over the data itself (`Ed`).
+
+use crate::crypto::{KeyPair, KeyType, PublicKey, Signature, Signer, Verifier};
+use crate::error::{Error, Result};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use blake2::{Blake2b512, Digest};
+use sha2::Sha256;
+use std::io::Read;
+use std::path::Path;
+
+/// Signature algorithm over the data itself
+const LEGACY_ALGORITHM: [u8; 2] = *b"Ed";
+
+/// Signature algorithm over the BLAKE2b-512 hash of the data
+const PREHASHED_ALGORITHM: [u8; 2] = *b"ED";
+
+const UNTRUSTED_PREFIX: &str = "untrusted comment: ";
+const TRUSTED_PREFIX: &str = "trusted comment: ";
+
+/// Length of a minisign key ID
+const KEY_ID_LENGTH: usize = 8;
+
+/// Length of an Ed25519 signature
+const SIGNATURE_LENGTH: usize = 64;
+
+/// A minisign public key: an Ed25519 key and its 8-byte key ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinisignPublicKey {
+    /// Key ID, stored in the little-endian byte order of minisign files
+    pub key_id: [u8; 8],
+    /// Ed25519 public key
+    pub public_key: PublicKey,
+}
+
+impl MinisignPublicKey {
+    /// Use `public_key` with a key ID derived from its SHA-256 hash
+    pub fn new(public_key: &PublicKey) -> Result<Self> {
+        if public_key.key_type() != KeyType::Ed25519 {
+            return Err(Error::InvalidKeyType(
+                "minisign only supports Ed25519 keys".to_string(),
+            ));
+        }
+        let digest = Sha256::digest(public_key.to_bytes());
+        let mut key_id = [0u8; KEY_ID_LENGTH];
+        key_id.copy_from_slice(&digest[..KEY_ID_LENGTH]);
+        Ok(Self {
+            key_id,
+            public_key: public_key.clone(),
+        })
+    }
+
+    /// The key ID as minisign displays it
+    pub fn key_id_hex(&self) -> String {
+        format!("{:016X}", u64::from_le_bytes(self.key_id))
+    }
+
+    /// Encode as the contents of a minisign `.pub` file
+    pub fn encode(&self) -> String {
+        // Public keys always use the legacy algorithm identifier
+        let mut bytes = LEGACY_ALGORITHM.to_vec();
+        bytes.extend_from_slice(&self.key_id);
+        bytes.extend_from_slice(&self.public_key.to_bytes());
+        format!(
+            "{UNTRUSTED_PREFIX}minisign public key {}\n{}\n",
+            self.key_id_hex(),
+            STANDARD.encode(bytes)
+        )
+    }
+
+    /// Parse a minisign `.pub` file, or just its base64 line
+    pub fn decode(text: &str) -> Result<Self> {
+        let line = text
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with(UNTRUSTED_PREFIX))
+            .ok_or_else(|| invalid("missing public key"))?;
+        let bytes = STANDARD.decode(line)?;
+        if bytes.len() != 2 + KEY_ID_LENGTH + 32 || bytes[..2] != LEGACY_ALGORITHM {
+            return Err(invalid("not an Ed25519 public key"));
+        }
+
+        let mut key_id = [0u8; KEY_ID_LENGTH];
+        key_id.copy_from_slice(&bytes[2..2 + KEY_ID_LENGTH]);
+        Ok(Self {
+            key_id,
+            public_key: PublicKey::from_bytes(KeyType::Ed25519, &bytes[2 + KEY_ID_LENGTH..])?,
+        })
+    }
+}
+
+/// A minisign signature with its comments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinisignSignature {
+    /// Comment not covered by any signature
+    pub untrusted_comment: String,
+    /// Comment covered by the global signature
+    pub trusted_comment: String,
+    /// Whether the signature is over the BLAKE2b-512 hash of the data
+    pub prehashed: bool,
+    /// Key ID of the signing key
+    pub key_id: [u8; 8],
+    /// Signature over the data or its hash
+    pub signature: [u8; 64],
+    /// Signature over `signature` and the trusted comment
+    pub global_signature: [u8; 64],
+}
+
+impl MinisignSignature {
+    /// Sign `data` with the Ed25519 `keypair`, attaching `trusted_comment`
+    pub fn sign(keypair: &KeyPair, data: &[u8], trusted_comment: &str) -> Result<Self> {
+        Self::sign_hash(keypair, &Blake2b512::digest(data), trusted_comment)
+    }
+
+    /// Sign everything read from `reader`
+    pub fn sign_reader<R: Read>(
+        keypair: &KeyPair,
+        reader: R,
+        trusted_comment: &str,
+    ) -> Result<Self> {
+        Self::sign_hash(keypair, &hash_reader(reader)?, trusted_comment)
+    }
+
+    /// Sign the file at `path` with minisign's default trusted comment
+    pub fn sign_file(keypair: &KeyPair, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let trusted_comment = format!(
+            "timestamp:{}\tfile:{file_name}\thashed",
+            chrono::Utc::now().timestamp()
+        );
+        Self::sign_reader(keypair, std::fs::File::open(path)?, &trusted_comment)
+    }
+
+    fn sign_hash(keypair: &KeyPair, hash: &[u8], trusted_comment: &str) -> Result<Self> {
+        check_comment(trusted_comment)?;
+        let public_key = MinisignPublicKey::new(keypair.public_key())?;
+
+        let signature = signature_bytes(&keypair.sign(hash)?)?;
+        let global_signature =
+            signature_bytes(&keypair.sign(&global_message(&signature, trusted_comment))?)?;
+        Ok(Self {
+            untrusted_comment: "signature from SAGE secret key".to_string(),
+            trusted_comment: trusted_comment.to_string(),
+            prehashed: true,
+            key_id: public_key.key_id,
+            signature,
+            global_signature,
+        })
+    }
+
+    /// Replace the untrusted comment
+    pub fn with_untrusted_comment(mut self, comment: impl Into<String>) -> Result<Self> {
+        let comment = comment.into();
+        check_comment(&comment)?;
+        self.untrusted_comment = comment;
+        Ok(self)
+    }
+
+    /// Verify the signature over `data` and the trusted comment
+    pub fn verify(&self, public_key: &MinisignPublicKey, data: &[u8]) -> Result<()> {
+        if self.prehashed {
+            self.verify_message(public_key, &Blake2b512::digest(data))
+        } else {
+            self.verify_message(public_key, data)
+        }
+    }
+
+    /// Verify the signature over everything read from `reader`
+    pub fn verify_reader<R: Read>(
+        &self,
+        public_key: &MinisignPublicKey,
+        mut reader: R,
+    ) -> Result<()> {
+        if self.prehashed {
+            return self.verify_message(public_key, &hash_reader(reader)?);
+        }
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+        self.verify_message(public_key, &data)
+    }
+
+    /// Verify the signature over the file at `path`
+    pub fn verify_file(
+        &self,
+        public_key: &MinisignPublicKey,
+        path: impl AsRef<Path>,
+    ) -> Result<()> {
+        self.verify_reader(public_key, std::fs::File::open(path)?)
+    }
+
+    fn verify_message(&self, public_key: &MinisignPublicKey, message: &[u8]) -> Result<()> {
+        if self.key_id != public_key.key_id {
+            return Err(Error::Verification(format!(
+                "Signature key ID does not match public key {}",
+                public_key.key_id_hex()
+            )));
+        }
+
+        let key = &public_key.public_key;
+        key.verify(
+            message,
+            &Signature::from_bytes(KeyType::Ed25519, &self.signature)?,
+        )?;
+        key.verify(
+            &global_message(&self.signature, &self.trusted_comment),
+            &Signature::from_bytes(KeyType::Ed25519, &self.global_signature)?,
+        )
+        .map_err(|_| Error::Verification("Invalid trusted comment signature".to_string()))
+    }
+
+    /// Encode as the contents of a minisign `.minisig` file
+    pub fn encode(&self) -> String {
+        let algorithm = if self.prehashed {
+            PREHASHED_ALGORITHM
+        } else {
+            LEGACY_ALGORITHM
+        };
+        let mut bytes = algorithm.to_vec();
+        bytes.extend_from_slice(&self.key_id);
+        bytes.extend_from_slice(&self.signature);
+        format!(
+            "{UNTRUSTED_PREFIX}{}\n{}\n{TRUSTED_PREFIX}{}\n{}\n",
+            self.untrusted_comment,
+            STANDARD.encode(bytes),
+            self.trusted_comment,
+            STANDARD.encode(self.global_signature)
+        )
+    }
+
+    /// Parse a minisign `.minisig` file
+    pub fn decode(text: &str) -> Result<Self> {
+        let mut lines = text.lines().map(|line| line.trim_end_matches('\r'));
+        let mut next = |what: &str| {
+            lines
+                .next()
+                .ok_or_else(|| invalid(&format!("missing {what}")))
+        };
+
+        let untrusted_comment = next("untrusted comment")?
+            .strip_prefix(UNTRUSTED_PREFIX)
+            .ok_or_else(|| invalid("malformed untrusted comment"))?
+            .to_string();
+        let bytes = STANDARD.decode(next("signature")?.trim())?;
+        let trusted_comment = next("trusted comment")?
+            .strip_prefix(TRUSTED_PREFIX)
+            .ok_or_else(|| invalid("malformed trusted comment"))?
+            .to_string();
+        let global_signature = STANDARD.decode(next("global signature")?.trim())?;
+
+        if bytes.len() != 2 + KEY_ID_LENGTH + SIGNATURE_LENGTH {
+            return Err(invalid("signature has an invalid length"));
+        }
+        let prehashed = match [bytes[0], bytes[1]] {
+            PREHASHED_ALGORITHM => true,
+            LEGACY_ALGORITHM => false,
+            _ => return Err(invalid("unknown signature algorithm")),
+        };
+
+        let mut key_id = [0u8; KEY_ID_LENGTH];
+        key_id.copy_from_slice(&bytes[2..2 + KEY_ID_LENGTH]);
+        let mut signature = [0u8; SIGNATURE_LENGTH];
+        signature.copy_from_slice(&bytes[2 + KEY_ID_LENGTH..]);
+        Ok(Self {
+            untrusted_comment,
+            trusted_comment,
+            prehashed,
+            key_id,
+            signature,
+            global_signature: global_signature
+                .try_into()
+                .map_err(|_| invalid("global signature has an invalid length"))?,
+        })
+    }
+}
+
+/// The message covered by the global signature
+fn global_message(signature: &[u8; 64], trusted_comment: &str) -> Vec<u8> {
+    let mut message = signature.to_vec();
+    message.extend_from_slice(trusted_comment.as_bytes());
+    message
+}
+
+fn signature_bytes(signature: &Signature) -> Result<[u8; 64]> {
+    signature
+        .to_bytes()
+        .try_into()
+        .map_err(|_| Error::Signature("Expected a 64-byte Ed25519 signature".to_string()))
+}
+
+/// BLAKE2b-512 hash of everything read from `reader`
+fn hash_reader<R: Read>(mut reader: R) -> Result<Vec<u8>> {
+    let mut hasher = Blake2b512::new();
+    let mut buffer = [0u8; 8192];
+    loop {
+        match reader.read(&mut buffer)? {
+            0 => break,
+            n => hasher.update(&buffer[..n]),
+        }
+    }
+    Ok(hasher.finalize().to_vec())
+}
+
+fn check_comment(comment: &str) -> Result<()> {
+    if comment.contains(['\r', '\n']) {
+        return Err(Error::InvalidInput(
+            "minisign comments must be a single line".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidInput(format!("Invalid minisign data: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Example public key from the minisign documentation
+    const PUBLIC_KEY: &str = "untrusted comment: minisign public key E7620F1842B4E81F\nRWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3\n";
+
+    #[test]
+    fn test_sign_and_verify() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let public_key = MinisignPublicKey::new(keypair.public_key()).unwrap();
+        let signature =
+            MinisignSignature::sign(&keypair, b"artifact", "timestamp:1700000000\tfile:a").unwrap();
+
+        let parsed = MinisignSignature::decode(&signature.encode()).unwrap();
+        assert_eq!(parsed, signature);
+        let parsed_key = MinisignPublicKey::decode(&public_key.encode()).unwrap();
+        assert_eq!(parsed_key, public_key);
+
+        assert!(parsed.verify(&parsed_key, b"artifact").is_ok());
+        assert!(parsed
+            .verify_reader(&parsed_key, b"artifact".as_slice())
+            .is_ok());
+        assert!(parsed.verify(&parsed_key, b"other").is_err());
+    }
+
+    #[test]
+    fn test_tampered_trusted_comment() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let public_key = MinisignPublicKey::new(keypair.public_key()).unwrap();
+        let signature = MinisignSignature::sign(&keypair, b"data", "trusted").unwrap();
+
+        let mut tampered = signature.clone();
+        tampered.trusted_comment = "forged".to_string();
+        assert!(tampered.verify(&public_key, b"data").is_err());
+
+        // The untrusted comment is not covered
+        let relabeled = signature.with_untrusted_comment("anything").unwrap();
+        assert!(relabeled.verify(&public_key, b"data").is_ok());
+        assert!(relabeled.with_untrusted_comment("two\nlines").is_err());
+    }
+
+    #[test]
+    fn test_legacy_signature() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let public_key = MinisignPublicKey::new(keypair.public_key()).unwrap();
+        let legacy_signature = signature_bytes(&keypair.sign(b"data").unwrap()).unwrap();
+        let signature = MinisignSignature {
+            untrusted_comment: "legacy".to_string(),
+            trusted_comment: "trusted".to_string(),
+            prehashed: false,
+            key_id: public_key.key_id,
+            signature: legacy_signature,
+            global_signature: signature_bytes(
+                &keypair
+                    .sign(&global_message(&legacy_signature, "trusted"))
+                    .unwrap(),
+            )
+            .unwrap(),
+        };
+
+        let parsed = MinisignSignature::decode(&signature.encode()).unwrap();
+        assert!(!parsed.prehashed);
+        assert!(parsed.verify(&public_key, b"data").is_ok());
+    }
+
+    #[test]
+    fn test_key_checks() {
+        let key = MinisignPublicKey::decode(PUBLIC_KEY).unwrap();
+        assert_eq!(key.key_id_hex(), "E7620F1842B4E81F");
+
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let signature = MinisignSignature::sign(&keypair, b"data", "").unwrap();
+        assert!(signature.verify(&key, b"data").is_err());
+
+        let secp256k1 = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        assert!(MinisignSignature::sign(&secp256k1, b"data", "").is_err());
+    }
+}
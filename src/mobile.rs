@@ -0,0 +1,412 @@
+//! UniFFI bindings for Swift and Kotlin
+//!
+//! Generate the bindings from the built library with
+//! `cargo run --features uniffi --bin uniffi-bindgen -- generate --library <lib> --language swift`
+//! (or `kotlin`).
+
+use crate::crypto::{KeyPair, KeyType, PublicKey, Signature, Signer, Verifier};
+use crate::error::Error;
+use crate::formats::{public_key_from_jwk, KeyExporter, KeyFormat};
+use crate::rfc9421::{HttpSigner, HttpVerifier, VerificationReport};
+use std::sync::Arc;
+
+/// Error returned to Swift and Kotlin
+#[derive(Debug, thiserror::Error, uniffi::Error)]
+#[uniffi(flat_error)]
+pub enum SageError {
+    /// Malformed input, keys or encodings
+    #[error("{0}")]
+    InvalidInput(String),
+    /// Signing or key generation failed
+    #[error("{0}")]
+    Crypto(String),
+    /// Signature verification failed
+    #[error("{0}")]
+    Verification(String),
+    /// Unsupported operation
+    #[error("{0}")]
+    Unsupported(String),
+    /// Any other failure
+    #[error("{0}")]
+    Other(String),
+}
+
+impl From<Error> for SageError {
+    fn from(err: Error) -> Self {
+        let message = err.to_string();
+        match err {
+            Error::InvalidInput(_)
+            | Error::InvalidKeyFormat(_)
+            | Error::InvalidKeyType(_)
+            | Error::Serialization(_)
+            | Error::Base64(_)
+            | Error::Pem(_)
+            | Error::HttpSignature(_) => SageError::InvalidInput(message),
+            Error::KeyGeneration(_) | Error::Signature(_) | Error::CryptoError(_) => {
+                SageError::Crypto(message)
+            }
+            Error::Verification(_) => SageError::Verification(message),
+            Error::Unsupported(_) => SageError::Unsupported(message),
+            Error::Io(_) | Error::Other(_) => SageError::Other(message),
+        }
+    }
+}
+
+/// Result type for the UniFFI bindings
+pub type SageResult<T> = std::result::Result<T, SageError>;
+
+/// Key type
+#[derive(Debug, Clone, Copy, uniffi::Enum)]
+pub enum SageKeyType {
+    /// Ed25519 key type
+    Ed25519,
+    /// Secp256k1 key type
+    Secp256k1,
+}
+
+impl From<SageKeyType> for KeyType {
+    fn from(key_type: SageKeyType) -> Self {
+        match key_type {
+            SageKeyType::Ed25519 => KeyType::Ed25519,
+            SageKeyType::Secp256k1 => KeyType::Secp256k1,
+        }
+    }
+}
+
+impl From<KeyType> for SageKeyType {
+    fn from(key_type: KeyType) -> Self {
+        match key_type {
+            KeyType::Ed25519 => SageKeyType::Ed25519,
+            KeyType::Secp256k1 => SageKeyType::Secp256k1,
+        }
+    }
+}
+
+/// Key encoding
+#[derive(Debug, Clone, Copy, uniffi::Enum)]
+pub enum SageKeyFormat {
+    /// JSON Web Key
+    Jwk,
+    /// PEM
+    Pem,
+    /// DER
+    Der,
+    /// Raw key bytes
+    Raw,
+}
+
+impl From<SageKeyFormat> for KeyFormat {
+    fn from(format: SageKeyFormat) -> Self {
+        match format {
+            SageKeyFormat::Jwk => KeyFormat::Jwk,
+            SageKeyFormat::Pem => KeyFormat::Pem,
+            SageKeyFormat::Der => KeyFormat::Der,
+            SageKeyFormat::Raw => KeyFormat::Raw,
+        }
+    }
+}
+
+/// Key pair
+#[derive(uniffi::Object)]
+pub struct SageKeyPair {
+    inner: KeyPair,
+}
+
+#[uniffi::export]
+impl SageKeyPair {
+    /// Generate a new key pair
+    #[uniffi::constructor]
+    pub fn generate(key_type: SageKeyType) -> SageResult<Arc<Self>> {
+        Ok(Arc::new(Self {
+            inner: KeyPair::generate(key_type.into())?,
+        }))
+    }
+
+    /// Import a key pair from raw private key bytes
+    #[uniffi::constructor]
+    pub fn from_private_key(key_type: SageKeyType, bytes: Vec<u8>) -> SageResult<Arc<Self>> {
+        Ok(Arc::new(Self {
+            inner: KeyPair::from_private_key_bytes(key_type.into(), &bytes)?,
+        }))
+    }
+
+    /// Derive a key pair deterministically from a seed of at least 32 bytes
+    #[uniffi::constructor]
+    pub fn from_seed(key_type: SageKeyType, seed: Vec<u8>) -> SageResult<Arc<Self>> {
+        Ok(Arc::new(Self {
+            inner: KeyPair::from_seed(key_type.into(), &seed)?,
+        }))
+    }
+
+    /// Get the key type
+    pub fn key_type(&self) -> SageKeyType {
+        self.inner.key_type().into()
+    }
+
+    /// Get the key ID
+    pub fn key_id(&self) -> String {
+        self.inner.key_id().to_string()
+    }
+
+    /// Get the public key
+    pub fn public_key(&self) -> Arc<SagePublicKey> {
+        Arc::new(SagePublicKey {
+            inner: self.inner.public_key().clone(),
+        })
+    }
+
+    /// Sign a message
+    pub fn sign(&self, message: Vec<u8>) -> SageResult<Vec<u8>> {
+        Ok(self.inner.sign(&message)?.to_bytes())
+    }
+
+    /// Verify a signature made with this key pair
+    pub fn verify(&self, message: Vec<u8>, signature: Vec<u8>) -> SageResult<()> {
+        self.public_key().verify(message, signature)
+    }
+
+    /// Export the private key (CAUTION: contains secret material)
+    pub fn export(&self, format: SageKeyFormat) -> SageResult<Vec<u8>> {
+        Ok(self.inner.export(format.into())?)
+    }
+}
+
+/// Public key
+#[derive(uniffi::Object)]
+pub struct SagePublicKey {
+    inner: PublicKey,
+}
+
+#[uniffi::export]
+impl SagePublicKey {
+    /// Import a public key from raw bytes
+    #[uniffi::constructor]
+    pub fn from_bytes(key_type: SageKeyType, bytes: Vec<u8>) -> SageResult<Arc<Self>> {
+        Ok(Arc::new(Self {
+            inner: PublicKey::from_bytes(key_type.into(), &bytes)?,
+        }))
+    }
+
+    /// Import a public key from a JWK
+    #[uniffi::constructor]
+    pub fn from_jwk(jwk: String) -> SageResult<Arc<Self>> {
+        let jwk: serde_json::Value = serde_json::from_str(&jwk)
+            .map_err(|e| SageError::InvalidInput(format!("Invalid JWK: {e}")))?;
+        Ok(Arc::new(Self {
+            inner: public_key_from_jwk(&jwk)?,
+        }))
+    }
+
+    /// Get the key type
+    pub fn key_type(&self) -> SageKeyType {
+        self.inner.key_type().into()
+    }
+
+    /// Get the key ID
+    pub fn key_id(&self) -> String {
+        self.inner.key_id()
+    }
+
+    /// Get the raw public key bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.inner.to_bytes()
+    }
+
+    /// Export the public key
+    pub fn export(&self, format: SageKeyFormat) -> SageResult<Vec<u8>> {
+        Ok(self.inner.export(format.into())?)
+    }
+
+    /// Verify a signature
+    pub fn verify(&self, message: Vec<u8>, signature: Vec<u8>) -> SageResult<()> {
+        let signature = Signature::from_bytes(self.inner.key_type(), &signature)?;
+        Ok(self.inner.verify(&message, &signature)?)
+    }
+}
+
+/// HTTP header
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct SageHttpHeader {
+    /// Header name
+    pub name: String,
+    /// Header value
+    pub value: String,
+}
+
+/// HTTP request to sign or verify
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct SageHttpRequest {
+    /// Request method
+    pub method: String,
+    /// Absolute request URI
+    pub uri: String,
+    /// Request headers
+    pub headers: Vec<SageHttpHeader>,
+    /// Request body, if any
+    pub body: Option<Vec<u8>>,
+}
+
+impl SageHttpRequest {
+    /// Convert to an `http::Request`
+    fn to_request(&self) -> SageResult<http::Request<Vec<u8>>> {
+        let mut builder = http::Request::builder()
+            .method(self.method.as_str())
+            .uri(self.uri.as_str());
+        for header in &self.headers {
+            builder = builder.header(header.name.as_str(), header.value.as_str());
+        }
+        builder
+            .body(self.body.clone().unwrap_or_default())
+            .map_err(|e| SageError::InvalidInput(format!("Invalid HTTP request: {e}")))
+    }
+}
+
+/// Outcome of a successful verification
+#[derive(Debug, Clone, uniffi::Record)]
+pub struct SageVerification {
+    /// Label of the verified signature
+    pub label: String,
+    /// Key ID the signature was verified with
+    pub key_id: String,
+    /// Identifiers of the covered components, e.g. `@method`
+    pub components: Vec<String>,
+    /// Creation timestamp (Unix timestamp)
+    pub created: Option<i64>,
+    /// Expiration timestamp (Unix timestamp)
+    pub expires: Option<i64>,
+}
+
+impl From<VerificationReport> for SageVerification {
+    fn from(report: VerificationReport) -> Self {
+        Self {
+            label: report.label,
+            key_id: report.key_id,
+            components: report
+                .components
+                .iter()
+                .map(|component| component.identifier())
+                .collect(),
+            created: report.created,
+            expires: report.expires,
+        }
+    }
+}
+
+/// RFC 9421 HTTP message signer
+#[derive(uniffi::Object)]
+pub struct SageHttpSigner {
+    inner: HttpSigner,
+}
+
+#[uniffi::export]
+impl SageHttpSigner {
+    /// Create a signer using the default components
+    #[uniffi::constructor]
+    pub fn new(keypair: Arc<SageKeyPair>) -> Arc<Self> {
+        Arc::new(Self {
+            inner: HttpSigner::new(keypair.inner.clone()),
+        })
+    }
+
+    /// Sign a request and return the headers to add to it
+    ///
+    /// Requests with a body also get a `content-digest` header, which is signed.
+    pub fn sign_request(&self, request: SageHttpRequest) -> SageResult<Vec<SageHttpHeader>> {
+        let has_body = request.body.is_some();
+        let http_request = request.to_request()?;
+        let signed = if has_body {
+            self.inner.sign_request_with_body(http_request)?
+        } else {
+            self.inner.sign_request(http_request)?
+        };
+
+        let mut headers = Vec::new();
+        for name in ["content-digest", "signature-input", "signature"] {
+            if !has_body && name == "content-digest" {
+                continue;
+            }
+            if let Some(value) = signed.headers().get(name) {
+                let value = value
+                    .to_str()
+                    .map_err(|e| SageError::Other(format!("Invalid {name} header: {e}")))?;
+                headers.push(SageHttpHeader {
+                    name: name.to_string(),
+                    value: value.to_string(),
+                });
+            }
+        }
+        Ok(headers)
+    }
+}
+
+/// RFC 9421 HTTP message verifier
+#[derive(uniffi::Object)]
+pub struct SageHttpVerifier {
+    inner: HttpVerifier,
+}
+
+#[uniffi::export]
+impl SageHttpVerifier {
+    /// Create a verifier trusting a single public key
+    #[uniffi::constructor]
+    pub fn new(public_key: Arc<SagePublicKey>) -> Arc<Self> {
+        Arc::new(Self {
+            inner: HttpVerifier::new(public_key.inner.clone()),
+        })
+    }
+
+    /// Verify the signature of a request
+    pub fn verify_request(&self, request: SageHttpRequest) -> SageResult<SageVerification> {
+        let http_request = request.to_request()?;
+        Ok(self.inner.verify_request_detailed(&http_request)?.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mobile_sign_and_verify_request() {
+        let keypair = SageKeyPair::generate(SageKeyType::Ed25519).unwrap();
+        let signer = SageHttpSigner::new(keypair.clone());
+        let verifier = SageHttpVerifier::new(keypair.public_key());
+
+        let mut request = SageHttpRequest {
+            method: "POST".to_string(),
+            uri: "https://agent.example/tasks".to_string(),
+            headers: Vec::new(),
+            body: Some(b"{\"task\":\"ping\"}".to_vec()),
+        };
+        let headers = signer.sign_request(request.clone()).unwrap();
+        assert_eq!(headers.len(), 3);
+        request.headers = headers;
+
+        let verification = verifier.verify_request(request.clone()).unwrap();
+        assert_eq!(verification.key_id, keypair.key_id());
+        assert!(verification
+            .components
+            .contains(&"content-digest".to_string()));
+
+        request.method = "DELETE".to_string();
+        assert!(matches!(
+            verifier.verify_request(request),
+            Err(SageError::Verification(_))
+        ));
+    }
+
+    #[test]
+    fn test_mobile_sign_and_verify_message() {
+        let keypair = SageKeyPair::generate(SageKeyType::Secp256k1).unwrap();
+        let signature = keypair.sign(b"hello".to_vec()).unwrap();
+        keypair
+            .verify(b"hello".to_vec(), signature.clone())
+            .unwrap();
+        assert!(keypair.verify(b"other".to_vec(), signature).is_err());
+
+        let jwk =
+            String::from_utf8(keypair.public_key().export(SageKeyFormat::Jwk).unwrap()).unwrap();
+        let public_key = SagePublicKey::from_jwk(jwk).unwrap();
+        assert_eq!(public_key.key_id(), keypair.key_id());
+    }
+}
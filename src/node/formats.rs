@@ -0,0 +1,80 @@
+//! Node.js bindings for key format operations
+
+use super::*;
+use crate::formats::{KeyExporter, KeyFormat, KeyImporter};
+
+/// Key format enum for Node.js
+#[napi(js_name = "WasmKeyFormat")]
+#[derive(Debug)]
+pub enum NodeKeyFormat {
+    /// Raw binary format
+    Raw = 0,
+    /// PEM format (Base64 encoded with headers)
+    Pem = 1,
+    /// DER format (binary ASN.1)
+    Der = 2,
+    /// JWK format (JSON Web Key)
+    Jwk = 3,
+}
+
+impl From<NodeKeyFormat> for KeyFormat {
+    fn from(format: NodeKeyFormat) -> Self {
+        match format {
+            NodeKeyFormat::Raw => KeyFormat::Raw,
+            NodeKeyFormat::Pem => KeyFormat::Pem,
+            NodeKeyFormat::Der => KeyFormat::Der,
+            NodeKeyFormat::Jwk => KeyFormat::Jwk,
+        }
+    }
+}
+
+/// Key format utilities for Node.js, backed by [`crate::formats`]
+#[napi(js_name = "WasmKeyFormatUtils")]
+pub struct NodeKeyFormatUtils;
+
+#[napi]
+impl NodeKeyFormatUtils {
+    /// Get supported formats for a key type
+    #[napi]
+    pub fn get_supported_formats(_key_type: NodeKeyType) -> Vec<NodeKeyFormat> {
+        // All key types support all formats in this implementation
+        vec![
+            NodeKeyFormat::Raw,
+            NodeKeyFormat::Pem,
+            NodeKeyFormat::Der,
+            NodeKeyFormat::Jwk,
+        ]
+    }
+
+    /// Export the private key of a key pair; DER is PKCS#8
+    #[napi]
+    pub fn export_private_key(
+        keypair: &NodeKeyPair,
+        format: NodeKeyFormat,
+    ) -> NodeResult<Uint8Array> {
+        Ok(keypair.inner.export(format.into())?.into())
+    }
+
+    /// Export a public key; DER is SPKI
+    #[napi]
+    pub fn export_public_key(
+        public_key: &NodePublicKey,
+        format: NodeKeyFormat,
+    ) -> NodeResult<Uint8Array> {
+        Ok(public_key.inner.export(format.into())?.into())
+    }
+
+    /// Import a key pair from an exported private key
+    #[napi]
+    pub fn import_key_pair(data: Uint8Array, format: NodeKeyFormat) -> NodeResult<NodeKeyPair> {
+        let keypair = KeyPair::import_key_pair(&data, format.into())?;
+        Ok(NodeKeyPair { inner: keypair })
+    }
+
+    /// Import an exported public key; raw keys are told apart by length
+    #[napi]
+    pub fn import_public_key(data: Uint8Array, format: NodeKeyFormat) -> NodeResult<NodePublicKey> {
+        let public_key = KeyPair::import_public_key(&data, format.into())?;
+        Ok(NodePublicKey { inner: public_key })
+    }
+}
@@ -0,0 +1,86 @@
+//! Node.js bindings for HTTP signature operations (RFC 9421)
+
+use super::*;
+use std::collections::HashMap;
+
+/// HTTP signer for Node.js
+#[napi(js_name = "WasmHttpSigner")]
+pub struct NodeHttpSigner {
+    keypair: KeyPair,
+}
+
+#[napi]
+impl NodeHttpSigner {
+    /// Create a new HTTP signer
+    #[napi(constructor)]
+    pub fn new(keypair: &NodeKeyPair) -> NodeHttpSigner {
+        NodeHttpSigner {
+            keypair: keypair.inner.clone(),
+        }
+    }
+
+    /// Get the key ID
+    #[napi(getter)]
+    pub fn key_id(&self) -> String {
+        self.keypair.key_id().to_string()
+    }
+
+    /// Sign a message (basic signature without HTTP semantics for now)
+    #[napi]
+    pub fn sign_message(&self, message: String) -> NodeResult<String> {
+        let signature = self.keypair.sign(message.as_bytes())?;
+        Ok(hex::encode(signature.to_bytes()))
+    }
+}
+
+/// HTTP verifier for Node.js
+#[napi(js_name = "WasmHttpVerifier")]
+pub struct NodeHttpVerifier {
+    public_key: PublicKey,
+}
+
+#[napi]
+impl NodeHttpVerifier {
+    /// Create a new HTTP verifier
+    #[napi(constructor)]
+    pub fn new(public_key: &NodePublicKey) -> NodeHttpVerifier {
+        NodeHttpVerifier {
+            public_key: public_key.inner.clone(),
+        }
+    }
+
+    /// Verify a message signature
+    #[napi]
+    pub fn verify_message(&self, message: String, signature_hex: String) -> NodeResult<bool> {
+        let bytes = hex::decode(signature_hex)
+            .map_err(|e| napi::Error::from_reason(format!("Invalid hex: {e}")))?;
+        let signature = Signature::from_bytes(self.public_key.key_type(), &bytes)?;
+        Ok(self
+            .public_key
+            .verify(message.as_bytes(), &signature)
+            .is_ok())
+    }
+}
+
+/// Utility functions for HTTP signature operations
+#[napi(js_name = "WasmHttpUtils")]
+pub struct NodeHttpUtils;
+
+#[napi]
+impl NodeHttpUtils {
+    /// Create signature input string
+    #[napi]
+    pub fn create_signature_input(components: Vec<String>, key_id: String) -> String {
+        let comp_str = components.join(" ");
+        format!("({comp_str}); keyid=\"{key_id}\"; alg=\"ed25519\"")
+    }
+
+    /// Parse signature headers from a response
+    #[napi]
+    pub fn parse_signature_headers(headers: HashMap<String, String>) -> HashMap<String, String> {
+        headers
+            .into_iter()
+            .filter(|(name, _)| name == "signature" || name == "signature-input")
+            .collect()
+    }
+}
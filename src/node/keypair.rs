@@ -0,0 +1,194 @@
+//! Node.js bindings for key pair operations
+
+use super::*;
+
+/// Key pair for Node.js
+#[napi(js_name = "WasmKeyPair")]
+pub struct NodeKeyPair {
+    pub(crate) inner: KeyPair,
+}
+
+#[napi]
+impl NodeKeyPair {
+    /// Generate a new key pair
+    #[napi(constructor)]
+    pub fn new(key_type: NodeKeyType) -> NodeResult<NodeKeyPair> {
+        KeyPair::generate(key_type.into())
+            .map(|keypair| NodeKeyPair { inner: keypair })
+            .map_err(Into::into)
+    }
+
+    /// Generate a new Ed25519 key pair
+    #[napi(factory, js_name = "generateEd25519")]
+    pub fn generate_ed25519() -> NodeResult<NodeKeyPair> {
+        Self::new(NodeKeyType::Ed25519)
+    }
+
+    /// Generate a new Secp256k1 key pair
+    #[napi(factory, js_name = "generateSecp256k1")]
+    pub fn generate_secp256k1() -> NodeResult<NodeKeyPair> {
+        Self::new(NodeKeyType::Secp256k1)
+    }
+
+    /// Get the key type
+    #[napi(getter)]
+    pub fn key_type(&self) -> NodeKeyType {
+        self.inner.key_type().into()
+    }
+
+    /// Get the key ID
+    #[napi(getter)]
+    pub fn key_id(&self) -> String {
+        self.inner.key_id().to_string()
+    }
+
+    /// Get the public key
+    #[napi]
+    pub fn get_public_key(&self) -> NodePublicKey {
+        NodePublicKey {
+            inner: self.inner.public_key().clone(),
+        }
+    }
+
+    /// Export private key as hex string
+    #[napi]
+    pub fn export_private_key_hex(&self) -> String {
+        hex::encode(self.inner.private_key_bytes())
+    }
+
+    /// Export public key as hex string
+    #[napi]
+    pub fn export_public_key_hex(&self) -> String {
+        hex::encode(self.inner.public_key_bytes())
+    }
+
+    /// Export private key as Uint8Array
+    #[napi]
+    pub fn export_private_key(&self) -> Uint8Array {
+        self.inner.private_key_bytes().into()
+    }
+
+    /// Export public key as Uint8Array
+    #[napi]
+    pub fn export_public_key(&self) -> Uint8Array {
+        self.inner.public_key_bytes().into()
+    }
+
+    /// Import key pair from private key hex string
+    #[napi(factory)]
+    pub fn from_private_key_hex(key_type: NodeKeyType, hex_key: String) -> NodeResult<NodeKeyPair> {
+        let bytes = hex::decode(hex_key)
+            .map_err(|e| napi::Error::from_reason(format!("Invalid hex: {e}")))?;
+        Self::from_private_key(key_type, bytes.into())
+    }
+
+    /// Import key pair from private key bytes
+    #[napi(factory)]
+    pub fn from_private_key(
+        key_type: NodeKeyType,
+        private_key: Uint8Array,
+    ) -> NodeResult<NodeKeyPair> {
+        KeyPair::from_private_key_bytes(key_type.into(), &private_key)
+            .map(|keypair| NodeKeyPair { inner: keypair })
+            .map_err(Into::into)
+    }
+
+    /// Sign a message
+    #[napi]
+    pub fn sign(&self, message: Uint8Array) -> NodeResult<NodeSignature> {
+        self.sign_bytes(&message)
+    }
+
+    /// Sign a string message
+    #[napi]
+    pub fn sign_string(&self, message: String) -> NodeResult<NodeSignature> {
+        self.sign_bytes(message.as_bytes())
+    }
+
+    /// Verify a signature
+    #[napi]
+    pub fn verify(&self, message: Uint8Array, signature: &NodeSignature) -> bool {
+        self.inner.verify(&message, &signature.inner).is_ok()
+    }
+
+    /// Verify a signature on a string message
+    #[napi]
+    pub fn verify_string(&self, message: String, signature: &NodeSignature) -> bool {
+        self.inner
+            .verify(message.as_bytes(), &signature.inner)
+            .is_ok()
+    }
+}
+
+impl NodeKeyPair {
+    /// Sign a byte slice
+    pub(crate) fn sign_bytes(&self, message: &[u8]) -> NodeResult<NodeSignature> {
+        self.inner
+            .sign(message)
+            .map(|sig| NodeSignature { inner: sig })
+            .map_err(Into::into)
+    }
+}
+
+/// Public key for Node.js
+#[napi(js_name = "WasmPublicKey")]
+pub struct NodePublicKey {
+    pub(crate) inner: PublicKey,
+}
+
+#[napi]
+impl NodePublicKey {
+    /// Get the key type
+    #[napi(getter)]
+    pub fn key_type(&self) -> NodeKeyType {
+        self.inner.key_type().into()
+    }
+
+    /// Get the key ID
+    #[napi(getter)]
+    pub fn key_id(&self) -> String {
+        self.inner.key_id()
+    }
+
+    /// Export as hex string
+    #[napi]
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.inner.to_bytes())
+    }
+
+    /// Export as Uint8Array
+    #[napi]
+    pub fn to_bytes(&self) -> Uint8Array {
+        self.inner.to_bytes().into()
+    }
+
+    /// Import from hex string
+    #[napi(factory)]
+    pub fn from_hex(key_type: NodeKeyType, hex_key: String) -> NodeResult<NodePublicKey> {
+        let bytes = hex::decode(hex_key)
+            .map_err(|e| napi::Error::from_reason(format!("Invalid hex: {e}")))?;
+        Self::from_bytes(key_type, bytes.into())
+    }
+
+    /// Import from bytes
+    #[napi(factory)]
+    pub fn from_bytes(key_type: NodeKeyType, bytes: Uint8Array) -> NodeResult<NodePublicKey> {
+        PublicKey::from_bytes(key_type.into(), &bytes)
+            .map(|key| NodePublicKey { inner: key })
+            .map_err(Into::into)
+    }
+
+    /// Verify a signature
+    #[napi]
+    pub fn verify(&self, message: Uint8Array, signature: &NodeSignature) -> bool {
+        self.inner.verify(&message, &signature.inner).is_ok()
+    }
+
+    /// Verify a signature on a string message
+    #[napi]
+    pub fn verify_string(&self, message: String, signature: &NodeSignature) -> bool {
+        self.inner
+            .verify(message.as_bytes(), &signature.inner)
+            .is_ok()
+    }
+}
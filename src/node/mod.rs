@@ -0,0 +1,64 @@
+//! Native Node.js bindings (napi-rs)
+//!
+//! Exposes the same classes and functions as the WASM build, so server-side
+//! code can switch between the two packages without changes.
+
+use crate::crypto::{KeyPair, KeyType, PublicKey, Signature};
+use crate::crypto::{Signer, Verifier};
+use napi::bindgen_prelude::Uint8Array;
+use napi_derive::napi;
+
+pub mod formats;
+pub mod http;
+pub mod keypair;
+pub mod signature;
+pub mod utils;
+
+pub use formats::*;
+pub use http::*;
+pub use keypair::*;
+pub use signature::*;
+pub use utils::*;
+
+/// Get the version string
+#[napi]
+pub fn version() -> String {
+    crate::VERSION.to_string()
+}
+
+/// Key type enum for Node.js
+#[napi(js_name = "WasmKeyType")]
+#[derive(Debug)]
+pub enum NodeKeyType {
+    /// Ed25519 key type for EdDSA signatures
+    Ed25519 = 0,
+    /// Secp256k1 key type for ECDSA signatures
+    Secp256k1 = 1,
+}
+
+impl From<NodeKeyType> for KeyType {
+    fn from(key_type: NodeKeyType) -> Self {
+        match key_type {
+            NodeKeyType::Ed25519 => KeyType::Ed25519,
+            NodeKeyType::Secp256k1 => KeyType::Secp256k1,
+        }
+    }
+}
+
+impl From<KeyType> for NodeKeyType {
+    fn from(key_type: KeyType) -> Self {
+        match key_type {
+            KeyType::Ed25519 => NodeKeyType::Ed25519,
+            KeyType::Secp256k1 => NodeKeyType::Secp256k1,
+        }
+    }
+}
+
+impl From<crate::error::Error> for napi::Error {
+    fn from(err: crate::error::Error) -> Self {
+        napi::Error::from_reason(err.to_string())
+    }
+}
+
+/// Result type for Node.js
+pub type NodeResult<T> = napi::Result<T>;
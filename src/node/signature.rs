@@ -0,0 +1,40 @@
+//! Node.js bindings for signature operations
+
+use super::*;
+
+/// Signature for Node.js
+#[napi(js_name = "WasmSignature")]
+pub struct NodeSignature {
+    pub(crate) inner: Signature,
+}
+
+#[napi]
+impl NodeSignature {
+    /// Export signature as hex string
+    #[napi]
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.inner.to_bytes())
+    }
+
+    /// Export signature as Uint8Array
+    #[napi]
+    pub fn to_bytes(&self) -> Uint8Array {
+        self.inner.to_bytes().into()
+    }
+
+    /// Import signature from hex string
+    #[napi(factory)]
+    pub fn from_hex(key_type: NodeKeyType, hex_sig: String) -> NodeResult<NodeSignature> {
+        let bytes = hex::decode(hex_sig)
+            .map_err(|e| napi::Error::from_reason(format!("Invalid hex: {e}")))?;
+        Self::from_bytes(key_type, bytes.into())
+    }
+
+    /// Import signature from bytes
+    #[napi(factory)]
+    pub fn from_bytes(key_type: NodeKeyType, bytes: Uint8Array) -> NodeResult<NodeSignature> {
+        Signature::from_bytes(key_type.into(), &bytes)
+            .map(|signature| NodeSignature { inner: signature })
+            .map_err(Into::into)
+    }
+}
@@ -0,0 +1,51 @@
+//! Node.js utility functions
+
+use super::*;
+
+/// Generate random bytes from the OS random number generator
+#[napi]
+pub fn generate_random_bytes(length: u32) -> Uint8Array {
+    use rand::RngCore;
+    let mut bytes = vec![0u8; length as usize];
+    rand::rngs::OsRng.fill_bytes(&mut bytes);
+    bytes.into()
+}
+
+/// Generate a random hex string
+#[napi]
+pub fn generate_random_hex(length: u32) -> String {
+    hex::encode(&*generate_random_bytes(length))
+}
+
+/// Convert hex string to bytes
+#[napi]
+pub fn hex_to_bytes(hex: String) -> NodeResult<Uint8Array> {
+    hex::decode(hex)
+        .map(Into::into)
+        .map_err(|e| napi::Error::from_reason(format!("Invalid hex: {e}")))
+}
+
+/// Convert bytes to hex string
+#[napi]
+pub fn bytes_to_hex(bytes: Uint8Array) -> String {
+    hex::encode(&*bytes)
+}
+
+/// Hash data with SHA256
+#[napi]
+pub fn sha256(data: Uint8Array) -> Uint8Array {
+    sha256_bytes(&data).into()
+}
+
+/// Hash string with SHA256
+#[napi]
+pub fn sha256_string(data: String) -> String {
+    hex::encode(sha256_bytes(data.as_bytes()))
+}
+
+fn sha256_bytes(data: &[u8]) -> Vec<u8> {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(data);
+    hasher.finalize().to_vec()
+}
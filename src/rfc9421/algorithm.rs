@@ -0,0 +1,108 @@
+//! Extension point for signature algorithms implemented outside this crate
+
+use crate::error::{Error, Result};
+use crate::rfc9421::SignatureAlgorithm;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Verifies signatures for a custom `alg` identifier
+///
+/// The implementation is responsible for finding the key named by `keyid`,
+/// and is only consulted when the verification policy allows its algorithm.
+pub trait CustomVerifier: Send + Sync {
+    /// The RFC 9421 `alg` identifier handled by this verifier
+    fn identifier(&self) -> &'static str;
+
+    /// Verify `signature` over the signature base `message`
+    fn verify(&self, key_id: Option<&str>, message: &[u8], signature: &[u8]) -> Result<()>;
+}
+
+/// Signs with a custom algorithm, e.g. an HSM-backed or experimental key
+pub trait CustomSigner: Send + Sync {
+    /// The RFC 9421 `alg` identifier of produced signatures
+    fn identifier(&self) -> &'static str;
+
+    /// The `keyid` of the signing key
+    fn key_id(&self) -> String;
+
+    /// Sign the signature base `message`
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>>;
+}
+
+/// Custom verifiers indexed by `alg` identifier
+#[derive(Clone, Default)]
+pub struct AlgorithmRegistry {
+    verifiers: HashMap<&'static str, Arc<dyn CustomVerifier>>,
+}
+
+impl AlgorithmRegistry {
+    /// Create an empty registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a custom verifier
+    ///
+    /// Identifiers of algorithms implemented by this crate cannot be
+    /// overridden; built-in identifiers without an implementation, such as
+    /// `ecdsa-p256-sha256`, can be registered.
+    pub fn register(&mut self, verifier: Arc<dyn CustomVerifier>) -> Result<()> {
+        let identifier = verifier.identifier();
+        if SignatureAlgorithm::from_identifier(identifier)
+            .is_some_and(|algorithm| algorithm.key_type().is_some())
+        {
+            return Err(Error::InvalidInput(format!(
+                "Algorithm {identifier} is built in and cannot be replaced"
+            )));
+        }
+        self.verifiers.insert(identifier, verifier);
+        Ok(())
+    }
+
+    /// Look up the verifier for an `alg` identifier
+    pub fn get(&self, identifier: &str) -> Option<&Arc<dyn CustomVerifier>> {
+        self.verifiers.get(identifier)
+    }
+
+    /// Check whether no custom algorithms are registered
+    pub fn is_empty(&self) -> bool {
+        self.verifiers.is_empty()
+    }
+}
+
+impl fmt::Debug for AlgorithmRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.verifiers.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Reject(&'static str);
+
+    impl CustomVerifier for Reject {
+        fn identifier(&self) -> &'static str {
+            self.0
+        }
+
+        fn verify(&self, _key_id: Option<&str>, _message: &[u8], _signature: &[u8]) -> Result<()> {
+            Err(Error::Verification("rejected".to_string()))
+        }
+    }
+
+    #[test]
+    fn test_register_custom_algorithm() {
+        let mut registry = AlgorithmRegistry::new();
+        assert!(registry.is_empty());
+        registry.register(Arc::new(Reject("ml-dsa-44"))).unwrap();
+        assert!(registry.get("ml-dsa-44").is_some());
+        assert!(registry.get("ed25519").is_none());
+        assert!(registry.register(Arc::new(Reject("ed25519"))).is_err());
+        assert!(registry
+            .register(Arc::new(Reject("ecdsa-p256-sha256")))
+            .is_ok());
+    }
+}
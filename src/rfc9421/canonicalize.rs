@@ -1,145 +1,633 @@
 //! HTTP message canonicalization for RFC 9421
 
+use super::components::{
+    validate_components, ComponentParams, SignatureComponent, SignatureParams,
+};
+use super::message::{HttpMessage, RequestTargetForm};
 use crate::error::{Error, Result};
-use http::{HeaderMap, Request, Response};
+use base64::{engine::general_purpose, Engine as _};
+use http::uri::PathAndQuery;
+use http::{HeaderMap, Request, Response, Uri};
+use std::fmt;
+
+/// Options controlling how messages are canonicalized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CanonicalizeOptions {
+    /// Normalize percent-encoding in the target URI path and query (RFC 3986 Section 6.2.2)
+    ///
+    /// Hex digits of escapes are uppercased and escaped unreserved characters
+    /// are decoded, so signatures survive intermediaries that re-encode URLs.
+    /// Signer and verifier must agree on this option.
+    pub normalize_percent_encoding: bool,
+}
+
+/// Trailer fields of a message, stored in its extensions for components using the `tr` parameter
+#[derive(Debug, Clone, Default)]
+pub struct Trailers(pub HeaderMap);
 
 /// Canonicalize an HTTP request for signing
 pub fn canonicalize_request<B>(
     request: &Request<B>,
-    components: &[super::SignatureComponent],
+    components: &[SignatureComponent],
 ) -> Result<Vec<(String, String)>> {
+    canonicalize_message::<_, Request<B>>(request, None, components)
+}
+
+/// Canonicalize an HTTP response for signing
+pub fn canonicalize_response<B>(
+    response: &Response<B>,
+    components: &[SignatureComponent],
+) -> Result<Vec<(String, String)>> {
+    canonicalize_message::<_, Request<()>>(response, None, components)
+}
+
+/// Canonicalize an HTTP response, resolving `req` components against the related request
+pub fn canonicalize_response_with_request<B, R>(
+    response: &Response<B>,
+    request: Option<&Request<R>>,
+    components: &[SignatureComponent],
+) -> Result<Vec<(String, String)>> {
+    canonicalize_message(response, request, components)
+}
+
+/// Canonicalize any [`HttpMessage`]
+///
+/// For responses, `req` components are resolved against `request`.
+pub fn canonicalize_message<M, R>(
+    message: &M,
+    request: Option<&R>,
+    components: &[SignatureComponent],
+) -> Result<Vec<(String, String)>>
+where
+    M: HttpMessage + ?Sized,
+    R: HttpMessage + ?Sized,
+{
+    canonicalize_message_with(
+        message,
+        request,
+        components,
+        &CanonicalizeOptions::default(),
+    )
+}
+
+/// Canonicalize any [`HttpMessage`] with explicit options
+pub fn canonicalize_message_with<M, R>(
+    message: &M,
+    request: Option<&R>,
+    components: &[SignatureComponent],
+    options: &CanonicalizeOptions,
+) -> Result<Vec<(String, String)>>
+where
+    M: HttpMessage + ?Sized,
+    R: HttpMessage + ?Sized,
+{
+    let is_response = message.status().is_some();
+    validate_components(components, is_response)?;
+
     let mut values = Vec::new();
 
     for component in components {
-        let (name, value) = match component {
-            super::SignatureComponent::Method => {
-                ("@method".to_string(), request.method().as_str().to_string())
-            }
-            super::SignatureComponent::TargetUri => {
-                ("@target-uri".to_string(), request.uri().to_string())
-            }
-            super::SignatureComponent::Authority => {
-                let authority = request
-                    .uri()
-                    .authority()
-                    .ok_or_else(|| Error::InvalidInput("Missing authority in URI".to_string()))?
-                    .to_string();
-                ("@authority".to_string(), authority)
-            }
-            super::SignatureComponent::Scheme => {
-                let scheme = request
-                    .uri()
-                    .scheme_str()
-                    .ok_or_else(|| Error::InvalidInput("Missing scheme in URI".to_string()))?
-                    .to_string();
-                ("@scheme".to_string(), scheme)
-            }
-            super::SignatureComponent::RequestTarget => {
-                let path = request.uri().path();
-                let query = request
-                    .uri()
-                    .query()
-                    .map(|q| format!("?{q}"))
-                    .unwrap_or_default();
-                ("@request-target".to_string(), format!("{path}{query}"))
-            }
-            super::SignatureComponent::Path => {
-                ("@path".to_string(), request.uri().path().to_string())
-            }
-            super::SignatureComponent::Query => {
-                let query = request
-                    .uri()
-                    .query()
-                    .map(|q| format!("?{q}"))
-                    .unwrap_or_else(|| "?".to_string());
-                ("@query".to_string(), query)
-            }
-            super::SignatureComponent::Status => {
+        let params = component.params().cloned().unwrap_or_default();
+
+        let component_values = if !is_response {
+            if params.req {
                 return Err(Error::InvalidInput(
-                    "@status component not valid for requests".to_string(),
-                ));
-            }
-            super::SignatureComponent::Header(name) => {
-                let header_value = get_header_value(request.headers(), name)?;
-                (name.to_lowercase(), header_value)
-            }
-            super::SignatureComponent::DerivedComponent { .. } => {
-                return Err(Error::Unsupported(
-                    "Custom derived components not yet supported".to_string(),
+                    "req parameter is only valid for responses".to_string(),
                 ));
             }
+            request_component_values(message, component.base(), &params, options)?
+        } else if params.req {
+            let request = request.ok_or_else(|| {
+                Error::InvalidInput(format!(
+                    "{} requires the related request",
+                    component.serialize()
+                ))
+            })?;
+            let request_params = ComponentParams {
+                req: false,
+                ..params
+            };
+            request_component_values(request, component.base(), &request_params, options)?
+        } else {
+            response_component_values(message, component.base(), &params)?
         };
 
-        values.push((name, value));
+        for value in component_values {
+            values.push((component_name(component), value));
+        }
     }
 
     Ok(values)
 }
 
-/// Canonicalize an HTTP response for signing
-pub fn canonicalize_response<B>(
-    response: &Response<B>,
-    components: &[super::SignatureComponent],
-) -> Result<Vec<(String, String)>> {
-    let mut values = Vec::new();
+/// Name used for a component line in the signature base
+fn component_name(component: &SignatureComponent) -> String {
+    match component {
+        SignatureComponent::Parameterized { .. } | SignatureComponent::DerivedComponent { .. } => {
+            component.serialize()
+        }
+        _ => component.identifier(),
+    }
+}
 
-    for component in components {
-        let (name, value) = match component {
-            super::SignatureComponent::Status => (
-                "@status".to_string(),
-                response.status().as_u16().to_string(),
-            ),
-            super::SignatureComponent::Header(name) => {
-                let header_value = get_header_value(response.headers(), name)?;
-                (name.to_lowercase(), header_value)
+/// Parse the target URI of a request, normalizing it as configured
+fn target_uri<M: HttpMessage + ?Sized>(request: &M, options: &CanonicalizeOptions) -> Result<Uri> {
+    let uri: Uri = request
+        .target_uri()
+        .ok_or_else(|| Error::InvalidInput("Missing target URI".to_string()))?
+        .parse()
+        .map_err(|_| Error::InvalidInput("Invalid target URI".to_string()))?;
+
+    if !options.normalize_percent_encoding {
+        return Ok(uri);
+    }
+
+    let mut parts = uri.into_parts();
+    if let Some(path_and_query) = parts.path_and_query {
+        let normalized = normalize_percent_encoding(path_and_query.as_str());
+        parts.path_and_query = Some(
+            PathAndQuery::try_from(normalized)
+                .map_err(|_| Error::InvalidInput("Invalid target URI".to_string()))?,
+        );
+    }
+    Uri::from_parts(parts).map_err(|_| Error::InvalidInput("Invalid target URI".to_string()))
+}
+
+/// Uppercase percent-escapes and decode escaped unreserved characters
+pub fn normalize_percent_encoding(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = String::with_capacity(input.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escape = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+
+        match escape {
+            Some(byte) if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) => {
+                out.push(byte as char);
+                i += 3;
             }
-            super::SignatureComponent::Method
-            | super::SignatureComponent::TargetUri
-            | super::SignatureComponent::Authority
-            | super::SignatureComponent::Scheme
-            | super::SignatureComponent::RequestTarget
-            | super::SignatureComponent::Path
-            | super::SignatureComponent::Query => {
-                return Err(Error::InvalidInput(format!(
-                    "{component:?} component not valid for responses"
-                )));
+            Some(byte) => {
+                out.push_str(&format!("%{byte:02X}"));
+                i += 3;
             }
-            super::SignatureComponent::DerivedComponent { .. } => {
-                return Err(Error::Unsupported(
-                    "Custom derived components not yet supported".to_string(),
-                ));
+            None => {
+                // Not a valid escape: copy the character as is
+                let c = input[i..].chars().next().unwrap_or_default();
+                out.push(c);
+                i += c.len_utf8();
             }
-        };
+        }
+    }
+    out
+}
+
+/// Get the form of a request's request-target
+fn target_form<M: HttpMessage + ?Sized>(request: &M, uri: &Uri) -> RequestTargetForm {
+    request
+        .request_target_form()
+        .unwrap_or_else(|| RequestTargetForm::infer(uri))
+}
 
-        values.push((name, value));
+/// Get the path of an origin- or absolute-form target, normalizing an empty path to "/"
+fn origin_path(uri: &Uri) -> String {
+    match uri.path() {
+        "" => "/".to_string(),
+        path => path.to_string(),
     }
+}
 
-    Ok(values)
+/// Resolve the absolute target URI of a request (RFC 9112 Section 3.3)
+///
+/// Authority- and asterisk-form requests have no path or query.
+fn absolute_target_uri<M: HttpMessage + ?Sized>(
+    request: &M,
+    options: &CanonicalizeOptions,
+) -> Result<String> {
+    let uri = target_uri(request, options)?;
+    let scheme = uri.scheme_str().ok_or_else(|| {
+        Error::InvalidInput("@target-uri requires the scheme of the target URI".to_string())
+    })?;
+
+    match target_form(request, &uri) {
+        RequestTargetForm::Origin | RequestTargetForm::Absolute => Ok(uri.to_string()),
+        RequestTargetForm::Authority | RequestTargetForm::Asterisk => {
+            Ok(format!("{scheme}://{}", authority(request)?))
+        }
+    }
 }
 
-/// Get a header value, handling multiple values according to RFC 9421
-fn get_header_value(headers: &HeaderMap, name: &str) -> Result<String> {
-    let values: Vec<&str> = headers
-        .get_all(name)
-        .iter()
-        .map(|v| v.to_str())
-        .collect::<std::result::Result<Vec<_>, _>>()
-        .map_err(|_| Error::InvalidInput(format!("Invalid header value for {name}")))?;
+/// Resolve the normalized `@authority` of a request
+///
+/// Falls back to the Host header for origin-form target URIs.
+fn authority<M: HttpMessage + ?Sized>(request: &M) -> Result<String> {
+    let uri = target_uri(request, &CanonicalizeOptions::default())?;
+    if let Some(authority) = uri.authority() {
+        return Ok(normalize_authority(authority.as_str(), uri.scheme_str()));
+    }
+
+    let host = get_header_value(&request.header_values("host"), "host")
+        .map_err(|_| Error::InvalidInput("Missing authority in URI".to_string()))?;
+    Ok(normalize_authority(&host, uri.scheme_str()))
+}
+
+/// Lowercase the host and drop the default port of the scheme (RFC 9110 Section 4.2.3)
+fn normalize_authority(authority: &str, scheme: Option<&str>) -> String {
+    let authority = authority.trim().to_ascii_lowercase();
+    let default_port = match scheme.map(str::to_ascii_lowercase).as_deref() {
+        Some("http") => Some("80"),
+        Some("https") => Some("443"),
+        _ => None,
+    };
+
+    match (authority.rsplit_once(':'), default_port) {
+        // An IPv6 literal without a port ends with ']'
+        (Some((host, port)), Some(default)) if port == default && !host.is_empty() => {
+            host.to_string()
+        }
+        _ => authority,
+    }
+}
+
+/// Resolve the value(s) of a request component
+fn request_component_values<M: HttpMessage + ?Sized>(
+    request: &M,
+    component: &SignatureComponent,
+    params: &ComponentParams,
+    options: &CanonicalizeOptions,
+) -> Result<Vec<String>> {
+    if let SignatureComponent::Header(name) = component {
+        return Ok(vec![field_value(
+            &field_values(request, name, params)?,
+            name,
+            params,
+        )?]);
+    }
 
+    if params.sf || params.bs || params.key.is_some() || params.tr {
+        return Err(Error::InvalidInput(format!(
+            "{} parameters are only valid for header fields",
+            component.identifier()
+        )));
+    }
+
+    let value = match component {
+        SignatureComponent::Method => request
+            .method()
+            .ok_or_else(|| Error::InvalidInput("Missing request method".to_string()))?
+            .to_string(),
+        SignatureComponent::TargetUri => absolute_target_uri(request, options)?,
+        SignatureComponent::Authority => authority(request)?,
+        SignatureComponent::Scheme => target_uri(request, options)?
+            .scheme_str()
+            .ok_or_else(|| Error::InvalidInput("Missing scheme in URI".to_string()))?
+            .to_string(),
+        SignatureComponent::RequestTarget => {
+            let uri = target_uri(request, options)?;
+            match target_form(request, &uri) {
+                RequestTargetForm::Origin => {
+                    let query = uri.query().map(|q| format!("?{q}")).unwrap_or_default();
+                    format!("{}{query}", origin_path(&uri))
+                }
+                RequestTargetForm::Absolute => absolute_target_uri(request, options)?,
+                RequestTargetForm::Authority => authority(request)?,
+                RequestTargetForm::Asterisk => "*".to_string(),
+            }
+        }
+        SignatureComponent::Path => {
+            let uri = target_uri(request, options)?;
+            match target_form(request, &uri) {
+                RequestTargetForm::Origin | RequestTargetForm::Absolute => origin_path(&uri),
+                // Both forms have an empty path, which is normalized to "/"
+                RequestTargetForm::Authority | RequestTargetForm::Asterisk => "/".to_string(),
+            }
+        }
+        SignatureComponent::Query => target_uri(request, options)?
+            .query()
+            .map(|q| format!("?{q}"))
+            .unwrap_or_else(|| "?".to_string()),
+        SignatureComponent::Status => {
+            return Err(Error::InvalidInput(
+                "@status component not valid for requests".to_string(),
+            ));
+        }
+        SignatureComponent::DerivedComponent { name, params } if name == "query-param" => {
+            return query_param_values(&target_uri(request, options)?, params);
+        }
+        SignatureComponent::DerivedComponent { .. } => {
+            return Err(Error::Unsupported(
+                "Custom derived components not yet supported".to_string(),
+            ));
+        }
+        SignatureComponent::Header(_) | SignatureComponent::Parameterized { .. } => {
+            unreachable!("handled above or flattened by SignatureComponent::base")
+        }
+    };
+
+    Ok(vec![value])
+}
+
+/// Resolve the value(s) of a response component
+fn response_component_values<M: HttpMessage + ?Sized>(
+    response: &M,
+    component: &SignatureComponent,
+    params: &ComponentParams,
+) -> Result<Vec<String>> {
+    match component {
+        SignatureComponent::Header(name) => Ok(vec![field_value(
+            &field_values(response, name, params)?,
+            name,
+            params,
+        )?]),
+        SignatureComponent::Status if params.is_empty() => Ok(vec![response
+            .status()
+            .ok_or_else(|| Error::InvalidInput("Missing response status".to_string()))?
+            .to_string()]),
+        SignatureComponent::Status => Err(Error::InvalidInput(
+            "@status does not accept component parameters".to_string(),
+        )),
+        SignatureComponent::Method
+        | SignatureComponent::TargetUri
+        | SignatureComponent::Authority
+        | SignatureComponent::Scheme
+        | SignatureComponent::RequestTarget
+        | SignatureComponent::Path
+        | SignatureComponent::Query => Err(Error::InvalidInput(format!(
+            "{component:?} component not valid for responses"
+        ))),
+        SignatureComponent::DerivedComponent { .. } | SignatureComponent::Parameterized { .. } => {
+            Err(Error::Unsupported(
+                "Custom derived components not yet supported".to_string(),
+            ))
+        }
+    }
+}
+
+/// Get the raw values of a header or, with the `tr` parameter, trailer field
+fn field_values<'a, M: HttpMessage + ?Sized>(
+    message: &'a M,
+    name: &str,
+    params: &ComponentParams,
+) -> Result<Vec<&'a [u8]>> {
+    let name = name.to_ascii_lowercase();
+    if params.tr {
+        message
+            .trailer_values(&name)
+            .ok_or_else(|| Error::InvalidInput("Message has no trailer fields".to_string()))
+    } else {
+        Ok(message.header_values(&name))
+    }
+}
+
+/// Get a field value with component parameters applied
+fn field_value(values: &[&[u8]], name: &str, params: &ComponentParams) -> Result<String> {
     if values.is_empty() {
         return Err(Error::InvalidInput(format!("Header {name} not found")));
     }
 
+    if params.bs {
+        return Ok(values
+            .iter()
+            .map(|v| format!(":{}:", general_purpose::STANDARD.encode(v)))
+            .collect::<Vec<_>>()
+            .join(", "));
+    }
+
+    let value = get_header_value(values, name)?;
+
+    if let Some(ref key) = params.key {
+        dictionary_member(&value, key)
+    } else if params.sf {
+        Ok(serialize_structured_field(&value))
+    } else {
+        Ok(value)
+    }
+}
+
+/// Join field values according to RFC 9421
+pub(crate) fn get_header_value(values: &[&[u8]], name: &str) -> Result<String> {
+    // Same rule as http::HeaderValue::to_str: visible ASCII, space and tab only
+    let values: Vec<&str> = values
+        .iter()
+        .map(|v| {
+            let visible = v.iter().all(|&b| (32..127).contains(&b) || b == b'\t');
+            std::str::from_utf8(v).ok().filter(|_| visible)
+        })
+        .collect::<Option<Vec<_>>>()
+        .ok_or_else(|| Error::InvalidInput(format!("Invalid header value for {name}")))?;
+
     // Join multiple values with comma and space
     Ok(values.join(", "))
 }
 
+/// Split a structured field on a separator, ignoring separators in strings and inner lists
+fn split_structured(input: &str, separator: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut depth = 0usize;
+    let mut start = 0;
+
+    for (i, c) in input.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            _ if c == separator && depth == 0 => {
+                parts.push(&input[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+/// Collapse whitespace outside of strings to a single space
+fn collapse_whitespace(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut pending_space = false;
+
+    for c in input.chars() {
+        if in_string {
+            out.push(c);
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        if c.is_whitespace() {
+            pending_space = true;
+            continue;
+        }
+        if pending_space && !out.is_empty() && !out.ends_with('(') && c != ')' {
+            out.push(' ');
+        }
+        pending_space = false;
+        if c == '"' {
+            in_string = true;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Serialize a single list or dictionary member with parameters
+fn serialize_member(member: &str) -> String {
+    split_structured(member.trim(), ';')
+        .into_iter()
+        .map(|part| collapse_whitespace(part.trim()))
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Re-serialize a structured field value (RFC 8941 Section 4.1)
+fn serialize_structured_field(value: &str) -> String {
+    split_structured(value, ',')
+        .into_iter()
+        .map(serialize_member)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Select and serialize a member of a dictionary structured field
+pub(crate) fn dictionary_member(value: &str, key: &str) -> Result<String> {
+    let mut found = None;
+
+    // Later members with the same name override earlier ones
+    for member in split_structured(value, ',') {
+        let member = serialize_member(member);
+        let name_end = member.find(['=', ';']).unwrap_or(member.len());
+        if &member[..name_end] != key {
+            continue;
+        }
+        found = Some(match member[name_end..].strip_prefix('=') {
+            Some(item) => item.to_string(),
+            None => format!("?1{}", &member[name_end..]),
+        });
+    }
+
+    found.ok_or_else(|| Error::InvalidInput(format!("Dictionary member {key} not found")))
+}
+
+/// List the member names of a dictionary structured field in order
+pub(crate) fn dictionary_keys(value: &str) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::new();
+    for member in split_structured(value, ',') {
+        let member = serialize_member(member);
+        let name = &member[..member.find(['=', ';']).unwrap_or(member.len())];
+        if !name.is_empty() && !keys.iter().any(|k| k == name) {
+            keys.push(name.to_string());
+        }
+    }
+    keys
+}
+
+/// Resolve `@query-param` values for the `name` parameter (RFC 9421 Section 2.2.8)
+///
+/// Names and values are form-decoded, and values are percent-encoded again.
+fn query_param_values(uri: &Uri, params: &[String]) -> Result<Vec<String>> {
+    let name = params
+        .iter()
+        .find_map(|p| p.strip_prefix("name="))
+        .ok_or_else(|| Error::InvalidInput("@query-param requires a name parameter".to_string()))?;
+    let name = super::structured::parse_string(name)?;
+    let decoded_name = form_decode(&name);
+
+    let values: Vec<String> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .filter_map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (form_decode(key) == decoded_name).then(|| form_encode(&form_decode(value)))
+        })
+        .collect();
+
+    if values.is_empty() {
+        return Err(Error::InvalidInput(format!(
+            "Query parameter {name} not found"
+        )));
+    }
+
+    Ok(values)
+}
+
+/// Decode an `application/x-www-form-urlencoded` name or value
+fn form_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escape = (bytes[i] == b'%')
+            .then(|| bytes.get(i + 1..i + 3))
+            .flatten()
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok());
+
+        match (escape, bytes[i]) {
+            (Some(byte), _) => {
+                out.push(byte);
+                i += 3;
+            }
+            (None, b'+') => {
+                out.push(b' ');
+                i += 1;
+            }
+            (None, byte) => {
+                out.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// Percent-encode with the `application/x-www-form-urlencoded` set, spaces as `%20`
+fn form_encode(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for &byte in input.as_bytes() {
+        if byte.is_ascii_alphanumeric() || b"*-._".contains(&byte) {
+            out.push(byte as char);
+        } else {
+            out.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    out
+}
+
 /// Build the signature base string from canonicalized components
 pub fn build_signature_base(components: &[(String, String)], signature_params: &str) -> String {
     let mut lines = Vec::new();
 
     for (name, value) in components {
-        lines.push(format!("\"{name}\": {value}"));
+        // Parameterized components are already serialized with their quotes
+        if name.starts_with('"') {
+            lines.push(format!("{name}: {value}"));
+        } else {
+            lines.push(format!("\"{name}\": {value}"));
+        }
     }
 
     lines.push(format!("\"@signature-params\": {signature_params}"));
@@ -147,11 +635,98 @@ pub fn build_signature_base(components: &[(String, String)], signature_params: &
     lines.join("\n")
 }
 
+/// The exact string that is signed, with the components and parameters it covers
+///
+/// Useful for logging or diffing when two implementations disagree.
+#[derive(Debug, Clone)]
+pub struct SignatureBase {
+    components: Vec<SignatureComponent>,
+    params: SignatureParams,
+    value: String,
+}
+
+impl SignatureBase {
+    /// Build a signature base from canonicalized components and the serialized `@signature-params`
+    pub fn new(
+        canonical_values: &[(String, String)],
+        components: Vec<SignatureComponent>,
+        params: SignatureParams,
+        signature_params: &str,
+    ) -> Self {
+        Self {
+            value: build_signature_base(canonical_values, signature_params),
+            components,
+            params,
+        }
+    }
+
+    /// The canonical signature base string
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+
+    /// The signature base as bytes
+    pub fn as_bytes(&self) -> &[u8] {
+        self.value.as_bytes()
+    }
+
+    /// Covered components
+    pub fn components(&self) -> &[SignatureComponent] {
+        &self.components
+    }
+
+    /// Signature parameters
+    pub fn params(&self) -> &SignatureParams {
+        &self.params
+    }
+}
+
+impl fmt::Display for SignatureBase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rfc9421::MessageParts;
     use http::Request;
 
+    #[test]
+    fn test_normalize_authority() {
+        assert_eq!(
+            normalize_authority("Example.COM:443", Some("https")),
+            "example.com"
+        );
+        assert_eq!(
+            normalize_authority("example.com:80", Some("http")),
+            "example.com"
+        );
+        assert_eq!(
+            normalize_authority("example.com:443", Some("http")),
+            "example.com:443"
+        );
+        assert_eq!(normalize_authority("[::1]:443", Some("https")), "[::1]");
+        assert_eq!(normalize_authority("[::1]", Some("https")), "[::1]");
+        assert_eq!(
+            normalize_authority("example.com:80", None),
+            "example.com:80"
+        );
+    }
+
+    #[test]
+    fn test_authority_from_host_header() {
+        let request = Request::builder()
+            .method("GET")
+            .uri("/foo")
+            .header("host", "Example.com")
+            .body(())
+            .unwrap();
+        let result = canonicalize_request(&request, &[SignatureComponent::Authority]).unwrap();
+        assert_eq!(result[0].1, "example.com");
+    }
+
     #[test]
     fn test_canonicalize_method() {
         let request = Request::builder()
@@ -160,7 +735,7 @@ mod tests {
             .body(())
             .unwrap();
 
-        let components = vec![super::super::SignatureComponent::Method];
+        let components = vec![SignatureComponent::Method];
         let result = canonicalize_request(&request, &components).unwrap();
 
         assert_eq!(result.len(), 1);
@@ -177,13 +752,267 @@ mod tests {
             .body(())
             .unwrap();
 
-        let components = vec![super::super::SignatureComponent::Header(
-            "content-type".to_string(),
-        )];
+        let components = vec![SignatureComponent::Header("content-type".to_string())];
         let result = canonicalize_request(&request, &components).unwrap();
 
         assert_eq!(result.len(), 1);
         assert_eq!(result[0].0, "content-type");
         assert_eq!(result[0].1, "application/json");
     }
+
+    fn dict_request() -> Request<()> {
+        Request::builder()
+            .method("GET")
+            .uri("https://example.com/parameters?var=this%20is%20a%20big%0Avalue&bar=with+plus+whitespace&fa%C3%A7ade%22%3A%20=something")
+            .header("Example-Dict", " a=1,    b=2;x=1;y=2,   c=(a   b   c), d")
+            .body(())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_structured_field_parameters() {
+        // RFC 9421 Section 2.1.1 and 2.1.2
+        let request = dict_request();
+        let header = || SignatureComponent::Header("example-dict".to_string());
+
+        let result = canonicalize_request(&request, &[header().sf()]).unwrap();
+        assert_eq!(result[0].0, "\"example-dict\";sf");
+        assert_eq!(result[0].1, "a=1, b=2;x=1;y=2, c=(a b c), d");
+
+        let cases = [
+            ("a", "1"),
+            ("b", "2;x=1;y=2"),
+            ("c", "(a b c)"),
+            ("d", "?1"),
+        ];
+        for (key, expected) in cases {
+            let result = canonicalize_request(&request, &[header().key(key)]).unwrap();
+            assert_eq!(result[0].1, expected);
+        }
+        assert!(canonicalize_request(&request, &[header().key("missing")]).is_err());
+    }
+
+    #[test]
+    fn test_binary_wrapped_field() {
+        // RFC 9421 Section 2.1.3
+        let request = Request::builder()
+            .uri("https://example.com/")
+            .header("Example-Header", "value, with, lots")
+            .header("Example-Header", "of, commas")
+            .body(())
+            .unwrap();
+        let component = SignatureComponent::Header("example-header".to_string()).bs();
+        let result = canonicalize_request(&request, &[component]).unwrap();
+        assert_eq!(
+            result[0].1,
+            ":dmFsdWUsIHdpdGgsIGxvdHM=:, :b2YsIGNvbW1hcw==:"
+        );
+
+        let invalid = SignatureComponent::Header("example-header".to_string())
+            .bs()
+            .sf();
+        assert!(canonicalize_request(&request, &[invalid]).is_err());
+    }
+
+    #[test]
+    fn test_query_param_component() {
+        // RFC 9421 Section 2.2.8
+        let request = dict_request();
+        let components = [
+            SignatureComponent::query_param("var"),
+            SignatureComponent::query_param("bar"),
+            SignatureComponent::query_param("fa%C3%A7ade%22%3A%20"),
+        ];
+        let result = canonicalize_request(&request, &components).unwrap();
+        assert_eq!(result[0].0, "\"@query-param\";name=\"var\"");
+        assert_eq!(result[0].1, "this%20is%20a%20big%0Avalue");
+        assert_eq!(result[1].0, "\"@query-param\";name=\"bar\"");
+        assert_eq!(result[1].1, "with%20plus%20whitespace");
+        assert_eq!(
+            result[2].0,
+            "\"@query-param\";name=\"fa%C3%A7ade%22%3A%20\""
+        );
+        assert_eq!(result[2].1, "something");
+
+        let request = Request::builder()
+            .method("GET")
+            .uri("https://example.com/path?param=value&foo=bar&baz=batman&qux=")
+            .body(())
+            .unwrap();
+        let components = [
+            SignatureComponent::query_param("baz"),
+            SignatureComponent::query_param("qux"),
+            SignatureComponent::query_param("param"),
+        ];
+        let result = canonicalize_request(&request, &components).unwrap();
+        assert_eq!(result[0].1, "batman");
+        assert_eq!(result[1].1, "");
+        assert_eq!(result[2].1, "value");
+
+        // Names are compared after decoding
+        let request = Request::builder()
+            .method("GET")
+            .uri("https://example.com/?fa%c3%a7ade=1&a+b=2")
+            .body(())
+            .unwrap();
+        let components = [
+            SignatureComponent::query_param("fa%C3%A7ade"),
+            SignatureComponent::query_param("a%20b"),
+        ];
+        let result = canonicalize_request(&request, &components).unwrap();
+        assert_eq!(result[0].1, "1");
+        assert_eq!(result[1].1, "2");
+    }
+
+    #[test]
+    fn test_percent_encoding_normalization() {
+        assert_eq!(
+            normalize_percent_encoding("/caf%c3%a9/%7euser%2Fx?q=%41%2b%zz%"),
+            "/caf%C3%A9/~user%2Fx?q=A%2B%zz%"
+        );
+
+        let components = [SignatureComponent::Path, SignatureComponent::Query];
+        let encoded = Request::builder()
+            .uri("https://example.com/%7euser/a%2fb?name=%4a%6fe")
+            .body(())
+            .unwrap();
+        let plain = Request::builder()
+            .uri("https://example.com/~user/a%2Fb?name=Joe")
+            .body(())
+            .unwrap();
+
+        let options = CanonicalizeOptions {
+            normalize_percent_encoding: true,
+        };
+        let normalize = |request: &Request<()>| {
+            canonicalize_message_with::<_, Request<()>>(request, None, &components, &options)
+                .unwrap()
+        };
+        assert_eq!(normalize(&encoded), normalize(&plain));
+        assert_eq!(normalize(&plain)[0].1, "/~user/a%2Fb");
+        assert_ne!(
+            canonicalize_request(&encoded, &components).unwrap(),
+            canonicalize_request(&plain, &components).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_request_target_forms() {
+        let values = |request: &Request<()>| {
+            canonicalize_request(
+                request,
+                &[
+                    SignatureComponent::RequestTarget,
+                    SignatureComponent::Path,
+                    SignatureComponent::Query,
+                ],
+            )
+            .unwrap()
+            .into_iter()
+            .map(|(_, value)| value)
+            .collect::<Vec<_>>()
+        };
+        let target_uri = |request: &Request<()>| {
+            canonicalize_request(request, &[SignatureComponent::TargetUri])
+                .map(|values| values[0].1.clone())
+        };
+
+        // Origin-form
+        let request = Request::builder()
+            .uri("https://example.com/path?param=value")
+            .body(())
+            .unwrap();
+        assert_eq!(
+            values(&request),
+            ["/path?param=value", "/path", "?param=value"]
+        );
+        assert_eq!(
+            target_uri(&request).unwrap(),
+            "https://example.com/path?param=value"
+        );
+        let relative = Request::builder().uri("/path").body(()).unwrap();
+        assert!(target_uri(&relative).is_err());
+
+        // Absolute-form, as sent to a forward proxy
+        let mut request = Request::builder()
+            .uri("https://example.com/path?param=value")
+            .body(())
+            .unwrap();
+        request.extensions_mut().insert(RequestTargetForm::Absolute);
+        assert_eq!(
+            values(&request),
+            [
+                "https://example.com/path?param=value",
+                "/path",
+                "?param=value"
+            ]
+        );
+
+        // Authority-form
+        let request = Request::builder()
+            .method("CONNECT")
+            .uri("Example.com:443")
+            .body(())
+            .unwrap();
+        assert_eq!(values(&request), ["example.com:443", "/", "?"]);
+        let mut request = request;
+        request
+            .extensions_mut()
+            .insert(RequestTargetForm::Authority);
+        assert!(target_uri(&request).is_err());
+
+        // Asterisk-form
+        let request = Request::builder()
+            .method("OPTIONS")
+            .uri("*")
+            .header("host", "www.example.com")
+            .body(())
+            .unwrap();
+        assert_eq!(values(&request), ["*", "/", "?"]);
+
+        let message = MessageParts::request("OPTIONS", "https://www.example.com:8443")
+            .with_target_form(RequestTargetForm::Asterisk);
+        let result = canonicalize_message::<_, MessageParts>(
+            &message,
+            None,
+            &[
+                SignatureComponent::RequestTarget,
+                SignatureComponent::TargetUri,
+            ],
+        )
+        .unwrap();
+        assert_eq!(result[0].1, "*");
+        assert_eq!(result[1].1, "https://www.example.com:8443");
+    }
+
+    #[test]
+    fn test_request_response_and_trailer_parameters() {
+        let request = Request::builder()
+            .method("POST")
+            .uri("https://example.com/foo")
+            .body(())
+            .unwrap();
+        let mut response = Response::builder()
+            .status(200)
+            .header("content-type", "application/json")
+            .body(())
+            .unwrap();
+        let mut trailer_fields = HeaderMap::new();
+        trailer_fields.insert("expires", "Wed, 9 Nov 2022 07:28:00 GMT".parse().unwrap());
+        response.extensions_mut().insert(Trailers(trailer_fields));
+
+        let components = vec![
+            SignatureComponent::Method.req(),
+            SignatureComponent::Header("expires".to_string()).tr(),
+        ];
+        let result =
+            canonicalize_response_with_request(&response, Some(&request), &components).unwrap();
+        assert_eq!(result[0].0, "\"@method\";req");
+        assert_eq!(result[0].1, "POST");
+        assert_eq!(result[1].0, "\"expires\";tr");
+
+        // req requires the related request
+        assert!(canonicalize_response(&response, &components).is_err());
+        assert!(canonicalize_request(&request, &[SignatureComponent::Method.req()]).is_err());
+    }
 }
@@ -1,5 +1,6 @@
 //! HTTP signature components for RFC 9421
 
+use super::structured::{check_string, parse_string, BareItem};
 use std::fmt;
 
 /// Signature component identifier
@@ -30,9 +31,77 @@ pub enum SignatureComponent {
         /// Parameters associated with the derived component
         params: Vec<String>,
     },
+    /// Component with RFC 9421 component parameters (`sf`, `bs`, `key`, `req`, `tr`)
+    Parameterized {
+        /// The underlying component
+        component: Box<SignatureComponent>,
+        /// Component parameters
+        params: ComponentParams,
+    },
+}
+
+/// Component parameters defined in RFC 9421 Section 2.1
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComponentParams {
+    /// Strict structured field serialization (`sf`)
+    pub sf: bool,
+    /// Binary-wrapped field values (`bs`)
+    pub bs: bool,
+    /// Dictionary member selection (`key="..."`)
+    pub key: Option<String>,
+    /// Component taken from the related request (`req`)
+    pub req: bool,
+    /// Component taken from the trailer section (`tr`)
+    pub tr: bool,
+}
+
+impl ComponentParams {
+    /// Check whether no parameter is set
+    pub fn is_empty(&self) -> bool {
+        *self == ComponentParams::default()
+    }
+
+    /// Validate parameter combinations
+    pub fn validate(&self) -> crate::error::Result<()> {
+        if self.bs && (self.sf || self.key.is_some()) {
+            return Err(crate::error::Error::InvalidInput(
+                "The bs parameter cannot be combined with sf or key".to_string(),
+            ));
+        }
+        if let Some(ref key) = self.key {
+            check_string("The key parameter", key)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ComponentParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.sf {
+            write!(f, ";sf")?;
+        }
+        if let Some(ref key) = self.key {
+            write!(f, ";key={}", BareItem::String(key.clone()))?;
+        }
+        if self.bs {
+            write!(f, ";bs")?;
+        }
+        if self.req {
+            write!(f, ";req")?;
+        }
+        if self.tr {
+            write!(f, ";tr")?;
+        }
+        Ok(())
+    }
 }
 
 impl SignatureComponent {
+    /// The `content-digest` header component (RFC 9530)
+    pub fn content_digest() -> Self {
+        SignatureComponent::Header(super::digest::CONTENT_DIGEST_HEADER.to_string())
+    }
+
     /// Get the component identifier string
     pub fn identifier(&self) -> String {
         match self {
@@ -52,8 +121,147 @@ impl SignatureComponent {
                     format!("@{};{}", name, params.join(";"))
                 }
             }
+            SignatureComponent::Parameterized { component, .. } => component.identifier(),
+        }
+    }
+
+    /// Serialize the component as it appears in `Signature-Input`, e.g. `"content-type";sf`
+    pub fn serialize(&self) -> String {
+        match self {
+            SignatureComponent::DerivedComponent { name, params } => {
+                let mut out = format!("\"@{name}\"");
+                for param in params {
+                    out.push(';');
+                    out.push_str(param);
+                }
+                out
+            }
+            SignatureComponent::Parameterized { component, params } => {
+                format!("{}{}", component.serialize(), params)
+            }
+            _ => format!("\"{}\"", self.identifier()),
+        }
+    }
+
+    /// Get the component without its parameters
+    pub fn base(&self) -> &SignatureComponent {
+        match self {
+            SignatureComponent::Parameterized { component, .. } => component.base(),
+            _ => self,
+        }
+    }
+
+    /// Get the component parameters, if any
+    pub fn params(&self) -> Option<&ComponentParams> {
+        match self {
+            SignatureComponent::Parameterized { params, .. } => Some(params),
+            _ => None,
         }
     }
+
+    fn with_params(self, update: impl FnOnce(&mut ComponentParams)) -> Self {
+        let (component, mut params) = match self {
+            SignatureComponent::Parameterized { component, params } => (component, params),
+            other => (Box::new(other), ComponentParams::default()),
+        };
+        update(&mut params);
+        SignatureComponent::Parameterized { component, params }
+    }
+
+    /// Add the `sf` parameter
+    pub fn sf(self) -> Self {
+        self.with_params(|p| p.sf = true)
+    }
+
+    /// Add the `bs` parameter
+    pub fn bs(self) -> Self {
+        self.with_params(|p| p.bs = true)
+    }
+
+    /// Add the `key` parameter selecting a dictionary member
+    pub fn key(self, key: impl Into<String>) -> Self {
+        let key = key.into();
+        self.with_params(|p| p.key = Some(key))
+    }
+
+    /// Add the `req` parameter
+    pub fn req(self) -> Self {
+        self.with_params(|p| p.req = true)
+    }
+
+    /// Add the `tr` parameter
+    pub fn tr(self) -> Self {
+        self.with_params(|p| p.tr = true)
+    }
+
+    /// The `@query-param` derived component for a named query parameter
+    ///
+    /// `name` is percent-encoded as in the query, e.g. `fa%C3%A7ade`.
+    pub fn query_param(name: impl Into<String>) -> Self {
+        SignatureComponent::DerivedComponent {
+            name: "query-param".to_string(),
+            params: vec![format!("name={}", BareItem::String(name.into()))],
+        }
+    }
+}
+
+/// Check a list of covered components
+///
+/// Rejects repeated components, invalid component parameters, `@status` in
+/// requests and request-only derived components in responses unless they
+/// carry the `req` parameter.
+pub fn validate_components(
+    components: &[SignatureComponent],
+    is_response: bool,
+) -> crate::error::Result<()> {
+    use crate::error::Error;
+
+    let mut seen: Vec<String> = Vec::with_capacity(components.len());
+    for component in components {
+        let id = component.serialize();
+        if seen.contains(&id) {
+            return Err(Error::InvalidInput(format!("Duplicate component: {id}")));
+        }
+
+        if let Some(params) = component.params() {
+            params.validate()?;
+        }
+        if let SignatureComponent::DerivedComponent { params, .. } = component.base() {
+            for param in params {
+                if let Some(name) = param.strip_prefix("name=") {
+                    check_string("The @query-param name", &parse_string(name)?)?;
+                }
+            }
+        }
+
+        let req = component.params().is_some_and(|p| p.req);
+        match component.base() {
+            SignatureComponent::Status if !is_response => {
+                return Err(Error::InvalidInput(
+                    "@status component is only valid for responses".to_string(),
+                ));
+            }
+            SignatureComponent::Method
+            | SignatureComponent::TargetUri
+            | SignatureComponent::Authority
+            | SignatureComponent::Scheme
+            | SignatureComponent::RequestTarget
+            | SignatureComponent::Path
+            | SignatureComponent::Query
+            | SignatureComponent::DerivedComponent { .. }
+                if is_response && !req =>
+            {
+                return Err(Error::InvalidInput(format!(
+                    "{id} requires the req parameter in responses"
+                )));
+            }
+            _ => {}
+        }
+
+        seen.push(id);
+    }
+
+    Ok(())
 }
 
 /// Signature parameters
@@ -73,16 +281,34 @@ pub struct SignatureParams {
     pub tag: Option<String>,
 }
 
+impl SignatureParams {
+    /// Check that the string parameters can be serialized in `Signature-Input`
+    pub fn validate(&self) -> crate::error::Result<()> {
+        for (name, value) in [
+            ("keyid", &self.key_id),
+            ("alg", &self.alg),
+            ("nonce", &self.nonce),
+            ("tag", &self.tag),
+        ] {
+            if let Some(value) = value {
+                check_string(&format!("The {name} parameter"), value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// String parameters are serialized as RFC 8941 Strings, escaping `"` and `\`
 impl fmt::Display for SignatureParams {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut params = Vec::new();
 
         if let Some(ref key_id) = self.key_id {
-            params.push(format!("keyid=\"{key_id}\""));
+            params.push(format!("keyid={}", BareItem::String(key_id.clone())));
         }
 
         if let Some(ref alg) = self.alg {
-            params.push(format!("alg=\"{alg}\""));
+            params.push(format!("alg={}", BareItem::String(alg.clone())));
         }
 
         if let Some(created) = self.created {
@@ -94,11 +320,11 @@ impl fmt::Display for SignatureParams {
         }
 
         if let Some(ref nonce) = self.nonce {
-            params.push(format!("nonce=\"{nonce}\""));
+            params.push(format!("nonce={}", BareItem::String(nonce.clone())));
         }
 
         if let Some(ref tag) = self.tag {
-            params.push(format!("tag=\"{tag}\""));
+            params.push(format!("tag={}", BareItem::String(tag.clone())));
         }
 
         write!(f, "{}", params.join(";"))
@@ -109,6 +335,33 @@ impl fmt::Display for SignatureParams {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_validate_components() {
+        let request = [
+            SignatureComponent::Method,
+            SignatureComponent::Header("Content-Type".to_string()),
+        ];
+        assert!(validate_components(&request, false).is_ok());
+        assert!(validate_components(&request, true).is_err());
+
+        let duplicate = [
+            SignatureComponent::Header("content-type".to_string()),
+            SignatureComponent::Header("Content-Type".to_string()),
+        ];
+        assert!(validate_components(&duplicate, false).is_err());
+
+        // The same field with different parameters is a distinct component
+        let distinct = [
+            SignatureComponent::Header("example-dict".to_string()),
+            SignatureComponent::Header("example-dict".to_string()).sf(),
+        ];
+        assert!(validate_components(&distinct, false).is_ok());
+
+        assert!(validate_components(&[SignatureComponent::Status], false).is_err());
+        let response = [SignatureComponent::Status, SignatureComponent::Method.req()];
+        assert!(validate_components(&response, true).is_ok());
+    }
+
     #[test]
     fn test_component_identifier() {
         assert_eq!(SignatureComponent::Method.identifier(), "@method");
@@ -118,6 +371,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_component_params_serialization() {
+        let component = SignatureComponent::Header("Example-Dict".to_string()).key("a");
+        assert_eq!(component.serialize(), "\"example-dict\";key=\"a\"");
+        assert_eq!(component.identifier(), "example-dict");
+
+        let component = SignatureComponent::Method.req();
+        assert_eq!(component.serialize(), "\"@method\";req");
+
+        let component = SignatureComponent::Header("x".to_string()).sf().tr();
+        assert_eq!(component.serialize(), "\"x\";sf;tr");
+
+        let component = SignatureComponent::query_param("Pet");
+        assert_eq!(component.serialize(), "\"@query-param\";name=\"Pet\"");
+    }
+
     #[test]
     fn test_signature_params_display() {
         let params = SignatureParams {
@@ -134,4 +403,37 @@ mod tests {
         assert!(display.contains("alg=\"ed25519\""));
         assert!(display.contains("created=1234567890"));
     }
+
+    #[test]
+    fn test_signature_params_escaping() {
+        let params = SignatureParams {
+            key_id: Some(r#"key "1" \ a"#.to_string()),
+            tag: Some(r#"x";expires=9999999999"#.to_string()),
+            ..Default::default()
+        };
+        assert!(params.validate().is_ok());
+        let component = SignatureComponent::query_param(r#"a"b\c"#);
+        assert!(validate_components(std::slice::from_ref(&component), false).is_ok());
+
+        let input = format!("({});{params}", component.serialize());
+        let (components, parsed) = super::super::verifier::parse_signature_input(&input).unwrap();
+        assert_eq!(components, vec![component]);
+        assert_eq!(parsed.key_id, params.key_id);
+        assert_eq!(parsed.tag, params.tag);
+        assert_eq!(parsed.expires, None);
+
+        let control = SignatureParams {
+            nonce: Some("a\nb".to_string()),
+            ..Default::default()
+        };
+        assert!(control.validate().is_err());
+        let non_ascii = [SignatureComponent::query_param("caf\u{e9}")];
+        assert!(validate_components(&non_ascii, false).is_err());
+        assert!(ComponentParams {
+            key: Some("\t".to_string()),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+    }
 }
@@ -0,0 +1,513 @@
+//! Content-Digest (RFC 9530) generation and validation
+
+use super::policy::VerificationPolicy;
+use crate::error::{Error, Result};
+use base64::{engine::general_purpose, Engine as _};
+use http::{HeaderMap, HeaderValue, Request, Response};
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+/// Name of the Content-Digest header field
+pub const CONTENT_DIGEST_HEADER: &str = "content-digest";
+
+/// Digest algorithms from the IANA Hash Algorithms for HTTP Digest Fields registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestAlgorithm {
+    /// SHA-256
+    Sha256,
+    /// SHA-512
+    Sha512,
+}
+
+impl DigestAlgorithm {
+    /// Get the algorithm identifier string
+    pub fn identifier(&self) -> &'static str {
+        match self {
+            DigestAlgorithm::Sha256 => "sha-256",
+            DigestAlgorithm::Sha512 => "sha-512",
+        }
+    }
+
+    /// Look up an algorithm by its identifier
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        match identifier {
+            "sha-256" => Some(DigestAlgorithm::Sha256),
+            "sha-512" => Some(DigestAlgorithm::Sha512),
+            _ => None,
+        }
+    }
+
+    /// Hash the given data
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            DigestAlgorithm::Sha256 => Sha256::digest(data).to_vec(),
+            DigestAlgorithm::Sha512 => Sha512::digest(data).to_vec(),
+        }
+    }
+}
+
+/// A hash algorithm usable in Content-Digest fields
+pub trait DigestHasher: Send + Sync {
+    /// The algorithm identifier, e.g. `sha-256`
+    fn identifier(&self) -> &'static str;
+
+    /// Hash the given data
+    fn digest(&self, data: &[u8]) -> Vec<u8>;
+}
+
+impl DigestHasher for DigestAlgorithm {
+    fn identifier(&self) -> &'static str {
+        DigestAlgorithm::identifier(self)
+    }
+
+    fn digest(&self, data: &[u8]) -> Vec<u8> {
+        DigestAlgorithm::digest(self, data)
+    }
+}
+
+impl<H: DigestHasher + ?Sized> DigestHasher for Arc<H> {
+    fn identifier(&self) -> &'static str {
+        (**self).identifier()
+    }
+
+    fn digest(&self, data: &[u8]) -> Vec<u8> {
+        (**self).digest(data)
+    }
+}
+
+/// Digest algorithms recognised when parsing and validating Content-Digest fields
+///
+/// Contains `sha-256` and `sha-512` by default.
+#[derive(Clone)]
+pub struct DigestRegistry {
+    hashers: HashMap<&'static str, Arc<dyn DigestHasher>>,
+}
+
+impl Default for DigestRegistry {
+    fn default() -> Self {
+        let mut hashers: HashMap<&'static str, Arc<dyn DigestHasher>> = HashMap::new();
+        for algorithm in [DigestAlgorithm::Sha256, DigestAlgorithm::Sha512] {
+            hashers.insert(algorithm.identifier(), Arc::new(algorithm));
+        }
+        Self { hashers }
+    }
+}
+
+impl DigestRegistry {
+    /// Create a registry with the built-in algorithms
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register an additional digest algorithm
+    ///
+    /// Identifiers of built-in algorithms cannot be overridden.
+    pub fn register(&mut self, hasher: Arc<dyn DigestHasher>) -> Result<()> {
+        let identifier = hasher.identifier();
+        if DigestAlgorithm::from_identifier(identifier).is_some() {
+            return Err(Error::InvalidInput(format!(
+                "Digest algorithm {identifier} is built in and cannot be replaced"
+            )));
+        }
+        self.hashers.insert(identifier, hasher);
+        Ok(())
+    }
+
+    /// Look up a digest algorithm by identifier
+    pub fn get(&self, identifier: &str) -> Option<&Arc<dyn DigestHasher>> {
+        self.hashers.get(identifier)
+    }
+}
+
+impl fmt::Debug for DigestRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.hashers.keys()).finish()
+    }
+}
+
+/// Hash state of a built-in digest algorithm
+#[derive(Clone)]
+enum HashState {
+    Sha256(Sha256),
+    Sha512(Sha512),
+}
+
+/// Incremental Content-Digest computation for streamed bodies
+///
+/// Feed the body in chunks with [`ContentDigestBuilder::update`], then
+/// [`ContentDigestBuilder::finalize`] or [`ContentDigestBuilder::verify`].
+#[derive(Clone)]
+pub struct ContentDigestBuilder {
+    states: Vec<(DigestAlgorithm, HashState)>,
+}
+
+impl ContentDigestBuilder {
+    /// Start digesting a body with a single algorithm
+    pub fn new(algorithm: DigestAlgorithm) -> Self {
+        Self::with_algorithms(&[algorithm])
+    }
+
+    /// Start digesting a body with several algorithms
+    pub fn with_algorithms(algorithms: &[DigestAlgorithm]) -> Self {
+        Self {
+            states: algorithms
+                .iter()
+                .map(|alg| {
+                    let state = match alg {
+                        DigestAlgorithm::Sha256 => HashState::Sha256(Sha256::new()),
+                        DigestAlgorithm::Sha512 => HashState::Sha512(Sha512::new()),
+                    };
+                    (*alg, state)
+                })
+                .collect(),
+        }
+    }
+
+    /// Hash the next chunk of the body
+    pub fn update(&mut self, chunk: &[u8]) -> &mut Self {
+        for (_, state) in &mut self.states {
+            match state {
+                HashState::Sha256(hasher) => hasher.update(chunk),
+                HashState::Sha512(hasher) => hasher.update(chunk),
+            }
+        }
+        self
+    }
+
+    /// Finish hashing and produce the Content-Digest
+    pub fn finalize(self) -> ContentDigest {
+        ContentDigest {
+            entries: self
+                .states
+                .into_iter()
+                .map(|(alg, state)| {
+                    let value = match state {
+                        HashState::Sha256(hasher) => hasher.finalize().to_vec(),
+                        HashState::Sha512(hasher) => hasher.finalize().to_vec(),
+                    };
+                    (alg.identifier(), value)
+                })
+                .collect(),
+        }
+    }
+
+    /// Finish hashing and check the body against a received Content-Digest
+    ///
+    /// Every algorithm present in both digests must match, and at least one must be shared.
+    pub fn verify(self, expected: &ContentDigest) -> Result<()> {
+        let computed = self.finalize();
+        let mut matched = false;
+
+        for (alg, value) in &computed.entries {
+            if let Some(expected_value) = expected.get_by_identifier(alg) {
+                if expected_value != value.as_slice() {
+                    return Err(Error::Verification(format!(
+                        "Content-digest mismatch for {alg}"
+                    )));
+                }
+                matched = true;
+            }
+        }
+
+        if !matched {
+            return Err(Error::Verification(
+                "Content-digest shares no algorithm with the computed digest".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Content-Digest field value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContentDigest {
+    entries: Vec<(&'static str, Vec<u8>)>,
+}
+
+impl ContentDigest {
+    /// Compute a digest of the body with a single algorithm
+    pub fn compute(body: &[u8], algorithm: impl DigestHasher) -> Self {
+        Self {
+            entries: vec![(algorithm.identifier(), algorithm.digest(body))],
+        }
+    }
+
+    /// Compute digests of the body with several algorithms
+    pub fn compute_all(body: &[u8], algorithms: &[DigestAlgorithm]) -> Self {
+        Self {
+            entries: algorithms
+                .iter()
+                .map(|alg| (alg.identifier(), alg.digest(body)))
+                .collect(),
+        }
+    }
+
+    /// Get the digest for a specific algorithm
+    pub fn get(&self, algorithm: DigestAlgorithm) -> Option<&[u8]> {
+        self.get_by_identifier(algorithm.identifier())
+    }
+
+    /// Get the digest for an algorithm identifier
+    pub fn get_by_identifier(&self, identifier: &str) -> Option<&[u8]> {
+        self.entries
+            .iter()
+            .find(|(alg, _)| *alg == identifier)
+            .map(|(_, value)| value.as_slice())
+    }
+
+    /// Identifiers of the algorithms present in this digest
+    pub fn algorithms(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.entries.iter().map(|(alg, _)| *alg)
+    }
+
+    /// Serialize as a structured field dictionary, e.g. `sha-256=:...:`
+    pub fn header_value(&self) -> String {
+        self.entries
+            .iter()
+            .map(|(alg, value)| format!("{alg}=:{}:", general_purpose::STANDARD.encode(value)))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Parse a Content-Digest header value
+    ///
+    /// Entries with unknown algorithms are ignored, as required by RFC 9530.
+    pub fn parse(value: &str) -> Result<Self> {
+        Self::parse_with(value, &DigestRegistry::default())
+    }
+
+    /// Parse a Content-Digest header value, recognising the algorithms of `registry`
+    pub fn parse_with(value: &str, registry: &DigestRegistry) -> Result<Self> {
+        let mut entries = Vec::new();
+
+        for member in value.split(',') {
+            let member = member.trim();
+            if member.is_empty() {
+                continue;
+            }
+
+            let (name, encoded) = member.split_once('=').ok_or_else(|| {
+                Error::InvalidInput(format!("Invalid content-digest member: {member}"))
+            })?;
+
+            let encoded = encoded
+                .strip_prefix(':')
+                .and_then(|v| v.strip_suffix(':'))
+                .ok_or_else(|| {
+                    Error::InvalidInput(format!(
+                        "Content-digest value must be a byte sequence: {member}"
+                    ))
+                })?;
+
+            if let Some(hasher) = registry.get(name.trim()) {
+                entries.push((
+                    hasher.identifier(),
+                    general_purpose::STANDARD.decode(encoded)?,
+                ));
+            }
+        }
+
+        if entries.is_empty() {
+            return Err(Error::InvalidInput(
+                "Content-digest contains no supported algorithm".to_string(),
+            ));
+        }
+
+        Ok(Self { entries })
+    }
+
+    /// Check that every recognised digest matches the body
+    pub fn verify(&self, body: &[u8]) -> Result<()> {
+        self.verify_with(body, &DigestRegistry::default())
+    }
+
+    /// Check that every digest matches the body, using the algorithms of `registry`
+    pub fn verify_with(&self, body: &[u8], registry: &DigestRegistry) -> Result<()> {
+        for (alg, expected) in &self.entries {
+            let hasher = registry.get(alg).ok_or_else(|| {
+                Error::Unsupported(format!("Unsupported digest algorithm: {alg}"))
+            })?;
+            if &hasher.digest(body) != expected {
+                return Err(Error::Verification(format!(
+                    "Content-digest mismatch for {alg}"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert the Content-Digest header, replacing any existing value
+    pub fn insert_into(&self, headers: &mut HeaderMap) -> Result<()> {
+        let value = HeaderValue::from_str(&self.header_value())
+            .map_err(|_| Error::InvalidInput("Invalid content-digest value".to_string()))?;
+        headers.insert(CONTENT_DIGEST_HEADER, value);
+        Ok(())
+    }
+
+    /// Read the Content-Digest header from a header map
+    pub fn from_headers(headers: &HeaderMap) -> Result<Self> {
+        let value = headers
+            .get(CONTENT_DIGEST_HEADER)
+            .ok_or_else(|| Error::InvalidInput("Missing content-digest header".to_string()))?
+            .to_str()
+            .map_err(|_| {
+                Error::InvalidInput("Invalid content-digest header encoding".to_string())
+            })?;
+        Self::parse(value)
+    }
+
+    /// Compute the digest of a request body and set the Content-Digest header
+    pub fn apply_to_request<B: AsRef<[u8]>>(
+        request: &mut Request<B>,
+        algorithm: impl DigestHasher,
+    ) -> Result<Self> {
+        let digest = Self::compute(request.body().as_ref(), algorithm);
+        digest.insert_into(request.headers_mut())?;
+        Ok(digest)
+    }
+
+    /// Compute the digest of a response body and set the Content-Digest header
+    pub fn apply_to_response<B: AsRef<[u8]>>(
+        response: &mut Response<B>,
+        algorithm: impl DigestHasher,
+    ) -> Result<Self> {
+        let digest = Self::compute(response.body().as_ref(), algorithm);
+        digest.insert_into(response.headers_mut())?;
+        Ok(digest)
+    }
+
+    /// Validate the Content-Digest header of a request against its body
+    pub fn verify_request<B: AsRef<[u8]>>(request: &Request<B>) -> Result<()> {
+        Self::from_headers(request.headers())?.verify(request.body().as_ref())
+    }
+
+    /// Validate the Content-Digest header of a response against its body
+    pub fn verify_response<B: AsRef<[u8]>>(response: &Response<B>) -> Result<()> {
+        Self::from_headers(response.headers())?.verify(response.body().as_ref())
+    }
+
+    /// Validate the Content-Digest of a request, requiring an algorithm `policy` allows
+    pub fn verify_request_with_policy<B: AsRef<[u8]>>(
+        request: &Request<B>,
+        policy: &VerificationPolicy,
+    ) -> Result<()> {
+        let digest = Self::from_headers(request.headers())?;
+        policy.check_content_digest(&digest)?;
+        digest.verify(request.body().as_ref())
+    }
+
+    /// Validate the Content-Digest of a response, requiring an algorithm `policy` allows
+    pub fn verify_response_with_policy<B: AsRef<[u8]>>(
+        response: &Response<B>,
+        policy: &VerificationPolicy,
+    ) -> Result<()> {
+        let digest = Self::from_headers(response.headers())?;
+        policy.check_content_digest(&digest)?;
+        digest.verify(response.body().as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rfc9530_sha256_example() {
+        // RFC 9530 Section 2 example body
+        let digest = ContentDigest::compute(b"{\"hello\": \"world\"}", DigestAlgorithm::Sha256);
+        assert_eq!(
+            digest.header_value(),
+            "sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:"
+        );
+    }
+
+    #[test]
+    fn test_parse_roundtrip_and_verify() {
+        let body = b"payload";
+        let digest =
+            ContentDigest::compute_all(body, &[DigestAlgorithm::Sha256, DigestAlgorithm::Sha512]);
+        let parsed = ContentDigest::parse(&digest.header_value()).unwrap();
+        assert_eq!(parsed, digest);
+        assert!(parsed.verify(body).is_ok());
+        assert!(parsed.verify(b"tampered").is_err());
+    }
+
+    #[test]
+    fn test_parse_ignores_unknown_algorithms() {
+        let value = "unixsum=:MTIz:, sha-256=:X48E9qOokqqrvdts8nOJRJN3OWDUoyWxBf7kbu9DBPE=:";
+        let parsed = ContentDigest::parse(value).unwrap();
+        assert!(parsed.get(DigestAlgorithm::Sha256).is_some());
+        assert!(ContentDigest::parse("unixsum=:MTIz:").is_err());
+        assert!(ContentDigest::parse("sha-256=abc").is_err());
+    }
+
+    struct Xor;
+
+    impl DigestHasher for Xor {
+        fn identifier(&self) -> &'static str {
+            "xor"
+        }
+
+        fn digest(&self, data: &[u8]) -> Vec<u8> {
+            vec![data.iter().fold(0, |acc, b| acc ^ b)]
+        }
+    }
+
+    #[test]
+    fn test_custom_digest_algorithm() {
+        let mut registry = DigestRegistry::new();
+        registry.register(Arc::new(Xor)).unwrap();
+        assert!(registry
+            .register(Arc::new(DigestAlgorithm::Sha256))
+            .is_err());
+
+        let digest = ContentDigest::compute(b"\x01\x02", Xor);
+        assert_eq!(digest.header_value(), "xor=:Aw==:");
+
+        let parsed = ContentDigest::parse_with(&digest.header_value(), &registry).unwrap();
+        assert!(parsed.verify_with(b"\x01\x02", &registry).is_ok());
+        assert!(parsed.verify_with(b"\x01", &registry).is_err());
+        assert!(ContentDigest::parse(&digest.header_value()).is_err());
+        assert!(digest.verify(b"\x01\x02").is_err());
+    }
+
+    #[test]
+    fn test_streaming_digest() {
+        let body = b"{\"hello\": \"world\"}";
+        let mut builder = ContentDigestBuilder::with_algorithms(&[
+            DigestAlgorithm::Sha256,
+            DigestAlgorithm::Sha512,
+        ]);
+        for chunk in body.chunks(5) {
+            builder.update(chunk);
+        }
+        assert_eq!(
+            builder.clone().finalize(),
+            ContentDigest::compute_all(body, &[DigestAlgorithm::Sha256, DigestAlgorithm::Sha512])
+        );
+
+        let expected = ContentDigest::compute(body, DigestAlgorithm::Sha256);
+        assert!(builder.verify(&expected).is_ok());
+
+        let mut tampered = ContentDigestBuilder::new(DigestAlgorithm::Sha256);
+        tampered.update(b"{}");
+        assert!(tampered.verify(&expected).is_err());
+
+        let unshared = ContentDigestBuilder::new(DigestAlgorithm::Sha512);
+        assert!(unshared.verify(&expected).is_err());
+    }
+
+    #[test]
+    fn test_apply_to_request() {
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("https://example.com/")
+            .body(b"{\"hello\": \"world\"}".to_vec())
+            .unwrap();
+        ContentDigest::apply_to_request(&mut request, DigestAlgorithm::Sha256).unwrap();
+        assert!(request.headers().contains_key(CONTENT_DIGEST_HEADER));
+        assert!(ContentDigest::verify_request(&request).is_ok());
+    }
+}
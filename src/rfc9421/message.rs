@@ -0,0 +1,271 @@
+//! Minimal HTTP message abstraction for RFC 9421 signing and verification
+
+use crate::rfc9421::canonicalize::Trailers;
+use http::{request, Request, Response, Uri};
+
+/// Form of the request-target sent on the wire (RFC 9112 Section 3.2)
+///
+/// Store it in the extensions of a request to override the form inferred from its URI.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestTargetForm {
+    /// `/path?query`, used for requests to an origin server
+    Origin,
+    /// `https://example.com/path?query`, used for requests to a proxy
+    Absolute,
+    /// `example.com:443`, used by `CONNECT`
+    Authority,
+    /// `*`, used by server-wide `OPTIONS`
+    Asterisk,
+}
+
+impl RequestTargetForm {
+    /// Infer the form from a target URI
+    ///
+    /// Absolute URIs are treated as origin-form, since clients usually hold the
+    /// absolute URI of a request they send to the origin server directly.
+    pub fn infer(uri: &Uri) -> Self {
+        match (uri.scheme(), uri.authority()) {
+            (None, None) if uri.path() == "*" => RequestTargetForm::Asterisk,
+            (None, Some(_)) => RequestTargetForm::Authority,
+            _ => RequestTargetForm::Origin,
+        }
+    }
+}
+
+/// Read-only view of an HTTP message
+///
+/// Implement this for the request and response types of HTTP stacks that are
+/// not built on the `http` crate. A message with a status code is treated as a
+/// response, otherwise as a request.
+pub trait HttpMessage {
+    /// Request method
+    fn method(&self) -> Option<&str>;
+
+    /// Target URI of a request, absolute or origin-form
+    fn target_uri(&self) -> Option<String>;
+
+    /// Status code of a response
+    fn status(&self) -> Option<u16>;
+
+    /// All values of a header field, in order; `name` is lowercase
+    fn header_values(&self, name: &str) -> Vec<&[u8]>;
+
+    /// All values of a trailer field, or `None` if the message has no trailers
+    fn trailer_values(&self, _name: &str) -> Option<Vec<&[u8]>> {
+        None
+    }
+
+    /// Form of the request-target, or `None` to infer it from the target URI
+    fn request_target_form(&self) -> Option<RequestTargetForm> {
+        None
+    }
+}
+
+impl<B> HttpMessage for Request<B> {
+    fn method(&self) -> Option<&str> {
+        Some(Request::method(self).as_str())
+    }
+
+    fn target_uri(&self) -> Option<String> {
+        Some(self.uri().to_string())
+    }
+
+    fn status(&self) -> Option<u16> {
+        None
+    }
+
+    fn header_values(&self, name: &str) -> Vec<&[u8]> {
+        self.headers()
+            .get_all(name)
+            .iter()
+            .map(|v| v.as_bytes())
+            .collect()
+    }
+
+    fn trailer_values(&self, name: &str) -> Option<Vec<&[u8]>> {
+        let trailers = self.extensions().get::<Trailers>()?;
+        Some(
+            trailers
+                .0
+                .get_all(name)
+                .iter()
+                .map(|v| v.as_bytes())
+                .collect(),
+        )
+    }
+
+    fn request_target_form(&self) -> Option<RequestTargetForm> {
+        self.extensions().get::<RequestTargetForm>().copied()
+    }
+}
+
+impl HttpMessage for request::Parts {
+    fn method(&self) -> Option<&str> {
+        Some(self.method.as_str())
+    }
+
+    fn target_uri(&self) -> Option<String> {
+        Some(self.uri.to_string())
+    }
+
+    fn status(&self) -> Option<u16> {
+        None
+    }
+
+    fn header_values(&self, name: &str) -> Vec<&[u8]> {
+        self.headers
+            .get_all(name)
+            .iter()
+            .map(|v| v.as_bytes())
+            .collect()
+    }
+
+    fn trailer_values(&self, name: &str) -> Option<Vec<&[u8]>> {
+        let trailers = self.extensions.get::<Trailers>()?;
+        Some(
+            trailers
+                .0
+                .get_all(name)
+                .iter()
+                .map(|v| v.as_bytes())
+                .collect(),
+        )
+    }
+
+    fn request_target_form(&self) -> Option<RequestTargetForm> {
+        self.extensions.get::<RequestTargetForm>().copied()
+    }
+}
+
+impl<B> HttpMessage for Response<B> {
+    fn method(&self) -> Option<&str> {
+        None
+    }
+
+    fn target_uri(&self) -> Option<String> {
+        None
+    }
+
+    fn status(&self) -> Option<u16> {
+        Some(Response::status(self).as_u16())
+    }
+
+    fn header_values(&self, name: &str) -> Vec<&[u8]> {
+        self.headers()
+            .get_all(name)
+            .iter()
+            .map(|v| v.as_bytes())
+            .collect()
+    }
+
+    fn trailer_values(&self, name: &str) -> Option<Vec<&[u8]>> {
+        let trailers = self.extensions().get::<Trailers>()?;
+        Some(
+            trailers
+                .0
+                .get_all(name)
+                .iter()
+                .map(|v| v.as_bytes())
+                .collect(),
+        )
+    }
+}
+
+/// Owned HTTP message built from its parts
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MessageParts {
+    /// Request method
+    pub method: Option<String>,
+    /// Target URI of a request
+    pub target_uri: Option<String>,
+    /// Form of the request-target, inferred from `target_uri` if unset
+    pub target_form: Option<RequestTargetForm>,
+    /// Status code of a response
+    pub status: Option<u16>,
+    /// Header fields in order
+    pub headers: Vec<(String, String)>,
+}
+
+impl MessageParts {
+    /// Create a request with a method and target URI
+    pub fn request(method: impl Into<String>, target_uri: impl Into<String>) -> Self {
+        Self {
+            method: Some(method.into()),
+            target_uri: Some(target_uri.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Create a response with a status code
+    pub fn response(status: u16) -> Self {
+        Self {
+            status: Some(status),
+            ..Default::default()
+        }
+    }
+
+    /// Set the form of the request-target
+    pub fn with_target_form(mut self, form: RequestTargetForm) -> Self {
+        self.target_form = Some(form);
+        self
+    }
+
+    /// Add a header field
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+}
+
+impl HttpMessage for MessageParts {
+    fn method(&self) -> Option<&str> {
+        self.method.as_deref()
+    }
+
+    fn target_uri(&self) -> Option<String> {
+        self.target_uri.clone()
+    }
+
+    fn status(&self) -> Option<u16> {
+        self.status
+    }
+
+    fn header_values(&self, name: &str) -> Vec<&[u8]> {
+        self.headers
+            .iter()
+            .filter(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.trim().as_bytes())
+            .collect()
+    }
+
+    fn request_target_form(&self) -> Option<RequestTargetForm> {
+        self.target_form
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_parts_headers() {
+        let message = MessageParts::request("GET", "https://example.com/")
+            .with_header("X-Example", "one")
+            .with_header("x-example", " two ");
+        assert_eq!(
+            message.header_values("x-example"),
+            vec![b"one".as_slice(), b"two".as_slice()]
+        );
+        assert!(message.header_values("missing").is_empty());
+        assert!(message.status().is_none());
+    }
+
+    #[test]
+    fn test_infer_request_target_form() {
+        let infer = |uri: &str| RequestTargetForm::infer(&uri.parse().unwrap());
+        assert_eq!(infer("/path?q=1"), RequestTargetForm::Origin);
+        assert_eq!(infer("https://example.com/path"), RequestTargetForm::Origin);
+        assert_eq!(infer("example.com:443"), RequestTargetForm::Authority);
+        assert_eq!(infer("*"), RequestTargetForm::Asterisk);
+    }
+}
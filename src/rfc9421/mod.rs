@@ -1,13 +1,37 @@
 //! RFC 9421 HTTP Message Signatures implementation
 
+pub mod algorithm;
 pub mod canonicalize;
 pub mod components;
+pub mod digest;
+pub mod message;
+pub mod nonce;
+pub mod policy;
+pub mod quorum;
+pub mod report;
+pub mod resolver;
 pub mod signer;
+mod structured;
 pub mod verifier;
 
-pub use components::{SignatureComponent, SignatureParams};
-pub use signer::HttpSigner;
-pub use verifier::HttpVerifier;
+pub use algorithm::{AlgorithmRegistry, CustomSigner, CustomVerifier};
+pub use canonicalize::{CanonicalizeOptions, SignatureBase};
+pub use components::{validate_components, ComponentParams, SignatureComponent, SignatureParams};
+pub use digest::{
+    ContentDigest, ContentDigestBuilder, DigestAlgorithm, DigestHasher, DigestRegistry,
+};
+pub use message::{HttpMessage, MessageParts, RequestTargetForm};
+pub use nonce::{InMemoryNonceStore, NonceStore};
+pub use policy::VerificationPolicy;
+pub use quorum::{QuorumReport, QuorumVerifier};
+pub use report::VerificationReport;
+pub use resolver::{KeyResolver, KeySet};
+pub use signer::{Expiry, HttpSigner, HttpSignerBuilder, SignatureHeaders};
+pub use verifier::{
+    extract_signature_headers, parse_signature_input, signature_labels, HttpVerifier,
+};
+
+use crate::crypto::KeyType;
 
 /// Signature algorithm identifiers for RFC 9421
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -18,6 +42,8 @@ pub enum SignatureAlgorithm {
     EcdsaP256Sha256,
     /// ECDSA Secp256k1 SHA-256
     EcdsaSecp256k1Sha256,
+    /// Algorithm provided by a [`CustomSigner`] or [`CustomVerifier`]
+    Custom(&'static str),
 }
 
 impl SignatureAlgorithm {
@@ -27,13 +53,42 @@ impl SignatureAlgorithm {
             SignatureAlgorithm::Ed25519 => "ed25519",
             SignatureAlgorithm::EcdsaP256Sha256 => "ecdsa-p256-sha256",
             SignatureAlgorithm::EcdsaSecp256k1Sha256 => "ecdsa-secp256k1-sha256",
+            SignatureAlgorithm::Custom(identifier) => identifier,
+        }
+    }
+
+    /// The algorithm used to sign with a key type
+    pub fn for_key_type(key_type: KeyType) -> Self {
+        match key_type {
+            KeyType::Ed25519 => SignatureAlgorithm::Ed25519,
+            KeyType::Secp256k1 => SignatureAlgorithm::EcdsaSecp256k1Sha256,
+        }
+    }
+
+    /// The key type this algorithm signs with, if supported by SAGE
+    pub fn key_type(&self) -> Option<KeyType> {
+        match self {
+            SignatureAlgorithm::Ed25519 => Some(KeyType::Ed25519),
+            SignatureAlgorithm::EcdsaSecp256k1Sha256 => Some(KeyType::Secp256k1),
+            SignatureAlgorithm::EcdsaP256Sha256 | SignatureAlgorithm::Custom(_) => None,
+        }
+    }
+
+    /// Look up a built-in algorithm by its identifier
+    pub fn from_identifier(identifier: &str) -> Option<Self> {
+        match identifier {
+            "ed25519" => Some(SignatureAlgorithm::Ed25519),
+            "ecdsa-p256-sha256" => Some(SignatureAlgorithm::EcdsaP256Sha256),
+            "ecdsa-secp256k1-sha256" => Some(SignatureAlgorithm::EcdsaSecp256k1Sha256),
+            _ => None,
         }
     }
 }
 
 /// HTTP signature input string builder
+#[derive(Debug, Clone)]
 pub struct SignatureInput {
-    components: Vec<String>,
+    components: Vec<SignatureComponent>,
     params: SignatureParams,
 }
 
@@ -52,9 +107,19 @@ impl SignatureInput {
         }
     }
 
+    /// Parse a signature input string such as `("@method" "@path");created=1618884473`
+    ///
+    /// Unknown signature parameters are dropped.
+    pub fn parse(input: &str) -> crate::error::Result<Self> {
+        let (components, params) = verifier::parse_signature_input(input)?;
+        Ok(Self { components, params })
+    }
+
     /// Add a component to sign
+    ///
+    /// Adding a component twice makes [`build`](Self::build) fail.
     pub fn add_component(mut self, component: SignatureComponent) -> Self {
-        self.components.push(component.identifier().to_string());
+        self.components.push(component);
         self
     }
 
@@ -82,15 +147,128 @@ impl SignatureInput {
         self
     }
 
+    /// Set the nonce
+    pub fn nonce(mut self, nonce: impl Into<String>) -> Self {
+        self.params.nonce = Some(nonce.into());
+        self
+    }
+
+    /// Set the tag
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.params.tag = Some(tag.into());
+        self
+    }
+
+    /// Get the covered components
+    pub fn components(&self) -> &[SignatureComponent] {
+        &self.components
+    }
+
+    /// Get the signature parameters
+    pub fn params(&self) -> &SignatureParams {
+        &self.params
+    }
+
+    /// Split into covered components and signature parameters
+    pub fn into_parts(self) -> (Vec<SignatureComponent>, SignatureParams) {
+        (self.components, self.params)
+    }
+
     /// Build the signature input string
-    pub fn build(self) -> String {
-        let components = self.components.join(" ");
+    ///
+    /// Fails on repeated components or parameters that cannot be serialized.
+    pub fn build(self) -> crate::error::Result<String> {
+        let mut seen = Vec::with_capacity(self.components.len());
+        for component in &self.components {
+            if let Some(params) = component.params() {
+                params.validate()?;
+            }
+            let id = component.serialize();
+            if seen.contains(&id) {
+                return Err(crate::error::Error::InvalidInput(format!(
+                    "Duplicate component: {id}"
+                )));
+            }
+            seen.push(id);
+        }
+        self.params.validate()?;
+
+        let components = self
+            .components
+            .iter()
+            .map(SignatureComponent::serialize)
+            .collect::<Vec<_>>()
+            .join(" ");
         let params = self.params.to_string();
 
         if params.is_empty() {
-            format!("({components})")
+            Ok(format!("({components})"))
         } else {
-            format!("({components});{params}")
+            Ok(format!("({components});{params}"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_algorithm_registry() {
+        assert_eq!(
+            SignatureAlgorithm::for_key_type(KeyType::Secp256k1).identifier(),
+            "ecdsa-secp256k1-sha256"
+        );
+        assert_eq!(
+            SignatureAlgorithm::for_key_type(KeyType::Ed25519).identifier(),
+            "ed25519"
+        );
+        for key_type in [KeyType::Ed25519, KeyType::Secp256k1] {
+            let alg = SignatureAlgorithm::for_key_type(key_type);
+            assert_eq!(alg.key_type(), Some(key_type));
+            assert_eq!(
+                SignatureAlgorithm::from_identifier(alg.identifier()),
+                Some(alg)
+            );
         }
+        assert_eq!(SignatureAlgorithm::EcdsaP256Sha256.key_type(), None);
+    }
+
+    #[test]
+    fn test_signature_input_roundtrip() {
+        let built = SignatureInput::new()
+            .add_component(SignatureComponent::Method)
+            .add_component(SignatureComponent::Header("Example-Dict".to_string()).key("a"))
+            .add_component(SignatureComponent::query_param("Pet"))
+            .add_component(SignatureComponent::Status.req())
+            .key_id("test-key")
+            .algorithm(SignatureAlgorithm::Ed25519)
+            .created(1_618_884_473)
+            .expires(1_618_884_773)
+            .nonce("abc")
+            .tag("sage")
+            .build()
+            .unwrap();
+
+        let parsed = SignatureInput::parse(&built).unwrap();
+        assert_eq!(parsed.components().len(), 4);
+        assert_eq!(parsed.params().key_id.as_deref(), Some("test-key"));
+        assert_eq!(parsed.clone().build().unwrap(), built);
+
+        let (components, params) = parsed.into_parts();
+        assert_eq!(components[0], SignatureComponent::Method);
+        assert_eq!(params.tag.as_deref(), Some("sage"));
+
+        assert!(SignatureInput::parse("\"@method\"").is_err());
+        assert!(SignatureInput::new()
+            .add_component(SignatureComponent::Method)
+            .add_component(SignatureComponent::Method)
+            .build()
+            .is_err());
+        assert!(SignatureInput::new()
+            .add_component(SignatureComponent::Method)
+            .tag("a\nb")
+            .build()
+            .is_err());
     }
 }
@@ -0,0 +1,145 @@
+//! Replay protection for RFC 9421 signatures
+
+use crate::error::{Error, Result};
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+/// Default number of entries kept by [`InMemoryNonceStore`]
+pub const DEFAULT_NONCE_STORE_CAPACITY: usize = 10_000;
+
+/// Store of previously seen signatures used to reject replays
+pub trait NonceStore: Send + Sync {
+    /// Record a `(key_id, nonce)` pair that stays relevant until `expires_at` (Unix timestamp)
+    ///
+    /// Returns an error if the pair has already been seen and has not yet expired.
+    fn check_and_insert(&self, key_id: &str, nonce: &str, expires_at: i64, now: i64) -> Result<()>;
+}
+
+/// In-memory nonce store holding at most `capacity` unexpired entries
+///
+/// Entries are never evicted before they expire, since that would let their
+/// signatures be replayed; new signatures are rejected while the store is full.
+#[derive(Debug)]
+pub struct InMemoryNonceStore {
+    capacity: usize,
+    state: Mutex<NonceState>,
+}
+
+#[derive(Debug, Default)]
+struct NonceState {
+    entries: HashMap<(String, String), i64>,
+    order: VecDeque<(String, String)>,
+}
+
+impl InMemoryNonceStore {
+    /// Create a store with the default capacity
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_NONCE_STORE_CAPACITY)
+    }
+
+    /// Create a store holding at most `capacity` unexpired entries
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            state: Mutex::new(NonceState::default()),
+        }
+    }
+
+    /// Number of tracked entries
+    pub fn len(&self) -> usize {
+        self.state
+            .lock()
+            .map(|state| state.entries.len())
+            .unwrap_or_default()
+    }
+
+    /// Check whether the store is empty
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for InMemoryNonceStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NonceStore for InMemoryNonceStore {
+    fn check_and_insert(&self, key_id: &str, nonce: &str, expires_at: i64, now: i64) -> Result<()> {
+        let mut state = self
+            .state
+            .lock()
+            .map_err(|_| Error::Other("Nonce store lock poisoned".to_string()))?;
+        let NonceState { entries, order } = &mut *state;
+
+        // Drop expired entries from the front of the queue
+        while let Some(oldest) = order.front() {
+            if entries.get(oldest).is_some_and(|exp| *exp >= now) {
+                break;
+            }
+            if let Some(oldest) = order.pop_front() {
+                entries.remove(&oldest);
+            }
+        }
+
+        let key = (key_id.to_string(), nonce.to_string());
+        if let Some(existing) = entries.get(&key) {
+            if *existing >= now {
+                return Err(Error::Verification("Signature replay detected".to_string()));
+            }
+        }
+
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            // Entries expire out of order, so look past the front of the queue
+            entries.retain(|_, exp| *exp >= now);
+            order.retain(|entry| entries.contains_key(entry));
+            if entries.len() >= self.capacity {
+                return Err(Error::Verification("Nonce store is full".to_string()));
+            }
+        }
+
+        if entries.insert(key.clone(), expires_at).is_none() {
+            order.push_back(key);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rejects_replay_within_window() {
+        let store = InMemoryNonceStore::new();
+        assert!(store.check_and_insert("key", "n1", 200, 100).is_ok());
+        assert!(store.check_and_insert("key", "n1", 200, 150).is_err());
+        assert!(store.check_and_insert("other", "n1", 200, 150).is_ok());
+    }
+
+    #[test]
+    fn test_accepts_after_expiry() {
+        let store = InMemoryNonceStore::new();
+        assert!(store.check_and_insert("key", "n1", 200, 100).is_ok());
+        assert!(store.check_and_insert("key", "n1", 400, 300).is_ok());
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_capacity_limit() {
+        let store = InMemoryNonceStore::with_capacity(2);
+        store.check_and_insert("key", "a", 2000, 0).unwrap();
+        store.check_and_insert("key", "b", 1000, 0).unwrap();
+        assert!(store.check_and_insert("key", "c", 1000, 0).is_err());
+        assert_eq!(store.len(), 2);
+        // Unexpired entries are kept, so "a" is still a replay
+        assert!(store.check_and_insert("key", "a", 2000, 0).is_err());
+
+        // Once "b" expires there is room again, even behind an unexpired entry
+        assert!(store.check_and_insert("key", "c", 3000, 1500).is_ok());
+        assert!(store.check_and_insert("key", "a", 2000, 1500).is_err());
+        assert_eq!(store.len(), 2);
+    }
+}
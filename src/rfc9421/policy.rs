@@ -0,0 +1,341 @@
+//! Verification policies for RFC 9421 signatures
+
+use crate::error::{Error, Result};
+use crate::rfc9421::{ContentDigest, SignatureAlgorithm, SignatureComponent, SignatureParams};
+use std::time::Duration;
+
+/// Default tolerance, in seconds, for `created` timestamps in the future
+pub const DEFAULT_CLOCK_SKEW: i64 = 300;
+
+/// Requirements a signature must satisfy in addition to being cryptographically valid
+///
+/// The default policy accepts any valid, unexpired signature.
+#[derive(Debug, Clone)]
+pub struct VerificationPolicy {
+    required_components: Vec<SignatureComponent>,
+    require_created: bool,
+    require_expires: bool,
+    max_age: Option<i64>,
+    clock_skew: i64,
+    allowed_algorithms: Vec<String>,
+    allowed_digest_algorithms: Vec<String>,
+    required_tag: Option<String>,
+}
+
+impl Default for VerificationPolicy {
+    fn default() -> Self {
+        Self {
+            required_components: Vec::new(),
+            require_created: false,
+            require_expires: false,
+            max_age: None,
+            clock_skew: DEFAULT_CLOCK_SKEW,
+            allowed_algorithms: Vec::new(),
+            allowed_digest_algorithms: Vec::new(),
+            required_tag: None,
+        }
+    }
+}
+
+impl VerificationPolicy {
+    /// Create a permissive policy
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Require a component to be covered by the signature
+    pub fn require_component(mut self, component: SignatureComponent) -> Self {
+        self.required_components.push(component);
+        self
+    }
+
+    /// Require several components to be covered by the signature
+    pub fn require_components(
+        mut self,
+        components: impl IntoIterator<Item = SignatureComponent>,
+    ) -> Self {
+        self.required_components.extend(components);
+        self
+    }
+
+    /// Require the `created` parameter
+    pub fn require_created(mut self, required: bool) -> Self {
+        self.require_created = required;
+        self
+    }
+
+    /// Require the `expires` parameter
+    pub fn require_expires(mut self, required: bool) -> Self {
+        self.require_expires = required;
+        self
+    }
+
+    /// Set how far in the future a `created` timestamp may be
+    pub fn clock_skew(mut self, skew: Duration) -> Self {
+        self.clock_skew = i64::try_from(skew.as_secs()).unwrap_or(i64::MAX);
+        self
+    }
+
+    /// Reject signatures whose `created` timestamp is older than `max_age`
+    ///
+    /// Implies that `created` must be present.
+    pub fn max_age(mut self, max_age: Duration) -> Self {
+        self.max_age = Some(i64::try_from(max_age.as_secs()).unwrap_or(i64::MAX));
+        self
+    }
+
+    /// Accept an algorithm; once any algorithm is allowed, all others are rejected
+    pub fn allow_algorithm(mut self, algorithm: SignatureAlgorithm) -> Self {
+        self.allowed_algorithms
+            .push(algorithm.identifier().to_string());
+        self
+    }
+
+    /// Whether `identifier` was accepted with [`allow_algorithm`](Self::allow_algorithm)
+    pub fn explicitly_allows_algorithm(&self, identifier: &str) -> bool {
+        self.allowed_algorithms.iter().any(|alg| alg == identifier)
+    }
+
+    /// Accept a Content-Digest algorithm by identifier, e.g. `sha-512`
+    ///
+    /// Once any digest algorithm is allowed, digests using only other algorithms are rejected.
+    /// [`HttpVerifier`](crate::rfc9421::HttpVerifier) checks the `Content-Digest`
+    /// header of every message it verifies against this list.
+    pub fn allow_digest_algorithm(mut self, identifier: impl Into<String>) -> Self {
+        self.allowed_digest_algorithms.push(identifier.into());
+        self
+    }
+
+    /// Require the `tag` parameter to equal `tag`
+    ///
+    /// Rejects signatures created for other applications or protocols.
+    pub fn require_tag(mut self, tag: impl Into<String>) -> Self {
+        self.required_tag = Some(tag.into());
+        self
+    }
+
+    /// Check covered components and signature parameters against the policy
+    ///
+    /// `algorithm` is the effective algorithm identifier of the signature.
+    pub fn check(
+        &self,
+        components: &[SignatureComponent],
+        params: &SignatureParams,
+        algorithm: &str,
+        now: i64,
+    ) -> Result<()> {
+        for required in &self.required_components {
+            let required_id = required.serialize();
+            if !components.iter().any(|c| c.serialize() == required_id) {
+                return Err(Error::Verification(format!(
+                    "Required component {required_id} is not covered"
+                )));
+            }
+        }
+
+        if (self.require_created || self.max_age.is_some()) && params.created.is_none() {
+            return Err(Error::Verification(
+                "Signature is missing the created parameter".to_string(),
+            ));
+        }
+
+        if self.require_expires && params.expires.is_none() {
+            return Err(Error::Verification(
+                "Signature is missing the expires parameter".to_string(),
+            ));
+        }
+
+        if let Some(created) = params.created {
+            if created > now.saturating_add(self.clock_skew) {
+                return Err(Error::Verification(
+                    "Signature created in the future".to_string(),
+                ));
+            }
+            if let Some(max_age) = self.max_age {
+                if now.saturating_sub(created) > max_age {
+                    return Err(Error::Verification("Signature is too old".to_string()));
+                }
+            }
+        }
+
+        if let Some(expires) = params.expires {
+            if expires < now {
+                return Err(Error::Verification("Signature expired".to_string()));
+            }
+        }
+
+        if let Some(ref required) = self.required_tag {
+            match params.tag {
+                Some(ref tag) if tag == required => {}
+                Some(ref tag) => {
+                    return Err(Error::Verification(format!(
+                        "Signature tag {tag} does not match {required}"
+                    )))
+                }
+                None => {
+                    return Err(Error::Verification(
+                        "Signature is missing the tag parameter".to_string(),
+                    ))
+                }
+            }
+        }
+
+        if !self.allowed_algorithms.is_empty()
+            && !self.allowed_algorithms.iter().any(|alg| alg == algorithm)
+        {
+            return Err(Error::Verification(format!(
+                "Algorithm {algorithm} is not allowed"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Whether the policy restricts Content-Digest algorithms
+    pub fn restricts_digest_algorithms(&self) -> bool {
+        !self.allowed_digest_algorithms.is_empty()
+    }
+
+    /// Check that a Content-Digest uses at least one allowed digest algorithm
+    pub fn check_content_digest(&self, digest: &ContentDigest) -> Result<()> {
+        if self.allowed_digest_algorithms.is_empty()
+            || digest
+                .algorithms()
+                .any(|alg| self.allowed_digest_algorithms.iter().any(|a| a == alg))
+        {
+            return Ok(());
+        }
+
+        Err(Error::Verification(
+            "Content-digest uses no allowed digest algorithm".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params(created: Option<i64>) -> SignatureParams {
+        SignatureParams {
+            created,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_policy_accepts_anything() {
+        let policy = VerificationPolicy::new();
+        assert!(policy.check(&[], &params(None), "ed25519", 0).is_ok());
+    }
+
+    #[test]
+    fn test_required_components() {
+        let policy = VerificationPolicy::new().require_components([
+            SignatureComponent::Method,
+            SignatureComponent::content_digest(),
+        ]);
+        let covered = vec![SignatureComponent::Method, SignatureComponent::Path];
+        assert!(policy.check(&covered, &params(None), "ed25519", 0).is_err());
+
+        let covered = vec![
+            SignatureComponent::Method,
+            SignatureComponent::Header("Content-Digest".to_string()),
+        ];
+        assert!(policy.check(&covered, &params(None), "ed25519", 0).is_ok());
+    }
+
+    #[test]
+    fn test_created_and_max_age() {
+        let policy = VerificationPolicy::new().max_age(Duration::from_secs(60));
+        assert!(policy.check(&[], &params(None), "ed25519", 1000).is_err());
+        assert!(policy
+            .check(&[], &params(Some(900)), "ed25519", 1000)
+            .is_err());
+        assert!(policy
+            .check(&[], &params(Some(950)), "ed25519", 1000)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_clock_skew_and_expires() {
+        let policy = VerificationPolicy::new();
+        assert!(policy
+            .check(&[], &params(Some(1200)), "ed25519", 1000)
+            .is_ok());
+        assert!(policy
+            .check(&[], &params(Some(1400)), "ed25519", 1000)
+            .is_err());
+
+        let strict = VerificationPolicy::new()
+            .clock_skew(Duration::from_secs(10))
+            .require_expires(true);
+        assert!(strict
+            .check(&[], &params(Some(1005)), "ed25519", 1000)
+            .is_err());
+        let expiring = SignatureParams {
+            created: Some(1005),
+            expires: Some(1100),
+            ..Default::default()
+        };
+        assert!(strict.check(&[], &expiring, "ed25519", 1000).is_ok());
+        assert!(strict.check(&[], &expiring, "ed25519", 1200).is_err());
+    }
+
+    #[test]
+    fn test_large_durations() {
+        let lenient = VerificationPolicy::new()
+            .clock_skew(Duration::from_secs(u64::MAX))
+            .max_age(Duration::from_secs(u64::MAX));
+        assert!(lenient
+            .check(&[], &params(Some(1000)), "ed25519", 1000)
+            .is_ok());
+        assert!(lenient
+            .check(&[], &params(Some(i64::MAX)), "ed25519", 1000)
+            .is_ok());
+        assert!(lenient
+            .check(&[], &params(Some(i64::MIN)), "ed25519", 1000)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_allowed_digest_algorithms() {
+        use crate::rfc9421::DigestAlgorithm;
+
+        let sha256 = ContentDigest::compute(b"body", DigestAlgorithm::Sha256);
+        let both = ContentDigest::compute_all(
+            b"body",
+            &[DigestAlgorithm::Sha256, DigestAlgorithm::Sha512],
+        );
+
+        assert!(VerificationPolicy::new()
+            .check_content_digest(&sha256)
+            .is_ok());
+        let policy = VerificationPolicy::new().allow_digest_algorithm("sha-512");
+        assert!(policy.check_content_digest(&sha256).is_err());
+        assert!(policy.check_content_digest(&both).is_ok());
+    }
+
+    #[test]
+    fn test_required_tag() {
+        let policy = VerificationPolicy::new().require_tag("sage-a2a");
+        let tagged = |tag: &str| SignatureParams {
+            tag: Some(tag.to_string()),
+            ..Default::default()
+        };
+        assert!(policy.check(&[], &tagged("sage-a2a"), "ed25519", 0).is_ok());
+        assert!(policy
+            .check(&[], &tagged("other-app"), "ed25519", 0)
+            .is_err());
+        assert!(policy.check(&[], &params(None), "ed25519", 0).is_err());
+    }
+
+    #[test]
+    fn test_allowed_algorithms() {
+        let policy = VerificationPolicy::new().allow_algorithm(SignatureAlgorithm::Ed25519);
+        assert!(policy.check(&[], &params(None), "ed25519", 0).is_ok());
+        assert!(policy
+            .check(&[], &params(None), "ecdsa-secp256k1-sha256", 0)
+            .is_err());
+    }
+}
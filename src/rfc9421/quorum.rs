@@ -0,0 +1,180 @@
+//! m-of-n verification of messages carrying several signatures
+
+use crate::error::{Error, Result};
+use crate::rfc9421::{
+    signature_labels, HttpMessage, HttpVerifier, KeySet, VerificationPolicy, VerificationReport,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Outcome of a quorum verification
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuorumReport {
+    /// Signatures that verified with a trusted key
+    pub passed: Vec<VerificationReport>,
+    /// Labels of signatures that failed verification
+    pub failed: Vec<String>,
+    /// Key ID of each distinct key with at least one valid signature
+    pub signers: Vec<String>,
+}
+
+impl QuorumReport {
+    /// Key IDs of the distinct keys with at least one valid signature
+    ///
+    /// A key trusted under several key IDs appears once.
+    pub fn key_ids(&self) -> Vec<&str> {
+        self.signers.iter().map(String::as_str).collect()
+    }
+}
+
+/// Verifier requiring valid signatures from at least `threshold` of a set of trusted keys
+///
+/// Every signature on the message is checked; several signatures by the same
+/// key count once, even when the key is trusted under several key IDs.
+pub struct QuorumVerifier {
+    keys: Arc<KeySet>,
+    threshold: usize,
+    policy: VerificationPolicy,
+}
+
+impl QuorumVerifier {
+    /// Require signatures from `threshold` distinct keys of `keys`
+    ///
+    /// Keys are told apart by their public key bytes, not their key IDs.
+    pub fn new(keys: KeySet, threshold: usize) -> Result<Self> {
+        let distinct: HashSet<Vec<u8>> = keys
+            .iter()
+            .map(|(_, public_key)| public_key.to_bytes())
+            .collect();
+        if threshold == 0 || threshold > distinct.len() {
+            return Err(Error::InvalidInput(format!(
+                "Quorum threshold must be between 1 and {}, got {threshold}",
+                distinct.len()
+            )));
+        }
+
+        Ok(Self {
+            keys: Arc::new(keys),
+            threshold,
+            policy: VerificationPolicy::default(),
+        })
+    }
+
+    /// Enforce a verification policy on every signature
+    pub fn with_policy(mut self, policy: VerificationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Verify the signatures of any [`HttpMessage`] and check the quorum
+    pub fn verify_message<M: HttpMessage + ?Sized>(&self, message: &M) -> Result<QuorumReport> {
+        let mut report = QuorumReport {
+            passed: Vec::new(),
+            failed: Vec::new(),
+            signers: Vec::new(),
+        };
+        let mut signed: HashSet<Vec<u8>> = HashSet::new();
+
+        for label in signature_labels(message)? {
+            let verifier = HttpVerifier::from_resolver(self.keys.clone())
+                .with_label(label.as_str())
+                .with_policy(self.policy.clone());
+            match verifier.verify_message(message) {
+                Ok(passed) => {
+                    let public_key = self.keys.get(&passed.key_id).ok_or_else(|| {
+                        Error::Verification(format!("Unknown key ID: {}", passed.key_id))
+                    })?;
+                    if signed.insert(public_key.to_bytes()) {
+                        report.signers.push(passed.key_id.clone());
+                    }
+                    report.passed.push(passed);
+                }
+                Err(_) => report.failed.push(label),
+            }
+        }
+
+        let valid = report.signers.len();
+        if valid < self.threshold {
+            return Err(Error::Verification(format!(
+                "Quorum not reached: {valid} of {} required signatures are valid",
+                self.threshold
+            )));
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyIdStrategy, KeyPair, KeyType};
+    use crate::rfc9421::HttpSigner;
+    use http::Request;
+
+    #[test]
+    fn test_quorum_verification() {
+        let keypairs: Vec<KeyPair> = (0..3)
+            .map(|_| KeyPair::generate(KeyType::Ed25519).unwrap())
+            .collect();
+        let keys: KeySet = keypairs.iter().map(|k| k.public_key().clone()).collect();
+        let outsider = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("https://example.com/transfers")
+            .body(())
+            .unwrap();
+        for (label, keypair) in [("a", &keypairs[0]), ("b", &keypairs[1]), ("c", &outsider)] {
+            let signer = HttpSigner::new(keypair.clone()).with_label(label);
+            let headers = signer.sign_message(&request).unwrap();
+            headers.append_to(request.headers_mut()).unwrap();
+        }
+
+        let report = QuorumVerifier::new(keys.clone(), 2)
+            .unwrap()
+            .verify_message(&request)
+            .unwrap();
+        assert_eq!(report.key_ids().len(), 2);
+        assert_eq!(report.failed, vec!["c".to_string()]);
+
+        let quorum = QuorumVerifier::new(keys.clone(), 3).unwrap();
+        assert!(quorum.verify_message(&request).is_err());
+        assert!(QuorumVerifier::new(keys, 4).is_err());
+    }
+
+    #[test]
+    fn test_quorum_counts_distinct_keys() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let alias = keypair
+            .clone()
+            .with_key_id_strategy(KeyIdStrategy::Sha256)
+            .unwrap();
+        let other = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let mut keys = KeySet::new();
+        keys.insert(keypair.public_key().clone());
+        keys.insert_with_id(alias.key_id(), alias.public_key().clone());
+        assert!(QuorumVerifier::new(keys.clone(), 2).is_err());
+        keys.insert(other.public_key().clone());
+
+        let mut request = Request::builder()
+            .method("POST")
+            .uri("https://example.com/transfers")
+            .body(())
+            .unwrap();
+        for (label, keypair) in [("a", &keypair), ("b", &alias)] {
+            let signer = HttpSigner::new(keypair.clone()).with_label(label);
+            let headers = signer.sign_message(&request).unwrap();
+            headers.append_to(request.headers_mut()).unwrap();
+        }
+
+        let quorum = QuorumVerifier::new(keys.clone(), 2).unwrap();
+        assert!(quorum.verify_message(&request).is_err());
+        let report = QuorumVerifier::new(keys, 1)
+            .unwrap()
+            .verify_message(&request)
+            .unwrap();
+        assert_eq!(report.passed.len(), 2);
+        assert_eq!(report.key_ids(), vec![keypair.key_id()]);
+    }
+}
@@ -0,0 +1,32 @@
+//! Verification results for RFC 9421 signatures
+
+use crate::rfc9421::{SignatureAlgorithm, SignatureComponent};
+
+/// Details of a successfully verified signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationReport {
+    /// Label of the verified signature, e.g. `sig1`
+    pub label: String,
+    /// Key ID the signature was verified with
+    pub key_id: String,
+    /// Algorithm the signature was verified with
+    pub algorithm: SignatureAlgorithm,
+    /// Components covered by the signature
+    pub components: Vec<SignatureComponent>,
+    /// Creation timestamp (Unix timestamp)
+    pub created: Option<i64>,
+    /// Expiration timestamp (Unix timestamp)
+    pub expires: Option<i64>,
+    /// Nonce value
+    pub nonce: Option<String>,
+    /// Tag value
+    pub tag: Option<String>,
+}
+
+impl VerificationReport {
+    /// Check whether a component is covered by the signature
+    pub fn covers(&self, component: &SignatureComponent) -> bool {
+        let id = component.serialize();
+        self.components.iter().any(|c| c.serialize() == id)
+    }
+}
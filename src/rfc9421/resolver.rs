@@ -0,0 +1,160 @@
+//! Key lookup by `keyid` for RFC 9421 verification
+
+use crate::crypto::PublicKey;
+use crate::error::{Error, Result};
+use crate::formats::public_key_from_jwk;
+use std::collections::HashMap;
+
+/// Resolves the public key for a signature's `keyid` parameter
+///
+/// Implementations should return an error for unknown key IDs.
+pub trait KeyResolver: Send + Sync {
+    /// Look up the public key identified by `key_id`
+    fn resolve(&self, key_id: &str) -> Result<PublicKey>;
+}
+
+impl<F> KeyResolver for F
+where
+    F: Fn(&str) -> Result<PublicKey> + Send + Sync,
+{
+    fn resolve(&self, key_id: &str) -> Result<PublicKey> {
+        self(key_id)
+    }
+}
+
+/// Fixed set of trusted public keys indexed by key ID
+#[derive(Debug, Clone, Default)]
+pub struct KeySet {
+    keys: HashMap<String, PublicKey>,
+}
+
+impl KeySet {
+    /// Create an empty key set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Trust a public key under its default key ID, which is returned
+    pub fn insert(&mut self, public_key: PublicKey) -> String {
+        let key_id = public_key.key_id();
+        self.keys.insert(key_id.clone(), public_key);
+        key_id
+    }
+
+    /// Trust a public key under an explicit key ID
+    pub fn insert_with_id(&mut self, key_id: impl Into<String>, public_key: PublicKey) {
+        self.keys.insert(key_id.into(), public_key);
+    }
+
+    /// Add a public key under its default key ID
+    pub fn with_key(mut self, public_key: PublicKey) -> Self {
+        self.insert(public_key);
+        self
+    }
+
+    /// Build a key set from a JWK Set (`{"keys": [...]}`)
+    ///
+    /// Keys are indexed by their `kid`, or by their default key ID if absent.
+    pub fn from_jwks(jwks: &serde_json::Value) -> Result<Self> {
+        let keys = jwks
+            .get("keys")
+            .and_then(|k| k.as_array())
+            .ok_or_else(|| Error::InvalidKeyFormat("JWK Set has no keys array".to_string()))?;
+
+        let mut key_set = Self::new();
+        for jwk in keys {
+            let public_key = public_key_from_jwk(jwk)?;
+            match jwk.get("kid").and_then(|kid| kid.as_str()) {
+                Some(kid) => key_set.insert_with_id(kid, public_key),
+                None => {
+                    key_set.insert(public_key);
+                }
+            }
+        }
+        Ok(key_set)
+    }
+
+    /// Get the key with the given ID
+    pub fn get(&self, key_id: &str) -> Option<&PublicKey> {
+        self.keys.get(key_id)
+    }
+
+    /// Iterate over the trusted keys and their key IDs
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PublicKey)> {
+        self.keys
+            .iter()
+            .map(|(key_id, public_key)| (key_id.as_str(), public_key))
+    }
+
+    /// Number of trusted keys
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Check whether the set is empty
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+impl FromIterator<PublicKey> for KeySet {
+    fn from_iter<I: IntoIterator<Item = PublicKey>>(iter: I) -> Self {
+        let mut key_set = Self::new();
+        for public_key in iter {
+            key_set.insert(public_key);
+        }
+        key_set
+    }
+}
+
+impl KeyResolver for KeySet {
+    fn resolve(&self, key_id: &str) -> Result<PublicKey> {
+        self.get(key_id)
+            .cloned()
+            .ok_or_else(|| Error::Verification(format!("Unknown key ID: {key_id}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::{KeyPair, KeyType};
+
+    #[test]
+    fn test_closure_resolver() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let known_id = keypair.key_id().to_string();
+        let public_key = keypair.public_key().clone();
+
+        let resolver = move |key_id: &str| {
+            if key_id == known_id {
+                Ok(public_key.clone())
+            } else {
+                Err(Error::Verification(format!("Unknown key ID: {key_id}")))
+            }
+        };
+
+        assert!(resolver.resolve(keypair.key_id()).is_ok());
+        assert!(resolver.resolve("unknown").is_err());
+    }
+
+    #[test]
+    fn test_key_set_from_jwks() {
+        use crate::formats::KeyExporter;
+
+        let first = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let second = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let mut named = second.public_key().to_jwk().unwrap();
+        named["kid"] = serde_json::json!("agent-2");
+
+        let jwks = serde_json::json!({
+            "keys": [first.public_key().to_jwk().unwrap(), named]
+        });
+        let key_set = KeySet::from_jwks(&jwks).unwrap();
+        assert_eq!(key_set.len(), 2);
+        assert!(key_set.resolve(first.key_id()).is_ok());
+        assert!(key_set.resolve("agent-2").is_ok());
+        assert!(key_set.resolve(second.key_id()).is_err());
+        assert!(KeySet::from_jwks(&serde_json::json!({})).is_err());
+    }
+}
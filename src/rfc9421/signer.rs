@@ -2,128 +2,661 @@
 
 use crate::crypto::{KeyPair, Signer as CryptoSigner};
 use crate::error::{Error, Result};
-use crate::rfc9421::{SignatureAlgorithm, SignatureComponent, SignatureParams};
+use crate::kms::RemoteSigner;
+use crate::rfc9421::canonicalize::CanonicalizeOptions;
+use crate::rfc9421::{
+    validate_components, ContentDigest, CustomSigner, DigestAlgorithm, DigestHasher, HttpMessage,
+    HttpVerifier, MessageParts, SignatureAlgorithm, SignatureBase, SignatureComponent,
+    SignatureParams, VerificationReport,
+};
 use base64::{engine::general_purpose, Engine as _};
-use http::{HeaderValue, Request, Response};
-use std::time::{SystemTime, UNIX_EPOCH};
+use http::{HeaderMap, HeaderValue, Request, Response};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Number of random bytes in a generated nonce
+pub const NONCE_LENGTH: usize = 16;
+
+/// Default lifetime of a signature in seconds
+pub const DEFAULT_EXPIRY: u64 = 300;
+
+/// Label of signatures in the `Signature` and `Signature-Input` dictionaries
+pub const DEFAULT_LABEL: &str = "sig1";
+
+/// `Signature` and `Signature-Input` header values for a signed message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignatureHeaders {
+    /// Value of the `Signature` header
+    pub signature: String,
+    /// Value of the `Signature-Input` header
+    pub signature_input: String,
+}
+
+impl SignatureHeaders {
+    /// Insert both headers, replacing any existing values
+    pub fn insert_into(&self, headers: &mut HeaderMap) -> Result<()> {
+        headers.insert(
+            "signature-input",
+            HeaderValue::from_str(&self.signature_input)
+                .map_err(|_| Error::InvalidInput("Invalid signature input".to_string()))?,
+        );
+
+        headers.insert(
+            "signature",
+            HeaderValue::from_str(&self.signature)
+                .map_err(|_| Error::InvalidInput("Invalid signature value".to_string()))?,
+        );
+
+        Ok(())
+    }
+
+    /// Append both headers to any signatures already present, preserving them
+    ///
+    /// Fails if a signature with the same label is already present.
+    pub fn append_to(&self, headers: &mut HeaderMap) -> Result<()> {
+        let signature_input = append_member(headers, "signature-input", &self.signature_input)?;
+        let signature = append_member(headers, "signature", &self.signature)?;
+
+        SignatureHeaders {
+            signature,
+            signature_input,
+        }
+        .insert_into(headers)
+    }
+}
+
+/// Append a dictionary member to the existing values of a header
+fn append_member(headers: &HeaderMap, name: &str, member: &str) -> Result<String> {
+    let values: Vec<&[u8]> = headers
+        .get_all(name)
+        .iter()
+        .map(HeaderValue::as_bytes)
+        .collect();
+    if values.is_empty() {
+        return Ok(member.to_string());
+    }
+
+    let existing = super::canonicalize::get_header_value(&values, name)?;
+    let label = member.split('=').next().unwrap_or(member);
+    if super::canonicalize::dictionary_member(&existing, label).is_ok() {
+        return Err(Error::InvalidInput(format!(
+            "A signature labeled {label} is already present"
+        )));
+    }
+    Ok(format!("{existing}, {member}"))
+}
+
+/// How the `expires` parameter of a signature is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    /// Expire a fixed duration after signing
+    Ttl(Duration),
+    /// Expire at an absolute Unix timestamp
+    At(i64),
+    /// Omit the `expires` parameter
+    Never,
+}
+
+impl Default for Expiry {
+    fn default() -> Self {
+        Expiry::Ttl(Duration::from_secs(DEFAULT_EXPIRY))
+    }
+}
+
+/// A lifetime of `None` omits the `expires` parameter
+impl From<Option<Duration>> for Expiry {
+    fn from(ttl: Option<Duration>) -> Self {
+        ttl.map_or(Expiry::Never, Expiry::Ttl)
+    }
+}
+
+impl Expiry {
+    /// The `expires` timestamp for a signature created at `now`
+    ///
+    /// Fails if a lifetime does not fit in a Unix timestamp.
+    pub fn expires(&self, now: i64) -> Result<Option<i64>> {
+        match self {
+            Expiry::Ttl(ttl) => i64::try_from(ttl.as_secs())
+                .ok()
+                .and_then(|ttl| now.checked_add(ttl))
+                .map(Some)
+                .ok_or_else(|| {
+                    Error::InvalidInput(format!(
+                        "Signature lifetime of {}s is too long",
+                        ttl.as_secs()
+                    ))
+                }),
+            Expiry::At(timestamp) => Ok(Some(*timestamp)),
+            Expiry::Never => Ok(None),
+        }
+    }
+}
+
+/// Key used by an [`HttpSigner`]
+#[derive(Clone)]
+enum SigningKey {
+    KeyPair(KeyPair),
+    Custom(Arc<dyn CustomSigner>),
+    Remote(Arc<dyn RemoteSigner>),
+}
 
 /// HTTP message signer
+#[derive(Clone)]
 pub struct HttpSigner {
-    keypair: KeyPair,
+    key: SigningKey,
+    label: String,
     default_components: Vec<SignatureComponent>,
+    generate_nonce: bool,
+    tag: Option<String>,
+    include_created: bool,
+    expiry: Expiry,
+    digest_algorithm: Arc<dyn DigestHasher>,
+    canonicalize_options: CanonicalizeOptions,
 }
 
 impl HttpSigner {
     /// Create a new HTTP signer with a keypair
     pub fn new(keypair: KeyPair) -> Self {
+        Self::with_signing_key(SigningKey::KeyPair(keypair))
+    }
+
+    /// Create an HTTP signer backed by a custom signature algorithm
+    pub fn from_custom_signer(signer: Arc<dyn CustomSigner>) -> Self {
+        Self::with_signing_key(SigningKey::Custom(signer))
+    }
+
+    /// Create an HTTP signer backed by a remote key, such as one held in a cloud KMS
+    ///
+    /// Remote keys sign asynchronously, so only [`HttpSigner::sign_request_async`]
+    /// and [`HttpSigner::sign_response_async`] can be used.
+    pub fn from_remote_signer(signer: Arc<dyn RemoteSigner>) -> Self {
+        Self::with_signing_key(SigningKey::Remote(signer))
+    }
+
+    /// Start building an HTTP signer for a keypair
+    pub fn builder(keypair: KeyPair) -> HttpSignerBuilder {
+        HttpSignerBuilder {
+            signer: Self::new(keypair),
+        }
+    }
+
+    fn with_signing_key(key: SigningKey) -> Self {
         Self {
-            keypair,
+            key,
+            label: DEFAULT_LABEL.to_string(),
             default_components: vec![
                 SignatureComponent::Method,
                 SignatureComponent::Path,
                 SignatureComponent::Authority,
             ],
+            generate_nonce: false,
+            tag: None,
+            include_created: true,
+            expiry: Expiry::default(),
+            digest_algorithm: Arc::new(DigestAlgorithm::Sha256),
+            canonicalize_options: CanonicalizeOptions::default(),
         }
     }
 
     /// Set default components to sign
+    ///
+    /// Invalid components are only reported when signing; use
+    /// [`HttpSigner::builder`] to check them up front.
     pub fn with_default_components(mut self, components: Vec<SignatureComponent>) -> Self {
         self.default_components = components;
         self
     }
 
+    /// Set the label of produced signatures (`sig1` by default)
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    /// Generate a random `nonce` parameter for every signed message
+    pub fn with_nonce(mut self, enabled: bool) -> Self {
+        self.generate_nonce = enabled;
+        self
+    }
+
+    /// Set the `tag` parameter identifying the application context of every signature
+    pub fn with_tag(mut self, tag: impl Into<String>) -> Self {
+        self.tag = Some(tag.into());
+        self
+    }
+
+    /// Include the `created` parameter (enabled by default)
+    pub fn with_created(mut self, enabled: bool) -> Self {
+        self.include_created = enabled;
+        self
+    }
+
+    /// Set how the `expires` parameter is computed
+    ///
+    /// A lifetime converts with `Expiry::from(Some(ttl))`; `None` omits the parameter.
+    pub fn with_expiry_mode(mut self, expiry: Expiry) -> Self {
+        self.expiry = expiry;
+        self
+    }
+
+    /// Set the digest algorithm used by [`HttpSigner::sign_request_with_body`]
+    pub fn with_digest_algorithm(mut self, algorithm: DigestAlgorithm) -> Self {
+        self.digest_algorithm = Arc::new(algorithm);
+        self
+    }
+
+    /// Use a registered digest algorithm for [`HttpSigner::sign_request_with_body`]
+    pub fn with_digest_hasher(mut self, hasher: Arc<dyn DigestHasher>) -> Self {
+        self.digest_algorithm = hasher;
+        self
+    }
+
+    /// Normalize percent-encoding in `@path`, `@query` and other URI components before signing
+    ///
+    /// The verifier must be configured the same way.
+    pub fn with_uri_normalization(mut self, enabled: bool) -> Self {
+        self.canonicalize_options.normalize_percent_encoding = enabled;
+        self
+    }
+
     /// Sign an HTTP request
     pub fn sign_request<B>(&self, mut request: Request<B>) -> Result<Request<B>> {
-        let components = &self.default_components;
+        self.sign_request_mut(&mut request)?;
+        Ok(request)
+    }
+
+    /// Sign an HTTP request in place
+    pub fn sign_request_mut<B>(&self, request: &mut Request<B>) -> Result<()> {
         let signature_params = self.build_signature_params()?;
+        self.sign_request_in_place(request, &self.default_components, &signature_params)
+    }
 
-        // Canonicalize the request
-        let canonical_values = super::canonicalize::canonicalize_request(&request, components)?;
+    /// Set the Content-Digest of the request body and sign it along with the default components
+    pub fn sign_request_with_body<B: AsRef<[u8]>>(
+        &self,
+        mut request: Request<B>,
+    ) -> Result<Request<B>> {
+        ContentDigest::apply_to_request(&mut request, self.digest_algorithm.clone())?;
 
-        // Build signature input
-        let sig_input = self.build_signature_input(components, &signature_params);
+        let components = self.components_with_digest();
+        let signature_params = self.build_signature_params()?;
+        self.sign_request_in_place(&mut request, &components, &signature_params)?;
+        Ok(request)
+    }
 
-        // Build signature base
-        let signature_base =
-            super::canonicalize::build_signature_base(&canonical_values, &sig_input);
+    /// Compute the Content-Digest of a body with the configured digest algorithm
+    pub fn content_digest(&self, body: &[u8]) -> ContentDigest {
+        ContentDigest::compute(body, self.digest_algorithm.clone())
+    }
 
-        // Sign the signature base
-        let signature = self.keypair.sign(signature_base.as_bytes())?;
-        let sig_value = general_purpose::STANDARD.encode(signature.to_bytes());
+    /// Sign a request carrying a `Content-Digest` header, covering it along with the default components
+    ///
+    /// Use [`HttpSigner::content_digest`] to compute the header value.
+    pub fn sign_message_with_digest<M: HttpMessage + ?Sized>(
+        &self,
+        message: &M,
+    ) -> Result<SignatureHeaders> {
+        let components = self.components_with_digest();
+        let signature_params = self.build_signature_params()?;
 
-        // Add signature headers
-        request.headers_mut().insert(
-            "signature-input",
-            HeaderValue::from_str(&format!("sig1={sig_input}"))
-                .map_err(|_| Error::InvalidInput("Invalid signature input".to_string()))?,
-        );
+        let canonical_values = self.canonicalize(message, &components)?;
 
-        request.headers_mut().insert(
-            "signature",
-            HeaderValue::from_str(&format!("sig1=:{sig_value}"))
-                .map_err(|_| Error::InvalidInput("Invalid signature value".to_string()))?,
-        );
+        self.compute_signature(&canonical_values, &components, &signature_params)
+    }
+
+    /// Default components plus `content-digest`
+    fn components_with_digest(&self) -> Vec<SignatureComponent> {
+        let mut components = self.default_components.clone();
+        let content_digest = SignatureComponent::content_digest();
+        if !components.contains(&content_digest) {
+            components.push(content_digest);
+        }
+        components
+    }
 
+    /// Sign an HTTP request with explicit components and signature parameters
+    ///
+    /// `params` is used as given; start from [`HttpSigner::signature_params`]
+    /// to keep the key-derived defaults.
+    pub fn sign_request_with_params<B>(
+        &self,
+        mut request: Request<B>,
+        components: &[SignatureComponent],
+        params: SignatureParams,
+    ) -> Result<Request<B>> {
+        self.sign_request_in_place(&mut request, components, &params)?;
         Ok(request)
     }
 
+    /// Verify the inbound signature of a request and add a proxy signature before forwarding it
+    ///
+    /// The proxy signature covers the default components and the `Signature`
+    /// and `Signature-Input` members of the inbound signature, so it commits to
+    /// the original signature and its parameters. It is appended under this
+    /// signer's label so the original signature is preserved. The signer must
+    /// be configured with a label and a `tag` distinct from those of the
+    /// inbound signature.
+    pub fn resign_request<B>(
+        &self,
+        verifier: &HttpVerifier,
+        mut request: Request<B>,
+    ) -> Result<(Request<B>, VerificationReport)> {
+        let report = verifier.verify_request_detailed(&request)?;
+
+        match &self.tag {
+            None => {
+                return Err(Error::InvalidInput(
+                    "Proxy signatures require a tag".to_string(),
+                ))
+            }
+            Some(tag) if report.tag.as_ref() == Some(tag) => {
+                return Err(Error::InvalidInput(format!(
+                    "Proxy signature tag {tag} must differ from the inbound signature"
+                )))
+            }
+            Some(_) => {}
+        }
+
+        self.countersignature(&request, &self.default_components, &report.label)?
+            .append_to(request.headers_mut())?;
+
+        Ok((request, report))
+    }
+
+    /// Countersign the signature labeled `label` of a request
+    ///
+    /// The countersignature covers the default components and the `label`
+    /// members of `Signature` and `Signature-Input`, so it commits to the
+    /// original signature and its parameters. It is appended under this
+    /// signer's label, which must differ from `label`.
+    pub fn countersign_request<B>(
+        &self,
+        mut request: Request<B>,
+        label: &str,
+    ) -> Result<Request<B>> {
+        self.countersignature(&request, &self.default_components, label)?
+            .append_to(request.headers_mut())?;
+        Ok(request)
+    }
+
+    /// Countersign the signature labeled `label` of a response
+    pub fn countersign_response<B>(
+        &self,
+        mut response: Response<B>,
+        label: &str,
+    ) -> Result<Response<B>> {
+        self.countersignature(&response, &response_components(), label)?
+            .append_to(response.headers_mut())?;
+        Ok(response)
+    }
+
+    /// Sign `components` and the signature labeled `label`
+    fn countersignature<M: HttpMessage + ?Sized>(
+        &self,
+        message: &M,
+        components: &[SignatureComponent],
+        label: &str,
+    ) -> Result<SignatureHeaders> {
+        if label == self.label {
+            return Err(Error::InvalidInput(format!(
+                "Countersignature label {label} must differ from the signature it covers"
+            )));
+        }
+
+        let mut components = components.to_vec();
+        components.push(SignatureComponent::Header("signature".to_string()).key(label));
+        components.push(SignatureComponent::Header("signature-input".to_string()).key(label));
+
+        let signature_params = self.build_signature_params()?;
+        let canonical_values = self.canonicalize(message, &components)?;
+        self.compute_signature(&canonical_values, &components, &signature_params)
+    }
+
+    /// Sign an HTTP request, waiting for a remote signer if there is one
+    pub async fn sign_request_async<B>(&self, mut request: Request<B>) -> Result<Request<B>> {
+        let signature_params = self.build_signature_params()?;
+        let canonical_values = self.canonicalize(&request, &self.default_components)?;
+        self.compute_signature_async(
+            &canonical_values,
+            &self.default_components,
+            &signature_params,
+        )
+        .await?
+        .insert_into(request.headers_mut())?;
+        Ok(request)
+    }
+
+    /// Sign an HTTP response, waiting for a remote signer if there is one
+    pub async fn sign_response_async<B>(&self, mut response: Response<B>) -> Result<Response<B>> {
+        let components = response_components();
+        let signature_params = self.build_signature_params()?;
+        let canonical_values = self.canonicalize(&response, &components)?;
+        self.compute_signature_async(&canonical_values, &components, &signature_params)
+            .await?
+            .insert_into(response.headers_mut())?;
+        Ok(response)
+    }
+
     /// Sign an HTTP response
     pub fn sign_response<B>(&self, mut response: Response<B>) -> Result<Response<B>> {
-        let components = vec![
-            SignatureComponent::Status,
-            SignatureComponent::Header("content-type".to_string()),
-        ];
+        self.sign_response_mut(&mut response)?;
+        Ok(response)
+    }
+
+    /// Sign an HTTP response in place
+    pub fn sign_response_mut<B>(&self, response: &mut Response<B>) -> Result<()> {
+        let components = response_components();
         let signature_params = self.build_signature_params()?;
 
         // Canonicalize the response
-        let canonical_values = super::canonicalize::canonicalize_response(&response, &components)?;
+        let canonical_values = self.canonicalize(response, &components)?;
+
+        self.sign_headers(
+            response.headers_mut(),
+            &canonical_values,
+            &components,
+            &signature_params,
+        )
+    }
+
+    /// Set the Content-Digest of the response body and sign it along with the default response components
+    pub fn sign_response_with_body<B: AsRef<[u8]>>(
+        &self,
+        mut response: Response<B>,
+    ) -> Result<Response<B>> {
+        ContentDigest::apply_to_response(&mut response, self.digest_algorithm.clone())?;
+
+        let mut components = response_components();
+        components.push(SignatureComponent::content_digest());
+        let signature_params = self.build_signature_params()?;
+        let canonical_values = self.canonicalize(&response, &components)?;
+        self.sign_headers(
+            response.headers_mut(),
+            &canonical_values,
+            &components,
+            &signature_params,
+        )?;
+        Ok(response)
+    }
+
+    fn sign_request_in_place<B>(
+        &self,
+        request: &mut Request<B>,
+        components: &[SignatureComponent],
+        params: &SignatureParams,
+    ) -> Result<()> {
+        // Canonicalize the request
+        let canonical_values = self.canonicalize(request, components)?;
+
+        self.sign_headers(request.headers_mut(), &canonical_values, components, params)
+    }
+
+    /// Default signature parameters for the next message
+    ///
+    /// Includes `keyid`, `alg`, `created`, `expires` and the `nonce` and `tag`
+    /// parameters, as configured.
+    pub fn signature_params(&self) -> Result<SignatureParams> {
+        self.build_signature_params()
+    }
+
+    /// Build the signature base that signing `request` with `components` and `params` covers
+    pub fn signature_base_for_request<B>(
+        &self,
+        request: &Request<B>,
+        components: &[SignatureComponent],
+        params: &SignatureParams,
+    ) -> Result<SignatureBase> {
+        let canonical_values = self.canonicalize(request, components)?;
+        let sig_input = self.build_signature_input(components, params);
+
+        Ok(SignatureBase::new(
+            &canonical_values,
+            components.to_vec(),
+            params.clone(),
+            &sig_input,
+        ))
+    }
+
+    /// Sign a request described by its method, target URI and header fields
+    ///
+    /// Only the `Signature` and `Signature-Input` header values are returned,
+    /// for HTTP stacks that do not use `http::Request`.
+    pub fn sign_request_parts(
+        &self,
+        method: &str,
+        uri: &str,
+        headers: &[(&str, &str)],
+    ) -> Result<SignatureHeaders> {
+        let message = headers.iter().fold(
+            MessageParts::request(method, uri),
+            |message, (name, value)| message.with_header(*name, *value),
+        );
+        self.sign_message(&message)
+    }
+
+    /// Sign any [`HttpMessage`] with the default components and parameters
+    ///
+    /// Requests are signed like [`HttpSigner::sign_request`], responses like
+    /// [`HttpSigner::sign_response`].
+    pub fn sign_message<M: HttpMessage + ?Sized>(&self, message: &M) -> Result<SignatureHeaders> {
+        let components = if message.status().is_some() {
+            response_components()
+        } else {
+            self.default_components.clone()
+        };
+        let signature_params = self.build_signature_params()?;
+
+        let canonical_values = self.canonicalize(message, &components)?;
+
+        self.compute_signature(&canonical_values, &components, &signature_params)
+    }
+
+    /// Canonicalize a message with the configured options
+    fn canonicalize<M: HttpMessage + ?Sized>(
+        &self,
+        message: &M,
+        components: &[SignatureComponent],
+    ) -> Result<Vec<(String, String)>> {
+        super::canonicalize::canonicalize_message_with::<M, M>(
+            message,
+            None,
+            components,
+            &self.canonicalize_options,
+        )
+    }
+
+    /// Sign the canonicalized components and add the signature headers
+    fn sign_headers(
+        &self,
+        headers: &mut HeaderMap,
+        canonical_values: &[(String, String)],
+        components: &[SignatureComponent],
+        params: &SignatureParams,
+    ) -> Result<()> {
+        self.compute_signature(canonical_values, components, params)?
+            .insert_into(headers)
+    }
+
+    /// Sign the canonicalized components
+    fn compute_signature(
+        &self,
+        canonical_values: &[(String, String)],
+        components: &[SignatureComponent],
+        params: &SignatureParams,
+    ) -> Result<SignatureHeaders> {
+        params.validate()?;
 
         // Build signature input
-        let sig_input = self.build_signature_input(&components, &signature_params);
+        let sig_input = self.build_signature_input(components, params);
 
         // Build signature base
         let signature_base =
-            super::canonicalize::build_signature_base(&canonical_values, &sig_input);
+            super::canonicalize::build_signature_base(canonical_values, &sig_input);
 
         // Sign the signature base
-        let signature = self.keypair.sign(signature_base.as_bytes())?;
-        let sig_value = general_purpose::STANDARD.encode(signature.to_bytes());
-
-        // Add signature headers
-        response.headers_mut().insert(
-            "signature-input",
-            HeaderValue::from_str(&format!("sig1={sig_input}"))
-                .map_err(|_| Error::InvalidInput("Invalid signature input".to_string()))?,
-        );
+        let signature = match &self.key {
+            SigningKey::KeyPair(keypair) => keypair.sign(signature_base.as_bytes())?.to_bytes(),
+            SigningKey::Custom(signer) => signer.sign(signature_base.as_bytes())?,
+            SigningKey::Remote(_) => {
+                return Err(Error::Unsupported(
+                    "Remote signers sign asynchronously; use sign_request_async".to_string(),
+                ))
+            }
+        };
+        Ok(self.signature_headers(&sig_input, &signature))
+    }
 
-        response.headers_mut().insert(
-            "signature",
-            HeaderValue::from_str(&format!("sig1=:{sig_value}"))
-                .map_err(|_| Error::InvalidInput("Invalid signature value".to_string()))?,
-        );
+    /// Sign the canonicalized components, waiting for a remote signer
+    async fn compute_signature_async(
+        &self,
+        canonical_values: &[(String, String)],
+        components: &[SignatureComponent],
+        params: &SignatureParams,
+    ) -> Result<SignatureHeaders> {
+        let SigningKey::Remote(signer) = &self.key else {
+            return self.compute_signature(canonical_values, components, params);
+        };
+        params.validate()?;
+        let sig_input = self.build_signature_input(components, params);
+        let signature_base =
+            super::canonicalize::build_signature_base(canonical_values, &sig_input);
+        let signature = signer.sign(signature_base.as_bytes()).await?;
+        Ok(self.signature_headers(&sig_input, &signature))
+    }
 
-        Ok(response)
+    /// Header values for a signature over `sig_input`
+    fn signature_headers(&self, sig_input: &str, signature: &[u8]) -> SignatureHeaders {
+        let sig_value = general_purpose::STANDARD.encode(signature);
+        SignatureHeaders {
+            signature: format!("{}=:{sig_value}:", self.label),
+            signature_input: format!("{}={sig_input}", self.label),
+        }
     }
 
     /// Build signature parameters
     fn build_signature_params(&self) -> Result<SignatureParams> {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|_| Error::Other("System time error".to_string()))?
-            .as_secs() as i64;
-
-        let alg = match self.keypair.key_type() {
-            crate::crypto::KeyType::Ed25519 => SignatureAlgorithm::Ed25519,
-            crate::crypto::KeyType::Secp256k1 => SignatureAlgorithm::EcdsaSecp256k1Sha256,
+        // chrono also reads the clock on wasm32, where `SystemTime::now` panics
+        let now = chrono::Utc::now().timestamp();
+
+        let (key_id, alg) = match &self.key {
+            SigningKey::KeyPair(keypair) => (
+                keypair.key_id().to_string(),
+                SignatureAlgorithm::for_key_type(keypair.key_type()),
+            ),
+            SigningKey::Custom(signer) => (
+                signer.key_id(),
+                SignatureAlgorithm::Custom(signer.identifier()),
+            ),
+            SigningKey::Remote(signer) => (signer.key_id(), signer.algorithm()),
         };
 
         Ok(SignatureParams {
-            key_id: Some(self.keypair.public_key().key_id()),
+            key_id: Some(key_id),
             alg: Some(alg.identifier().to_string()),
-            created: Some(now),
-            expires: Some(now + 300), // 5 minutes
-            nonce: None,
-            tag: None,
+            created: self.include_created.then_some(now),
+            expires: self.expiry.expires(now)?,
+            nonce: self.generate_nonce.then(generate_nonce),
+            tag: self.tag.clone(),
         })
     }
 
@@ -133,15 +666,98 @@ impl HttpSigner {
         components: &[SignatureComponent],
         params: &SignatureParams,
     ) -> String {
-        let component_ids: Vec<String> = components
-            .iter()
-            .map(|c| format!("\"{}\"", c.identifier()))
-            .collect();
+        let component_ids: Vec<String> = components.iter().map(|c| c.serialize()).collect();
+        let params = params.to_string();
 
-        format!("({});{}", component_ids.join(" "), params)
+        if params.is_empty() {
+            format!("({})", component_ids.join(" "))
+        } else {
+            format!("({});{}", component_ids.join(" "), params)
+        }
     }
 }
 
+/// Builder for [`HttpSigner`]
+pub struct HttpSignerBuilder {
+    signer: HttpSigner,
+}
+
+impl HttpSignerBuilder {
+    /// Expire signatures `ttl` after they are created
+    pub fn ttl(mut self, ttl: Duration) -> Self {
+        self.signer.expiry = Expiry::Ttl(ttl);
+        self
+    }
+
+    /// Expire signatures at an absolute Unix timestamp
+    pub fn expires_at(mut self, timestamp: i64) -> Self {
+        self.signer.expiry = Expiry::At(timestamp);
+        self
+    }
+
+    /// Omit the `expires` parameter
+    pub fn no_expiry(mut self) -> Self {
+        self.signer.expiry = Expiry::Never;
+        self
+    }
+
+    /// Include the `created` parameter (enabled by default)
+    pub fn created(mut self, enabled: bool) -> Self {
+        self.signer.include_created = enabled;
+        self
+    }
+
+    /// Set default components to sign
+    pub fn components(mut self, components: Vec<SignatureComponent>) -> Self {
+        self.signer.default_components = components;
+        self
+    }
+
+    /// Set the label of produced signatures
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.signer.label = label.into();
+        self
+    }
+
+    /// Generate a random `nonce` parameter for every signed message
+    pub fn nonce(mut self, enabled: bool) -> Self {
+        self.signer.generate_nonce = enabled;
+        self
+    }
+
+    /// Set the `tag` parameter of every signature
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.signer.tag = Some(tag.into());
+        self
+    }
+
+    /// Build the signer
+    ///
+    /// Fails if the default components or the signature parameters are invalid.
+    pub fn build(self) -> Result<HttpSigner> {
+        validate_components(&self.signer.default_components, false)?;
+        self.signer.build_signature_params()?.validate()?;
+        Ok(self.signer)
+    }
+}
+
+/// Components covered by response signatures
+fn response_components() -> Vec<SignatureComponent> {
+    vec![
+        SignatureComponent::Status,
+        SignatureComponent::Header("content-type".to_string()),
+    ]
+}
+
+/// Generate a random base64url-encoded nonce
+pub fn generate_nonce() -> String {
+    use rand::{rngs::OsRng, RngCore};
+
+    let mut bytes = [0u8; NONCE_LENGTH];
+    OsRng.fill_bytes(&mut bytes);
+    general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,5 +768,171 @@ mod tests {
         let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
         let signer = HttpSigner::new(keypair);
         assert_eq!(signer.default_components.len(), 3);
+        assert!(!signer.generate_nonce);
+    }
+
+    #[test]
+    fn test_nonce_generation() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+        let params = HttpSigner::new(keypair.clone())
+            .build_signature_params()
+            .unwrap();
+        assert!(params.nonce.is_none());
+
+        let signer = HttpSigner::new(keypair).with_nonce(true);
+        let first = signer.build_signature_params().unwrap().nonce.unwrap();
+        let second = signer.build_signature_params().unwrap().nonce.unwrap();
+        assert_eq!(first.len(), 22);
+        assert_ne!(first, second);
+    }
+
+    #[test]
+    fn test_omit_timestamps() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+        let params = HttpSigner::new(keypair.clone())
+            .build_signature_params()
+            .unwrap();
+        assert_eq!(
+            params.expires,
+            params.created.map(|c| c + DEFAULT_EXPIRY as i64)
+        );
+
+        let params = HttpSigner::new(keypair)
+            .with_created(false)
+            .with_expiry_mode(Expiry::Never)
+            .build_signature_params()
+            .unwrap();
+        assert!(params.created.is_none());
+        assert!(params.expires.is_none());
+        assert!(params.key_id.is_some());
+    }
+
+    #[test]
+    fn test_builder_expiry_modes() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+        let params = HttpSigner::builder(keypair.clone())
+            .ttl(Duration::from_secs(60))
+            .build()
+            .unwrap()
+            .build_signature_params()
+            .unwrap();
+        assert_eq!(params.expires, params.created.map(|c| c + 60));
+
+        let params = HttpSigner::builder(keypair.clone())
+            .expires_at(2_000_000_000)
+            .tag("app")
+            .build()
+            .unwrap()
+            .build_signature_params()
+            .unwrap();
+        assert_eq!(params.expires, Some(2_000_000_000));
+        assert_eq!(params.tag.as_deref(), Some("app"));
+
+        let params = HttpSigner::builder(keypair)
+            .no_expiry()
+            .build()
+            .unwrap()
+            .build_signature_params()
+            .unwrap();
+        assert!(params.expires.is_none());
+        assert!(params.created.is_some());
+    }
+
+    #[test]
+    fn test_expiry_overflow() {
+        assert_eq!(Expiry::from(None).expires(100).unwrap(), None);
+        let ttl = Expiry::from(Some(Duration::from_secs(60)));
+        assert_eq!(ttl.expires(100).unwrap(), Some(160));
+        assert!(Expiry::Ttl(Duration::from_secs(u64::MAX))
+            .expires(0)
+            .is_err());
+        assert!(Expiry::Ttl(Duration::from_secs(i64::MAX as u64))
+            .expires(1)
+            .is_err());
+
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        assert!(HttpSigner::builder(keypair)
+            .ttl(Duration::from_secs(u64::MAX))
+            .build()
+            .is_err());
+    }
+
+    #[test]
+    fn test_builder_validation() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+        assert!(HttpSigner::builder(keypair.clone())
+            .components(vec![SignatureComponent::Method, SignatureComponent::Method])
+            .build()
+            .is_err());
+        assert!(HttpSigner::builder(keypair.clone())
+            .components(vec![SignatureComponent::Status])
+            .build()
+            .is_err());
+        assert!(HttpSigner::builder(keypair.clone())
+            .tag("a\nb")
+            .build()
+            .is_err());
+        assert!(HttpSigner::builder(keypair)
+            .components(vec![SignatureComponent::Method, SignatureComponent::Path])
+            .tag("app")
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn test_sign_request_mut() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let signer = HttpSigner::new(keypair);
+        let mut request = Request::builder()
+            .method("GET")
+            .uri("https://example.com/")
+            .body(())
+            .unwrap();
+
+        signer.sign_request_mut(&mut request).unwrap();
+        assert!(request.headers().contains_key("signature"));
+        assert!(request.headers().contains_key("signature-input"));
+    }
+
+    #[test]
+    fn test_sign_request_parts() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let signer = HttpSigner::new(keypair);
+        let headers = signer
+            .sign_request_parts(
+                "POST",
+                "https://example.com/tasks",
+                &[("content-type", "application/json")],
+            )
+            .unwrap();
+        assert!(headers.signature.starts_with("sig1=:"));
+        assert!(headers
+            .signature_input
+            .starts_with("sig1=(\"@method\" \"@path\" \"@authority\")"));
+        assert!(signer.sign_request_parts("GET", "not a uri", &[]).is_err());
+    }
+
+    #[test]
+    fn test_sign_response_with_body() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let verifier = HttpVerifier::new(keypair.public_key().clone());
+        let signer = HttpSigner::new(keypair);
+        let response = Response::builder()
+            .status(200)
+            .header("content-type", "application/json")
+            .body(b"{\"ok\":true}".to_vec())
+            .unwrap();
+
+        let signed = signer.sign_response_with_body(response).unwrap();
+        assert!(signed.headers()["signature-input"]
+            .to_str()
+            .unwrap()
+            .contains("\"content-digest\""));
+        verifier.verify_response(&signed).unwrap();
+        ContentDigest::verify_response(&signed).unwrap();
     }
 }
@@ -0,0 +1,406 @@
+//! Structured Field Values (RFC 8941) parsing for signature headers
+
+use crate::error::{Error, Result};
+use base64::{engine::general_purpose, Engine as _};
+use std::fmt;
+
+/// A bare item value
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum BareItem {
+    Integer(i64),
+    Decimal(f64),
+    String(String),
+    Token(String),
+    ByteSequence(Vec<u8>),
+    Boolean(bool),
+}
+
+impl fmt::Display for BareItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BareItem::Integer(value) => write!(f, "{value}"),
+            BareItem::Decimal(value) => write!(f, "{value}"),
+            BareItem::String(value) => {
+                write!(
+                    f,
+                    "\"{}\"",
+                    value.replace('\\', "\\\\").replace('"', "\\\"")
+                )
+            }
+            BareItem::Token(value) => f.write_str(value),
+            BareItem::ByteSequence(value) => {
+                write!(f, ":{}:", general_purpose::STANDARD.encode(value))
+            }
+            BareItem::Boolean(value) => write!(f, "?{}", u8::from(*value)),
+        }
+    }
+}
+
+/// Ordered parameters attached to an item or inner list
+pub(crate) type Parameters = Vec<(String, BareItem)>;
+
+/// An item with its parameters
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Item {
+    pub(crate) value: BareItem,
+    pub(crate) params: Parameters,
+}
+
+/// An inner list with its parameters
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct InnerList {
+    pub(crate) items: Vec<Item>,
+    pub(crate) params: Parameters,
+}
+
+/// Check that `value` can be serialized as a String (printable ASCII only)
+pub(crate) fn check_string(name: &str, value: &str) -> Result<()> {
+    if value.bytes().all(|b| (0x20..=0x7e).contains(&b)) {
+        Ok(())
+    } else {
+        Err(Error::InvalidInput(format!(
+            "{name} must be printable ASCII to serialize as a structured field string"
+        )))
+    }
+}
+
+/// Parse a serialized String, e.g. `"a\"b"`, into its value
+pub(crate) fn parse_string(input: &str) -> Result<String> {
+    let mut parser = Parser::new(input);
+    match parser.bare_item()? {
+        BareItem::String(value) => {
+            parser.expect_end()?;
+            Ok(value)
+        }
+        other => Err(Error::InvalidInput(format!(
+            "Expected a string, got {other}"
+        ))),
+    }
+}
+
+/// Parse a field value consisting of a single inner list, e.g. a signature-input member
+pub(crate) fn parse_inner_list(input: &str) -> Result<InnerList> {
+    let mut parser = Parser::new(input);
+    parser.skip_sp();
+    let list = parser.inner_list()?;
+    parser.skip_sp();
+    parser.expect_end()?;
+    Ok(list)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input: input.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let c = self.peek()?;
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn error(&self, message: &str) -> Error {
+        Error::InvalidInput(format!(
+            "Invalid structured field at offset {}: {message}",
+            self.pos
+        ))
+    }
+
+    fn skip_sp(&mut self) {
+        while self.peek() == Some(b' ') {
+            self.pos += 1;
+        }
+    }
+
+    fn expect_end(&self) -> Result<()> {
+        match self.peek() {
+            None => Ok(()),
+            Some(_) => Err(self.error("unexpected trailing characters")),
+        }
+    }
+
+    fn inner_list(&mut self) -> Result<InnerList> {
+        if self.bump() != Some(b'(') {
+            return Err(self.error("expected '('"));
+        }
+
+        let mut items = Vec::new();
+        loop {
+            self.skip_sp();
+            if self.peek() == Some(b')') {
+                self.pos += 1;
+                let params = self.parameters()?;
+                return Ok(InnerList { items, params });
+            }
+
+            let value = self.bare_item()?;
+            let params = self.parameters()?;
+            items.push(Item { value, params });
+
+            match self.peek() {
+                Some(b' ') | Some(b')') => {}
+                None => return Err(self.error("unterminated inner list")),
+                Some(_) => return Err(self.error("expected ' ' or ')' after item")),
+            }
+        }
+    }
+
+    fn parameters(&mut self) -> Result<Parameters> {
+        let mut params: Parameters = Vec::new();
+
+        while self.peek() == Some(b';') {
+            self.pos += 1;
+            self.skip_sp();
+            let key = self.key()?;
+            let value = if self.peek() == Some(b'=') {
+                self.pos += 1;
+                self.bare_item()?
+            } else {
+                BareItem::Boolean(true)
+            };
+
+            // Later parameters with the same key override earlier ones
+            match params.iter_mut().find(|(k, _)| *k == key) {
+                Some(existing) => existing.1 = value,
+                None => params.push((key, value)),
+            }
+        }
+
+        Ok(params)
+    }
+
+    fn key(&mut self) -> Result<String> {
+        let start = self.pos;
+        match self.peek() {
+            Some(c) if c.is_ascii_lowercase() || c == b'*' => self.pos += 1,
+            _ => return Err(self.error("expected key")),
+        }
+        while let Some(c) = self.peek() {
+            if c.is_ascii_lowercase() || c.is_ascii_digit() || b"_-.*".contains(&c) {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        Ok(self.slice(start))
+    }
+
+    fn bare_item(&mut self) -> Result<BareItem> {
+        match self.peek() {
+            Some(b'-') | Some(b'0'..=b'9') => self.number(),
+            Some(b'"') => self.string(),
+            Some(b':') => self.byte_sequence(),
+            Some(b'?') => self.boolean(),
+            Some(c) if c.is_ascii_alphabetic() || c == b'*' => self.token(),
+            _ => Err(self.error("expected item")),
+        }
+    }
+
+    fn number(&mut self) -> Result<BareItem> {
+        let start = self.pos;
+        if self.peek() == Some(b'-') {
+            self.pos += 1;
+        }
+
+        let mut digits = 0;
+        let mut decimal = false;
+        while let Some(c) = self.peek() {
+            match c {
+                b'0'..=b'9' => digits += 1,
+                b'.' if !decimal && digits > 0 && digits <= 12 => decimal = true,
+                _ => break,
+            }
+            self.pos += 1;
+        }
+
+        let text = self.slice(start);
+        if digits == 0 || text.ends_with('.') {
+            return Err(self.error("invalid number"));
+        }
+
+        if decimal {
+            let fraction = text.len() - text.find('.').unwrap_or(text.len()) - 1;
+            if fraction > 3 {
+                return Err(self.error("decimal has too many fractional digits"));
+            }
+            text.parse()
+                .map(BareItem::Decimal)
+                .map_err(|_| self.error("invalid decimal"))
+        } else {
+            if digits > 15 {
+                return Err(self.error("integer has too many digits"));
+            }
+            text.parse()
+                .map(BareItem::Integer)
+                .map_err(|_| self.error("invalid integer"))
+        }
+    }
+
+    fn string(&mut self) -> Result<BareItem> {
+        self.pos += 1;
+        let mut out = String::new();
+        loop {
+            match self.bump() {
+                Some(b'\\') => match self.bump() {
+                    Some(c @ (b'"' | b'\\')) => out.push(c as char),
+                    _ => return Err(self.error("invalid escape in string")),
+                },
+                Some(b'"') => return Ok(BareItem::String(out)),
+                Some(c @ 0x20..=0x7e) => out.push(c as char),
+                Some(_) => return Err(self.error("invalid character in string")),
+                None => return Err(self.error("unterminated string")),
+            }
+        }
+    }
+
+    fn token(&mut self) -> Result<BareItem> {
+        let start = self.pos;
+        self.pos += 1;
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~:/".contains(&c) {
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+        Ok(BareItem::Token(self.slice(start)))
+    }
+
+    fn byte_sequence(&mut self) -> Result<BareItem> {
+        self.pos += 1;
+        let start = self.pos;
+        while let Some(c) = self.peek() {
+            if c == b':' {
+                let encoded = self.slice(start);
+                self.pos += 1;
+                return general_purpose::STANDARD
+                    .decode(encoded)
+                    .map(BareItem::ByteSequence)
+                    .map_err(|_| self.error("invalid base64 in byte sequence"));
+            }
+            if !(c.is_ascii_alphanumeric() || b"+/=".contains(&c)) {
+                return Err(self.error("invalid character in byte sequence"));
+            }
+            self.pos += 1;
+        }
+        Err(self.error("unterminated byte sequence"))
+    }
+
+    fn boolean(&mut self) -> Result<BareItem> {
+        self.pos += 1;
+        match self.bump() {
+            Some(b'1') => Ok(BareItem::Boolean(true)),
+            Some(b'0') => Ok(BareItem::Boolean(false)),
+            _ => Err(self.error("invalid boolean")),
+        }
+    }
+
+    fn slice(&self, start: usize) -> String {
+        String::from_utf8_lossy(&self.input[start..self.pos]).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_signature_input_list() {
+        let list = parse_inner_list(
+            r#"("@method" "content-type";sf "example-dict";key="a");created=1618884473;keyid="test-key-rsa-pss""#,
+        )
+        .unwrap();
+        assert_eq!(list.items.len(), 3);
+        assert_eq!(list.items[0].value, BareItem::String("@method".to_string()));
+        assert_eq!(
+            list.items[1].params,
+            vec![("sf".to_string(), BareItem::Boolean(true))]
+        );
+        assert_eq!(
+            list.items[2].params,
+            vec![("key".to_string(), BareItem::String("a".to_string()))]
+        );
+        assert_eq!(
+            list.params,
+            vec![
+                ("created".to_string(), BareItem::Integer(1618884473)),
+                (
+                    "keyid".to_string(),
+                    BareItem::String("test-key-rsa-pss".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_strings_with_delimiters() {
+        let list = parse_inner_list(r#"("a");tag="x;y) \"z\"";nonce="\\""#).unwrap();
+        assert_eq!(list.params[0].1, BareItem::String("x;y) \"z\"".to_string()));
+        assert_eq!(list.params[1].1, BareItem::String("\\".to_string()));
+    }
+
+    #[test]
+    fn test_bare_item_types() {
+        let list = parse_inner_list("(1 -2 3.5 tok/en :AQI=: ?0);p; q=*x").unwrap();
+        let values: Vec<_> = list.items.into_iter().map(|i| i.value).collect();
+        assert_eq!(
+            values,
+            vec![
+                BareItem::Integer(1),
+                BareItem::Integer(-2),
+                BareItem::Decimal(3.5),
+                BareItem::Token("tok/en".to_string()),
+                BareItem::ByteSequence(vec![1, 2]),
+                BareItem::Boolean(false),
+            ]
+        );
+        assert_eq!(list.params[0].1, BareItem::Boolean(true));
+        assert_eq!(list.params[1].1, BareItem::Token("*x".to_string()));
+    }
+
+    #[test]
+    fn test_bare_item_display() {
+        assert_eq!(
+            BareItem::String("a\"b\\".to_string()).to_string(),
+            r#""a\"b\\""#
+        );
+        assert_eq!(BareItem::ByteSequence(vec![1, 2]).to_string(), ":AQI=:");
+        assert_eq!(BareItem::Boolean(true).to_string(), "?1");
+        assert_eq!(BareItem::Decimal(1.5).to_string(), "1.5");
+    }
+
+    #[test]
+    fn test_malformed_inner_lists() {
+        for input in [
+            "",
+            "\"@method\"",
+            "(\"@method\"",
+            "(\"@method\"\"@path\")",
+            "(\"unterminated)",
+            "(\"bad\\escape\")",
+            "(\"@method\");Created=1",
+            "(\"@method\");created=",
+            "(\"@method\");created=1234567890123456",
+            "(\"@method\");x=1.2345",
+            "(\"@method\");x=:not base64!:",
+            "(\"@method\");x=?2",
+            "(\"@method\") trailing",
+            "(\"@method\");;",
+            "(\"caf\u{e9}\")",
+        ] {
+            assert!(parse_inner_list(input).is_err(), "accepted {input:?}");
+        }
+    }
+}
@@ -1,239 +1,621 @@
 //! HTTP message signature verification for RFC 9421
 
-use crate::crypto::{PublicKey, Signature, Verifier as CryptoVerifier};
+use crate::crypto::{KeyIdStrategy, PublicKey, Signature, Verifier as CryptoVerifier};
 use crate::error::{Error, Result};
-use crate::rfc9421::{SignatureComponent, SignatureParams};
+use crate::rfc9421::{ComponentParams, SignatureBase, SignatureComponent, SignatureParams};
 use base64::{engine::general_purpose, Engine as _};
-use http::{HeaderMap, Request, Response};
-use std::time::{SystemTime, UNIX_EPOCH};
+use http::{Request, Response};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+use super::algorithm::AlgorithmRegistry;
+use super::canonicalize::CanonicalizeOptions;
+use super::digest::{ContentDigest, CONTENT_DIGEST_HEADER};
+use super::message::HttpMessage;
+use super::nonce::NonceStore;
+use super::policy::VerificationPolicy;
+use super::report::VerificationReport;
+use super::resolver::{KeyResolver, KeySet};
+use super::signer::DEFAULT_LABEL;
+use super::structured::{parse_inner_list, BareItem, Item};
+use super::SignatureAlgorithm;
+
+/// Replay window used when a signature carries neither `expires` nor `created`
+const DEFAULT_REPLAY_WINDOW: i64 = 300;
+
+/// Source of the public key used to verify a signature
+#[derive(Clone)]
+enum KeySource {
+    /// A single pinned public key
+    Fixed(PublicKey),
+    /// Keys looked up by the signature's `keyid` parameter
+    Resolver(Arc<dyn KeyResolver>),
+}
 
 /// HTTP message signature verifier
+#[derive(Clone)]
 pub struct HttpVerifier {
-    public_key: PublicKey,
+    keys: KeySource,
+    label: String,
+    key_id_strategy: KeyIdStrategy,
+    nonce_store: Option<Arc<dyn NonceStore>>,
+    policy: VerificationPolicy,
+    algorithms: AlgorithmRegistry,
+    canonicalize_options: CanonicalizeOptions,
 }
 
 impl HttpVerifier {
     /// Create a new HTTP verifier with a public key
     pub fn new(public_key: PublicKey) -> Self {
-        Self { public_key }
+        Self::with_key_source(KeySource::Fixed(public_key))
+    }
+
+    /// Create a verifier that looks up the public key by the signature's `keyid`
+    ///
+    /// Signatures without a `keyid` parameter are rejected.
+    pub fn from_resolver(resolver: Arc<dyn KeyResolver>) -> Self {
+        Self::with_key_source(KeySource::Resolver(resolver))
     }
 
-    /// Parse signature bytes into a Signature enum based on the public key type
-    fn parse_signature(&self, signature_bytes: &[u8]) -> Result<Signature> {
-        match &self.public_key {
-            PublicKey::Ed25519(_) => {
-                if signature_bytes.len() != 64 {
-                    return Err(Error::InvalidInput(
-                        "Ed25519 signature must be 64 bytes".to_string(),
-                    ));
+    /// Create a verifier that trusts every key in a key set
+    ///
+    /// The key matching the signature's `keyid` is used.
+    pub fn from_key_set(key_set: KeySet) -> Self {
+        Self::from_resolver(Arc::new(key_set))
+    }
+
+    fn with_key_source(keys: KeySource) -> Self {
+        Self {
+            keys,
+            label: DEFAULT_LABEL.to_string(),
+            key_id_strategy: KeyIdStrategy::default(),
+            nonce_store: None,
+            policy: VerificationPolicy::default(),
+            algorithms: AlgorithmRegistry::default(),
+            canonicalize_options: CanonicalizeOptions::default(),
+        }
+    }
+
+    /// Verify signatures whose `alg` names a custom algorithm with the registered verifiers
+    ///
+    /// Custom verifiers find their own keys, bypassing the configured public
+    /// key or resolver, so a custom algorithm is only used once the policy
+    /// accepts it with [`VerificationPolicy::allow_algorithm`].
+    pub fn with_algorithms(mut self, algorithms: AlgorithmRegistry) -> Self {
+        self.algorithms = algorithms;
+        self
+    }
+
+    /// Verify the signature with the given label (`sig1` by default)
+    ///
+    /// Other signatures on the message are ignored.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = label.into();
+        self
+    }
+
+    /// Normalize percent-encoding in URI components before verifying
+    ///
+    /// Must match the configuration of the signer.
+    pub fn with_uri_normalization(mut self, enabled: bool) -> Self {
+        self.canonicalize_options.normalize_percent_encoding = enabled;
+        self
+    }
+
+    /// Enforce a verification policy on every signature
+    pub fn with_policy(mut self, policy: VerificationPolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// The verification policy enforced on every signature
+    pub fn policy(&self) -> &VerificationPolicy {
+        &self.policy
+    }
+
+    /// Reject replayed signatures using the given nonce store
+    ///
+    /// Signatures are identified by `(keyid, nonce)`; signatures without a nonce
+    /// fall back to a hash of the signature base, so re-encoding a signature
+    /// does not get it past the store.
+    pub fn with_nonce_store(mut self, store: Arc<dyn NonceStore>) -> Self {
+        self.nonce_store = Some(store);
+        self
+    }
+
+    /// Set the strategy used to derive the expected key ID of a pinned public key
+    ///
+    /// This must match the strategy of the signing `KeyPair`.
+    pub fn with_key_id_strategy(mut self, strategy: KeyIdStrategy) -> Self {
+        self.key_id_strategy = strategy;
+        self
+    }
+
+    /// Find the public key for a signature and the key ID it is known by
+    fn resolve_key(&self, params: &SignatureParams) -> Result<(PublicKey, String)> {
+        match &self.keys {
+            KeySource::Fixed(public_key) => {
+                let expected_key_id = public_key.key_id_with(&self.key_id_strategy)?;
+                if let Some(ref key_id) = params.key_id {
+                    if key_id != &expected_key_id {
+                        return Err(Error::Verification("Key ID mismatch".to_string()));
+                    }
                 }
-                let mut sig_array = [0u8; 64];
-                sig_array.copy_from_slice(signature_bytes);
-                Ok(Signature::Ed25519(ed25519_dalek::Signature::from_bytes(
-                    &sig_array,
-                )))
+                Ok((public_key.clone(), expected_key_id))
             }
-            PublicKey::Secp256k1(_) => {
-                Ok(Signature::Secp256k1(
-                    k256::ecdsa::Signature::from_der(signature_bytes).or_else(|_| {
-                        // Try fixed-size format if DER fails
-                        if signature_bytes.len() == 64 {
-                            k256::ecdsa::Signature::try_from(signature_bytes).map_err(|e| {
-                                Error::InvalidInput(format!("Invalid ECDSA signature: {e}"))
-                            })
-                        } else {
-                            Err(Error::InvalidInput(
-                                "Invalid Secp256k1 signature format".to_string(),
-                            ))
-                        }
-                    })?,
-                ))
+            KeySource::Resolver(resolver) => {
+                let key_id = params.key_id.as_deref().ok_or_else(|| {
+                    Error::Verification("Signature is missing the keyid parameter".to_string())
+                })?;
+                Ok((resolver.resolve(key_id)?, key_id.to_string()))
             }
         }
     }
 
-    /// Verify an HTTP request signature
-    pub fn verify_request<B>(&self, request: &Request<B>) -> Result<()> {
-        // Extract signature and signature-input headers
-        let (sig_value, sig_input) = extract_signature_headers(request.headers())?;
+    /// Check the covered components and parameters against the verification policy
+    ///
+    /// Returns the algorithm the signature must be verified with.
+    fn check_policy(
+        &self,
+        public_key: &PublicKey,
+        components: &[SignatureComponent],
+        params: &SignatureParams,
+    ) -> Result<SignatureAlgorithm> {
+        let key_algorithm = SignatureAlgorithm::for_key_type(public_key.key_type());
+
+        // The alg parameter must never override the algorithm implied by the key
+        if let Some(ref alg) = params.alg {
+            match SignatureAlgorithm::from_identifier(alg) {
+                Some(algorithm) if algorithm == key_algorithm => {}
+                Some(_) => {
+                    return Err(Error::Verification(format!(
+                        "Algorithm {alg} does not match the {:?} key",
+                        public_key.key_type()
+                    )))
+                }
+                None => {
+                    return Err(Error::Verification(format!(
+                        "Unsupported signature algorithm: {alg}"
+                    )))
+                }
+            }
+        }
 
-        // Parse signature input to get components and parameters
-        let (components, params) = parse_signature_input(&sig_input)?;
+        self.policy
+            .check(components, params, key_algorithm.identifier(), unix_now()?)?;
+        Ok(key_algorithm)
+    }
 
-        // Verify signature parameters
-        verify_signature_params(&params, &self.public_key)?;
+    /// Consult the nonce store, if any, to reject replayed signatures
+    fn check_replay(&self, signature_base: &SignatureBase, key_id: &str) -> Result<()> {
+        let Some(store) = &self.nonce_store else {
+            return Ok(());
+        };
+
+        let now = unix_now()?;
+        let params = signature_base.params();
+        // The signature base covers `created`, unlike the signature encoding
+        let nonce = match &params.nonce {
+            Some(nonce) => nonce.clone(),
+            None => hex::encode(Sha256::digest(signature_base.as_bytes())),
+        };
+        let expires_at = params
+            .expires
+            .or(params
+                .created
+                .map(|created| created + DEFAULT_REPLAY_WINDOW))
+            .unwrap_or(now + DEFAULT_REPLAY_WINDOW);
+
+        store.check_and_insert(key_id, &nonce, expires_at, now)
+    }
 
-        // Canonicalize the request
-        let canonical_values = super::canonicalize::canonicalize_request(request, &components)?;
+    /// Check the message's Content-Digest header against the allowed digest algorithms
+    ///
+    /// The body itself is checked by [`ContentDigest::verify_request_with_policy`]
+    /// or the streaming integrations.
+    fn check_content_digest<M: HttpMessage + ?Sized>(&self, message: &M) -> Result<()> {
+        if !self.policy.restricts_digest_algorithms() {
+            return Ok(());
+        }
+        let values = message.header_values(CONTENT_DIGEST_HEADER);
+        if values.is_empty() {
+            return Ok(());
+        }
+        let value = values
+            .iter()
+            .map(|value| std::str::from_utf8(value))
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|_| Error::InvalidInput("Invalid content-digest header encoding".to_string()))?
+            .join(", ");
+        self.policy
+            .check_content_digest(&ContentDigest::parse(&value)?)
+    }
 
-        // Build signature base
-        let signature_base =
-            super::canonicalize::build_signature_base(&canonical_values, &sig_input);
+    /// Run every check on a signature and the signature base it claims to cover
+    fn verify_parsed(
+        &self,
+        sig_value: &str,
+        signature_base: SignatureBase,
+    ) -> Result<VerificationReport> {
+        let params = signature_base.params();
 
-        // Decode and verify signature
+        // Decode the signature
         let signature_bytes = general_purpose::STANDARD
-            .decode(&sig_value)
+            .decode(sig_value)
             .map_err(|_| Error::InvalidInput("Invalid base64 signature".to_string()))?;
 
-        let signature = self.parse_signature(&signature_bytes)?;
+        let custom = params
+            .alg
+            .as_deref()
+            .and_then(|alg| self.algorithms.get(alg));
+
+        let (key_id, algorithm) = match custom {
+            Some(custom) => {
+                let algorithm = SignatureAlgorithm::from_identifier(custom.identifier())
+                    .unwrap_or(SignatureAlgorithm::Custom(custom.identifier()));
+                if !self
+                    .policy
+                    .explicitly_allows_algorithm(algorithm.identifier())
+                {
+                    return Err(Error::Verification(format!(
+                        "Algorithm {} is not allowed",
+                        algorithm.identifier()
+                    )));
+                }
+                self.policy.check(
+                    signature_base.components(),
+                    params,
+                    algorithm.identifier(),
+                    unix_now()?,
+                )?;
+
+                custom.verify(
+                    params.key_id.as_deref(),
+                    signature_base.as_bytes(),
+                    &signature_bytes,
+                )?;
+                (params.key_id.clone().unwrap_or_default(), algorithm)
+            }
+            None => {
+                // Find the signing key and check parameters against the policy
+                let (public_key, key_id) = self.resolve_key(params)?;
+                let algorithm =
+                    self.check_policy(&public_key, signature_base.components(), params)?;
+
+                let signature = parse_signature(&public_key, &signature_bytes)?;
+                public_key.verify(signature_base.as_bytes(), &signature)?;
+                (key_id, algorithm)
+            }
+        };
+
+        // Record the signature for replay detection
+        self.check_replay(&signature_base, &key_id)?;
+
+        Ok(VerificationReport {
+            label: self.label.clone(),
+            key_id,
+            algorithm,
+            components: signature_base.components().to_vec(),
+            created: params.created,
+            expires: params.expires,
+            nonce: params.nonce.clone(),
+            tag: params.tag.clone(),
+        })
+    }
 
-        self.public_key
-            .verify(signature_base.as_bytes(), &signature)?;
+    /// Verify an HTTP request signature
+    pub fn verify_request<B>(&self, request: &Request<B>) -> Result<()> {
+        self.verify_request_detailed(request).map(|_| ())
+    }
 
-        Ok(())
+    /// Verify an HTTP request signature and report what was verified
+    pub fn verify_request_detailed<B>(&self, request: &Request<B>) -> Result<VerificationReport> {
+        self.verify_message(request)
     }
 
     /// Verify an HTTP response signature
     pub fn verify_response<B>(&self, response: &Response<B>) -> Result<()> {
-        // Extract signature and signature-input headers
-        let (sig_value, sig_input) = extract_signature_headers(response.headers())?;
+        self.verify_response_detailed(response).map(|_| ())
+    }
 
-        // Parse signature input to get components and parameters
-        let (components, params) = parse_signature_input(&sig_input)?;
+    /// Verify an HTTP response signature and report what was verified
+    pub fn verify_response_detailed<B>(
+        &self,
+        response: &Response<B>,
+    ) -> Result<VerificationReport> {
+        self.verify_message(response)
+    }
 
-        // Verify signature parameters
-        verify_signature_params(&params, &self.public_key)?;
+    /// Verify an HTTP response signature whose components may refer to the request (`;req`)
+    pub fn verify_response_with_request<B, R>(
+        &self,
+        response: &Response<B>,
+        request: &Request<R>,
+    ) -> Result<()> {
+        self.check_content_digest(response)?;
+        let (sig_value, signature_base) = message_signature_base(
+            response,
+            Some(request),
+            &self.label,
+            &self.canonicalize_options,
+        )?;
+        self.verify_parsed(&sig_value, signature_base).map(|_| ())
+    }
 
-        // Canonicalize the response
-        let canonical_values = super::canonicalize::canonicalize_response(response, &components)?;
+    /// Verify the signature of any [`HttpMessage`]
+    pub fn verify_message<M: HttpMessage + ?Sized>(
+        &self,
+        message: &M,
+    ) -> Result<VerificationReport> {
+        self.check_content_digest(message)?;
+        let (sig_value, signature_base) =
+            message_signature_base::<M, M>(message, None, &self.label, &self.canonicalize_options)?;
+        self.verify_parsed(&sig_value, signature_base)
+    }
 
-        // Build signature base
-        let signature_base =
-            super::canonicalize::build_signature_base(&canonical_values, &sig_input);
+    /// Reconstruct the signature base of a signed request without verifying it
+    pub fn signature_base_for_request<B>(&self, request: &Request<B>) -> Result<SignatureBase> {
+        self.signature_base_for_message(request)
+    }
 
-        // Decode and verify signature
-        let signature_bytes = general_purpose::STANDARD
-            .decode(&sig_value)
-            .map_err(|_| Error::InvalidInput("Invalid base64 signature".to_string()))?;
+    /// Reconstruct the signature base of a signed response without verifying it
+    pub fn signature_base_for_response<B>(&self, response: &Response<B>) -> Result<SignatureBase> {
+        self.signature_base_for_message(response)
+    }
 
-        let signature = self.parse_signature(&signature_bytes)?;
+    /// Reconstruct the signature base of any signed [`HttpMessage`] without verifying it
+    pub fn signature_base_for_message<M: HttpMessage + ?Sized>(
+        &self,
+        message: &M,
+    ) -> Result<SignatureBase> {
+        message_signature_base::<M, M>(message, None, &self.label, &self.canonicalize_options)
+            .map(|(_, signature_base)| signature_base)
+    }
+}
 
-        self.public_key
-            .verify(signature_base.as_bytes(), &signature)?;
+/// Extract the labeled signature value and rebuild the signature base of a message
+fn message_signature_base<M, R>(
+    message: &M,
+    request: Option<&R>,
+    label: &str,
+    options: &CanonicalizeOptions,
+) -> Result<(String, SignatureBase)>
+where
+    M: HttpMessage + ?Sized,
+    R: HttpMessage + ?Sized,
+{
+    // Extract signature and signature-input headers
+    let (sig_value, sig_input) = extract_signature_headers(message, label)?;
+
+    // Parse signature input to get components and parameters
+    let (components, params) = parse_signature_input(&sig_input)?;
+
+    // Canonicalize the message
+    let canonical_values =
+        super::canonicalize::canonicalize_message_with(message, request, &components, options)?;
+
+    let signature_base = SignatureBase::new(&canonical_values, components, params, &sig_input);
+    Ok((sig_value, signature_base))
+}
 
-        Ok(())
+/// Parse signature bytes into a Signature enum based on the public key type
+fn parse_signature(public_key: &PublicKey, signature_bytes: &[u8]) -> Result<Signature> {
+    match public_key {
+        PublicKey::Ed25519(_) => {
+            if signature_bytes.len() != 64 {
+                return Err(Error::InvalidInput(
+                    "Ed25519 signature must be 64 bytes".to_string(),
+                ));
+            }
+            let mut sig_array = [0u8; 64];
+            sig_array.copy_from_slice(signature_bytes);
+            Ok(Signature::Ed25519(ed25519_dalek::Signature::from_bytes(
+                &sig_array,
+            )))
+        }
+        PublicKey::Secp256k1(_) => {
+            Ok(Signature::Secp256k1(
+                k256::ecdsa::Signature::from_der(signature_bytes).or_else(|_| {
+                    // Try fixed-size format if DER fails
+                    if signature_bytes.len() == 64 {
+                        k256::ecdsa::Signature::try_from(signature_bytes).map_err(|e| {
+                            Error::InvalidInput(format!("Invalid ECDSA signature: {e}"))
+                        })
+                    } else {
+                        Err(Error::InvalidInput(
+                            "Invalid Secp256k1 signature format".to_string(),
+                        ))
+                    }
+                })?,
+            ))
+        }
     }
 }
 
-/// Extract signature headers from HTTP headers
-fn extract_signature_headers(headers: &HeaderMap) -> Result<(String, String)> {
-    let sig_header = headers
-        .get("signature")
-        .ok_or_else(|| Error::InvalidInput("Missing signature header".to_string()))?
-        .to_str()
-        .map_err(|_| Error::InvalidInput("Invalid signature header encoding".to_string()))?;
-
-    let sig_input_header = headers
-        .get("signature-input")
-        .ok_or_else(|| Error::InvalidInput("Missing signature-input header".to_string()))?
-        .to_str()
-        .map_err(|_| Error::InvalidInput("Invalid signature-input header encoding".to_string()))?;
-
-    // Extract sig1 value from headers (simplified - real implementation would handle multiple signatures)
-    let sig_value = sig_header
-        .strip_prefix("sig1=:")
-        .ok_or_else(|| Error::InvalidInput("Invalid signature header format".to_string()))?
-        .to_string();
+/// Labels of the signatures on an HTTP message, in `Signature-Input` order
+///
+/// Lets applications see which signatures are present before choosing a verifier.
+pub fn signature_labels<M: HttpMessage + ?Sized>(message: &M) -> Result<Vec<String>> {
+    let values = message.header_values("signature-input");
+    if values.is_empty() {
+        return Ok(Vec::new());
+    }
+    let value = super::canonicalize::get_header_value(&values, "signature-input")?;
+    Ok(super::canonicalize::dictionary_keys(&value))
+}
 
-    let sig_input = sig_input_header
-        .strip_prefix("sig1=")
-        .ok_or_else(|| Error::InvalidInput("Invalid signature-input header format".to_string()))?
+/// Extract the signature value and signature input with the given label from an HTTP message
+///
+/// The signature value is returned base64-encoded, without the byte sequence delimiters.
+pub fn extract_signature_headers<M: HttpMessage + ?Sized>(
+    message: &M,
+    label: &str,
+) -> Result<(String, String)> {
+    let sig_member = dictionary_header(message, "signature", label)?;
+    let sig_input = dictionary_header(message, "signature-input", label)?;
+
+    let sig_value = sig_member
+        .strip_prefix(':')
+        .and_then(|value| value.strip_suffix(':'))
+        .ok_or_else(|| Error::InvalidInput("Invalid signature header format".to_string()))?
         .to_string();
 
     Ok((sig_value, sig_input))
 }
 
-/// Parse signature input to extract components and parameters
-fn parse_signature_input(input: &str) -> Result<(Vec<SignatureComponent>, SignatureParams)> {
-    // This is a simplified parser - a real implementation would be more robust
-    let parts: Vec<&str> = input.splitn(2, ')').collect();
-    if parts.len() != 2 {
-        return Err(Error::InvalidInput(
-            "Invalid signature input format".to_string(),
-        ));
+/// Get the member with the given label of a dictionary header
+fn dictionary_header<M: HttpMessage + ?Sized>(
+    message: &M,
+    name: &str,
+    label: &str,
+) -> Result<String> {
+    let values = message.header_values(name);
+    if values.is_empty() {
+        return Err(Error::InvalidInput(format!("Missing {name} header")));
     }
+    let value = super::canonicalize::get_header_value(&values, name)?;
+    super::canonicalize::dictionary_member(&value, label)
+        .map_err(|_| Error::InvalidInput(format!("No {label} member in {name} header")))
+}
 
-    let components_str = parts[0].trim_start_matches('(');
-    let params_str = parts[1];
+/// Parse a single signature input, e.g. `("@method" "@path");keyid="k"`, into components and parameters
+///
+/// Unknown signature parameters are ignored.
+pub fn parse_signature_input(input: &str) -> Result<(Vec<SignatureComponent>, SignatureParams)> {
+    let list = parse_inner_list(input)?;
 
     // Parse components
-    let components: Result<Vec<SignatureComponent>> = components_str
-        .split_whitespace()
-        .map(|s| {
-            let component_id = s.trim_matches('"');
-            match component_id {
-                "@method" => Ok(SignatureComponent::Method),
-                "@target-uri" => Ok(SignatureComponent::TargetUri),
-                "@authority" => Ok(SignatureComponent::Authority),
-                "@scheme" => Ok(SignatureComponent::Scheme),
-                "@request-target" => Ok(SignatureComponent::RequestTarget),
-                "@path" => Ok(SignatureComponent::Path),
-                "@query" => Ok(SignatureComponent::Query),
-                "@status" => Ok(SignatureComponent::Status),
-                _ if component_id.starts_with('@') => Err(Error::Unsupported(format!(
-                    "Unsupported derived component: {component_id}"
-                ))),
-                _ => Ok(SignatureComponent::Header(component_id.to_string())),
-            }
-        })
-        .collect();
-
-    let components = components?;
+    let mut components: Vec<SignatureComponent> = Vec::with_capacity(list.items.len());
+    for item in &list.items {
+        let component = parse_component(item)?;
+        if components
+            .iter()
+            .any(|c| c.serialize() == component.serialize())
+        {
+            return Err(Error::InvalidInput(format!(
+                "Duplicate component: {}",
+                component.serialize()
+            )));
+        }
+        components.push(component);
+    }
 
-    // Parse parameters (simplified)
+    // Parse parameters, ignoring unknown ones
     let mut params = SignatureParams::default();
-    for param in params_str.split(';') {
-        let param = param.trim();
-        if let Some(stripped) = param.strip_prefix("keyid=") {
-            params.key_id = Some(stripped.trim_matches('"').to_string());
-        } else if let Some(stripped) = param.strip_prefix("alg=") {
-            params.alg = Some(stripped.trim_matches('"').to_string());
-        } else if let Some(stripped) = param.strip_prefix("created=") {
-            params.created = stripped.parse().ok();
-        } else if let Some(stripped) = param.strip_prefix("expires=") {
-            params.expires = stripped.parse().ok();
+    for (key, value) in &list.params {
+        match key.as_str() {
+            "keyid" => params.key_id = Some(string_param(key, value)?),
+            "alg" => params.alg = Some(string_param(key, value)?),
+            "created" => params.created = Some(integer_param(key, value)?),
+            "expires" => params.expires = Some(integer_param(key, value)?),
+            "nonce" => params.nonce = Some(string_param(key, value)?),
+            "tag" => params.tag = Some(string_param(key, value)?),
+            _ => {}
         }
     }
 
     Ok((components, params))
 }
 
-/// Verify signature parameters
-fn verify_signature_params(params: &SignatureParams, public_key: &PublicKey) -> Result<()> {
-    // Verify timestamp if present
-    if let Some(created) = params.created {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|_| Error::Other("System time error".to_string()))?
-            .as_secs() as i64;
-
-        // Allow some clock skew (5 minutes)
-        if created > now + 300 {
-            return Err(Error::Verification(
-                "Signature created in the future".to_string(),
-            ));
-        }
+/// Get the value of a string parameter
+fn string_param(key: &str, value: &BareItem) -> Result<String> {
+    match value {
+        BareItem::String(s) => Ok(s.clone()),
+        other => Err(Error::InvalidInput(format!(
+            "Parameter {key} must be a string, got {other}"
+        ))),
     }
+}
+
+/// Get the value of an integer parameter
+fn integer_param(key: &str, value: &BareItem) -> Result<i64> {
+    match value {
+        BareItem::Integer(i) => Ok(*i),
+        other => Err(Error::InvalidInput(format!(
+            "Parameter {key} must be an integer, got {other}"
+        ))),
+    }
+}
 
-    if let Some(expires) = params.expires {
-        let now = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map_err(|_| Error::Other("System time error".to_string()))?
-            .as_secs() as i64;
+/// Get the value of a boolean component parameter, which must be `true`
+fn flag_param(key: &str, value: &BareItem) -> Result<bool> {
+    match value {
+        BareItem::Boolean(true) => Ok(true),
+        other => Err(Error::InvalidInput(format!(
+            "Component parameter {key} must be true, got {other}"
+        ))),
+    }
+}
 
-        if expires < now {
-            return Err(Error::Verification("Signature expired".to_string()));
+/// Parse a single component identifier with optional component parameters
+fn parse_component(item: &Item) -> Result<SignatureComponent> {
+    let component_id = match &item.value {
+        BareItem::String(id) => id.as_str(),
+        other => {
+            return Err(Error::InvalidInput(format!(
+                "Component identifier must be a string, got {other}"
+            )))
+        }
+    };
+
+    let mut derived_params = Vec::new();
+    let mut params = ComponentParams::default();
+    for (key, value) in &item.params {
+        match key.as_str() {
+            "sf" => params.sf = flag_param(key, value)?,
+            "bs" => params.bs = flag_param(key, value)?,
+            "req" => params.req = flag_param(key, value)?,
+            "tr" => params.tr = flag_param(key, value)?,
+            "key" => params.key = Some(string_param(key, value)?),
+            "name" if component_id == "@query-param" => {
+                derived_params.push(format!(
+                    "name={}",
+                    BareItem::String(string_param(key, value)?)
+                ));
+            }
+            _ => {
+                return Err(Error::InvalidInput(format!(
+                    "Unsupported component parameter: {key}"
+                )))
+            }
         }
     }
 
-    // Verify key ID matches
-    if let Some(ref key_id) = params.key_id {
-        if key_id != &public_key.key_id() {
-            return Err(Error::Verification("Key ID mismatch".to_string()));
+    let component = match component_id {
+        "@method" => SignatureComponent::Method,
+        "@target-uri" => SignatureComponent::TargetUri,
+        "@authority" => SignatureComponent::Authority,
+        "@scheme" => SignatureComponent::Scheme,
+        "@request-target" => SignatureComponent::RequestTarget,
+        "@path" => SignatureComponent::Path,
+        "@query" => SignatureComponent::Query,
+        "@status" => SignatureComponent::Status,
+        "@query-param" => SignatureComponent::DerivedComponent {
+            name: "query-param".to_string(),
+            params: derived_params,
+        },
+        _ if component_id.starts_with('@') => {
+            return Err(Error::Unsupported(format!(
+                "Unsupported derived component: {component_id}"
+            )))
         }
+        _ => SignatureComponent::Header(component_id.to_string()),
+    };
+
+    if params.is_empty() {
+        Ok(component)
+    } else {
+        Ok(SignatureComponent::Parameterized {
+            component: Box::new(component),
+            params,
+        })
     }
+}
 
-    Ok(())
+/// Current Unix timestamp in seconds
+fn unix_now() -> Result<i64> {
+    // chrono also reads the clock on wasm32, where `SystemTime::now` panics
+    Ok(chrono::Utc::now().timestamp())
 }
 
 #[cfg(test)]
@@ -245,6 +627,98 @@ mod tests {
     fn test_verifier_creation() {
         let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
         let verifier = HttpVerifier::new(keypair.public_key().clone());
-        assert_eq!(verifier.public_key.key_id(), keypair.public_key().key_id());
+        let params = SignatureParams::default();
+        let (public_key, key_id) = verifier.resolve_key(&params).unwrap();
+        assert_eq!(public_key.key_id(), keypair.public_key().key_id());
+        assert_eq!(key_id, keypair.key_id());
+    }
+
+    #[test]
+    fn test_alg_must_match_key_type() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let verifier = HttpVerifier::new(keypair.public_key().clone());
+        let with_alg = |alg: &str| SignatureParams {
+            alg: Some(alg.to_string()),
+            ..Default::default()
+        };
+
+        let public_key = keypair.public_key();
+        assert!(verifier
+            .check_policy(public_key, &[], &with_alg("ed25519"))
+            .is_ok());
+        assert!(verifier
+            .check_policy(public_key, &[], &with_alg("ecdsa-secp256k1-sha256"))
+            .is_err());
+        assert!(verifier
+            .check_policy(public_key, &[], &with_alg("hmac-sha256"))
+            .is_err());
+    }
+
+    #[test]
+    fn test_digest_algorithm_policy() {
+        use crate::rfc9421::{DigestAlgorithm, HttpSigner};
+
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let request = || {
+            Request::builder()
+                .method("POST")
+                .uri("https://agent.example/tasks")
+                .body(b"{\"task\":\"ping\"}".to_vec())
+                .unwrap()
+        };
+        let sha256 = HttpSigner::new(keypair.clone())
+            .sign_request_with_body(request())
+            .unwrap();
+        let sha512 = HttpSigner::new(keypair.clone())
+            .with_digest_algorithm(DigestAlgorithm::Sha512)
+            .sign_request_with_body(request())
+            .unwrap();
+
+        let verifier = HttpVerifier::new(keypair.public_key().clone())
+            .with_policy(VerificationPolicy::new().allow_digest_algorithm("sha-512"));
+        assert!(verifier.verify_request(&sha512).is_ok());
+        assert!(verifier.verify_request(&sha256).is_err());
+        assert!(ContentDigest::verify_request_with_policy(&sha512, verifier.policy()).is_ok());
+        assert!(ContentDigest::verify_request_with_policy(&sha256, verifier.policy()).is_err());
+
+        // Without a restriction any supported digest algorithm is accepted
+        let verifier = HttpVerifier::new(keypair.public_key().clone());
+        assert!(verifier.verify_request(&sha256).is_ok());
+    }
+
+    #[test]
+    fn test_parse_signature_input() {
+        let (components, params) = parse_signature_input(
+            r#"("@method"  "@query-param";name="id" "example-dict";key="a" "x-list";sf);created=1618884473;keyid="k;1)";tag="app";foo=bar"#,
+        )
+        .unwrap();
+        assert_eq!(components.len(), 4);
+        assert_eq!(components[1].serialize(), r#""@query-param";name="id""#);
+        assert_eq!(components[2].serialize(), r#""example-dict";key="a""#);
+        assert_eq!(params.created, Some(1618884473));
+        assert_eq!(params.key_id.as_deref(), Some("k;1)"));
+        assert_eq!(params.tag.as_deref(), Some("app"));
+
+        let (components, params) = parse_signature_input("()").unwrap();
+        assert!(components.is_empty());
+        assert!(params.key_id.is_none());
+    }
+
+    #[test]
+    fn test_parse_malformed_signature_input() {
+        for input in [
+            "",
+            r#""@method";created=1"#,
+            r#"(@method)"#,
+            r#"("@method" "@method")"#,
+            r#"("@method");created="1""#,
+            r#"("@method");keyid=abc"#,
+            r#"("@method";foo)"#,
+            r#"("@method";sf=?0)"#,
+            r#"("@unknown")"#,
+            r#"("@method"));created=1"#,
+        ] {
+            assert!(parse_signature_input(input).is_err(), "accepted {input:?}");
+        }
     }
 }
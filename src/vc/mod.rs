@@ -0,0 +1,279 @@
+//! Verifiable Credential Data Integrity proofs
+//!
+//! Implements the `eddsa-jcs-2022` cryptosuite of the W3C Data Integrity
+//! EdDSA Cryptosuites, so agents can issue and verify Verifiable Credentials
+//! with their Ed25519 keys. The signature covers the SHA-256 hashes of the
+//! JCS-canonicalized proof configuration and document, and is attached as a
+//! `DataIntegrityProof` with a base58btc multibase `proofValue`.
+
+use crate::crypto::{KeyPair, KeyType, PublicKey, Signature, Signer, Verifier};
+use crate::error::{Error, Result};
+use crate::jcs;
+use crate::rfc9421::KeyResolver;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+
+/// Proof type of Data Integrity proofs
+pub const PROOF_TYPE: &str = "DataIntegrityProof";
+
+/// Identifier of the cryptosuite
+pub const CRYPTOSUITE: &str = "eddsa-jcs-2022";
+
+/// Options of a proof, besides its type and cryptosuite
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProofOptions {
+    /// DID URL of the verification method, e.g. `did:example:123#key-1`
+    pub verification_method: String,
+    /// Proof purpose, `assertionMethod` for credentials
+    pub proof_purpose: String,
+    /// Creation time as an XML Schema `dateTimeStamp`
+    pub created: String,
+}
+
+impl ProofOptions {
+    /// Options for an `assertionMethod` proof created now
+    pub fn new(verification_method: impl Into<String>) -> Self {
+        Self {
+            verification_method: verification_method.into(),
+            proof_purpose: "assertionMethod".to_string(),
+            created: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+        }
+    }
+
+    /// Set the proof purpose, e.g. `authentication`
+    pub fn with_proof_purpose(mut self, proof_purpose: impl Into<String>) -> Self {
+        self.proof_purpose = proof_purpose.into();
+        self
+    }
+
+    /// Set the creation time
+    pub fn with_created(mut self, created: impl Into<String>) -> Self {
+        self.created = created.into();
+        self
+    }
+}
+
+/// Secure `document` with an `eddsa-jcs-2022` proof made with `keypair`
+pub fn add_proof(document: &Value, keypair: &KeyPair, options: &ProofOptions) -> Result<Value> {
+    if keypair.key_type() != KeyType::Ed25519 {
+        return Err(Error::InvalidKeyType(format!(
+            "{CRYPTOSUITE} requires an Ed25519 key"
+        )));
+    }
+    let mut secured = as_object(document)?.clone();
+    if secured.contains_key("proof") {
+        return Err(Error::Unsupported(
+            "Adding a proof to a document that already has one".to_string(),
+        ));
+    }
+
+    let mut proof = Map::new();
+    proof.insert("type".to_string(), PROOF_TYPE.into());
+    proof.insert("cryptosuite".to_string(), CRYPTOSUITE.into());
+    proof.insert("created".to_string(), options.created.clone().into());
+    proof.insert(
+        "verificationMethod".to_string(),
+        options.verification_method.clone().into(),
+    );
+    proof.insert(
+        "proofPurpose".to_string(),
+        options.proof_purpose.clone().into(),
+    );
+    if let Some(context) = secured.get("@context") {
+        proof.insert("@context".to_string(), context.clone());
+    }
+
+    let hash_data = hash_data(&proof, &secured)?;
+    let signature = keypair.sign(&hash_data)?.to_bytes();
+    proof.insert(
+        "proofValue".to_string(),
+        format!("z{}", bs58::encode(signature).into_string()).into(),
+    );
+    secured.insert("proof".to_string(), Value::Object(proof));
+    Ok(Value::Object(secured))
+}
+
+/// Verify the `eddsa-jcs-2022` proof of `document` with `public_key`
+pub fn verify_proof(document: &Value, public_key: &PublicKey) -> Result<()> {
+    let (proof, unsecured, proof_value) = split_proof(document)?;
+    if public_key.key_type() != KeyType::Ed25519 {
+        return Err(Error::InvalidKeyType(format!(
+            "{CRYPTOSUITE} requires an Ed25519 key"
+        )));
+    }
+
+    let signature = Signature::from_bytes(KeyType::Ed25519, &proof_value)?;
+    public_key.verify(&hash_data(&proof, &unsecured)?, &signature)
+}
+
+/// Verify the proof of `document` with the key `resolver` finds for its `verificationMethod`
+pub fn verify_proof_with(document: &Value, resolver: &dyn KeyResolver) -> Result<()> {
+    let verification_method = document
+        .get("proof")
+        .and_then(|proof| proof.get("verificationMethod"))
+        .and_then(Value::as_str)
+        .ok_or_else(|| invalid("proof has no verificationMethod"))?;
+    verify_proof(document, &resolver.resolve(verification_method)?)
+}
+
+/// Split a secured document into its proof options, unsecured document and decoded `proofValue`
+fn split_proof(document: &Value) -> Result<(Map<String, Value>, Map<String, Value>, Vec<u8>)> {
+    let mut unsecured = as_object(document)?.clone();
+    let mut proof = match unsecured.remove("proof") {
+        Some(Value::Object(proof)) => proof,
+        Some(Value::Array(_)) => {
+            return Err(Error::Unsupported(
+                "Documents with multiple proofs".to_string(),
+            ))
+        }
+        _ => return Err(invalid("document has no proof")),
+    };
+
+    if proof.get("type").and_then(Value::as_str) != Some(PROOF_TYPE)
+        || proof.get("cryptosuite").and_then(Value::as_str) != Some(CRYPTOSUITE)
+    {
+        return Err(Error::Verification(format!(
+            "Proof is not a {PROOF_TYPE} with the {CRYPTOSUITE} cryptosuite"
+        )));
+    }
+
+    let proof_value = match proof.remove("proofValue") {
+        Some(Value::String(value)) => value,
+        _ => return Err(invalid("proof has no proofValue")),
+    };
+    let proof_value = proof_value
+        .strip_prefix('z')
+        .ok_or_else(|| invalid("proofValue is not base58btc multibase"))?;
+    let proof_value = bs58::decode(proof_value)
+        .into_vec()
+        .map_err(|e| invalid(&format!("proofValue is not base58: {e}")))?;
+
+    // The proof's @context must be a prefix of the document's
+    if let Some(context) = proof.get("@context") {
+        let contexts = |value: &Value| match value {
+            Value::Array(items) => items.clone(),
+            other => vec![other.clone()],
+        };
+        let expected = contexts(context);
+        let actual = unsecured.get("@context").map(contexts).unwrap_or_default();
+        if !actual.starts_with(&expected) {
+            return Err(Error::Verification(
+                "Proof @context does not match the document".to_string(),
+            ));
+        }
+        unsecured.insert("@context".to_string(), context.clone());
+    }
+
+    Ok((proof, unsecured, proof_value))
+}
+
+/// SHA-256 of the canonical proof configuration followed by SHA-256 of the canonical document
+fn hash_data(proof: &Map<String, Value>, document: &Map<String, Value>) -> Result<Vec<u8>> {
+    let proof = jcs::canonicalize(&Value::Object(proof.clone()))?;
+    let document = jcs::canonicalize(&Value::Object(document.clone()))?;
+    let mut hash_data = Sha256::digest(proof.as_bytes()).to_vec();
+    hash_data.extend_from_slice(&Sha256::digest(document.as_bytes()));
+    Ok(hash_data)
+}
+
+fn as_object(document: &Value) -> Result<&Map<String, Value>> {
+    document
+        .as_object()
+        .ok_or_else(|| invalid("document is not a JSON object"))
+}
+
+fn invalid(reason: &str) -> Error {
+    Error::InvalidInput(format!("Invalid Data Integrity document: {reason}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rfc9421::KeySet;
+    use serde_json::json;
+
+    fn credential() -> Value {
+        json!({
+            "@context": [
+                "https://www.w3.org/ns/credentials/v2",
+                "https://www.w3.org/ns/credentials/examples/v2"
+            ],
+            "id": "urn:uuid:58172aac-d8ba-11ed-83dd-0b3aef56cc33",
+            "type": ["VerifiableCredential", "AlumniCredential"],
+            "name": "Alumni Credential",
+            "issuer": "https://vc.example/issuers/5678",
+            "validFrom": "2023-01-01T00:00:00Z",
+            "credentialSubject": {
+                "id": "did:example:abcdefgh",
+                "alumniOf": "The School of Examples"
+            }
+        })
+    }
+
+    #[test]
+    fn test_add_and_verify_proof() {
+        // Key and proof options from the Data Integrity EdDSA Cryptosuites examples
+        let secret =
+            hex::decode("c96ef9ea10c5e414c471723aff9de72c35fa5b70fae97e8832ecac7d2e2b8ed6")
+                .unwrap();
+        let keypair = KeyPair::from_private_key_bytes(KeyType::Ed25519, &secret).unwrap();
+        assert_eq!(
+            hex::encode(keypair.public_key().to_bytes()),
+            "b00d8d938e7f773d51565aad36a623f5344f7f5d1960f9cf3e8e12620ea2810f"
+        );
+        let options = ProofOptions::new(
+            "did:key:z6MkrJVnaZkeFzdQyMZu1cgjg7k1pZZ6pvBQ7XJPt4swbTQ2#z6MkrJVnaZkeFzdQyMZu1cgjg7k1pZZ6pvBQ7XJPt4swbTQ2",
+        )
+        .with_created("2023-02-24T23:36:38Z");
+
+        let secured = add_proof(&credential(), &keypair, &options).unwrap();
+        let proof = &secured["proof"];
+        assert_eq!(proof["type"], PROOF_TYPE);
+        assert_eq!(proof["cryptosuite"], CRYPTOSUITE);
+        assert_eq!(proof["proofPurpose"], "assertionMethod");
+        assert_eq!(proof["@context"], credential()["@context"]);
+        assert_eq!(
+            proof["proofValue"],
+            "z3rGgoCE3cGrVf8VqUYfSauEyRGebG6cwriY6mPKsKJsbsqYzXgbU4VMyy7mLLCKAo19dpQd43xPEp9tcfApbehNd"
+        );
+        assert!(verify_proof(&secured, keypair.public_key()).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_credential() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let options = ProofOptions::new("did:example:issuer#key-1");
+        let secured = add_proof(&credential(), &keypair, &options).unwrap();
+
+        // Member order and whitespace do not matter
+        let reparsed: Value =
+            serde_json::from_str(&serde_json::to_string_pretty(&secured).unwrap()).unwrap();
+        assert!(verify_proof(&reparsed, keypair.public_key()).is_ok());
+
+        let mut tampered = secured.clone();
+        tampered["credentialSubject"]["alumniOf"] = "Another School".into();
+        assert!(verify_proof(&tampered, keypair.public_key()).is_err());
+
+        let mut tampered = secured.clone();
+        tampered["proof"]["proofPurpose"] = "authentication".into();
+        assert!(verify_proof(&tampered, keypair.public_key()).is_err());
+
+        let other = KeyPair::generate(KeyType::Ed25519).unwrap();
+        assert!(verify_proof(&secured, other.public_key()).is_err());
+        assert!(verify_proof(&credential(), keypair.public_key()).is_err());
+    }
+
+    #[test]
+    fn test_verify_with_resolver() {
+        let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+        let mut key_set = KeySet::new();
+        key_set.insert_with_id("did:example:issuer#key-1", keypair.public_key().clone());
+
+        let options = ProofOptions::new("did:example:issuer#key-1");
+        let secured = add_proof(&credential(), &keypair, &options).unwrap();
+        assert!(verify_proof_with(&secured, &key_set).is_ok());
+
+        let secp256k1 = KeyPair::generate(KeyType::Secp256k1).unwrap();
+        assert!(add_proof(&credential(), &secp256k1, &options).is_err());
+    }
+}
@@ -1,7 +1,33 @@
 //! WASM bindings for key format operations
 
 use super::*;
-use crate::formats::KeyFormat;
+use crate::formats::{KeyExporter, KeyFormat, KeyImporter};
+use js_sys::JSON;
+
+/// Convert a JSON value into a plain JS object
+pub(crate) fn json_to_js(value: &serde_json::Value) -> WasmResult<JsValue> {
+    JSON::parse(&value.to_string()).map_err(|e| {
+        WasmError::new(
+            WasmErrorCode::Serialization,
+            format!("Failed to convert JSON: {e:?}"),
+        )
+    })
+}
+
+/// Convert a plain JS object into a JSON value
+pub(crate) fn js_to_json(value: &JsValue) -> WasmResult<serde_json::Value> {
+    let text = JSON::stringify(value)
+        .ok()
+        .and_then(|text| text.as_string())
+        .ok_or_else(|| {
+            WasmError::new(
+                WasmErrorCode::Serialization,
+                "Value cannot be converted to JSON".to_string(),
+            )
+        })?;
+    serde_json::from_str(&text)
+        .map_err(|e| WasmError::new(WasmErrorCode::InvalidInput, format!("Invalid JSON: {e}")))
+}
 
 /// Key format enum for WASM
 #[wasm_bindgen]
@@ -28,12 +54,12 @@ impl From<WasmKeyFormat> for KeyFormat {
     }
 }
 
-/// Key format utilities for WASM
+/// Key format utilities for WASM, backed by [`crate::formats`]
 #[wasm_bindgen]
-pub struct WasmKeyFormats;
+pub struct WasmKeyFormatUtils;
 
 #[wasm_bindgen]
-impl WasmKeyFormats {
+impl WasmKeyFormatUtils {
     /// Get supported formats for a key type
     #[wasm_bindgen(js_name = getSupportedFormats)]
     pub fn get_supported_formats(_key_type: WasmKeyType) -> Vec<WasmKeyFormat> {
@@ -45,4 +71,37 @@ impl WasmKeyFormats {
             WasmKeyFormat::Jwk,
         ]
     }
+
+    /// Export the private key of a key pair
+    ///
+    /// DER is PKCS#8, and JWK and PEM are returned as UTF-8 bytes.
+    #[wasm_bindgen(js_name = exportPrivateKey)]
+    pub fn export_private_key(keypair: &WasmKeyPair, format: WasmKeyFormat) -> WasmResult<Vec<u8>> {
+        Ok(keypair.inner.export(format.into())?)
+    }
+
+    /// Export a public key; DER is SPKI
+    #[wasm_bindgen(js_name = exportPublicKey)]
+    pub fn export_public_key(
+        public_key: &WasmPublicKey,
+        format: WasmKeyFormat,
+    ) -> WasmResult<Vec<u8>> {
+        Ok(public_key.inner.export(format.into())?)
+    }
+
+    /// Import a key pair from an exported private key
+    ///
+    /// Raw private keys carry no key type; use `WasmKeyPair.fromPrivateKey` for those.
+    #[wasm_bindgen(js_name = importKeyPair)]
+    pub fn import_key_pair(data: &[u8], format: WasmKeyFormat) -> WasmResult<WasmKeyPair> {
+        let keypair = KeyPair::import_key_pair(data, format.into())?;
+        Ok(WasmKeyPair { inner: keypair })
+    }
+
+    /// Import an exported public key; raw keys are told apart by length
+    #[wasm_bindgen(js_name = importPublicKey)]
+    pub fn import_public_key(data: &[u8], format: WasmKeyFormat) -> WasmResult<WasmPublicKey> {
+        let public_key = KeyPair::import_public_key(data, format.into())?;
+        Ok(WasmPublicKey { inner: public_key })
+    }
 }
@@ -1,20 +1,320 @@
 //! WASM bindings for HTTP signature operations (RFC 9421)
 
 use super::*;
-use js_sys::{Object, Reflect};
+use crate::error::Error;
+use crate::rfc9421::signer::DEFAULT_LABEL;
+use crate::rfc9421::{
+    extract_signature_headers, parse_signature_input, validate_components, ContentDigest, Expiry,
+    HttpSigner, HttpVerifier, KeySet, SignatureComponent, VerificationReport,
+};
+use js_sys::{Array, Function, Object, Promise, Reflect, Uint8Array};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_bytes::Bytes;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+
+/// Signature headers of a message
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SignedHeaders {
+    /// Value of the `Signature` header
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Value of the `Signature-Input` header
+    #[serde(rename = "signature-input", skip_serializing_if = "Option::is_none")]
+    pub signature_input: Option<String>,
+    /// Value of the `Content-Digest` header
+    #[serde(rename = "content-digest", skip_serializing_if = "Option::is_none")]
+    pub content_digest: Option<String>,
+}
+
+/// Details of a verified signature, as returned to JS
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationDetails {
+    /// Label of the verified signature
+    pub label: String,
+    /// Key ID the signature was verified with
+    pub key_id: String,
+    /// Algorithm identifier, e.g. `ed25519`
+    pub algorithm: String,
+    /// Identifiers of the covered components, e.g. `@method`
+    pub components: Vec<String>,
+    /// Creation timestamp (Unix timestamp)
+    pub created: Option<i64>,
+    /// Expiration timestamp (Unix timestamp)
+    pub expires: Option<i64>,
+    /// Nonce value
+    pub nonce: Option<String>,
+    /// Tag value
+    pub tag: Option<String>,
+}
+
+impl From<VerificationReport> for VerificationDetails {
+    fn from(report: VerificationReport) -> Self {
+        Self {
+            label: report.label,
+            key_id: report.key_id,
+            algorithm: report.algorithm.identifier().to_string(),
+            components: report
+                .components
+                .iter()
+                .map(|component| component.identifier())
+                .collect(),
+            created: report.created,
+            expires: report.expires,
+            nonce: report.nonce,
+            tag: report.tag,
+        }
+    }
+}
+
+/// Options accepted by the `WasmHttpSigner` constructor
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct SignerOptions {
+    /// Request components, e.g. `["@method", "@path", "content-type", "content-digest"]`
+    components: Option<Vec<String>>,
+    /// Signature label
+    label: Option<String>,
+    /// Signature lifetime in seconds; `0` omits `expires`
+    ttl: Option<u64>,
+    /// Add a random `nonce` parameter
+    nonce: Option<bool>,
+    /// `tag` parameter
+    tag: Option<String>,
+    /// Add the `created` parameter
+    created: Option<bool>,
+}
+
+impl SignerOptions {
+    /// Apply the options to a signer
+    fn apply(self, mut signer: HttpSigner) -> WasmResult<HttpSigner> {
+        if let Some(components) = self.components {
+            let components = parse_components(&components)?;
+            validate_components(&components, false)?;
+            signer = signer.with_default_components(components);
+        }
+        if let Some(label) = self.label {
+            signer = signer.with_label(label);
+        }
+        if let Some(ttl) = self.ttl {
+            let ttl = (ttl > 0).then(|| Duration::from_secs(ttl));
+            signer = signer.with_expiry_mode(Expiry::from(ttl));
+        }
+        if let Some(nonce) = self.nonce {
+            signer = signer.with_nonce(nonce);
+        }
+        if let Some(tag) = self.tag {
+            signer = signer.with_tag(tag);
+        }
+        if let Some(created) = self.created {
+            signer = signer.with_created(created);
+        }
+        Ok(signer)
+    }
+}
+
+/// Per-message options given as `signature` in `signedFetch` and `signResponse` options
+///
+/// These override the signer's own `nonce` and `tag` options for one message.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct MessageSignatureOptions {
+    /// Add a random `nonce` parameter
+    nonce: Option<bool>,
+    /// `tag` parameter
+    tag: Option<String>,
+}
+
+impl WasmHttpSigner {
+    /// Signer for one message, applying the `signature` member of its options
+    fn message_signer(&self, options: &JsValue) -> WasmResult<HttpSigner> {
+        let options: MessageSignatureOptions =
+            from_options(option(options, "signature")?, "signature options")?;
+        let mut signer = self.signer.clone();
+        if let Some(nonce) = options.nonce {
+            signer = signer.with_nonce(nonce);
+        }
+        if let Some(tag) = options.tag {
+            signer = signer.with_tag(tag);
+        }
+        Ok(signer)
+    }
+}
+
+/// Parse component identifiers such as `@method`, `content-type` or `"@query-param";name="id"`
+fn parse_components(components: &[String]) -> crate::error::Result<Vec<SignatureComponent>> {
+    let items: Vec<String> = components
+        .iter()
+        .map(|component| {
+            if component.starts_with('"') {
+                component.clone()
+            } else {
+                format!("\"{component}\"")
+            }
+        })
+        .collect();
+    let (components, _) = parse_signature_input(&format!("({})", items.join(" ")))?;
+    Ok(components)
+}
+
+/// `fetch` options set by `signedFetch`
+#[derive(Serialize)]
+struct SignedFetchInit<'a> {
+    method: &'a str,
+    headers: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<&'a Bytes>,
+}
+
+/// `Response` constructor options set by `signResponse`, or the whole
+/// response returned by `signResponseObject`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedResponseInit<'a> {
+    status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    status_text: Option<String>,
+    headers: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body: Option<&'a Bytes>,
+}
+
+/// Convert a value into plain JS objects and arrays
+fn to_js<T: Serialize + ?Sized>(value: &T) -> WasmResult<JsValue> {
+    value
+        .serialize(&serde_wasm_bindgen::Serializer::json_compatible())
+        .map_err(|e| {
+            WasmError::new(
+                WasmErrorCode::Serialization,
+                format!("Failed to convert to JS: {e}"),
+            )
+        })
+}
+
+/// Convert a JS exception into a `WasmError`
+fn js_error(context: &str, err: JsValue) -> WasmError {
+    WasmError::new(WasmErrorCode::JsError, format!("{context}: {err:?}"))
+}
+
+/// Whether a value is a fetch `Response`
+#[cfg(feature = "wasm-web")]
+fn is_fetch_response(value: &JsValue) -> bool {
+    value.is_instance_of::<web_sys::Response>()
+}
+
+/// Without the web bindings there is no fetch `Response`
+#[cfg(not(feature = "wasm-web"))]
+fn is_fetch_response(_value: &JsValue) -> bool {
+    false
+}
+
+/// Read headers from a `Headers` instance, an iterable of pairs or a plain object of strings
+fn headers_from_js(headers: &JsValue) -> WasmResult<Vec<(String, String)>> {
+    if headers.is_undefined() || headers.is_null() {
+        return Ok(Vec::new());
+    }
+    let object = headers.dyn_ref::<Object>().ok_or_else(|| {
+        WasmError::new(
+            WasmErrorCode::InvalidInput,
+            "Headers must be an object".to_string(),
+        )
+    })?;
+
+    let entries =
+        match js_sys::try_iter(headers).map_err(|e| js_error("Failed to iterate headers", e))? {
+            Some(iter) => iter
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| js_error("Failed to iterate headers", e))?,
+            None => Object::entries(object).iter().collect(),
+        };
+
+    entries
+        .into_iter()
+        .map(|entry| {
+            let pair = Array::from(&entry);
+            match (pair.get(0).as_string(), pair.get(1).as_string()) {
+                (Some(name), Some(value)) => Ok((name, value)),
+                _ => Err(WasmError::new(
+                    WasmErrorCode::InvalidInput,
+                    "Header names and values must be strings".to_string(),
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Read a body given as a string, `Uint8Array` or `ArrayBuffer`
+fn body_from_js(body: &JsValue) -> WasmResult<Option<Vec<u8>>> {
+    if body.is_undefined() || body.is_null() {
+        Ok(None)
+    } else if let Some(text) = body.as_string() {
+        Ok(Some(text.into_bytes()))
+    } else if body.is_instance_of::<Uint8Array>() || body.is_instance_of::<js_sys::ArrayBuffer>() {
+        Ok(Some(Uint8Array::new(body).to_vec()))
+    } else {
+        Err(WasmError::new(
+            WasmErrorCode::InvalidInput,
+            "Body must be a string, Uint8Array or ArrayBuffer".to_string(),
+        ))
+    }
+}
+
+/// Collect the headers of a signed message
+fn header_map(headers: &::http::HeaderMap) -> WasmResult<BTreeMap<String, String>> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = value.to_str().map_err(|e| {
+                WasmError::new(
+                    WasmErrorCode::InvalidInput,
+                    format!("Invalid {name} header: {e}"),
+                )
+            })?;
+            Ok((name.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// Read a property of an options object, treating a missing object as empty
+fn option(options: &JsValue, name: &str) -> WasmResult<JsValue> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(JsValue::UNDEFINED);
+    }
+    Reflect::get(options, &name.into()).map_err(|e| js_error("Failed to read options", e))
+}
+
+/// Deserialize an options object, treating `undefined` and `null` as empty
+fn from_options<T: DeserializeOwned + Default>(options: JsValue, what: &str) -> WasmResult<T> {
+    if options.is_undefined() || options.is_null() {
+        return Ok(T::default());
+    }
+    serde_wasm_bindgen::from_value(options)
+        .map_err(|e| WasmError::new(WasmErrorCode::InvalidInput, format!("Invalid {what}: {e}")))
+}
 
 /// HTTP signer for WASM
 #[wasm_bindgen]
 pub struct WasmHttpSigner {
     keypair: WasmKeyPair,
+    signer: HttpSigner,
 }
 
 #[wasm_bindgen]
 impl WasmHttpSigner {
     /// Create a new HTTP signer
+    ///
+    /// `options` may set `{ components, label, ttl, nonce, tag, created }`;
+    /// `components` replaces the default `@method`, `@path` and `@authority`
+    /// of signed requests, and `ttl` is in seconds with `0` omitting `expires`.
     #[wasm_bindgen(constructor)]
-    pub fn new(keypair: WasmKeyPair) -> WasmHttpSigner {
-        WasmHttpSigner { keypair }
+    pub fn new(keypair: WasmKeyPair, options: Option<JsValue>) -> WasmResult<WasmHttpSigner> {
+        let options: SignerOptions =
+            from_options(options.unwrap_or(JsValue::UNDEFINED), "signer options")?;
+        let signer = options.apply(HttpSigner::new(keypair.inner.clone()))?;
+        Ok(WasmHttpSigner { keypair, signer })
     }
 
     /// Get the key ID
@@ -29,12 +329,178 @@ impl WasmHttpSigner {
         let signature = self.keypair.sign_string(message)?;
         Ok(signature.to_hex())
     }
+
+    /// Sign a request and send it with `fetch`, resolving to the `Response`
+    ///
+    /// `options` takes the usual `fetch` options; `method`, `headers` and a
+    /// string, `Uint8Array` or `ArrayBuffer` `body` are signed, with a
+    /// `content-digest` header covering the body when one is given. An extra
+    /// `signature: { nonce, tag }` member sets the nonce and tag of this request.
+    #[wasm_bindgen(js_name = signedFetch)]
+    pub fn signed_fetch(&self, url: &str, options: JsValue) -> WasmResult<Promise> {
+        let method = option(&options, "method")?
+            .as_string()
+            .unwrap_or_else(|| "GET".to_string());
+        let headers = headers_from_js(&option(&options, "headers")?)?;
+        let body = body_from_js(&option(&options, "body")?)?;
+
+        let mut builder = ::http::Request::builder().method(method.as_str()).uri(url);
+        for (name, value) in &headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        let has_body = body.is_some();
+        let request = builder.body(body.unwrap_or_default()).map_err(|e| {
+            WasmError::new(WasmErrorCode::InvalidInput, format!("Invalid request: {e}"))
+        })?;
+
+        let signer = self.message_signer(&options)?;
+        let signed = if has_body {
+            signer.sign_request_with_body(request)?
+        } else {
+            signer.sign_request(request)?
+        };
+
+        let init = Object::new();
+        if !options.is_undefined() && !options.is_null() {
+            let options = options.dyn_ref::<Object>().ok_or_else(|| {
+                WasmError::new(
+                    WasmErrorCode::InvalidInput,
+                    "Options must be an object".to_string(),
+                )
+            })?;
+            Object::assign(&init, options);
+        }
+        let signed_init = to_js(&SignedFetchInit {
+            method: &method,
+            headers: header_map(signed.headers())?,
+            body: has_body.then(|| Bytes::new(signed.body())),
+        })?;
+        Object::assign(&init, signed_init.unchecked_ref());
+        Reflect::delete_property(&init, &"signature".into())
+            .map_err(|e| js_error("Failed to build fetch options", e))?;
+
+        let fetch = Reflect::get(&js_sys::global(), &"fetch".into())
+            .map_err(|e| js_error("Failed to look up fetch", e))?
+            .dyn_into::<Function>()
+            .map_err(|_| {
+                WasmError::new(
+                    WasmErrorCode::Unsupported,
+                    "fetch is not available in this environment".to_string(),
+                )
+            })?;
+        fetch
+            .call2(&JsValue::UNDEFINED, &url.into(), &init)
+            .map_err(|e| js_error("fetch failed", e))?
+            .dyn_into::<Promise>()
+            .map_err(|_| {
+                WasmError::new(
+                    WasmErrorCode::JsError,
+                    "fetch did not return a Promise".to_string(),
+                )
+            })
+    }
+
+    /// Sign a response given as `{ status, statusText, headers, body }` and build a `Response`
+    ///
+    /// The status and `content-type` header are signed, along with a
+    /// `content-digest` header covering the body when one is given. As with
+    /// `signedFetch`, `signature: { nonce, tag }` applies to this response only.
+    #[cfg(feature = "wasm-web")]
+    #[wasm_bindgen(js_name = signResponse)]
+    pub fn sign_response(&self, response: JsValue) -> WasmResult<web_sys::Response> {
+        let (signed, has_body) = self.sign_response_message(&response)?;
+        let init = to_js(&SignedResponseInit {
+            status: signed.status().as_u16(),
+            status_text: option(&response, "statusText")?.as_string(),
+            headers: header_map(signed.headers())?,
+            body: None,
+        })?;
+
+        let body = if has_body {
+            Uint8Array::from(signed.body().as_slice()).into()
+        } else {
+            JsValue::NULL
+        };
+        let constructor = Reflect::get(&js_sys::global(), &"Response".into())
+            .map_err(|e| js_error("Failed to look up Response", e))?
+            .dyn_into::<Function>()
+            .map_err(|_| {
+                WasmError::new(
+                    WasmErrorCode::Unsupported,
+                    "Response is not available in this environment".to_string(),
+                )
+            })?;
+        Reflect::construct(&constructor, &Array::of2(&body, &init))
+            .map_err(|e| js_error("Failed to create Response", e))?
+            .dyn_into::<web_sys::Response>()
+            .map_err(|_| {
+                WasmError::new(
+                    WasmErrorCode::JsError,
+                    "Response constructor did not return a Response".to_string(),
+                )
+            })
+    }
+
+    /// Sign a response given as `{ status, statusText, headers, body }`
+    ///
+    /// Returns a new object of the same shape, with `body` as a `Uint8Array`,
+    /// for runtimes without a fetch `Response`.
+    #[wasm_bindgen(js_name = signResponseObject)]
+    pub fn sign_response_object(&self, response: JsValue) -> WasmResult<JsValue> {
+        let (signed, has_body) = self.sign_response_message(&response)?;
+        to_js(&SignedResponseInit {
+            status: signed.status().as_u16(),
+            status_text: option(&response, "statusText")?.as_string(),
+            headers: header_map(signed.headers())?,
+            body: has_body.then(|| Bytes::new(signed.body())),
+        })
+    }
+}
+
+impl WasmHttpSigner {
+    /// Build and sign a response from a `{ status, headers, body }` object
+    fn sign_response_message(
+        &self,
+        response: &JsValue,
+    ) -> WasmResult<(::http::Response<Vec<u8>>, bool)> {
+        let status = option(response, "status")?.as_f64().unwrap_or(200.0);
+        let headers = headers_from_js(&option(response, "headers")?)?;
+        let body = body_from_js(&option(response, "body")?)?;
+
+        let mut builder = ::http::Response::builder().status(status as u16);
+        for (name, value) in &headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        let has_body = body.is_some();
+        let unsigned = builder.body(body.unwrap_or_default()).map_err(|e| {
+            WasmError::new(
+                WasmErrorCode::InvalidInput,
+                format!("Invalid response: {e}"),
+            )
+        })?;
+
+        let signer = self.message_signer(response)?;
+        let signed = if has_body {
+            signer.sign_response_with_body(unsigned)?
+        } else {
+            signer.sign_response(unsigned)?
+        };
+        Ok((signed, has_body))
+    }
+}
+
+/// Key used by a `WasmHttpVerifier`
+enum VerifierKey {
+    /// A single pinned public key
+    Fixed(PublicKey),
+    /// JS function `(keyId) => publicKeyBytes | Promise<publicKeyBytes>`
+    Resolver(Function),
 }
 
 /// HTTP verifier for WASM
 #[wasm_bindgen]
 pub struct WasmHttpVerifier {
-    public_key: WasmPublicKey,
+    key: VerifierKey,
 }
 
 #[wasm_bindgen]
@@ -42,14 +508,235 @@ impl WasmHttpVerifier {
     /// Create a new HTTP verifier
     #[wasm_bindgen(constructor)]
     pub fn new(public_key: WasmPublicKey) -> WasmHttpVerifier {
-        WasmHttpVerifier { public_key }
+        WasmHttpVerifier {
+            key: VerifierKey::Fixed(public_key.inner),
+        }
+    }
+
+    /// Create a verifier that looks up keys by the signature's `keyid`
+    ///
+    /// `resolver` is called as `(keyId) => publicKeyBytes` and may return a
+    /// `Promise`, in which case `verifyResponseAsync` must be used. Ed25519
+    /// keys are 32 bytes; secp256k1 keys are SEC1-encoded.
+    #[wasm_bindgen(js_name = withKeyResolver)]
+    pub fn with_key_resolver(resolver: Function) -> WasmHttpVerifier {
+        WasmHttpVerifier {
+            key: VerifierKey::Resolver(resolver),
+        }
     }
 
     /// Verify a message signature
     #[wasm_bindgen(js_name = verifyMessage)]
     pub fn verify_message(&self, message: &str, signature_hex: &str) -> WasmResult<bool> {
-        let signature = WasmSignature::from_hex(self.public_key.key_type(), signature_hex)?;
-        Ok(self.public_key.verify_string(message, &signature))
+        let VerifierKey::Fixed(public_key) = &self.key else {
+            return Err(WasmError::new(
+                WasmErrorCode::Unsupported,
+                "verifyMessage needs a verifier created with a public key".to_string(),
+            ));
+        };
+        let bytes = hex::decode(signature_hex).map_err(|e| {
+            WasmError::new(WasmErrorCode::InvalidInput, format!("Invalid hex: {e}"))
+        })?;
+        let signature = Signature::from_bytes(public_key.key_type(), &bytes)?;
+        Ok(public_key.verify(message.as_bytes(), &signature).is_ok())
+    }
+
+    /// Verify the signature of a fetch `Response`
+    ///
+    /// The body of a `Response` can only be read asynchronously, so it is not
+    /// checked against a signed `content-digest` header; use
+    /// `verifyResponseObject` with the body for that.
+    #[cfg(feature = "wasm-web")]
+    #[wasm_bindgen(js_name = verifyResponse)]
+    pub fn verify_response(&self, response: &web_sys::Response) -> WasmResult<bool> {
+        let response = response_from_js(response)?;
+        verified(self.verify_response_sync(&response, false))
+    }
+
+    /// Verify the signature of a fetch `Response` and describe what was verified
+    ///
+    /// Resolves to `{ label, keyId, algorithm, components, created, expires,
+    /// nonce, tag }` and throws if the signature does not verify. As with
+    /// `verifyResponse`, the body is not checked.
+    #[cfg(feature = "wasm-web")]
+    #[wasm_bindgen(js_name = verifyResponseDetailed)]
+    pub fn verify_response_detailed(&self, response: &web_sys::Response) -> WasmResult<JsValue> {
+        let response = response_from_js(response)?;
+        let report = self.verify_response_sync(&response, false)?;
+        to_js(&VerificationDetails::from(report))
+    }
+
+    /// Verify the signature of a response given as `{ status, headers, body }`
+    ///
+    /// `headers` may be a `Headers` instance or a plain object. When `body` is
+    /// given it must match the `content-digest` header.
+    #[wasm_bindgen(js_name = verifyResponseObject)]
+    pub fn verify_response_object(&self, response: JsValue) -> WasmResult<bool> {
+        let has_body = !option(&response, "body")?.is_undefined();
+        let response = response_from_js(&response)?;
+        verified(self.verify_response_sync(&response, has_body))
+    }
+
+    /// Verify a `{ status, headers, body }` object and describe what was verified
+    ///
+    /// Returns the same details as `verifyResponseDetailed`, and also checks
+    /// the body when it is given.
+    #[wasm_bindgen(js_name = verifyResponseObjectDetailed)]
+    pub fn verify_response_object_detailed(&self, response: JsValue) -> WasmResult<JsValue> {
+        let has_body = !option(&response, "body")?.is_undefined();
+        let response = response_from_js(&response)?;
+        let report = self.verify_response_sync(&response, has_body)?;
+        to_js(&VerificationDetails::from(report))
+    }
+
+    /// Verify a fetch `Response` or `{ status, headers, body }` object, resolving to a boolean
+    ///
+    /// Supports key resolvers that return a `Promise`.
+    #[wasm_bindgen(js_name = verifyResponseAsync)]
+    pub fn verify_response_async(&self, response: JsValue) -> WasmResult<Promise> {
+        let has_body = !is_fetch_response(&response) && !option(&response, "body")?.is_undefined();
+        let response = response_from_js(&response)?;
+
+        let VerifierKey::Resolver(resolver) = &self.key else {
+            let result = verified(self.verify_response_sync(&response, has_body))?;
+            return Ok(Promise::resolve(&result.into()));
+        };
+        let key_id = match signature_key_id(&response) {
+            Ok(key_id) => key_id,
+            Err(e) => return Ok(Promise::resolve(&verified(Err(e))?.into())),
+        };
+        let resolved = resolver
+            .call1(&JsValue::NULL, &key_id.as_str().into())
+            .map_err(|e| js_error("Key resolver failed", e))?;
+
+        Ok(future_to_promise(async move {
+            let resolved = match resolved.dyn_into::<Promise>() {
+                Ok(promise) => JsFuture::from(promise).await?,
+                Err(value) => value,
+            };
+            let result = resolved_verifier(key_id, &resolved)
+                .and_then(|verifier| verify_response_with(&verifier, &response, has_body));
+            Ok(verified(result)?.into())
+        }))
+    }
+}
+
+/// Map a verification result to `true`, `false` on a signature mismatch, or an error
+fn verified(result: crate::error::Result<VerificationReport>) -> WasmResult<bool> {
+    match result {
+        Ok(_) => Ok(true),
+        Err(Error::Verification(_)) => Ok(false),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Read a fetch `Response` or a `{ status, headers, body }` object
+///
+/// The body of a fetch `Response` is not read.
+fn response_from_js(response: &JsValue) -> WasmResult<::http::Response<Vec<u8>>> {
+    #[cfg(feature = "wasm-web")]
+    if let Some(response) = response.dyn_ref::<web_sys::Response>() {
+        return build_response(
+            response.status(),
+            headers_from_js(&response.headers())?,
+            None,
+        );
+    }
+
+    let status = option(response, "status")?.as_f64().ok_or_else(|| {
+        WasmError::new(
+            WasmErrorCode::InvalidInput,
+            "Response status must be a number".to_string(),
+        )
+    })?;
+    let headers = headers_from_js(&option(response, "headers")?)?;
+    let body = body_from_js(&option(response, "body")?)?;
+    build_response(status as u16, headers, body)
+}
+
+/// Build a response to verify
+fn build_response(
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Option<Vec<u8>>,
+) -> WasmResult<::http::Response<Vec<u8>>> {
+    let mut builder = ::http::Response::builder().status(status);
+    for (name, value) in &headers {
+        builder = builder.header(name.as_str(), value.as_str());
+    }
+    builder.body(body.unwrap_or_default()).map_err(|e| {
+        WasmError::new(
+            WasmErrorCode::InvalidInput,
+            format!("Invalid response: {e}"),
+        )
+    })
+}
+
+/// Key ID of the signature on a response
+fn signature_key_id(response: &::http::Response<Vec<u8>>) -> crate::error::Result<String> {
+    let (_, signature_input) = extract_signature_headers(response, DEFAULT_LABEL)?;
+    let (_, params) = parse_signature_input(&signature_input)?;
+    params
+        .key_id
+        .ok_or_else(|| Error::Verification("Signature is missing the keyid parameter".to_string()))
+}
+
+/// Build a verifier trusting the key a resolver returned for `key_id`
+///
+/// A resolver returning anything but a `Uint8Array` does not know the key.
+fn resolved_verifier(key_id: String, resolved: &JsValue) -> crate::error::Result<HttpVerifier> {
+    if !resolved.is_instance_of::<Uint8Array>() {
+        return Err(Error::Verification(format!("Unknown key ID: {key_id}")));
+    }
+    let bytes = Uint8Array::new(resolved).to_vec();
+    let key_type = if bytes.len() == 32 {
+        KeyType::Ed25519
+    } else {
+        KeyType::Secp256k1
+    };
+    let public_key = PublicKey::from_bytes(key_type, &bytes)?;
+
+    let mut key_set = KeySet::new();
+    key_set.insert_with_id(key_id, public_key);
+    Ok(HttpVerifier::from_key_set(key_set))
+}
+
+/// Verify a response, and its `content-digest` when the body was given
+fn verify_response_with(
+    verifier: &HttpVerifier,
+    response: &::http::Response<Vec<u8>>,
+    has_body: bool,
+) -> crate::error::Result<VerificationReport> {
+    let report = verifier.verify_response_detailed(response)?;
+    if has_body {
+        ContentDigest::verify_response(response)?;
+    }
+    Ok(report)
+}
+
+impl WasmHttpVerifier {
+    /// Verify a response, calling a resolver synchronously
+    fn verify_response_sync(
+        &self,
+        response: &::http::Response<Vec<u8>>,
+        has_body: bool,
+    ) -> crate::error::Result<VerificationReport> {
+        let verifier = match &self.key {
+            VerifierKey::Fixed(public_key) => HttpVerifier::new(public_key.clone()),
+            VerifierKey::Resolver(resolver) => {
+                let key_id = signature_key_id(response)?;
+                let resolved = resolver
+                    .call1(&JsValue::NULL, &key_id.as_str().into())
+                    .map_err(|e| Error::Other(format!("Key resolver failed: {e:?}")))?;
+                if resolved.is_instance_of::<Promise>() {
+                    return Err(Error::Unsupported(
+                        "Key resolver returned a Promise; use verifyResponseAsync".to_string(),
+                    ));
+                }
+                resolved_verifier(key_id, &resolved)?
+            }
+        };
+        verify_response_with(&verifier, response, has_body)
     }
 }
 
@@ -69,35 +756,23 @@ impl WasmHttpUtils {
     /// Parse signature headers from a response
     #[wasm_bindgen(js_name = parseSignatureHeaders)]
     pub fn parse_signature_headers(headers: &JsValue) -> WasmResult<JsValue> {
-        let headers_obj = headers
-            .clone()
-            .dyn_into::<Object>()
-            .map_err(|_| WasmError {
-                message: "Headers must be an object".to_string(),
-            })?;
-
-        let result = Object::new();
-
-        // Extract signature header
-        if let Ok(signature) = Reflect::get(&headers_obj, &"signature".into()) {
-            if !signature.is_undefined() {
-                Reflect::set(&result, &"signature".into(), &signature).map_err(|e| WasmError {
-                    message: format!("Failed to set signature: {e:?}"),
-                })?;
-            }
-        }
-
-        // Extract signature-input header
-        if let Ok(signature_input) = Reflect::get(&headers_obj, &"signature-input".into()) {
-            if !signature_input.is_undefined() {
-                Reflect::set(&result, &"signature-input".into(), &signature_input).map_err(
-                    |e| WasmError {
-                        message: format!("Failed to set signature-input: {e:?}"),
-                    },
-                )?;
-            }
+        if !headers.is_object() {
+            return Err(WasmError::new(
+                WasmErrorCode::InvalidInput,
+                "Headers must be an object".to_string(),
+            ));
         }
+        let parsed: SignedHeaders =
+            serde_wasm_bindgen::from_value(headers.clone()).map_err(|e| {
+                WasmError::new(
+                    WasmErrorCode::InvalidInput,
+                    format!("Invalid signature headers: {e}"),
+                )
+            })?;
 
-        Ok(result.into())
+        to_js(&SignedHeaders {
+            content_digest: None,
+            ..parsed
+        })
     }
 }
@@ -1,6 +1,10 @@
 //! WASM bindings for key pair operations
 
 use super::*;
+use crate::crypto::SignStream;
+use crate::formats::{keypair_from_jwk, public_key_from_jwk, KeyExporter, KeyFormat};
+use std::cell::RefCell;
+use std::rc::Rc;
 
 /// Key pair for WASM
 #[wasm_bindgen]
@@ -74,11 +78,44 @@ impl WasmKeyPair {
         self.inner.public_key_bytes()
     }
 
+    /// Export the private key as a JWK object (`{kty, crv, x, d, ...}`)
+    ///
+    /// The object can be passed to `crypto.subtle.importKey("jwk", ...)`.
+    #[wasm_bindgen(js_name = exportPrivateJwk)]
+    pub fn export_private_jwk(&self) -> WasmResult<JsValue> {
+        json_to_js(&self.inner.to_jwk()?)
+    }
+
+    /// Export the public key as a JWK object (`{kty, crv, x, ...}`)
+    #[wasm_bindgen(js_name = exportPublicJwk)]
+    pub fn export_public_jwk(&self) -> WasmResult<JsValue> {
+        json_to_js(&self.inner.public_key().to_jwk()?)
+    }
+
+    /// Export the private key as PKCS#8 DER, for `crypto.subtle.importKey("pkcs8", ...)`
+    #[wasm_bindgen(js_name = exportPkcs8)]
+    pub fn export_pkcs8(&self) -> WasmResult<Vec<u8>> {
+        Ok(self.inner.export(KeyFormat::Der)?)
+    }
+
+    /// Export the public key as SPKI DER, for `crypto.subtle.importKey("spki", ...)`
+    #[wasm_bindgen(js_name = exportSpki)]
+    pub fn export_spki(&self) -> WasmResult<Vec<u8>> {
+        Ok(self.inner.public_key().export(KeyFormat::Der)?)
+    }
+
+    /// Import key pair from a private JWK object
+    #[wasm_bindgen(js_name = fromJwk)]
+    pub fn from_jwk(jwk: &JsValue) -> WasmResult<WasmKeyPair> {
+        let keypair = keypair_from_jwk(&js_to_json(jwk)?)?;
+        Ok(WasmKeyPair { inner: keypair })
+    }
+
     /// Import key pair from private key hex string
     #[wasm_bindgen(js_name = fromPrivateKeyHex)]
     pub fn from_private_key_hex(key_type: WasmKeyType, hex_key: &str) -> WasmResult<WasmKeyPair> {
-        let bytes = hex::decode(hex_key).map_err(|e| WasmError {
-            message: format!("Invalid hex: {e}"),
+        let bytes = hex::decode(hex_key).map_err(|e| {
+            WasmError::new(WasmErrorCode::InvalidInput, format!("Invalid hex: {e}"))
         })?;
         Self::from_private_key(key_type, &bytes)
     }
@@ -105,6 +142,23 @@ impl WasmKeyPair {
         self.sign(message.as_bytes())
     }
 
+    /// Start signing a message fed in chunks, such as a `File` read from its stream
+    ///
+    /// Only Secp256k1 keys can stream: Ed25519 would have to hold the whole
+    /// message in memory, so it is rejected with `WasmErrorCode.Unsupported`.
+    #[wasm_bindgen(js_name = createSignStream)]
+    pub fn create_sign_stream(&self) -> WasmResult<WasmSignStream> {
+        if self.inner.key_type() == KeyType::Ed25519 {
+            return Err(WasmError::new(
+                WasmErrorCode::Unsupported,
+                "Ed25519 cannot sign a stream without buffering it; sign the whole message or use a Secp256k1 key",
+            ));
+        }
+        Ok(WasmSignStream {
+            inner: Rc::new(RefCell::new(Some(self.inner.sign_stream()))),
+        })
+    }
+
     /// Verify a signature
     pub fn verify(&self, message: &[u8], signature: &WasmSignature) -> bool {
         self.inner.verify(message, &signature.inner).is_ok()
@@ -149,11 +203,30 @@ impl WasmPublicKey {
         self.inner.to_bytes()
     }
 
+    /// Export as a JWK object (`{kty, crv, x, ...}`)
+    #[wasm_bindgen(js_name = toJwk)]
+    pub fn to_jwk(&self) -> WasmResult<JsValue> {
+        json_to_js(&self.inner.to_jwk()?)
+    }
+
+    /// Export as SPKI DER, for `crypto.subtle.importKey("spki", ...)`
+    #[wasm_bindgen(js_name = exportSpki)]
+    pub fn export_spki(&self) -> WasmResult<Vec<u8>> {
+        Ok(self.inner.export(KeyFormat::Der)?)
+    }
+
+    /// Import from a JWK object; private members are ignored
+    #[wasm_bindgen(js_name = fromJwk)]
+    pub fn from_jwk(jwk: &JsValue) -> WasmResult<WasmPublicKey> {
+        let key = public_key_from_jwk(&js_to_json(jwk)?)?;
+        Ok(WasmPublicKey { inner: key })
+    }
+
     /// Import from hex string
     #[wasm_bindgen(js_name = fromHex)]
     pub fn from_hex(key_type: WasmKeyType, hex_key: &str) -> WasmResult<WasmPublicKey> {
-        let bytes = hex::decode(hex_key).map_err(|e| WasmError {
-            message: format!("Invalid hex: {e}"),
+        let bytes = hex::decode(hex_key).map_err(|e| {
+            WasmError::new(WasmErrorCode::InvalidInput, format!("Invalid hex: {e}"))
         })?;
         Self::from_bytes(key_type, &bytes)
     }
@@ -177,3 +250,42 @@ impl WasmPublicKey {
         self.verify(message.as_bytes(), signature)
     }
 }
+
+/// Signer for messages fed in chunks
+///
+/// Chunks are hashed as they arrive, so memory use does not grow with the message.
+#[wasm_bindgen]
+pub struct WasmSignStream {
+    inner: Rc<RefCell<Option<SignStream>>>,
+}
+
+#[wasm_bindgen]
+impl WasmSignStream {
+    /// Feed the next chunk, returning the stream so calls can be chained
+    pub fn update(&self, chunk: &[u8]) -> WasmResult<WasmSignStream> {
+        match self.inner.borrow_mut().as_mut() {
+            Some(stream) => stream.update(chunk),
+            None => return Err(finalized()),
+        }
+        Ok(WasmSignStream {
+            inner: Rc::clone(&self.inner),
+        })
+    }
+
+    /// Sign the message fed so far; the stream cannot be used afterwards
+    pub fn finalize(&self) -> WasmResult<WasmSignature> {
+        let stream = self.inner.borrow_mut().take().ok_or_else(finalized)?;
+        stream
+            .finalize()
+            .map(|sig| WasmSignature { inner: sig })
+            .map_err(Into::into)
+    }
+}
+
+/// Error for a stream used after `finalize`
+fn finalized() -> WasmError {
+    WasmError::new(
+        WasmErrorCode::InvalidInput,
+        "Sign stream has already been finalized".to_string(),
+    )
+}
@@ -1,4 +1,8 @@
 //! WebAssembly bindings for browser and Node.js integration
+//!
+//! The `wasm` feature only uses plain objects and `Uint8Array`s, so it runs
+//! in Node, Deno and workers; `wasm-web` adds fetch `Response` and WebCrypto
+//! `CryptoKey` bindings.
 
 use crate::crypto::{KeyPair, KeyType, PublicKey, Signature};
 use crate::crypto::{Signer, Verifier};
@@ -10,12 +14,16 @@ pub mod http;
 pub mod keypair;
 pub mod signature;
 pub mod utils;
+#[cfg(feature = "wasm-web")]
+pub mod webcrypto;
 
 pub use formats::*;
 pub use http::*;
 pub use keypair::*;
 pub use signature::*;
 pub use utils::*;
+#[cfg(feature = "wasm-web")]
+pub use webcrypto::*;
 
 /// Initialize the WASM module
 #[wasm_bindgen(start)]
@@ -59,25 +67,122 @@ impl From<KeyType> for WasmKeyType {
     }
 }
 
+/// Machine-readable kind of a `WasmError`
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WasmErrorCode {
+    /// Malformed arguments, encodings or messages
+    InvalidInput = 0,
+    /// Malformed key or wrong key type
+    InvalidKey = 1,
+    /// Key generation failed
+    KeyGeneration = 2,
+    /// Signing failed
+    SigningFailed = 3,
+    /// Signature verification failed
+    VerificationFailed = 4,
+    /// HTTP message signature could not be built or parsed
+    HttpSignature = 5,
+    /// Serialization or conversion failed
+    Serialization = 6,
+    /// Operation not supported by the key or environment
+    Unsupported = 7,
+    /// A JavaScript call threw or returned an unexpected value
+    JsError = 8,
+    /// Any other failure
+    Other = 9,
+}
+
+impl From<&crate::error::Error> for WasmErrorCode {
+    fn from(err: &crate::error::Error) -> Self {
+        use crate::error::Error;
+        match err {
+            Error::InvalidInput(_) => WasmErrorCode::InvalidInput,
+            Error::InvalidKeyFormat(_) | Error::InvalidKeyType(_) => WasmErrorCode::InvalidKey,
+            Error::KeyGeneration(_) => WasmErrorCode::KeyGeneration,
+            Error::Signature(_) | Error::CryptoError(_) => WasmErrorCode::SigningFailed,
+            Error::Verification(_) => WasmErrorCode::VerificationFailed,
+            Error::HttpSignature(_) => WasmErrorCode::HttpSignature,
+            Error::Serialization(_) | Error::Base64(_) | Error::Pem(_) => {
+                WasmErrorCode::Serialization
+            }
+            Error::Unsupported(_) => WasmErrorCode::Unsupported,
+            Error::Io(_) | Error::Other(_) => WasmErrorCode::Other,
+        }
+    }
+}
+
 /// Error type for WASM
 #[wasm_bindgen]
+#[derive(Debug)]
 pub struct WasmError {
+    code: WasmErrorCode,
     message: String,
+    reason: String,
+}
+
+impl WasmError {
+    /// Create an error whose reason is its message
+    pub(crate) fn new(code: WasmErrorCode, message: impl Into<String>) -> Self {
+        let message = message.into();
+        WasmError {
+            code,
+            reason: message.clone(),
+            message,
+        }
+    }
 }
 
 #[wasm_bindgen]
 impl WasmError {
+    /// Get the error code
+    #[wasm_bindgen(getter)]
+    pub fn code(&self) -> WasmErrorCode {
+        self.code
+    }
+
     /// Get the error message
     #[wasm_bindgen(getter)]
     pub fn message(&self) -> String {
         self.message.clone()
     }
+
+    /// Get the reason, without the error kind prefix of the message
+    #[wasm_bindgen(getter)]
+    pub fn reason(&self) -> String {
+        self.reason.clone()
+    }
+
+    /// Format as `message`
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        self.message.clone()
+    }
 }
 
 impl From<crate::error::Error> for WasmError {
     fn from(err: crate::error::Error) -> Self {
+        use crate::error::Error;
+        let reason = match &err {
+            Error::KeyGeneration(reason)
+            | Error::Signature(reason)
+            | Error::Verification(reason)
+            | Error::InvalidKeyFormat(reason)
+            | Error::Serialization(reason)
+            | Error::HttpSignature(reason)
+            | Error::InvalidKeyType(reason)
+            | Error::Other(reason)
+            | Error::InvalidInput(reason)
+            | Error::CryptoError(reason)
+            | Error::Unsupported(reason) => reason.clone(),
+            Error::Base64(e) => e.to_string(),
+            Error::Pem(e) => e.to_string(),
+            Error::Io(e) => e.to_string(),
+        };
         WasmError {
+            code: (&err).into(),
             message: err.to_string(),
+            reason,
         }
     }
 }
@@ -25,8 +25,8 @@ impl WasmSignature {
     /// Import signature from hex string
     #[wasm_bindgen(js_name = fromHex)]
     pub fn from_hex(key_type: WasmKeyType, hex_sig: &str) -> WasmResult<WasmSignature> {
-        let bytes = hex::decode(hex_sig).map_err(|e| WasmError {
-            message: format!("Invalid hex: {e}"),
+        let bytes = hex::decode(hex_sig).map_err(|e| {
+            WasmError::new(WasmErrorCode::InvalidInput, format!("Invalid hex: {e}"))
         })?;
         Self::from_bytes(key_type, &bytes)
     }
@@ -37,9 +37,10 @@ impl WasmSignature {
         let signature = match key_type {
             WasmKeyType::Ed25519 => {
                 if bytes.len() != 64 {
-                    return Err(WasmError {
-                        message: "Ed25519 signature must be 64 bytes".to_string(),
-                    });
+                    return Err(WasmError::new(
+                        WasmErrorCode::InvalidInput,
+                        "Ed25519 signature must be 64 bytes".to_string(),
+                    ));
                 }
                 let mut sig_bytes = [0u8; 64];
                 sig_bytes.copy_from_slice(bytes);
@@ -54,8 +55,11 @@ impl WasmSignature {
                             Err(k256::ecdsa::Error::new())
                         }
                     })
-                    .map_err(|e| WasmError {
-                        message: format!("Invalid Secp256k1 signature: {e}"),
+                    .map_err(|e| {
+                        WasmError::new(
+                            WasmErrorCode::InvalidInput,
+                            format!("Invalid Secp256k1 signature: {e}"),
+                        )
                     })?,
             ),
         };
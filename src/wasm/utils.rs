@@ -21,9 +21,8 @@ pub fn generate_random_hex(length: usize) -> String {
 /// Convert hex string to bytes
 #[wasm_bindgen(js_name = hexToBytes)]
 pub fn hex_to_bytes(hex: &str) -> WasmResult<Vec<u8>> {
-    hex::decode(hex).map_err(|e| WasmError {
-        message: format!("Invalid hex: {e}"),
-    })
+    hex::decode(hex)
+        .map_err(|e| WasmError::new(WasmErrorCode::InvalidInput, format!("Invalid hex: {e}")))
 }
 
 /// Convert bytes to hex string
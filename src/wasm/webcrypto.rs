@@ -0,0 +1,177 @@
+//! WASM interoperability with WebCrypto `CryptoKey`s
+//!
+//! Only Ed25519 keys can be converted: WebCrypto has no secp256k1 curve, so
+//! secp256k1 keys stay with the WASM implementation.
+
+use super::*;
+use crate::formats::{keypair_from_jwk, public_key_from_jwk, KeyExporter};
+use js_sys::{Array, ArrayBuffer, Function, Object, Promise, Reflect, Uint8Array};
+use wasm_bindgen_futures::{future_to_promise, JsFuture};
+use web_sys::CryptoKey;
+
+/// WebCrypto algorithm name for Ed25519
+const ED25519: &str = "Ed25519";
+
+/// Get `crypto.subtle` from the global scope
+fn subtle() -> WasmResult<Object> {
+    let crypto = Reflect::get(&js_sys::global(), &"crypto".into()).unwrap_or(JsValue::UNDEFINED);
+    Reflect::get(&crypto, &"subtle".into())
+        .ok()
+        .and_then(|subtle| subtle.dyn_into::<Object>().ok())
+        .ok_or_else(|| {
+            WasmError::new(
+                WasmErrorCode::Unsupported,
+                "WebCrypto is not available in this environment".to_string(),
+            )
+        })
+}
+
+/// Call a `SubtleCrypto` method and wait for its result
+async fn call_subtle(method: &str, args: Array) -> Result<JsValue, JsValue> {
+    let subtle = subtle()?;
+    let function = Reflect::get(&subtle, &method.into())?.dyn_into::<Function>()?;
+    let promise = function.apply(&subtle, &args)?.dyn_into::<Promise>()?;
+    JsFuture::from(promise).await
+}
+
+/// Import a JWK as an Ed25519 `CryptoKey`
+async fn import_jwk(
+    jwk: serde_json::Value,
+    extractable: bool,
+    usage: &str,
+) -> Result<JsValue, JsValue> {
+    let args = Array::new();
+    args.push(&"jwk".into());
+    args.push(&json_to_js(&jwk)?);
+    args.push(&ED25519.into());
+    args.push(&extractable.into());
+    args.push(&Array::of1(&usage.into()));
+    call_subtle("importKey", args).await
+}
+
+/// Export a `CryptoKey` as a JWK
+async fn export_jwk(key: CryptoKey) -> Result<serde_json::Value, JsValue> {
+    let jwk = call_subtle("exportKey", Array::of2(&"jwk".into(), &key)).await?;
+    Ok(js_to_json(&jwk)?)
+}
+
+/// Check that a key pair can be used with WebCrypto
+fn require_ed25519(key_type: KeyType) -> WasmResult<()> {
+    match key_type {
+        KeyType::Ed25519 => Ok(()),
+        KeyType::Secp256k1 => Err(WasmError::new(
+            WasmErrorCode::Unsupported,
+            "WebCrypto does not support secp256k1 keys".to_string(),
+        )),
+    }
+}
+
+#[wasm_bindgen]
+impl WasmKeyPair {
+    /// Import into WebCrypto, resolving to `{ privateKey, publicKey }` `CryptoKey`s
+    ///
+    /// With `extractable` set to `false` the private key cannot be read back,
+    /// and signing goes through `WasmCryptoKeySigner`.
+    #[wasm_bindgen(js_name = toCryptoKeyPair)]
+    pub fn to_crypto_key_pair(&self, extractable: bool) -> WasmResult<Promise> {
+        require_ed25519(self.inner.key_type())?;
+        let private_jwk = self.inner.to_jwk()?;
+        let public_jwk = self.inner.public_key().to_jwk()?;
+
+        Ok(future_to_promise(async move {
+            let private_key = import_jwk(private_jwk, extractable, "sign").await?;
+            let public_key = import_jwk(public_jwk, true, "verify").await?;
+            let pair = Object::new();
+            Reflect::set(&pair, &"privateKey".into(), &private_key)?;
+            Reflect::set(&pair, &"publicKey".into(), &public_key)?;
+            Ok(pair.into())
+        }))
+    }
+
+    /// Create a key pair from an extractable Ed25519 private `CryptoKey`
+    #[wasm_bindgen(js_name = fromCryptoKey)]
+    pub fn from_crypto_key(private_key: CryptoKey) -> Promise {
+        future_to_promise(async move {
+            let jwk = export_jwk(private_key).await?;
+            let keypair = keypair_from_jwk(&jwk).map_err(WasmError::from)?;
+            Ok(WasmKeyPair { inner: keypair }.into())
+        })
+    }
+}
+
+#[wasm_bindgen]
+impl WasmPublicKey {
+    /// Import into WebCrypto as an Ed25519 `CryptoKey` for verification
+    #[wasm_bindgen(js_name = toCryptoKey)]
+    pub fn to_crypto_key(&self) -> WasmResult<Promise> {
+        require_ed25519(self.inner.key_type())?;
+        let jwk = self.inner.to_jwk()?;
+        Ok(future_to_promise(async move {
+            import_jwk(jwk, true, "verify").await
+        }))
+    }
+
+    /// Create a public key from an Ed25519 public `CryptoKey`
+    #[wasm_bindgen(js_name = fromCryptoKey)]
+    pub fn from_crypto_key(public_key: CryptoKey) -> Promise {
+        future_to_promise(async move {
+            let jwk = export_jwk(public_key).await?;
+            let key = public_key_from_jwk(&jwk).map_err(WasmError::from)?;
+            Ok(WasmPublicKey { inner: key }.into())
+        })
+    }
+}
+
+/// Signer backed by a WebCrypto private key, which may be non-extractable
+#[wasm_bindgen]
+pub struct WasmCryptoKeySigner {
+    private_key: CryptoKey,
+    public_key: PublicKey,
+}
+
+#[wasm_bindgen]
+impl WasmCryptoKeySigner {
+    /// Create a signer from an Ed25519 private `CryptoKey` and its public key
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        private_key: CryptoKey,
+        public_key: &WasmPublicKey,
+    ) -> WasmResult<WasmCryptoKeySigner> {
+        require_ed25519(public_key.inner.key_type())?;
+        Ok(WasmCryptoKeySigner {
+            private_key,
+            public_key: public_key.inner.clone(),
+        })
+    }
+
+    /// Get the key ID
+    #[wasm_bindgen(getter, js_name = keyId)]
+    pub fn key_id(&self) -> String {
+        self.public_key.key_id()
+    }
+
+    /// Sign a message with `crypto.subtle.sign`, resolving to a `WasmSignature`
+    ///
+    /// The signature is checked against the public key before it is returned.
+    pub fn sign(&self, message: &[u8]) -> Promise {
+        let private_key = self.private_key.clone();
+        let public_key = self.public_key.clone();
+        let message = message.to_vec();
+
+        future_to_promise(async move {
+            let args = Array::new();
+            args.push(&ED25519.into());
+            args.push(&private_key);
+            args.push(&Uint8Array::from(message.as_slice()));
+            let buffer = call_subtle("sign", args).await?.dyn_into::<ArrayBuffer>()?;
+
+            let bytes = Uint8Array::new(&buffer).to_vec();
+            let signature =
+                Signature::from_bytes(KeyType::Ed25519, &bytes).map_err(WasmError::from)?;
+            public_key
+                .verify(&message, &signature)
+                .map_err(WasmError::from)?;
+            Ok(WasmSignature { inner: signature }.into())
+        })
+    }
+}
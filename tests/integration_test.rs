@@ -181,3 +181,215 @@ fn test_key_id_consistency() {
     assert_eq!(key_id1.len(), 16);
     assert!(key_id1.chars().all(|c| c.is_ascii_hexdigit()));
 }
+
+#[test]
+fn test_http_signing_with_key_id_strategy() {
+    use sage_crypto_core::crypto::KeyIdStrategy;
+
+    let keypair = KeyPair::generate(KeyType::Ed25519)
+        .unwrap()
+        .with_key_id_strategy(KeyIdStrategy::JwkThumbprint)
+        .unwrap();
+    let signer = HttpSigner::new(keypair.clone());
+
+    let request = Request::builder()
+        .method("GET")
+        .uri("https://example.com/test")
+        .body(())
+        .unwrap();
+    let signed_request = signer.sign_request(request).unwrap();
+
+    let sig_input = signed_request
+        .headers()
+        .get("signature-input")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(sig_input.contains(&format!("keyid=\"{}\"", keypair.key_id())));
+
+    // Default strategy no longer matches the advertised key ID
+    let verifier = HttpVerifier::new(keypair.public_key().clone());
+    assert!(verifier.verify_request(&signed_request).is_err());
+
+    let verifier = HttpVerifier::new(keypair.public_key().clone())
+        .with_key_id_strategy(KeyIdStrategy::JwkThumbprint);
+    assert!(verifier.verify_request(&signed_request).is_ok());
+}
+
+#[test]
+fn test_interop_with_underlying_crates() {
+    use ed25519_dalek::Signer as _;
+    use sage_crypto_core::{PublicKey, Signature};
+
+    // A signature produced directly with ed25519-dalek verifies through sage types
+    let signing_key = sage_crypto_core::crypto::ed25519::generate_signing_key();
+    let message = b"interop";
+    let dalek_signature = signing_key.sign(message);
+
+    let public_key = PublicKey::from(signing_key.verifying_key());
+    let signature = Signature::from(dalek_signature);
+    assert!(public_key.verify(message, &signature).is_ok());
+
+    let back = ed25519_dalek::Signature::try_from(signature).unwrap();
+    assert_eq!(back.to_bytes(), dalek_signature.to_bytes());
+    assert!(k256::ecdsa::Signature::try_from(Signature::from(dalek_signature)).is_err());
+}
+
+#[test]
+fn test_http_signing_with_nonce() {
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let signer = HttpSigner::new(keypair.clone()).with_nonce(true);
+
+    let request = Request::builder()
+        .method("GET")
+        .uri("https://example.com/test")
+        .body(())
+        .unwrap();
+    let signed_request = signer.sign_request(request).unwrap();
+
+    let sig_input = signed_request
+        .headers()
+        .get("signature-input")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(sig_input.contains("nonce=\""));
+
+    let verifier = HttpVerifier::new(keypair.public_key().clone());
+    assert!(verifier.verify_request(&signed_request).is_ok());
+}
+
+#[test]
+fn test_http_signing_with_custom_params() {
+    use sage_crypto_core::rfc9421::SignatureComponent;
+
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let signer = HttpSigner::new(keypair.clone());
+    let verifier = HttpVerifier::new(keypair.public_key().clone());
+
+    let request = || {
+        Request::builder()
+            .method("POST")
+            .uri("https://example.com/tasks")
+            .header("content-type", "application/json")
+            .body(())
+            .unwrap()
+    };
+    let components = [
+        SignatureComponent::Method,
+        SignatureComponent::Path,
+        SignatureComponent::Header("content-type".to_string()),
+    ];
+
+    // Start from the defaults and override per message
+    let mut params = signer.signature_params().unwrap();
+    params.tag = Some("sage-agent".to_string());
+    params.nonce = Some("n-0001".to_string());
+    params.expires = params.created.map(|created| created + 30);
+
+    let signed = signer
+        .sign_request_with_params(request(), &components, params)
+        .unwrap();
+    let sig_input = signed
+        .headers()
+        .get("signature-input")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(sig_input.contains("\"content-type\""));
+    assert!(sig_input.contains("tag=\"sage-agent\""));
+    assert!(sig_input.contains("nonce=\"n-0001\""));
+    assert!(verifier.verify_request(&signed).is_ok());
+
+    // Omitting every parameter is allowed as well
+    let signed = signer
+        .sign_request_with_params(request(), &components, Default::default())
+        .unwrap();
+    let sig_input = signed.headers().get("signature-input").unwrap();
+    assert_eq!(
+        sig_input.to_str().unwrap(),
+        "sig1=(\"@method\" \"@path\" \"content-type\")"
+    );
+    assert!(verifier.verify_request(&signed).is_ok());
+}
+
+#[test]
+fn test_headers_only_signing() {
+    let keypair = KeyPair::generate(KeyType::Secp256k1).unwrap();
+    let signer = HttpSigner::new(keypair.clone());
+    let verifier = HttpVerifier::new(keypair.public_key().clone());
+
+    let headers = signer
+        .sign_request_parts("DELETE", "https://api.example.com/agents/42", &[])
+        .unwrap();
+
+    // Attach the values to a request built by another HTTP stack
+    let request = Request::builder()
+        .method("DELETE")
+        .uri("https://api.example.com/agents/42")
+        .header("signature", headers.signature.as_str())
+        .header("signature-input", headers.signature_input.as_str())
+        .body(())
+        .unwrap();
+    assert!(verifier.verify_request(&request).is_ok());
+}
+
+#[test]
+fn test_verifier_with_trusted_key_set() {
+    use sage_crypto_core::rfc9421::KeySet;
+
+    let clients: Vec<KeyPair> = (0..3)
+        .map(|_| KeyPair::generate(KeyType::Ed25519).unwrap())
+        .collect();
+    let key_set: KeySet = clients.iter().map(|kp| kp.public_key().clone()).collect();
+    let verifier = HttpVerifier::from_key_set(key_set);
+
+    for client in &clients {
+        let request = Request::builder()
+            .method("GET")
+            .uri("https://example.com/data")
+            .body(())
+            .unwrap();
+        let signed = HttpSigner::new(client.clone())
+            .sign_request(request)
+            .unwrap();
+        let report = verifier.verify_request_detailed(&signed).unwrap();
+        assert_eq!(report.key_id, client.key_id());
+    }
+
+    let outsider = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let request = Request::builder()
+        .method("GET")
+        .uri("https://example.com/data")
+        .body(())
+        .unwrap();
+    let signed = HttpSigner::new(outsider).sign_request(request).unwrap();
+    assert!(verifier.verify_request(&signed).is_err());
+}
+
+#[test]
+fn test_sign_request_with_body() {
+    use sage_crypto_core::rfc9421::{ContentDigest, SignatureComponent, VerificationPolicy};
+
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let signer = HttpSigner::new(keypair.clone());
+    let verifier = HttpVerifier::new(keypair.public_key().clone()).with_policy(
+        VerificationPolicy::new().require_component(SignatureComponent::content_digest()),
+    );
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("https://example.com/tasks")
+        .header("content-type", "application/json")
+        .body(br#"{"task":"summarize"}"#.to_vec())
+        .unwrap();
+    let signed = signer.sign_request_with_body(request).unwrap();
+
+    assert!(ContentDigest::verify_request(&signed).is_ok());
+    assert!(verifier.verify_request(&signed).is_ok());
+
+    // Tampering with the body is caught by the digest check
+    let (parts, _) = signed.into_parts();
+    let tampered = Request::from_parts(parts, br#"{"task":"delete"}"#.to_vec());
+    assert!(ContentDigest::verify_request(&tampered).is_err());
+}
@@ -221,3 +221,572 @@ fn test_signature_base_string_format() {
         "\"@signature-params\": (\"@method\" \"@authority\" \"@path\");created=1618884475"
     ));
 }
+
+#[test]
+fn test_content_digest_as_signed_component() {
+    use sage_crypto_core::rfc9421::{ContentDigest, DigestAlgorithm};
+
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let signer = HttpSigner::new(keypair.clone()).with_default_components(vec![
+        SignatureComponent::Method,
+        SignatureComponent::Path,
+        SignatureComponent::Authority,
+        SignatureComponent::content_digest(),
+    ]);
+
+    let mut request = Request::builder()
+        .method("POST")
+        .uri("https://example.com/foo")
+        .header("content-type", "application/json")
+        .body(b"{\"hello\": \"world\"}".to_vec())
+        .unwrap();
+    ContentDigest::apply_to_request(&mut request, DigestAlgorithm::Sha512).unwrap();
+
+    let signed = signer.sign_request(request).unwrap();
+    let sig_input = signed
+        .headers()
+        .get("signature-input")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(sig_input.contains("\"content-digest\""));
+
+    let verifier = HttpVerifier::new(keypair.public_key().clone());
+    assert!(verifier.verify_request(&signed).is_ok());
+    assert!(ContentDigest::verify_request(&signed).is_ok());
+
+    // Swapping the body keeps the signature valid but breaks the digest
+    let (parts, _) = signed.into_parts();
+    let tampered = Request::from_parts(parts, b"{\"hello\": \"mars\"}".to_vec());
+    assert!(ContentDigest::verify_request(&tampered).is_err());
+}
+
+#[test]
+fn test_parameterized_components_roundtrip() {
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let signer = HttpSigner::new(keypair.clone()).with_default_components(vec![
+        SignatureComponent::Method,
+        SignatureComponent::Header("example-dict".to_string()).key("b"),
+        SignatureComponent::Header("example-dict".to_string()).sf(),
+        SignatureComponent::Header("content-type".to_string()).bs(),
+        SignatureComponent::query_param("pet"),
+    ]);
+
+    let request = Request::builder()
+        .method("GET")
+        .uri("https://example.com/pets?pet=dog&owner=alice")
+        .header("example-dict", "a=1,  b=2;x=1")
+        .header("content-type", "application/json")
+        .body(())
+        .unwrap();
+
+    let signed = signer.sign_request(request).unwrap();
+    let sig_input = signed
+        .headers()
+        .get("signature-input")
+        .unwrap()
+        .to_str()
+        .unwrap();
+    assert!(sig_input.contains("\"example-dict\";key=\"b\""));
+    assert!(sig_input.contains("\"example-dict\";sf"));
+    assert!(sig_input.contains("\"content-type\";bs"));
+    assert!(sig_input.contains("\"@query-param\";name=\"pet\""));
+
+    let verifier = HttpVerifier::new(keypair.public_key().clone());
+    assert!(verifier.verify_request(&signed).is_ok());
+}
+
+#[test]
+fn test_verification_policy_enforced() {
+    use sage_crypto_core::rfc9421::{SignatureAlgorithm, VerificationPolicy};
+    use std::time::Duration;
+
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let request = Request::builder()
+        .method("POST")
+        .uri("https://example.com/api")
+        .body(())
+        .unwrap();
+    // Default components: @method, @path, @authority
+    let signed = HttpSigner::new(keypair.clone())
+        .sign_request(request)
+        .unwrap();
+
+    let strict = HttpVerifier::new(keypair.public_key().clone()).with_policy(
+        VerificationPolicy::new()
+            .require_component(SignatureComponent::Method)
+            .require_component(SignatureComponent::TargetUri),
+    );
+    assert!(strict.verify_request(&signed).is_err());
+
+    let matching = HttpVerifier::new(keypair.public_key().clone()).with_policy(
+        VerificationPolicy::new()
+            .require_components([SignatureComponent::Method, SignatureComponent::Authority])
+            .require_created(true)
+            .max_age(Duration::from_secs(60))
+            .allow_algorithm(SignatureAlgorithm::Ed25519),
+    );
+    assert!(matching.verify_request(&signed).is_ok());
+
+    let wrong_alg = HttpVerifier::new(keypair.public_key().clone()).with_policy(
+        VerificationPolicy::new().allow_algorithm(SignatureAlgorithm::EcdsaSecp256k1Sha256),
+    );
+    assert!(wrong_alg.verify_request(&signed).is_err());
+}
+
+#[test]
+fn test_verifier_with_key_resolver() {
+    use sage_crypto_core::rfc9421::KeyResolver;
+    use sage_crypto_core::{Error, PublicKey};
+    use std::collections::HashMap;
+    use std::sync::Arc;
+
+    struct Registry(HashMap<String, PublicKey>);
+
+    impl KeyResolver for Registry {
+        fn resolve(&self, key_id: &str) -> sage_crypto_core::Result<PublicKey> {
+            self.0
+                .get(key_id)
+                .cloned()
+                .ok_or_else(|| Error::Verification(format!("Unknown key ID: {key_id}")))
+        }
+    }
+
+    let agent_a = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let agent_b = KeyPair::generate(KeyType::Secp256k1).unwrap();
+    let stranger = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+    let registry = Registry(
+        [&agent_a, &agent_b]
+            .iter()
+            .map(|kp| (kp.key_id().to_string(), kp.public_key().clone()))
+            .collect(),
+    );
+    let verifier = HttpVerifier::from_resolver(Arc::new(registry));
+
+    let request = || {
+        Request::builder()
+            .method("GET")
+            .uri("https://example.com/agents")
+            .body(())
+            .unwrap()
+    };
+
+    for keypair in [&agent_a, &agent_b] {
+        let signed = HttpSigner::new((*keypair).clone())
+            .sign_request(request())
+            .unwrap();
+        assert!(verifier.verify_request(&signed).is_ok());
+    }
+
+    let signed = HttpSigner::new(stranger).sign_request(request()).unwrap();
+    assert!(verifier.verify_request(&signed).is_err());
+}
+
+#[test]
+fn test_verification_policy_timing() {
+    use sage_crypto_core::rfc9421::VerificationPolicy;
+    use std::time::Duration;
+
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let request = Request::builder()
+        .method("GET")
+        .uri("https://example.com/")
+        .body(())
+        .unwrap();
+    let signed = HttpSigner::new(keypair.clone())
+        .sign_request(request)
+        .unwrap();
+
+    // The signer sets both created and expires
+    let strict = HttpVerifier::new(keypair.public_key().clone()).with_policy(
+        VerificationPolicy::new()
+            .require_expires(true)
+            .max_age(Duration::from_secs(60))
+            .clock_skew(Duration::from_secs(5)),
+    );
+    assert!(strict.verify_request(&signed).is_ok());
+
+    let too_old = HttpVerifier::new(keypair.public_key().clone())
+        .with_policy(VerificationPolicy::new().max_age(Duration::from_secs(0)));
+    std::thread::sleep(Duration::from_millis(1100));
+    assert!(too_old.verify_request(&signed).is_err());
+}
+
+#[test]
+fn test_signature_base_introspection() {
+    use sage_crypto_core::rfc9421::SignatureComponent;
+
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let signer = HttpSigner::new(keypair.clone());
+    let verifier = HttpVerifier::new(keypair.public_key().clone());
+
+    let request = || {
+        Request::builder()
+            .method("POST")
+            .uri("https://example.com/foo?param=value")
+            .body(())
+            .unwrap()
+    };
+    let components = [SignatureComponent::Method, SignatureComponent::Authority];
+    let params = signer.signature_params().unwrap();
+
+    let expected = signer
+        .signature_base_for_request(&request(), &components, &params)
+        .unwrap();
+    assert!(expected
+        .as_str()
+        .starts_with("\"@method\": POST\n\"@authority\": example.com\n\"@signature-params\": "));
+
+    // The verifier reconstructs the identical base from the signed message
+    let signed = signer
+        .sign_request_with_params(request(), &components, params)
+        .unwrap();
+    let received = verifier.signature_base_for_request(&signed).unwrap();
+    assert_eq!(received.as_str(), expected.as_str());
+    assert_eq!(received.components().len(), 2);
+    assert_eq!(received.params().key_id.as_deref(), Some(keypair.key_id()));
+}
+
+#[test]
+fn test_verification_report() {
+    use sage_crypto_core::rfc9421::{SignatureAlgorithm, SignatureComponent};
+
+    let keypair = KeyPair::generate(KeyType::Secp256k1).unwrap();
+    let signer = HttpSigner::new(keypair.clone()).with_nonce(true);
+    let verifier = HttpVerifier::new(keypair.public_key().clone());
+
+    let request = Request::builder()
+        .method("PUT")
+        .uri("https://example.com/agents/1")
+        .body(())
+        .unwrap();
+    let signed = signer.sign_request(request).unwrap();
+
+    let report = verifier.verify_request_detailed(&signed).unwrap();
+    assert_eq!(report.label, "sig1");
+    assert_eq!(report.key_id, keypair.key_id());
+    assert_eq!(report.algorithm, SignatureAlgorithm::EcdsaSecp256k1Sha256);
+    assert!(report.covers(&SignatureComponent::Method));
+    assert!(!report.covers(&SignatureComponent::Query));
+    assert!(report.created.is_some() && report.expires.is_some());
+    assert!(report.nonce.is_some());
+    assert!(report.tag.is_none());
+}
+
+#[test]
+fn test_signing_without_http_types() {
+    use sage_crypto_core::rfc9421::MessageParts;
+
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let signer = HttpSigner::new(keypair.clone());
+    let verifier = HttpVerifier::new(keypair.public_key().clone());
+
+    let request = MessageParts::request("POST", "https://example.com/inbox")
+        .with_header("Content-Type", "application/json");
+    let headers = signer.sign_message(&request).unwrap();
+    let signed = request
+        .with_header("Signature", headers.signature)
+        .with_header("Signature-Input", headers.signature_input);
+    assert!(verifier.verify_message(&signed).is_ok());
+
+    let response = MessageParts::response(202).with_header("content-type", "text/plain");
+    let headers = signer.sign_message(&response).unwrap();
+    assert!(headers
+        .signature_input
+        .starts_with("sig1=(\"@status\" \"content-type\")"));
+    let signed = response
+        .with_header("signature", headers.signature)
+        .with_header("signature-input", headers.signature_input);
+    let report = verifier.verify_message(&signed).unwrap();
+    assert_eq!(report.components.len(), 2);
+}
+
+#[test]
+fn test_presigned_request_without_timestamps() {
+    use sage_crypto_core::rfc9421::{Expiry, VerificationPolicy};
+
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let signer = HttpSigner::new(keypair.clone())
+        .with_created(false)
+        .with_expiry_mode(Expiry::Never);
+    let request = Request::builder()
+        .method("GET")
+        .uri("https://example.com/artifacts/1")
+        .body(())
+        .unwrap();
+    let signed = signer.sign_request(request).unwrap();
+
+    let sig_input = signed.headers()["signature-input"].to_str().unwrap();
+    assert!(!sig_input.contains("created="));
+    assert!(!sig_input.contains("expires="));
+
+    // The default policy allows missing timestamps, stricter ones can require them
+    let lenient = HttpVerifier::new(keypair.public_key().clone());
+    assert!(lenient.verify_request(&signed).is_ok());
+
+    let strict = HttpVerifier::new(keypair.public_key().clone())
+        .with_policy(VerificationPolicy::new().require_created(true));
+    assert!(strict.verify_request(&signed).is_err());
+    let strict = HttpVerifier::new(keypair.public_key().clone())
+        .with_policy(VerificationPolicy::new().require_expires(true));
+    assert!(strict.verify_request(&signed).is_err());
+}
+
+#[test]
+fn test_custom_signature_algorithm() {
+    use sage_crypto_core::rfc9421::{
+        AlgorithmRegistry, CustomSigner, CustomVerifier, SignatureAlgorithm, VerificationPolicy,
+    };
+    use sage_crypto_core::Error;
+    use sha2::{Digest, Sha256};
+    use std::sync::Arc;
+
+    // Toy keyed hash standing in for an HSM or experimental algorithm
+    struct KeyedHash(&'static [u8]);
+
+    impl KeyedHash {
+        fn mac(&self, message: &[u8]) -> Vec<u8> {
+            Sha256::new()
+                .chain_update(self.0)
+                .chain_update(message)
+                .finalize()
+                .to_vec()
+        }
+    }
+
+    impl CustomSigner for KeyedHash {
+        fn identifier(&self) -> &'static str {
+            "x-keyed-sha256"
+        }
+
+        fn key_id(&self) -> String {
+            "shared-1".to_string()
+        }
+
+        fn sign(&self, message: &[u8]) -> sage_crypto_core::Result<Vec<u8>> {
+            Ok(self.mac(message))
+        }
+    }
+
+    impl CustomVerifier for KeyedHash {
+        fn identifier(&self) -> &'static str {
+            "x-keyed-sha256"
+        }
+
+        fn verify(
+            &self,
+            key_id: Option<&str>,
+            message: &[u8],
+            signature: &[u8],
+        ) -> sage_crypto_core::Result<()> {
+            if key_id == Some("shared-1") && self.mac(message) == signature {
+                Ok(())
+            } else {
+                Err(Error::Verification("Invalid keyed hash".to_string()))
+            }
+        }
+    }
+
+    let signer = HttpSigner::from_custom_signer(Arc::new(KeyedHash(b"secret")));
+    let request = Request::builder()
+        .method("GET")
+        .uri("https://example.com/")
+        .body(())
+        .unwrap();
+    let signed = signer.sign_request(request).unwrap();
+
+    let mut algorithms = AlgorithmRegistry::new();
+    algorithms.register(Arc::new(KeyedHash(b"secret"))).unwrap();
+
+    // The pinned key is unrelated, so the custom algorithm needs an explicit opt-in
+    let unrelated = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let verifier = HttpVerifier::new(unrelated.public_key().clone()).with_algorithms(algorithms);
+    assert!(verifier.verify_request(&signed).is_err());
+    let verifier = verifier.with_policy(
+        VerificationPolicy::new().allow_algorithm(SignatureAlgorithm::Custom("x-keyed-sha256")),
+    );
+    let report = verifier.verify_request_detailed(&signed).unwrap();
+    assert_eq!(
+        report.algorithm,
+        SignatureAlgorithm::Custom("x-keyed-sha256")
+    );
+    assert_eq!(report.key_id, "shared-1");
+
+    // Without the registry the signature cannot be verified
+    let plain = HttpVerifier::new(unrelated.public_key().clone());
+    assert!(plain.verify_request(&signed).is_err());
+
+    // Policies can still restrict algorithms
+    let mut algorithms = AlgorithmRegistry::new();
+    algorithms.register(Arc::new(KeyedHash(b"secret"))).unwrap();
+    let restricted = HttpVerifier::new(unrelated.public_key().clone())
+        .with_algorithms(algorithms)
+        .with_policy(VerificationPolicy::new().allow_algorithm(SignatureAlgorithm::Ed25519));
+    assert!(restricted.verify_request(&signed).is_err());
+}
+
+#[test]
+fn test_proxy_resigning() {
+    let client_key = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let proxy_key = KeyPair::generate(KeyType::Ed25519).unwrap();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("https://gateway.example.com/tasks")
+        .header("forwarded", "for=192.0.2.123")
+        .body(())
+        .unwrap();
+    let signed = HttpSigner::new(client_key.clone())
+        .with_tag("client")
+        .sign_request(request)
+        .unwrap();
+
+    let inbound = HttpVerifier::new(client_key.public_key().clone());
+    let proxy = HttpSigner::new(proxy_key.clone())
+        .with_label("proxy")
+        .with_tag("gateway")
+        .with_default_components(vec![
+            SignatureComponent::Method,
+            SignatureComponent::Authority,
+            SignatureComponent::Header("forwarded".to_string()),
+        ]);
+    let (forwarded, report) = proxy.resign_request(&inbound, signed).unwrap();
+    assert_eq!(report.tag.as_deref(), Some("client"));
+
+    // Both the original and the proxy signature verify
+    inbound.verify_request(&forwarded).unwrap();
+    let report = HttpVerifier::new(proxy_key.public_key().clone())
+        .with_label("proxy")
+        .verify_request_detailed(&forwarded)
+        .unwrap();
+    assert_eq!(report.label, "proxy");
+    assert!(report.covers(&SignatureComponent::Header("signature".to_string()).key("sig1")));
+    assert!(report.covers(&SignatureComponent::Header("signature-input".to_string()).key("sig1")));
+
+    // Changing the parameters of the inbound signature breaks the proxy signature
+    let mut tampered = forwarded.clone();
+    let input = tampered.headers()["signature-input"]
+        .to_str()
+        .unwrap()
+        .replacen("tag=\"client\"", "tag=\"other\"", 1);
+    tampered
+        .headers_mut()
+        .insert("signature-input", input.parse().unwrap());
+    assert!(HttpVerifier::new(proxy_key.public_key().clone())
+        .with_label("proxy")
+        .verify_request(&tampered)
+        .is_err());
+
+    // The proxy signature must use its own label and tag
+    let same_label = HttpSigner::new(proxy_key.clone()).with_tag("gateway");
+    assert!(same_label
+        .resign_request(&inbound, forwarded.clone())
+        .is_err());
+    let same_tag = HttpSigner::new(proxy_key)
+        .with_label("proxy2")
+        .with_tag("client");
+    assert!(same_tag.resign_request(&inbound, forwarded).is_err());
+}
+
+#[test]
+fn test_countersignature() {
+    use sage_crypto_core::rfc9421::extract_signature_headers;
+
+    let agent_key = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let approver_key = KeyPair::generate(KeyType::Secp256k1).unwrap();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("https://example.com/payments")
+        .body(())
+        .unwrap();
+    let signed = HttpSigner::new(agent_key.clone())
+        .sign_request(request)
+        .unwrap();
+
+    let approver = HttpSigner::new(approver_key.clone()).with_label("approval");
+    let approved = approver
+        .countersign_request(signed.clone(), "sig1")
+        .unwrap();
+
+    // Both signatures verify, and the countersignature covers the original
+    HttpVerifier::new(agent_key.public_key().clone())
+        .verify_request(&approved)
+        .unwrap();
+    let report = HttpVerifier::new(approver_key.public_key().clone())
+        .with_label("approval")
+        .verify_request_detailed(&approved)
+        .unwrap();
+    assert!(report.covers(&SignatureComponent::Header("signature".to_string()).key("sig1")));
+    assert!(report.covers(&SignatureComponent::Header("signature-input".to_string()).key("sig1")));
+
+    // Replacing the original signature breaks the countersignature
+    let (approval, approval_input) = extract_signature_headers(&approved, "approval").unwrap();
+    let mut replaced = HttpSigner::new(agent_key.clone())
+        .with_tag("replaced")
+        .sign_request(signed)
+        .unwrap();
+    let headers = replaced.headers_mut();
+    let signature = format!(
+        "{}, approval=:{approval}:",
+        headers["signature"].to_str().unwrap()
+    );
+    let signature_input = format!(
+        "{}, approval={approval_input}",
+        headers["signature-input"].to_str().unwrap()
+    );
+    headers.insert("signature", signature.parse().unwrap());
+    headers.insert("signature-input", signature_input.parse().unwrap());
+    assert!(HttpVerifier::new(approver_key.public_key().clone())
+        .with_label("approval")
+        .verify_request(&replaced)
+        .is_err());
+
+    // A countersignature needs its own label and an existing signature
+    assert!(HttpSigner::new(approver_key.clone())
+        .countersign_request(approved.clone(), "sig1")
+        .is_err());
+    assert!(approver.countersign_request(approved, "missing").is_err());
+}
+
+#[test]
+fn test_inspect_signatures_before_verifying() {
+    use sage_crypto_core::rfc9421::{
+        extract_signature_headers, parse_signature_input, signature_labels,
+    };
+
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let request = Request::builder()
+        .method("GET")
+        .uri("https://example.com/agents")
+        .body(())
+        .unwrap();
+    assert!(signature_labels(&request).unwrap().is_empty());
+
+    let signed = HttpSigner::new(keypair.clone())
+        .with_tag("sage")
+        .sign_request(request)
+        .unwrap();
+    let signed = HttpSigner::new(keypair.clone())
+        .with_label("audit")
+        .sign_request(signed)
+        .unwrap();
+
+    // sign_request replaces existing signatures, so only the last label remains
+    assert_eq!(
+        signature_labels(&signed).unwrap(),
+        vec!["audit".to_string()]
+    );
+
+    let (_, input) = extract_signature_headers(&signed, "audit").unwrap();
+    let (components, params) = parse_signature_input(&input).unwrap();
+    assert_eq!(components.len(), 3);
+    assert_eq!(params.key_id.as_deref(), Some(keypair.key_id()));
+    assert!(extract_signature_headers(&signed, "sig1").is_err());
+
+    HttpVerifier::new(keypair.public_key().clone())
+        .with_label("audit")
+        .verify_request(&signed)
+        .unwrap();
+}
@@ -204,3 +204,115 @@ fn test_http_signature_replay_protection() {
     let input2 = signed2.headers().get("signature-input").unwrap();
     assert_ne!(input1, input2);
 }
+
+#[test]
+fn test_http_signature_replay_rejected() {
+    use http::Request;
+    use sage_crypto_core::rfc9421::{HttpSigner, HttpVerifier, InMemoryNonceStore};
+    use std::sync::Arc;
+
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let store = Arc::new(InMemoryNonceStore::new());
+    let verifier = HttpVerifier::new(keypair.public_key().clone()).with_nonce_store(store.clone());
+
+    for with_nonce in [true, false] {
+        let signer = HttpSigner::new(keypair.clone()).with_nonce(with_nonce);
+        let request = Request::builder()
+            .method("POST")
+            .uri("https://example.com/transfer")
+            .body(())
+            .unwrap();
+        let signed = signer.sign_request(request).unwrap();
+
+        // First delivery is accepted, an identical replay is rejected
+        assert!(verifier.verify_request(&signed).is_ok());
+        assert!(verifier.verify_request(&signed).is_err());
+    }
+
+    // A fresh signature over the same request is still accepted
+    let signer = HttpSigner::new(keypair.clone()).with_nonce(true);
+    let request = Request::builder()
+        .method("POST")
+        .uri("https://example.com/transfer")
+        .body(())
+        .unwrap();
+    assert!(verifier
+        .verify_request(&signer.sign_request(request).unwrap())
+        .is_ok());
+    assert_eq!(store.len(), 3);
+}
+
+#[test]
+fn test_http_signature_reencoded_replay_rejected() {
+    use base64::{engine::general_purpose, Engine as _};
+    use http::{HeaderValue, Request};
+    use sage_crypto_core::rfc9421::{HttpSigner, HttpVerifier, InMemoryNonceStore};
+    use std::sync::Arc;
+
+    let keypair = KeyPair::generate(KeyType::Secp256k1).unwrap();
+    let verifier = HttpVerifier::new(keypair.public_key().clone())
+        .with_nonce_store(Arc::new(InMemoryNonceStore::new()));
+    let request = Request::builder()
+        .method("POST")
+        .uri("https://example.com/transfer")
+        .body(())
+        .unwrap();
+    let signed = HttpSigner::new(keypair.clone())
+        .sign_request(request)
+        .unwrap();
+    assert!(verifier.verify_request(&signed).is_ok());
+
+    // Re-encode the DER signature as fixed-size r || s, which also verifies
+    let header = signed.headers()["signature"].to_str().unwrap();
+    let encoded = header
+        .strip_prefix("sig1=:")
+        .and_then(|value| value.strip_suffix(':'))
+        .unwrap();
+    let der = general_purpose::STANDARD.decode(encoded).unwrap();
+    let signature = k256::ecdsa::Signature::from_der(&der).unwrap();
+    let raw = general_purpose::STANDARD.encode(signature.as_ref());
+    assert_ne!(raw, encoded);
+
+    let mut replayed = signed;
+    replayed.headers_mut().insert(
+        "signature",
+        HeaderValue::from_str(&format!("sig1=:{raw}:")).unwrap(),
+    );
+    let fresh = HttpVerifier::new(keypair.public_key().clone());
+    assert!(fresh.verify_request(&replayed).is_ok());
+    assert!(verifier.verify_request(&replayed).is_err());
+}
+
+#[test]
+fn test_http_signature_cross_application_rejected() {
+    use http::Request;
+    use sage_crypto_core::rfc9421::{HttpSigner, HttpVerifier, VerificationPolicy};
+
+    let keypair = KeyPair::generate(KeyType::Ed25519).unwrap();
+    let verifier = HttpVerifier::new(keypair.public_key().clone())
+        .with_policy(VerificationPolicy::new().require_tag("sage-a2a"));
+
+    let request = || {
+        Request::builder()
+            .method("POST")
+            .uri("https://example.com/messages")
+            .body(())
+            .unwrap()
+    };
+
+    let ours = HttpSigner::new(keypair.clone()).with_tag("sage-a2a");
+    assert!(verifier
+        .verify_request(&ours.sign_request(request()).unwrap())
+        .is_ok());
+
+    // A valid signature made for another protocol must not be accepted
+    let theirs = HttpSigner::new(keypair.clone()).with_tag("payments");
+    assert!(verifier
+        .verify_request(&theirs.sign_request(request()).unwrap())
+        .is_err());
+
+    let untagged = HttpSigner::new(keypair);
+    assert!(verifier
+        .verify_request(&untagged.sign_request(request()).unwrap())
+        .is_err());
+}
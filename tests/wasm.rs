@@ -0,0 +1,65 @@
+//! WASM binding tests, run with `wasm-pack test --node --features wasm`
+
+#![cfg(all(target_arch = "wasm32", feature = "wasm"))]
+
+use sage_crypto_core::wasm::{
+    WasmErrorCode, WasmKeyFormat, WasmKeyFormatUtils, WasmKeyPair, WasmKeyType, WasmPublicKey,
+};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const FORMATS: [WasmKeyFormat; 3] = [WasmKeyFormat::Jwk, WasmKeyFormat::Pem, WasmKeyFormat::Der];
+
+#[wasm_bindgen_test]
+fn test_key_pair_round_trip() {
+    for key_type in [WasmKeyType::Ed25519, WasmKeyType::Secp256k1] {
+        let keypair = WasmKeyPair::new(key_type).unwrap();
+        for format in FORMATS {
+            let exported = WasmKeyFormatUtils::export_private_key(&keypair, format).unwrap();
+            let imported = WasmKeyFormatUtils::import_key_pair(&exported, format).unwrap();
+            assert_eq!(imported.export_private_key(), keypair.export_private_key());
+            assert_eq!(imported.key_id(), keypair.key_id());
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_public_key_round_trip() {
+    for key_type in [WasmKeyType::Ed25519, WasmKeyType::Secp256k1] {
+        let public_key = WasmKeyPair::new(key_type).unwrap().get_public_key();
+        for format in [WasmKeyFormat::Raw].into_iter().chain(FORMATS) {
+            let exported = WasmKeyFormatUtils::export_public_key(&public_key, format).unwrap();
+            let imported: WasmPublicKey =
+                WasmKeyFormatUtils::import_public_key(&exported, format).unwrap();
+            assert_eq!(imported.to_bytes(), public_key.to_bytes());
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+fn test_raw_private_key_needs_key_type() {
+    let keypair = WasmKeyPair::new(WasmKeyType::Ed25519).unwrap();
+    let raw = WasmKeyFormatUtils::export_private_key(&keypair, WasmKeyFormat::Raw).unwrap();
+    assert!(WasmKeyFormatUtils::import_key_pair(&raw, WasmKeyFormat::Raw).is_err());
+
+    let imported = WasmKeyPair::from_private_key(WasmKeyType::Ed25519, &raw).unwrap();
+    assert_eq!(imported.key_id(), keypair.key_id());
+}
+
+#[wasm_bindgen_test]
+fn test_sign_stream() {
+    let keypair = WasmKeyPair::new(WasmKeyType::Secp256k1).unwrap();
+    let stream = keypair.create_sign_stream().unwrap();
+    stream
+        .update(b"chunked ")
+        .unwrap()
+        .update(b"upload")
+        .unwrap();
+    let signature = stream.finalize().unwrap();
+    assert!(keypair.verify(b"chunked upload", &signature));
+    assert!(stream.update(b"more").is_err());
+
+    // Ed25519 would buffer the whole message, so it refuses to stream
+    let ed25519 = WasmKeyPair::new(WasmKeyType::Ed25519).unwrap();
+    let error = ed25519.create_sign_stream().err().unwrap();
+    assert_eq!(error.code(), WasmErrorCode::Unsupported);
+}